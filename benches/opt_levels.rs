@@ -0,0 +1,95 @@
+// run time of the same program at every optimization level,
+// `cargo bench --bench opt_levels`
+//
+// each level is built into its own binary with `axon build -O<n>`,
+// the binaries are then run a few times and the fastest run is kept
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+const LEVELS: &[&str] = &["-O0", "-O1", "-O2", "-O3", "-Os"];
+const RUNS: usize = 5;
+
+// recursive calls, a hot loop with division and remainder, and plenty of locals for mem2reg
+const PROGRAM: &str = r#"cast fib(n: i32) -> i32 >>
+    if (n < 2) >>
+        return n;
+    << else >>
+        return fib(n - 1) + fib(n - 2);
+    <<
+<<
+cast digit_sum(n: i32) -> i32 >>
+    set: sum(i32) = 0;
+    set: rest(i32) = n;
+    while (rest > 0) >>
+        math([sum + rest % 10], sum);
+        math([rest / 10], rest);
+    <<
+    return sum;
+<<
+cast Start() >>
+    out(fib(32));
+    set: i(i32) = 0;
+    set: total(i32) = 0;
+    while (i < 5000000) >>
+        math([total + digit_sum(i) % 7], total);
+        math([i + 1], i);
+    <<
+    out(total);
+    return;
+<<
+"#;
+
+fn main() {
+    let project = std::env::temp_dir().join("axon_bench_opt_levels");
+    fs::remove_dir_all(&project).ok();
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(
+        project.join("project.asml"),
+        "__Project__\n_name_ = \"bench\"\n_version_ = \"0.1.0\"\n",
+    )
+    .unwrap();
+    fs::write(project.join("src/init.ax"), PROGRAM).unwrap();
+
+    println!("{:<6} {:>12} {:>11}  speedup", "level", "best", "size");
+    let mut baseline = None;
+    for level in LEVELS {
+        let exe = format!("bench{}", level);
+        let built = Command::new(env!("CARGO_BIN_EXE_AxonScript"))
+            .args(["build", level, "--output", &exe])
+            .current_dir(&project)
+            .stdout(Stdio::null())
+            .status()
+            .expect("failed to start axon build");
+        let exe_path = project.join("release").join(&exe);
+        if !built.success() || !exe_path.exists() {
+            println!("{:<6} build failed", level);
+            continue;
+        }
+
+        let best = (0..RUNS).map(|_| time_run(&exe_path)).min().unwrap();
+        let size = fs::metadata(&exe_path).map_or(0, |m| m.len());
+        let baseline = *baseline.get_or_insert(best);
+        println!(
+            "{:<6} {:>10.1}ms {:>10}B  {:.2}x",
+            level,
+            best.as_secs_f64() * 1000.0,
+            size,
+            baseline.as_secs_f64() / best.as_secs_f64()
+        );
+    }
+    fs::remove_dir_all(&project).ok();
+}
+
+fn time_run(exe: &Path) -> Duration {
+    let start = Instant::now();
+    let status = Command::new(exe)
+        .stdout(Stdio::null())
+        .status()
+        .expect("failed to run the benchmark binary");
+    let elapsed = start.elapsed();
+    assert!(status.success(), "{} exited with {}", exe.display(), status);
+    elapsed
+}
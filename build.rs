@@ -3,10 +3,21 @@
 // and defines an environment variable needed by the llvm-sys crate
 // you need to have llvm installed on your system,
 // and if it's not in the default location, update the paths below accordingly
+// it also compiles the AxonScript runtime (runtime/axon_runtime.c) into the compiler,
+// so programs run through the JIT can call into it, together with runtime/axon_jit.c, which hands a failed program back to the compiler
+// (only with the `llvm` feature, an interpreter-only build has no JIT to return from)
 
 
 fn main() {
-    
+    let mut runtime = cc::Build::new();
+    runtime.file("runtime/axon_runtime.c");
+    if std::env::var_os("CARGO_FEATURE_LLVM").is_some() {
+        runtime.file("runtime/axon_jit.c");
+    }
+    runtime.compile("axon_runtime");
+    println!("cargo:rerun-if-changed=runtime/axon_runtime.c");
+    println!("cargo:rerun-if-changed=runtime/axon_jit.c");
+
     //println!("cargo:rustc-link-search=native=/usr/lib");
     //println!("cargo:rustc-link-lib=dylib=LLVM-20");
     //println!("cargo:rustc-env=LLVM_SYS_201_PREFIX=/usr");
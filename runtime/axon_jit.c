@@ -0,0 +1,49 @@
+// `axon run` and `axon repl` support, compiled into the compiler only (never shipped with built binaries).
+// a runtime error in generated code calls exit(), in the JIT that call is mapped to axon_jit_exit,
+// and so is axon_runtime_exit for the errors the runtime library raises itself,
+// both jump back to the axon_jit_main / axon_jit_call that started the code, so a failed program
+// comes back as its exit code instead of ending the process that runs it (a REPL session, an editor, ...)
+
+#include <setjmp.h>
+#include <stdio.h>
+
+extern void (*axon_runtime_exit)(int);
+
+static jmp_buf jit_return;
+static int jit_exit_code;
+
+void axon_jit_exit(int code) {
+    fflush(stdout);
+    jit_exit_code = code;
+    longjmp(jit_return, 1);
+}
+
+// runs a program's main, what it returned or the code it tried to exit with
+int axon_jit_main(int (*main_fn)(void)) {
+    void (*outer_exit)(int) = axon_runtime_exit;
+    int code;
+    axon_runtime_exit = axon_jit_exit;
+    if (setjmp(jit_return)) {
+        code = jit_exit_code;
+    } else {
+        code = main_fn();
+    }
+    fflush(stdout);
+    axon_runtime_exit = outer_exit;
+    return code;
+}
+
+// runs one REPL entry, 0 when it finished normally, otherwise the code it tried to exit with
+int axon_jit_call(void (*entry)(void)) {
+    void (*outer_exit)(int) = axon_runtime_exit;
+    int code = 0;
+    axon_runtime_exit = axon_jit_exit;
+    if (setjmp(jit_return)) {
+        code = jit_exit_code;
+    } else {
+        entry();
+    }
+    fflush(stdout);
+    axon_runtime_exit = outer_exit;
+    return code;
+}
@@ -0,0 +1,648 @@
+// AxonScript runtime library,
+// the generated code calls into it for everything that is too big to emit inline (tensors for now).
+// build.rs compiles it into the compiler so the JIT can map the symbols,
+// and `axon build` compiles the same file next to the object and links it into the binary
+
+#include <math.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+// row-major f32 tensor, rank 0 is a scalar with size 1.
+// every tensor also remembers the operation that produced it (the tape),
+// so backward() can walk the graph in reverse and fill in `grad`.
+// `refs` counts the variables and temporaries holding it plus the tensors computed from it,
+// a new tensor starts with the one reference its caller gets back
+typedef struct AxonTensor {
+    int64_t refs;
+    int64_t rank;
+    int64_t size;
+    int64_t *shape;
+    float *data;
+    float *grad;
+    int32_t node;
+    int32_t op;
+    int64_t axis;
+    struct AxonTensor *parents[2];
+    uint64_t visited;
+} AxonTensor;
+
+enum { AXON_ADD = 0, AXON_SUB = 1, AXON_MUL = 2, AXON_DIV = 3 };
+enum { AXON_SUM = 0, AXON_MEAN = 1, AXON_MAX = 2 };
+enum { AXON_RELU = 0, AXON_SIGMOID = 1, AXON_TANH = 2, AXON_EXP = 3 };
+
+// how a tensor was produced, `op` and `axis` hold the operation details
+enum {
+    NODE_LEAF = 0,
+    NODE_BINARY,
+    NODE_MATMUL,
+    NODE_RESHAPE,
+    NODE_TRANSPOSE,
+    NODE_REDUCE,
+    NODE_REDUCE_AXIS,
+    NODE_UNARY,
+    NODE_SOFTMAX,
+};
+
+// where a runtime error goes once its message is printed, exit() in a built program,
+// the JIT points it at axon_jit_exit (runtime/axon_jit.c) while a program or REPL entry runs
+void (*axon_runtime_exit)(int) = exit;
+
+// the .ax position of the tensor operation being run ("src/init.ax:3:9"), set by the caller before each one
+// so a runtime error here points at the source like the generated checks do, NULL when it is not known
+static const char *axon_tensor_location = NULL;
+
+void axon_tensor_at(const char *location) {
+    axon_tensor_location = location;
+}
+
+static void print_location(void) {
+    if (axon_tensor_location) {
+        printf("  --> %s\n", axon_tensor_location);
+    }
+}
+
+static void axon_runtime_fail(const char *message) {
+    printf("\n[ERR-RT-010] Runtime error: %s\n", message);
+    print_location();
+    axon_runtime_exit(1);
+}
+
+static void *axon_alloc(size_t bytes) {
+    void *ptr = calloc(1, bytes == 0 ? 1 : bytes);
+    if (!ptr) {
+        axon_runtime_fail("out of memory");
+    }
+    return ptr;
+}
+
+// grows a buffer or ends the program like axon_alloc, without leaking the old buffer to a JIT that goes on running
+static void *axon_realloc(void *ptr, size_t bytes) {
+    void *grown = realloc(ptr, bytes);
+    if (!grown) {
+        free(ptr);
+        axon_runtime_fail("out of memory");
+    }
+    return grown;
+}
+
+static AxonTensor *tensor_alloc(int64_t rank, const int64_t *shape) {
+    AxonTensor *t = axon_alloc(sizeof(AxonTensor));
+    t->refs = 1;
+    t->rank = rank;
+    t->shape = axon_alloc(sizeof(int64_t) * (size_t)rank);
+    t->size = 1;
+    for (int64_t i = 0; i < rank; i++) {
+        t->shape[i] = shape[i];
+        t->size *= shape[i];
+    }
+    t->data = axon_alloc(sizeof(float) * (size_t)t->size);
+    return t;
+}
+
+// the tape keeps the inputs alive for as long as `out` is
+static AxonTensor *record(AxonTensor *out, int32_t node, int32_t op, const AxonTensor *a, const AxonTensor *b) {
+    out->node = node;
+    out->op = op;
+    out->parents[0] = (AxonTensor *)a;
+    out->parents[1] = (AxonTensor *)b;
+    for (int i = 0; i < 2; i++) {
+        if (out->parents[i]) {
+            out->parents[i]->refs++;
+        }
+    }
+    return out;
+}
+
+// a variable taking another reference to `t`
+void axon_tensor_retain(AxonTensor *t) {
+    if (t) {
+        t->refs++;
+    }
+}
+
+// drops a reference, the last one frees `t` and lets go of the tensors it was computed from.
+// iterative like backward(), a graph that was never detached is as deep as the training loop is long
+void axon_tensor_release(AxonTensor *t) {
+    if (!t || --t->refs > 0) {
+        return;
+    }
+    size_t cap = 16, len = 0;
+    AxonTensor **dead = axon_alloc(sizeof(AxonTensor *) * cap);
+    dead[len++] = t;
+    while (len > 0) {
+        AxonTensor *d = dead[--len];
+        for (int i = 0; i < 2; i++) {
+            AxonTensor *p = d->parents[i];
+            if (!p || --p->refs > 0) {
+                continue;
+            }
+            if (len == cap) {
+                cap *= 2;
+                dead = axon_realloc(dead, sizeof(AxonTensor *) * cap);
+            }
+            dead[len++] = p;
+        }
+        free(d->shape);
+        free(d->data);
+        free(d->grad);
+        free(d);
+    }
+    free(dead);
+}
+
+AxonTensor *axon_tensor_fill(int64_t rank, const int64_t *shape, float value) {
+    AxonTensor *t = tensor_alloc(rank, shape);
+    for (int64_t i = 0; i < t->size; i++) {
+        t->data[i] = value;
+    }
+    return t;
+}
+
+AxonTensor *axon_tensor_from(int64_t rank, const int64_t *shape, const float *data) {
+    AxonTensor *t = tensor_alloc(rank, shape);
+    memcpy(t->data, data, sizeof(float) * (size_t)t->size);
+    return t;
+}
+
+// xorshift with a fixed seed, so a program initializes its weights the same way on every run
+static uint64_t axon_rand_state = 0x2545F4914F6CDD1DULL;
+
+AxonTensor *axon_tensor_rand(int64_t rank, const int64_t *shape) {
+    AxonTensor *t = tensor_alloc(rank, shape);
+    for (int64_t i = 0; i < t->size; i++) {
+        axon_rand_state ^= axon_rand_state << 13;
+        axon_rand_state ^= axon_rand_state >> 7;
+        axon_rand_state ^= axon_rand_state << 17;
+        t->data[i] = (float)((axon_rand_state >> 40) / (double)(1ULL << 24));
+    }
+    return t;
+}
+
+// elementwise op with numpy-style broadcasting: shapes are aligned from the right,
+// and a dimension of 1 is stretched to match the other operand
+AxonTensor *axon_tensor_binary(int32_t op, const AxonTensor *a, const AxonTensor *b) {
+    int64_t rank = a->rank > b->rank ? a->rank : b->rank;
+    int64_t *shape = axon_alloc(sizeof(int64_t) * (size_t)rank);
+    int64_t *a_strides = axon_alloc(sizeof(int64_t) * (size_t)rank);
+    int64_t *b_strides = axon_alloc(sizeof(int64_t) * (size_t)rank);
+    int64_t a_stride = 1, b_stride = 1;
+    for (int64_t i = rank - 1; i >= 0; i--) {
+        int64_t ai = i - (rank - a->rank);
+        int64_t bi = i - (rank - b->rank);
+        int64_t a_dim = ai >= 0 ? a->shape[ai] : 1;
+        int64_t b_dim = bi >= 0 ? b->shape[bi] : 1;
+        if (a_dim != b_dim && a_dim != 1 && b_dim != 1) {
+            axon_runtime_fail("tensor shapes cannot be broadcast together");
+        }
+        shape[i] = a_dim > b_dim ? a_dim : b_dim;
+        a_strides[i] = a_dim == 1 ? 0 : a_stride;
+        b_strides[i] = b_dim == 1 ? 0 : b_stride;
+        a_stride *= a_dim;
+        b_stride *= b_dim;
+    }
+    AxonTensor *out = tensor_alloc(rank, shape);
+    for (int64_t flat = 0; flat < out->size; flat++) {
+        int64_t rest = flat, a_off = 0, b_off = 0;
+        for (int64_t i = rank - 1; i >= 0; i--) {
+            int64_t idx = rest % shape[i];
+            rest /= shape[i];
+            a_off += idx * a_strides[i];
+            b_off += idx * b_strides[i];
+        }
+        float x = a->data[a_off], y = b->data[b_off];
+        switch (op) {
+        case AXON_ADD: out->data[flat] = x + y; break;
+        case AXON_SUB: out->data[flat] = x - y; break;
+        case AXON_MUL: out->data[flat] = x * y; break;
+        default: out->data[flat] = x / y; break;
+        }
+    }
+    free(shape);
+    free(a_strides);
+    free(b_strides);
+    return record(out, NODE_BINARY, op, a, b);
+}
+
+AxonTensor *axon_tensor_matmul(const AxonTensor *a, const AxonTensor *b) {
+    if (a->rank != 2 || b->rank != 2 || a->shape[1] != b->shape[0]) {
+        axon_runtime_fail("matmul needs [m, k] x [k, n] tensors");
+    }
+    int64_t m = a->shape[0], k = a->shape[1], n = b->shape[1];
+    int64_t shape[2] = {m, n};
+    AxonTensor *out = tensor_alloc(2, shape);
+    for (int64_t i = 0; i < m; i++) {
+        for (int64_t p = 0; p < k; p++) {
+            float x = a->data[i * k + p];
+            for (int64_t j = 0; j < n; j++) {
+                out->data[i * n + j] += x * b->data[p * n + j];
+            }
+        }
+    }
+    return record(out, NODE_MATMUL, 0, a, b);
+}
+
+AxonTensor *axon_tensor_reshape(const AxonTensor *t, int64_t rank, const int64_t *shape) {
+    AxonTensor *out = tensor_alloc(rank, shape);
+    if (out->size != t->size) {
+        axon_runtime_fail("reshape must keep the number of elements");
+    }
+    memcpy(out->data, t->data, sizeof(float) * (size_t)t->size);
+    return record(out, NODE_RESHAPE, 0, t, NULL);
+}
+
+AxonTensor *axon_tensor_transpose(const AxonTensor *t) {
+    if (t->rank != 2) {
+        axon_runtime_fail("transpose needs a 2-D tensor");
+    }
+    int64_t rows = t->shape[0], cols = t->shape[1];
+    int64_t shape[2] = {cols, rows};
+    AxonTensor *out = tensor_alloc(2, shape);
+    for (int64_t i = 0; i < rows; i++) {
+        for (int64_t j = 0; j < cols; j++) {
+            out->data[j * rows + i] = t->data[i * cols + j];
+        }
+    }
+    return record(out, NODE_TRANSPOSE, 0, t, NULL);
+}
+
+// reduces the whole tensor to a rank-0 one, which stays on the tape so a loss can be `t.mean()`
+AxonTensor *axon_tensor_reduce(int32_t op, const AxonTensor *t) {
+    float acc = op == AXON_MAX ? -INFINITY : 0.0f;
+    for (int64_t i = 0; i < t->size; i++) {
+        if (op == AXON_MAX) {
+            acc = t->data[i] > acc ? t->data[i] : acc;
+        } else {
+            acc += t->data[i];
+        }
+    }
+    if (op == AXON_MEAN && t->size > 0) {
+        acc /= (float)t->size;
+    }
+    AxonTensor *out = tensor_alloc(0, NULL);
+    out->data[0] = acc;
+    return record(out, NODE_REDUCE, op, t, NULL);
+}
+
+// reduces one axis away: [2, 3, 4] reduced over axis 1 gives [2, 4]
+AxonTensor *axon_tensor_reduce_axis(int32_t op, const AxonTensor *t, int64_t axis) {
+    if (axis < 0 || axis >= t->rank) {
+        axon_runtime_fail("reduction axis is out of range");
+    }
+    int64_t outer = 1, inner = 1, len = t->shape[axis];
+    int64_t *shape = axon_alloc(sizeof(int64_t) * (size_t)(t->rank > 1 ? t->rank - 1 : 1));
+    for (int64_t i = 0, j = 0; i < t->rank; i++) {
+        if (i < axis) {
+            outer *= t->shape[i];
+        } else if (i > axis) {
+            inner *= t->shape[i];
+        }
+        if (i != axis) {
+            shape[j++] = t->shape[i];
+        }
+    }
+    AxonTensor *out = tensor_alloc(t->rank - 1, shape);
+    free(shape);
+    for (int64_t o = 0; o < outer; o++) {
+        for (int64_t in = 0; in < inner; in++) {
+            float acc = op == AXON_MAX ? -INFINITY : 0.0f;
+            for (int64_t l = 0; l < len; l++) {
+                float x = t->data[(o * len + l) * inner + in];
+                if (op == AXON_MAX) {
+                    acc = x > acc ? x : acc;
+                } else {
+                    acc += x;
+                }
+            }
+            if (op == AXON_MEAN && len > 0) {
+                acc /= (float)len;
+            }
+            out->data[o * inner + in] = acc;
+        }
+    }
+    record(out, NODE_REDUCE_AXIS, op, t, NULL);
+    out->axis = axis;
+    return out;
+}
+
+AxonTensor *axon_tensor_unary(int32_t op, const AxonTensor *t) {
+    AxonTensor *out = tensor_alloc(t->rank, t->shape);
+    for (int64_t i = 0; i < t->size; i++) {
+        float x = t->data[i];
+        switch (op) {
+        case AXON_RELU: out->data[i] = x > 0.0f ? x : 0.0f; break;
+        case AXON_SIGMOID: out->data[i] = 1.0f / (1.0f + expf(-x)); break;
+        case AXON_TANH: out->data[i] = tanhf(x); break;
+        default: out->data[i] = expf(x); break;
+        }
+    }
+    return record(out, NODE_UNARY, op, t, NULL);
+}
+
+// softmax over the last axis, shifted by the row max for numerical stability
+AxonTensor *axon_tensor_softmax(const AxonTensor *t) {
+    AxonTensor *out = tensor_alloc(t->rank, t->shape);
+    int64_t len = t->rank > 0 ? t->shape[t->rank - 1] : 1;
+    for (int64_t row = 0; row + len <= t->size; row += len) {
+        float max = -INFINITY, sum = 0.0f;
+        for (int64_t i = 0; i < len; i++) {
+            max = t->data[row + i] > max ? t->data[row + i] : max;
+        }
+        for (int64_t i = 0; i < len; i++) {
+            out->data[row + i] = expf(t->data[row + i] - max);
+            sum += out->data[row + i];
+        }
+        for (int64_t i = 0; i < len; i++) {
+            out->data[row + i] /= sum;
+        }
+    }
+    return record(out, NODE_SOFTMAX, 0, t, NULL);
+}
+
+float axon_tensor_get(const AxonTensor *t, const int64_t *index) {
+    int64_t offset = 0;
+    for (int64_t i = 0; i < t->rank; i++) {
+        if (index[i] < 0 || index[i] >= t->shape[i]) {
+            printf("\n[ERR-RT-011] Runtime error: index %lld is out of bounds for tensor axis %lld of size %lld\n",
+                   (long long)index[i], (long long)i, (long long)t->shape[i]);
+            print_location();
+            axon_runtime_exit(1);
+        }
+        offset = offset * t->shape[i] + index[i];
+    }
+    return t->data[offset];
+}
+
+// the value of a one-element tensor, the semantic pass has checked the size
+float axon_tensor_item(const AxonTensor *t) {
+    return t->data[0];
+}
+
+static int64_t print_dim(const AxonTensor *t, int64_t dim, int64_t offset) {
+    if (dim == t->rank) {
+        printf("%f", t->data[offset]);
+        return offset + 1;
+    }
+    printf("[");
+    for (int64_t i = 0; i < t->shape[dim]; i++) {
+        if (i > 0) {
+            printf(", ");
+        }
+        offset = print_dim(t, dim + 1, offset);
+    }
+    printf("]");
+    return offset;
+}
+
+void axon_tensor_print(const AxonTensor *t, int32_t newline) {
+    print_dim(t, 0, 0);
+    if (newline) {
+        printf("\n");
+    }
+}
+
+// ---- reverse-mode autodiff ----
+
+static float *grad_of(AxonTensor *t) {
+    if (!t->grad) {
+        t->grad = axon_alloc(sizeof(float) * (size_t)t->size);
+    }
+    return t->grad;
+}
+
+// offsets of the two operands that produced element `flat` of a broadcast result
+static void broadcast_offsets(const AxonTensor *out, const AxonTensor *a, const AxonTensor *b, int64_t flat,
+                              int64_t *a_off, int64_t *b_off) {
+    int64_t a_stride = 1, b_stride = 1;
+    *a_off = 0;
+    *b_off = 0;
+    for (int64_t i = out->rank - 1; i >= 0; i--) {
+        int64_t idx = flat % out->shape[i];
+        flat /= out->shape[i];
+        int64_t ai = i - (out->rank - a->rank);
+        int64_t bi = i - (out->rank - b->rank);
+        int64_t a_dim = ai >= 0 ? a->shape[ai] : 1;
+        int64_t b_dim = bi >= 0 ? b->shape[bi] : 1;
+        *a_off += (a_dim == 1 ? 0 : idx) * a_stride;
+        *b_off += (b_dim == 1 ? 0 : idx) * b_stride;
+        a_stride *= a_dim;
+        b_stride *= b_dim;
+    }
+}
+
+// pushes t->grad into the grads of the tensors t was computed from
+static void backward_node(AxonTensor *t) {
+    const float *g = t->grad;
+    AxonTensor *a = t->parents[0];
+    AxonTensor *b = t->parents[1];
+    switch (t->node) {
+    case NODE_BINARY: {
+        float *ga = grad_of(a), *gb = grad_of(b);
+        for (int64_t flat = 0; flat < t->size; flat++) {
+            int64_t a_off, b_off;
+            broadcast_offsets(t, a, b, flat, &a_off, &b_off);
+            float x = a->data[a_off], y = b->data[b_off];
+            switch (t->op) {
+            case AXON_ADD: ga[a_off] += g[flat]; gb[b_off] += g[flat]; break;
+            case AXON_SUB: ga[a_off] += g[flat]; gb[b_off] -= g[flat]; break;
+            case AXON_MUL: ga[a_off] += g[flat] * y; gb[b_off] += g[flat] * x; break;
+            default: ga[a_off] += g[flat] / y; gb[b_off] -= g[flat] * x / (y * y); break;
+            }
+        }
+        break;
+    }
+    case NODE_MATMUL: {
+        // dA = dC * B^T, dB = A^T * dC
+        int64_t m = a->shape[0], k = a->shape[1], n = b->shape[1];
+        float *ga = grad_of(a), *gb = grad_of(b);
+        for (int64_t i = 0; i < m; i++) {
+            for (int64_t p = 0; p < k; p++) {
+                float acc = 0.0f;
+                for (int64_t j = 0; j < n; j++) {
+                    acc += g[i * n + j] * b->data[p * n + j];
+                    gb[p * n + j] += a->data[i * k + p] * g[i * n + j];
+                }
+                ga[i * k + p] += acc;
+            }
+        }
+        break;
+    }
+    case NODE_RESHAPE: {
+        float *ga = grad_of(a);
+        for (int64_t i = 0; i < t->size; i++) {
+            ga[i] += g[i];
+        }
+        break;
+    }
+    case NODE_TRANSPOSE: {
+        int64_t rows = a->shape[0], cols = a->shape[1];
+        float *ga = grad_of(a);
+        for (int64_t i = 0; i < rows; i++) {
+            for (int64_t j = 0; j < cols; j++) {
+                ga[i * cols + j] += g[j * rows + i];
+            }
+        }
+        break;
+    }
+    case NODE_REDUCE: {
+        float *ga = grad_of(a);
+        for (int64_t i = 0; i < a->size; i++) {
+            if (t->op == AXON_MAX) {
+                // only the first maximum receives the gradient
+                if (a->data[i] == t->data[0]) {
+                    ga[i] += g[0];
+                    break;
+                }
+            } else {
+                ga[i] += t->op == AXON_MEAN ? g[0] / (float)a->size : g[0];
+            }
+        }
+        break;
+    }
+    case NODE_REDUCE_AXIS: {
+        int64_t outer = 1, inner = 1, len = a->shape[t->axis];
+        for (int64_t i = 0; i < a->rank; i++) {
+            if (i < t->axis) {
+                outer *= a->shape[i];
+            } else if (i > t->axis) {
+                inner *= a->shape[i];
+            }
+        }
+        float *ga = grad_of(a);
+        for (int64_t o = 0; o < outer; o++) {
+            for (int64_t in = 0; in < inner; in++) {
+                float go = g[o * inner + in];
+                if (t->op == AXON_MAX) {
+                    // only the first maximum receives the gradient
+                    for (int64_t l = 0; l < len; l++) {
+                        int64_t idx = (o * len + l) * inner + in;
+                        if (a->data[idx] == t->data[o * inner + in]) {
+                            ga[idx] += go;
+                            break;
+                        }
+                    }
+                } else {
+                    float scale = t->op == AXON_MEAN ? 1.0f / (float)len : 1.0f;
+                    for (int64_t l = 0; l < len; l++) {
+                        ga[(o * len + l) * inner + in] += go * scale;
+                    }
+                }
+            }
+        }
+        break;
+    }
+    case NODE_UNARY: {
+        float *ga = grad_of(a);
+        for (int64_t i = 0; i < t->size; i++) {
+            float x = a->data[i], y = t->data[i];
+            switch (t->op) {
+            case AXON_RELU: ga[i] += x > 0.0f ? g[i] : 0.0f; break;
+            case AXON_SIGMOID: ga[i] += g[i] * y * (1.0f - y); break;
+            case AXON_TANH: ga[i] += g[i] * (1.0f - y * y); break;
+            default: ga[i] += g[i] * y; break;
+            }
+        }
+        break;
+    }
+    case NODE_SOFTMAX: {
+        int64_t len = t->rank > 0 ? t->shape[t->rank - 1] : 1;
+        float *ga = grad_of(a);
+        for (int64_t row = 0; row + len <= t->size; row += len) {
+            float dot = 0.0f;
+            for (int64_t i = 0; i < len; i++) {
+                dot += g[row + i] * t->data[row + i];
+            }
+            for (int64_t i = 0; i < len; i++) {
+                ga[row + i] += t->data[row + i] * (g[row + i] - dot);
+            }
+        }
+        break;
+    }
+    default:
+        break;
+    }
+}
+
+static uint64_t axon_tape_epoch = 0;
+
+// differentiates the sum of all elements of `loss` (just the value itself for a single-element loss).
+// the whole graph is walked again on every call, so gradients are recomputed rather than accumulated
+void axon_tensor_backward(AxonTensor *loss) {
+    axon_tape_epoch++;
+
+    // iterative post-order walk, long training loops build deep graphs
+    size_t cap = 64, len = 0, order_len = 0;
+    AxonTensor **stack = axon_alloc(sizeof(AxonTensor *) * cap);
+    uint8_t *expanded = axon_alloc(cap);
+    AxonTensor **order = axon_alloc(sizeof(AxonTensor *) * cap);
+    size_t order_cap = cap;
+    stack[len] = loss;
+    expanded[len++] = 0;
+    while (len > 0) {
+        AxonTensor *t = stack[len - 1];
+        if (expanded[len - 1]) {
+            len--;
+            if (order_len == order_cap) {
+                order_cap *= 2;
+                order = axon_realloc(order, sizeof(AxonTensor *) * order_cap);
+            }
+            order[order_len++] = t;
+            continue;
+        }
+        // a tensor shared by several paths can sit on the stack more than once
+        if (t->visited == axon_tape_epoch) {
+            len--;
+            continue;
+        }
+        t->visited = axon_tape_epoch;
+        expanded[len - 1] = 1;
+        for (int i = 0; i < 2; i++) {
+            AxonTensor *p = t->parents[i];
+            if (!p || p->visited == axon_tape_epoch) {
+                continue;
+            }
+            if (len == cap) {
+                cap *= 2;
+                stack = axon_realloc(stack, sizeof(AxonTensor *) * cap);
+                expanded = axon_realloc(expanded, cap);
+            }
+            stack[len] = p;
+            expanded[len++] = 0;
+        }
+    }
+
+    for (size_t i = 0; i < order_len; i++) {
+        memset(grad_of(order[i]), 0, sizeof(float) * (size_t)order[i]->size);
+    }
+    for (int64_t i = 0; i < loss->size; i++) {
+        loss->grad[i] = 1.0f;
+    }
+    // `order` lists every tensor after its inputs, so walking it backwards visits outputs first
+    for (size_t i = order_len; i > 0; i--) {
+        backward_node(order[i - 1]);
+    }
+    free(stack);
+    free(expanded);
+    free(order);
+}
+
+// the gradient left on `t` by the last backward(), zeros if `t` took no part in it
+AxonTensor *axon_tensor_grad(const AxonTensor *t) {
+    AxonTensor *out = tensor_alloc(t->rank, t->shape);
+    if (t->grad && t->visited == axon_tape_epoch) {
+        memcpy(out->data, t->grad, sizeof(float) * (size_t)t->size);
+    }
+    return out;
+}
+
+// `grad(loss, w)`: d(loss)/d(w) in one step
+AxonTensor *axon_tensor_grad_of(AxonTensor *loss, const AxonTensor *t) {
+    axon_tensor_backward(loss);
+    return axon_tensor_grad(t);
+}
+
+// a copy cut off from the tape, used for parameter updates so the graph does not keep growing
+AxonTensor *axon_tensor_detach(const AxonTensor *t) {
+    return axon_tensor_from(t->rank, t->shape, t->data);
+}
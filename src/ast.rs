@@ -1,6 +1,9 @@
 //AST (abstract syntax tree) is what the code turns into after parsing
 //its a tree structure that shows the syntactic structure of the program
 
+// byte range in the source file, straight from the lexer
+pub type Span = std::ops::Range<usize>;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     I32,
@@ -10,6 +13,7 @@ pub enum Type {
     String,
     Bool,
     Vector(Box<Type>),
+    Tensor(Box<Type>, Vec<usize>),
 }
 #[derive(Debug, Clone)]
 pub enum Expr {
@@ -26,6 +30,36 @@ pub enum Expr {
         op: Operator,
         right: Box<Expr>,
     },
+    Index {
+        target: Box<Expr>,
+        index: Box<Expr>,
+    },
+    Call {
+        name: String,
+        args: Vec<Expr>,
+    },
+    MethodCall {
+        target: Box<Expr>,
+        method: String,
+        args: Vec<Expr>,
+    },
+    Not {
+        expr: Box<Expr>,
+    },
+    // where the wrapped expression came from, literals are left unwrapped
+    Located {
+        span: Span,
+        expr: Box<Expr>,
+    },
+}
+
+impl Expr {
+    pub fn unlocated(&self) -> &Expr {
+        match self {
+            Expr::Located { expr, .. } => expr.unlocated(),
+            other => other,
+        }
+    }
 }
 #[derive(Debug, Clone)]
 pub enum Statement {
@@ -37,7 +71,8 @@ pub enum Statement {
     },
     FunctionCall {
         name: String,
-        params: Vec<Expr>,
+        params: Vec<(String, Type)>,
+        return_type: Option<Type>,
         start: bool,
         body: Vec<Statement>,
     },
@@ -46,14 +81,18 @@ pub enum Statement {
     },
 
     Do(Box<Statement>),
+    // every parsed statement is wrapped in its source span
+    Located {
+        span: Span,
+        stmt: Box<Statement>,
+    },
     Math {
         expression: Expr,
         destination: String,
         err: Option<String>,
     },
     If {
-        logic: Logic,
-        args: Vec<Expr>,
+        condition: Expr,
         body: Vec<Statement>,
         else_body: Option<Vec<Statement>>,
     },
@@ -61,15 +100,43 @@ pub enum Statement {
         body: Vec<Statement>,
     },
     While {
-        logic: Logic,
-        args: Vec<Expr>,
+        condition: Expr,
         body: Vec<Statement>,
     },
     Break,
+    Return {
+        value: Option<Expr>,
+    },
+    IndexAssign {
+        name: String,
+        index: Expr,
+        value: Expr,
+    },
+    ExprStatement {
+        expr: Expr,
+    },
     Input {
         target: Expr,
         err: Option<String>,
     },
+    // `assert(a < b);` / `assert_eq(a, b);`, keeps its source text for the failure message
+    Assert {
+        logic: Logic,
+        args: Vec<Expr>,
+        source: String,
+    },
+    // `use util.calc;`, loads src/util/calc.ax, resolved away by the module loader
+    Use {
+        path: Vec<String>,
+    },
+}
+impl Statement {
+    pub fn unlocated(&self) -> &Statement {
+        match self {
+            Statement::Located { stmt, .. } => stmt.unlocated(),
+            other => other,
+        }
+    }
 }
 #[derive(Debug, Clone, Copy)]
 pub enum Logic {
@@ -86,4 +153,8 @@ pub enum Operator {
     Minus,
     Multiply,
     Divide,
+    Modulo,
+    Compare(Logic),
+    And,
+    Or,
 }
@@ -0,0 +1,110 @@
+//what the two backends of `run` have in common, kept out of compiler_neuron
+//so the interpreter still builds without the `llvm` feature:
+//the errors both report and the options a compile is described with
+
+use crate::ast::Span;
+use std::fmt;
+
+// the span points at the statement that failed to compile, when there is one
+#[derive(Debug)]
+pub struct CompilerError(pub String, pub Option<Span>);
+
+impl fmt::Display for CompilerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Compiler Error: {}", self.0)
+    }
+}
+
+impl From<String> for CompilerError {
+    fn from(error: String) -> Self {
+        CompilerError(error, None)
+    }
+}
+
+pub type CompileResult<T> = Result<T, Vec<CompilerError>>;
+
+// what `--emit=` asks the build to stop at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmitKind {
+    LlvmIr,
+    Bitcode,
+    Asm,
+    Obj,
+    Exe,
+}
+
+impl EmitKind {
+    pub fn from_flag(value: &str) -> Option<Self> {
+        match value {
+            "llvm-ir" => Some(EmitKind::LlvmIr),
+            "bc" => Some(EmitKind::Bitcode),
+            "asm" => Some(EmitKind::Asm),
+            "obj" => Some(EmitKind::Obj),
+            "exe" => Some(EmitKind::Exe),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmitKind::LlvmIr => "llvm-ir",
+            EmitKind::Bitcode => "bc",
+            EmitKind::Asm => "asm",
+            EmitKind::Obj => "obj",
+            EmitKind::Exe => "exe",
+        }
+    }
+
+    pub fn extension(&self, target_triple: &str) -> &'static str {
+        let windows = target_triple.contains("windows");
+        match self {
+            EmitKind::LlvmIr => "ll",
+            EmitKind::Bitcode => "bc",
+            EmitKind::Asm if windows => "asm",
+            EmitKind::Asm => "s",
+            EmitKind::Obj | EmitKind::Exe if windows => "obj",
+            EmitKind::Obj | EmitKind::Exe => "o",
+        }
+    }
+}
+
+// the LLVM pipeline it selects is in compiler_pass_pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OptLevel {
+    O0,
+    O1,
+    #[default]
+    O2,
+    O3,
+    // like O2, but keeps code size down
+    Os,
+}
+
+impl OptLevel {
+    // `-O2` on the command line
+    pub fn from_flag(flag: &str) -> Option<Self> {
+        flag.strip_prefix("-O").and_then(Self::parse)
+    }
+
+    // `2` or `s`, as written in _opt_level_
+    pub fn parse(level: &str) -> Option<Self> {
+        match level {
+            "0" => Some(OptLevel::O0),
+            "1" => Some(OptLevel::O1),
+            "2" => Some(OptLevel::O2),
+            "3" => Some(OptLevel::O3),
+            "s" => Some(OptLevel::Os),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OptLevel::O0 => "O0",
+            OptLevel::O1 => "O1",
+            OptLevel::O2 => "O2",
+            OptLevel::O3 => "O3",
+            OptLevel::Os => "Os",
+        }
+    }
+}
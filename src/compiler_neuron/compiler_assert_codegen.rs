@@ -0,0 +1,88 @@
+//llvm ir generation for assert / assert_eq,
+//a failed assertion prints where it happened and both values, then exits with code 1
+//(that is what `axon test` looks at)
+
+use super::compiler_context::Compiler;
+use crate::high_level_ir::{HIROperator, HIRStatement, HIRType};
+use llvm_sys::core::*;
+use llvm_sys::prelude::LLVMValueRef;
+use llvm_sys::{LLVMIntPredicate, LLVMRealPredicate};
+
+pub fn codegen_assert(c: &mut Compiler, stmt: &HIRStatement) -> Result<(), String> {
+    unsafe {
+        let fun = c.current_function.ok_or("[ERR-SEM-524]")?;
+
+        let HIRStatement::Assert { left, op, right, ty, source } = stmt else {
+            return Err("[ERR-SEM-546] Provided statement is not an assertion".into());
+        };
+
+        let (left_val, _) = c.codegen_expr(left)?;
+        let (right_val, _) = c.codegen_expr(right)?;
+
+        let passed = match ty {
+            HIRType::F32 | HIRType::F64 => {
+                let pred = match op {
+                    HIROperator::Equals => LLVMRealPredicate::LLVMRealOEQ,
+                    HIROperator::NotEquals => LLVMRealPredicate::LLVMRealONE,
+                    HIROperator::GreaterThan => LLVMRealPredicate::LLVMRealOGT,
+                    HIROperator::LessThan => LLVMRealPredicate::LLVMRealOLT,
+                    HIROperator::GreaterEqual => LLVMRealPredicate::LLVMRealOGE,
+                    _ => LLVMRealPredicate::LLVMRealOLE,
+                };
+                LLVMBuildFCmp(c.builder, pred, left_val, right_val, b"assert_cmp\0".as_ptr() as _)
+            }
+            _ => {
+                let pred = match op {
+                    HIROperator::Equals => LLVMIntPredicate::LLVMIntEQ,
+                    HIROperator::NotEquals => LLVMIntPredicate::LLVMIntNE,
+                    HIROperator::GreaterThan => LLVMIntPredicate::LLVMIntSGT,
+                    HIROperator::LessThan => LLVMIntPredicate::LLVMIntSLT,
+                    HIROperator::GreaterEqual => LLVMIntPredicate::LLVMIntSGE,
+                    _ => LLVMIntPredicate::LLVMIntSLE,
+                };
+                LLVMBuildICmp(c.builder, pred, left_val, right_val, b"assert_cmp\0".as_ptr() as _)
+            }
+        };
+
+        let fail_bb = LLVMAppendBasicBlockInContext(c.context, fun, b"assert.fail\0".as_ptr() as _);
+        let ok_bb = LLVMAppendBasicBlockInContext(c.context, fun, b"assert.ok\0".as_ptr() as _);
+        LLVMBuildCondBr(c.builder, passed, ok_bb, fail_bb);
+
+        LLVMPositionBuilderAtEnd(c.builder, fail_bb);
+        let spec = match ty {
+            HIRType::I64 => "%lld",
+            HIRType::F32 | HIRType::F64 => "%f",
+            _ => "%d",
+        };
+        let format = format!(
+            "\n[ERR-RT-020] Assertion failed: {}\n  left:  {}\n  right: {}\n",
+            source.replace('%', "%%"),
+            spec,
+            spec
+        );
+        let args = [printable(c, left_val, ty), printable(c, right_val, ty)];
+        c.build_runtime_error(&format, &args);
+
+        LLVMPositionBuilderAtEnd(c.builder, ok_bb);
+        Ok(())
+    }
+}
+
+// printf wants doubles for floats and at least an int for bools
+unsafe fn printable(c: &Compiler, value: LLVMValueRef, ty: &HIRType) -> LLVMValueRef { unsafe {
+    match ty {
+        HIRType::F32 => LLVMBuildFPExt(
+            c.builder,
+            value,
+            LLVMDoubleTypeInContext(c.context),
+            b"fpext\0".as_ptr() as _,
+        ),
+        HIRType::Bool => LLVMBuildZExt(
+            c.builder,
+            value,
+            LLVMInt32TypeInContext(c.context),
+            b"zext\0".as_ptr() as _,
+        ),
+        _ => value,
+    }
+}}
@@ -2,7 +2,11 @@
 //handles context/module/builder, tracks vars & funcs,
 //and converts hir expressions into llvm ir
 
-use crate::high_level_ir::{HIRExpr, HIRType};
+use super::compiler_debug_info::DebugInfo;
+use super::compiler_tensor_codegen::{codegen_tensor_release, owned_tensor};
+use crate::ast::Span;
+use crate::module_loader::SourceMap;
+use crate::high_level_ir::{HIRExpr, HIROperator, HIRType};
 use llvm_sys::LLVMLinkage;
 use llvm_sys::core::*;
 use llvm_sys::prelude::*;
@@ -19,6 +23,18 @@ pub struct Compiler {
     pub current_function: Option<LLVMValueRef>,
     pub string_counter: usize,
     pub break_targets: Vec<LLVMBasicBlockRef>,
+    // entry blocks of the enclosing `.Err(...)` handlers, innermost last
+    pub error_handlers: Vec<LLVMBasicBlockRef>,
+    pub current_span: Option<Span>,
+    // runtime overflow, division and conversion checks (`--checked`)
+    pub checked: bool,
+    pub sources: SourceMap,
+    // set with `-g`
+    pub debug: Option<DebugInfo>,
+    // the `axon repl` entry being compiled, its variables become globals the next entry can reach
+    pub repl_entry: Option<LLVMValueRef>,
+    // allocas of the tensor variables and parameters of the function being compiled
+    pub tensor_locals: Vec<LLVMValueRef>,
 }
 
 impl Compiler {
@@ -49,6 +65,13 @@ impl Compiler {
                 current_function: None,
                 string_counter: 0,
                 break_targets: Vec::new(),
+                error_handlers: Vec::new(),
+                current_span: None,
+                checked: false,
+                sources: SourceMap::default(),
+                debug: None,
+                repl_entry: None,
+                tensor_locals: Vec::new(),
             }
         }
     }
@@ -71,12 +94,157 @@ impl Compiler {
                 HIRType::F32 => LLVMFloatTypeInContext(self.context),
                 HIRType::F64 => LLVMDoubleTypeInContext(self.context),
                 HIRType::Bool => LLVMInt1TypeInContext(self.context),
-                HIRType::String => LLVMPointerType(LLVMInt8TypeInContext(self.context), 0),
+                HIRType::String | HIRType::Vector(_) | HIRType::Tensor(_) => {
+                    LLVMPointerType(LLVMInt8TypeInContext(self.context), 0)
+                }
                 HIRType::Void => LLVMVoidTypeInContext(self.context),
             }
         }
     }
 
+    // vectors live on the heap as { i64 len, i64 cap, ptr data }
+    pub fn vector_header_type(&self) -> LLVMTypeRef {
+        unsafe {
+            let i64_type = LLVMInt64TypeInContext(self.context);
+            let ptr_type = LLVMPointerType(LLVMInt8TypeInContext(self.context), 0);
+            let mut fields = [i64_type, i64_type, ptr_type];
+            LLVMStructTypeInContext(self.context, fields.as_mut_ptr(), 3, 0)
+        }
+    }
+
+    // allocas go to the top of the entry block so code inside loops doesn't grow the stack
+    pub fn build_entry_alloca(&self, ty: LLVMTypeRef, name: &str) -> Result<LLVMValueRef, String> {
+        unsafe {
+            let fun = self
+                .current_function
+                .ok_or("\x1b[31m[ERR-SEM-670] Cannot allocate outside of a function\x1b[0m")?;
+            let entry = LLVMGetEntryBasicBlock(fun);
+            let tmp_builder = LLVMCreateBuilderInContext(self.context);
+            let first = LLVMGetFirstInstruction(entry);
+            if first.is_null() {
+                LLVMPositionBuilderAtEnd(tmp_builder, entry);
+            } else {
+                LLVMPositionBuilderBefore(tmp_builder, first);
+            }
+            let name_c = CString::new(name).unwrap();
+            let alloca = LLVMBuildAlloca(tmp_builder, ty, name_c.as_ptr());
+            LLVMDisposeBuilder(tmp_builder);
+            Ok(alloca)
+        }
+    }
+
+    // `name` is how AxonScript calls it, `symbol` the name in the module (`Start` is `main`)
+    pub fn declare_function(&mut self, name: &str, symbol: &str, params: &[HIRType], return_type: HIRType) -> LLVMValueRef {
+        unsafe {
+            let mut param_types: Vec<_> = params.iter().map(|ty| self.hir_type_to_llvm_type(ty)).collect();
+            let func_type = LLVMFunctionType(
+                self.hir_type_to_llvm_type(&return_type),
+                param_types.as_mut_ptr(),
+                param_types.len() as u32,
+                0,
+            );
+            let symbol_c = CString::new(symbol).unwrap();
+            let llvm_func = LLVMAddFunction(self.module, symbol_c.as_ptr(), func_type);
+            self.functions.insert(name.to_string(), (llvm_func, func_type, return_type));
+            llvm_func
+        }
+    }
+
+    // REPL globals get a prefix so `set: printf(i32) = 1;` does not clash with a C symbol
+    pub fn global_name(&self, name: &str) -> String {
+        match self.repl_entry {
+            Some(_) => format!("repl.{}", name),
+            None => name.to_string(),
+        }
+    }
+
+    pub fn get_or_declare_function(&self, name: &str, func_type: LLVMTypeRef) -> LLVMValueRef {
+        unsafe {
+            let name_c = CString::new(name).unwrap();
+            let f = LLVMGetNamedFunction(self.module, name_c.as_ptr());
+            if f.is_null() {
+                LLVMAddFunction(self.module, name_c.as_ptr(), func_type)
+            } else {
+                f
+            }
+        }
+    }
+
+    // prints the message with printf and terminates the program with exit code 1,
+    // the current block is closed with `unreachable`
+    pub fn build_runtime_error(&mut self, format: &str, args: &[LLVMValueRef]) {
+        unsafe {
+            let (printf_func, printf_type, _) = self.functions.get("printf").cloned().unwrap();
+            let located = self
+                .current_span
+                .as_ref()
+                .and_then(|span| self.sources.position(span.start))
+                .map(|position| format!("{}  --> {}\n", format, position.replace('%', "%%")));
+            let format_c = CString::new(located.as_deref().unwrap_or(format)).unwrap();
+            let fmt_ptr = LLVMBuildGlobalStringPtr(
+                self.builder,
+                format_c.as_ptr(),
+                b"rt_err_fmt\0".as_ptr() as _,
+            );
+            let mut printf_args = vec![fmt_ptr];
+            printf_args.extend_from_slice(args);
+            LLVMBuildCall2(
+                self.builder,
+                printf_type,
+                printf_func,
+                printf_args.as_mut_ptr(),
+                printf_args.len() as u32,
+                b"rt_err_print\0".as_ptr() as _,
+            );
+
+            let i32_type = LLVMInt32TypeInContext(self.context);
+            let exit_type = LLVMFunctionType(
+                LLVMVoidTypeInContext(self.context),
+                [i32_type].as_ptr() as *mut _,
+                1,
+                0,
+            );
+            let exit_func = self.get_or_declare_function("exit", exit_type);
+            let mut exit_args = [LLVMConstInt(i32_type, 1, 0)];
+            LLVMBuildCall2(
+                self.builder,
+                exit_type,
+                exit_func,
+                exit_args.as_mut_ptr(),
+                1,
+                b"\0".as_ptr() as _,
+            );
+            LLVMBuildUnreachable(self.builder);
+        }
+    }
+
+    // whether math, conversions and input get runtime checks here: inside a `.Err(...)` handler
+    // or in checked mode, and never in global initializers (they have to stay constant)
+    pub fn runtime_checks(&self) -> bool {
+        self.current_function.is_some() && (self.checked || !self.error_handlers.is_empty())
+    }
+
+    // when `failed` is set, jumps to the innermost `.Err(...)` handler or, without one,
+    // stops the program with `trap`, code after this is the ok path
+    pub fn build_guard(&mut self, failed: LLVMValueRef, trap: &str) {
+        unsafe {
+            let fun = LLVMGetBasicBlockParent(LLVMGetInsertBlock(self.builder));
+            let ok_bb = LLVMAppendBasicBlockInContext(self.context, fun, b"guard.ok\0".as_ptr() as _);
+            match self.error_handlers.last() {
+                Some(&handler) => {
+                    LLVMBuildCondBr(self.builder, failed, handler, ok_bb);
+                }
+                None => {
+                    let trap_bb = LLVMAppendBasicBlockInContext(self.context, fun, b"guard.trap\0".as_ptr() as _);
+                    LLVMBuildCondBr(self.builder, failed, trap_bb, ok_bb);
+                    LLVMPositionBuilderAtEnd(self.builder, trap_bb);
+                    self.build_runtime_error(trap, &[]);
+                }
+            }
+            LLVMPositionBuilderAtEnd(self.builder, ok_bb);
+        }
+    }
+
     pub fn codegen_expr(&mut self, expr: &HIRExpr) -> Result<(LLVMValueRef, HIRType), String> {
         unsafe {
             match expr {
@@ -135,9 +303,16 @@ impl Compiler {
                         LLVMBuildLoad2(self.builder, var_type_ref, *ptr, name_c.as_ptr());
                     Ok((loaded_val, ty.clone()))
                 }
+                HIRExpr::BinaryOp { left, op: op @ (HIROperator::And | HIROperator::Or), right } => {
+                    super::compiler_if_codegen::codegen_logical(self, left, op, right)
+                }
                 HIRExpr::BinaryOp { left, op, right } => {
                     super::compiler_math_codegen::codegen_math_expr(self, left, op, right, None)
                 }
+                HIRExpr::Not { expr } => {
+                    let (val, _) = self.codegen_expr(expr)?;
+                    Ok((LLVMBuildNot(self.builder, val, b"not\0".as_ptr() as _), HIRType::Bool))
+                }
                 HIRExpr::FunctionCall { name, args } => {
                     let (func, func_type, return_hir_type) = self
                         .functions
@@ -145,18 +320,28 @@ impl Compiler {
                         .cloned()
                         .ok_or_else(|| format!("Unknown function: {}", name))?;
                     let mut arg_values = Vec::with_capacity(args.len());
+                    let mut temporaries = Vec::new();
                     for arg_expr in args {
-                        let (arg_val, _) = self.codegen_expr(arg_expr)?;
+                        let (arg_val, arg_ty) = self.codegen_expr(arg_expr)?;
+                        if owned_tensor(arg_expr, &arg_ty) {
+                            temporaries.push(arg_val);
+                        }
                         arg_values.push(arg_val);
                     }
+                    // llvm does not allow naming a call that returns void
+                    let call_name: &[u8] = if return_hir_type == HIRType::Void { b"\0" } else { b"call\0" };
                     let call = LLVMBuildCall2(
                         self.builder,
                         func_type,
                         func,
                         arg_values.as_mut_ptr(),
                         arg_values.len() as u32,
-                        b"call\0".as_ptr() as *const _,
+                        call_name.as_ptr() as *const _,
                     );
+                    // a tensor parameter takes its own reference
+                    for temporary in temporaries {
+                        codegen_tensor_release(self, temporary);
+                    }
                     Ok((call, return_hir_type))
                 }
                 HIRExpr::Coerce { expr, target } => {
@@ -181,6 +366,54 @@ impl Compiler {
                         (HIRType::F32, HIRType::F64) => {
                             LLVMBuildFPExt(self.builder, val, to_llvm_ty, b"fpext\0".as_ptr() as _)
                         }
+                        (HIRType::I32 | HIRType::I64, HIRType::F32) => LLVMBuildSIToFP(
+                            self.builder,
+                            val,
+                            to_llvm_ty,
+                            b"sitofp\0".as_ptr() as _,
+                        ),
+                        (HIRType::F64, HIRType::F32) => {
+                            if self.runtime_checks() {
+                                // finite doubles past f32::MAX would silently become inf
+                                let max = LLVMConstReal(LLVMTypeOf(val), f32::MAX as f64);
+                                let min = LLVMConstReal(LLVMTypeOf(val), f32::MIN as f64);
+                                let too_big = LLVMBuildFCmp(
+                                    self.builder,
+                                    llvm_sys::LLVMRealPredicate::LLVMRealOGT,
+                                    val,
+                                    max,
+                                    b"too_big\0".as_ptr() as _,
+                                );
+                                let too_small = LLVMBuildFCmp(
+                                    self.builder,
+                                    llvm_sys::LLVMRealPredicate::LLVMRealOLT,
+                                    val,
+                                    min,
+                                    b"too_small\0".as_ptr() as _,
+                                );
+                                let out_of_range = LLVMBuildOr(self.builder, too_big, too_small, b"f32_range\0".as_ptr() as _);
+                                self.build_guard(out_of_range, "\n[ERR-RT-032] Runtime error: value is out of range for f32\n");
+                            }
+                            LLVMBuildFPTrunc(self.builder, val, to_llvm_ty, b"fptrunc\0".as_ptr() as _)
+                        }
+                        (HIRType::I64, HIRType::I32) => {
+                            let narrowed = LLVMBuildTrunc(self.builder, val, to_llvm_ty, b"trunc\0".as_ptr() as _);
+                            if self.runtime_checks() {
+                                let widened = LLVMBuildSExt(self.builder, narrowed, LLVMTypeOf(val), b"sext\0".as_ptr() as _);
+                                let lost = LLVMBuildICmp(
+                                    self.builder,
+                                    llvm_sys::LLVMIntPredicate::LLVMIntNE,
+                                    widened,
+                                    val,
+                                    b"i32_range\0".as_ptr() as _,
+                                );
+                                self.build_guard(lost, "\n[ERR-RT-032] Runtime error: value is out of range for i32\n");
+                            }
+                            narrowed
+                        }
+                        (HIRType::Bool, HIRType::I32 | HIRType::I64) => {
+                            LLVMBuildZExt(self.builder, val, to_llvm_ty, b"zext\0".as_ptr() as _)
+                        }
                         _ => {
                             return Err(format!(
                                 "[ERR-SEM-511] Unsupported coercion from {:?} to {:?}",
@@ -190,6 +423,27 @@ impl Compiler {
                     };
                     Ok((casted, target.clone()))
                 }
+                HIRExpr::Vector { elements, elem_type } => {
+                    super::compiler_vector_codegen::codegen_vector_literal(self, elements, elem_type)
+                }
+                HIRExpr::Index { target, index } => {
+                    super::compiler_vector_codegen::codegen_index(self, target, index)
+                }
+                HIRExpr::VectorLen { target } => {
+                    super::compiler_vector_codegen::codegen_len(self, target)
+                }
+                HIRExpr::VectorPush { target, value } => {
+                    super::compiler_vector_codegen::codegen_push(self, target, value)
+                }
+                HIRExpr::VectorPop { target } => {
+                    super::compiler_vector_codegen::codegen_pop(self, target)
+                }
+                HIRExpr::Tensor { elements, shape } => {
+                    super::compiler_tensor_codegen::codegen_tensor_literal(self, elements, shape)
+                }
+                HIRExpr::TensorCall { func, args, ty } => {
+                    super::compiler_tensor_codegen::codegen_tensor_call(self, func, args, ty)
+                }
             }
         }
     }
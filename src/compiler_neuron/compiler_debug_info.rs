@@ -0,0 +1,277 @@
+// dwarf debug info (`axon build -g`),
+// a compile unit for the program, a subprogram per function and a declared variable per local,
+// every statement sets the builder location from its span so gdb/lldb can step through .ax files
+
+use super::compiler_context::Compiler;
+use crate::ast::Span;
+use crate::high_level_ir::HIRType;
+use crate::module_loader::SourceMap;
+use llvm_sys::core::*;
+use llvm_sys::debuginfo::*;
+use llvm_sys::prelude::*;
+use llvm_sys::LLVMModuleFlagBehavior;
+use std::collections::HashMap;
+use std::ptr::null_mut;
+
+// DW_ATE_* from the DWARF spec
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+const DW_ATE_UNSIGNED_CHAR: u32 = 0x08;
+
+pub struct DebugInfo {
+    builder: LLVMDIBuilderRef,
+    files: HashMap<String, LLVMMetadataRef>,
+    // the project directory, file paths in the source map are relative to it
+    directory: String,
+    // subprogram of the function being generated, None at the top level
+    scope: Option<LLVMMetadataRef>,
+}
+
+impl DebugInfo {
+    pub fn new(module: LLVMModuleRef, context: LLVMContextRef, sources: &SourceMap, optimized: bool) -> Self {
+        unsafe {
+            let i32_type = LLVMInt32TypeInContext(context);
+            let dwarf_version = LLVMValueAsMetadata(LLVMConstInt(i32_type, 4, 0));
+            let debug_version = LLVMValueAsMetadata(LLVMConstInt(i32_type, LLVMDebugMetadataVersion() as u64, 0));
+            let behavior = LLVMModuleFlagBehavior::LLVMModuleFlagBehaviorWarning;
+            LLVMAddModuleFlag(module, behavior, b"Dwarf Version\0".as_ptr() as *const _, 13, dwarf_version);
+            LLVMAddModuleFlag(module, behavior, b"Debug Info Version\0".as_ptr() as *const _, 18, debug_version);
+
+            let builder = LLVMCreateDIBuilder(module);
+            let directory = std::env::current_dir().map_or(".".to_string(), |d| d.display().to_string());
+            let mut debug = DebugInfo {
+                builder,
+                files: HashMap::new(),
+                directory,
+                scope: None,
+            };
+            let entry = sources.entry().map_or("init.ax".to_string(), |f| f.display_path());
+            let file = debug.file(&entry);
+            let producer = format!("AxonScript {}", env!("CARGO_PKG_VERSION"));
+            // there is no DWARF language code for AxonScript, C keeps debuggers on familiar ground
+            LLVMDIBuilderCreateCompileUnit(
+                builder,
+                LLVMDWARFSourceLanguage::LLVMDWARFSourceLanguageC,
+                file,
+                producer.as_ptr() as *const _,
+                producer.len(),
+                optimized as LLVMBool,
+                b"\0".as_ptr() as *const _,
+                0,
+                0,
+                b"\0".as_ptr() as *const _,
+                0,
+                LLVMDWARFEmissionKind::LLVMDWARFEmissionKindFull,
+                0,
+                0,
+                0,
+                b"\0".as_ptr() as *const _,
+                0,
+                b"\0".as_ptr() as *const _,
+                0,
+            );
+            debug
+        }
+    }
+
+    fn file(&mut self, path: &str) -> LLVMMetadataRef {
+        if let Some(file) = self.files.get(path) {
+            return *file;
+        }
+        let file = unsafe {
+            LLVMDIBuilderCreateFile(
+                self.builder,
+                path.as_ptr() as *const _,
+                path.len(),
+                self.directory.as_ptr() as *const _,
+                self.directory.len(),
+            )
+        };
+        self.files.insert(path.to_string(), file);
+        file
+    }
+
+    fn type_of(&self, ty: &HIRType) -> LLVMMetadataRef {
+        let basic = |name: &str, bits: u64, encoding: u32| unsafe {
+            LLVMDIBuilderCreateBasicType(
+                self.builder,
+                name.as_ptr() as *const _,
+                name.len(),
+                bits,
+                encoding,
+                LLVMDIFlagZero,
+            )
+        };
+        // strings, vectors and tensors are all a pointer to bytes in the generated code
+        let pointer = |name: &str| unsafe {
+            LLVMDIBuilderCreatePointerType(
+                self.builder,
+                basic("u8", 8, DW_ATE_UNSIGNED_CHAR),
+                64,
+                0,
+                0,
+                name.as_ptr() as *const _,
+                name.len(),
+            )
+        };
+        match ty {
+            HIRType::I32 => basic("i32", 32, DW_ATE_SIGNED),
+            HIRType::I64 => basic("i64", 64, DW_ATE_SIGNED),
+            HIRType::F32 => basic("f32", 32, DW_ATE_FLOAT),
+            HIRType::F64 => basic("f64", 64, DW_ATE_FLOAT),
+            HIRType::Bool => basic("bool", 8, DW_ATE_BOOLEAN),
+            HIRType::String => pointer("str"),
+            HIRType::Vector(_) => pointer("vector"),
+            HIRType::Tensor(_) => pointer("tensor"),
+            HIRType::Void => null_mut(),
+        }
+    }
+
+    pub fn finalize(self) {
+        unsafe {
+            LLVMDIBuilderFinalize(self.builder);
+            LLVMDisposeDIBuilder(self.builder);
+        }
+    }
+}
+
+impl Compiler {
+    // file, line and column of a span, when debug info is being generated for it
+    fn debug_position(&mut self, span: Option<&Span>) -> Option<(LLVMMetadataRef, u32, u32)> {
+        let debug = self.debug.as_mut()?;
+        let (file, line, column) = self.sources.line_col(span?.start)?;
+        Some((debug.file(&file.display_path()), line as u32, column as u32))
+    }
+
+    // opens the subprogram for `func`, the current span is the function declaration
+    pub fn debug_begin_function(
+        &mut self,
+        func: LLVMValueRef,
+        name: &str,
+        params: &[(String, HIRType)],
+        return_type: &HIRType,
+    ) {
+        let span = self.current_span.clone();
+        let Some((file, line, _)) = self.debug_position(span.as_ref()) else { return };
+        let Some(debug) = self.debug.as_mut() else { return };
+        unsafe {
+            let mut types: Vec<_> = std::iter::once(return_type)
+                .chain(params.iter().map(|(_, ty)| ty))
+                .map(|ty| debug.type_of(ty))
+                .collect();
+            let signature =
+                LLVMDIBuilderCreateSubroutineType(debug.builder, file, types.as_mut_ptr(), types.len() as u32, LLVMDIFlagZero);
+            let mut linkage_len = 0;
+            let linkage = LLVMGetValueName2(func, &mut linkage_len);
+            let subprogram = LLVMDIBuilderCreateFunction(
+                debug.builder,
+                file,
+                name.as_ptr() as *const _,
+                name.len(),
+                linkage,
+                linkage_len,
+                file,
+                line,
+                signature,
+                0,
+                1,
+                line,
+                LLVMDIFlagZero,
+                0,
+            );
+            LLVMSetSubprogram(func, subprogram);
+            debug.scope = Some(subprogram);
+        }
+        self.debug_location(span.as_ref());
+    }
+
+    // parameters are declared where the function starts, after their entry allocas
+    pub fn debug_declare_param(&mut self, name: &str, ty: &HIRType, arg_no: u32, alloca: LLVMValueRef) {
+        let span = self.current_span.clone();
+        let Some((file, line, column)) = self.debug_position(span.as_ref()) else { return };
+        let Some(debug) = self.debug.as_ref() else { return };
+        let Some(scope) = debug.scope else { return };
+        unsafe {
+            let variable = LLVMDIBuilderCreateParameterVariable(
+                debug.builder,
+                scope,
+                name.as_ptr() as *const _,
+                name.len(),
+                arg_no,
+                file,
+                line,
+                debug.type_of(ty),
+                1,
+                LLVMDIFlagZero,
+            );
+            self.debug_declare(variable, alloca, line, column, scope);
+        }
+    }
+
+    // a local from `set:`, declared at the statement that introduces it
+    pub fn debug_declare_variable(&mut self, name: &str, ty: &HIRType, alloca: LLVMValueRef) {
+        let span = self.current_span.clone();
+        let Some((file, line, column)) = self.debug_position(span.as_ref()) else { return };
+        let Some(debug) = self.debug.as_ref() else { return };
+        let Some(scope) = debug.scope else { return };
+        unsafe {
+            let variable = LLVMDIBuilderCreateAutoVariable(
+                debug.builder,
+                scope,
+                name.as_ptr() as *const _,
+                name.len(),
+                file,
+                line,
+                debug.type_of(ty),
+                1,
+                LLVMDIFlagZero,
+                0,
+            );
+            self.debug_declare(variable, alloca, line, column, scope);
+        }
+    }
+
+    fn debug_declare(
+        &self,
+        variable: LLVMMetadataRef,
+        alloca: LLVMValueRef,
+        line: u32,
+        column: u32,
+        scope: LLVMMetadataRef,
+    ) {
+        let Some(debug) = self.debug.as_ref() else { return };
+        unsafe {
+            let location = LLVMDIBuilderCreateDebugLocation(self.context, line, column, scope, null_mut());
+            let expression = LLVMDIBuilderCreateExpression(debug.builder, null_mut(), 0);
+            LLVMDIBuilderInsertDeclareRecordAtEnd(
+                debug.builder,
+                alloca,
+                variable,
+                expression,
+                location,
+                LLVMGetInsertBlock(self.builder),
+            );
+        }
+    }
+
+    // instructions built from here on belong to the line of `span`
+    pub fn debug_location(&mut self, span: Option<&Span>) {
+        let Some((_, line, column)) = self.debug_position(span) else { return };
+        let Some(scope) = self.debug.as_ref().and_then(|debug| debug.scope) else { return };
+        unsafe {
+            let location = LLVMDIBuilderCreateDebugLocation(self.context, line, column, scope, null_mut());
+            LLVMSetCurrentDebugLocation2(self.builder, location);
+        }
+    }
+
+    pub fn debug_end_function(&mut self) {
+        let Some(debug) = self.debug.as_mut() else { return };
+        unsafe {
+            if let Some(subprogram) = debug.scope.take() {
+                LLVMDIBuilderFinalizeSubprogram(debug.builder, subprogram);
+            }
+            LLVMSetCurrentDebugLocation2(self.builder, null_mut());
+        }
+    }
+}
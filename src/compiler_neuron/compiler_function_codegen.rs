@@ -1,5 +1,6 @@
 use super::compiler_context::Compiler;
-use crate::high_level_ir::{HIRStatement, HIRType};
+use super::compiler_tensor_codegen::{codegen_tensor_retain, owned_tensor, release_tensor_locals};
+use crate::high_level_ir::{HIRExpr, HIRStatement, HIRType};
 use llvm_sys::core::*;
 use std::ffi::CString;
 
@@ -29,6 +30,8 @@ pub fn codegen_function(compiler: &mut Compiler, func: &HIRStatement) -> Result<
 
             let old_vars = compiler.variables.clone();
             compiler.current_function = Some(llvm_func_ref);
+            compiler.tensor_locals.clear();
+            compiler.debug_begin_function(llvm_func_ref, name, params, return_type);
 
             for (i, (param_name, param_ty)) in params.iter().enumerate() {
                 let param_val = LLVMGetParam(llvm_func_ref, i as u32);
@@ -45,6 +48,11 @@ pub fn codegen_function(compiler: &mut Compiler, func: &HIRStatement) -> Result<
                     param_name_c.as_ptr(),
                 );
                 LLVMBuildStore(compiler.builder, param_val, alloca);
+                if let HIRType::Tensor(_) = param_ty {
+                    codegen_tensor_retain(compiler, param_val);
+                    compiler.tensor_locals.push(alloca);
+                }
+                compiler.debug_declare_param(param_name, param_ty, i as u32 + 1, alloca);
                 compiler
                     .variables
                     .insert(param_name.clone(), (alloca, param_ty.clone()));
@@ -52,6 +60,9 @@ pub fn codegen_function(compiler: &mut Compiler, func: &HIRStatement) -> Result<
 
             for statement in body {
                 super::codegen_statement(compiler, statement)?;
+                if !LLVMGetBasicBlockTerminator(LLVMGetInsertBlock(compiler.builder)).is_null() {
+                    break;
+                }
             }
 
             compiler.variables = old_vars;
@@ -61,7 +72,12 @@ pub fn codegen_function(compiler: &mut Compiler, func: &HIRStatement) -> Result<
             while !block.is_null() {
                 if LLVMGetBasicBlockTerminator(block).is_null() {
                     LLVMPositionBuilderAtEnd(compiler.builder, block);
-                    if *start {
+                    // e.g. the merge block of an if/else where both branches return
+                    let unreachable = block != entry_block
+                        && LLVMGetFirstUse(LLVMBasicBlockAsValue(block)).is_null();
+                    if unreachable {
+                        LLVMBuildUnreachable(compiler.builder);
+                    } else if *start {
                         LLVMBuildRet(
                             compiler.builder,
                             LLVMConstInt(LLVMInt32TypeInContext(compiler.context), 0, 0),
@@ -77,10 +93,49 @@ pub fn codegen_function(compiler: &mut Compiler, func: &HIRStatement) -> Result<
                 }
                 block = LLVMGetNextBasicBlock(block);
             }
+            release_tensor_locals(compiler, llvm_func_ref);
+            compiler.debug_end_function();
 
             Ok(())
         } else {
             Err("[ERR-SEM-523] Provided statement is not a function".to_string())
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn codegen_return(compiler: &mut Compiler, value: Option<&HIRExpr>) -> Result<(), String> {
+    unsafe {
+        let ret_ty = compiler
+            .current_function_return_type()
+            .ok_or("[ERR-SEM-525] 'return' outside of a function")?;
+        match value {
+            Some(expr) => {
+                let (val, val_ty) = compiler.codegen_expr(expr)?;
+                if val_ty != ret_ty {
+                    return Err(format!(
+                        "[ERR-SEM-526] Return type mismatch: expected {:?}, found {:?}",
+                        ret_ty, val_ty
+                    ));
+                }
+                // the variable's reference goes away with the function's other locals
+                if let HIRType::Tensor(_) = val_ty
+                    && !owned_tensor(expr, &val_ty)
+                {
+                    codegen_tensor_retain(compiler, val);
+                }
+                LLVMBuildRet(compiler.builder, val);
+            }
+            // `Start` is emitted as `main` and still has to hand an exit code back
+            None if ret_ty == HIRType::Void => {
+                LLVMBuildRetVoid(compiler.builder);
+            }
+            None => {
+                LLVMBuildRet(
+                    compiler.builder,
+                    LLVMConstInt(compiler.hir_type_to_llvm_type(&ret_ty), 0, 0),
+                );
+            }
+        }
+        Ok(())
+    }
+}
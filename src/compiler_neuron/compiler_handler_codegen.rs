@@ -0,0 +1,48 @@
+//llvm ir generation for `.Err("...")` handlers,
+//checks inside the guarded statement jump to a block that prints the message,
+//then execution goes on with the next statement
+
+use super::{codegen_statement, compiler_context::Compiler};
+use crate::high_level_ir::{HIRExpr, HIRStatement};
+use llvm_sys::core::*;
+
+pub fn codegen_on_error(c: &mut Compiler, stmt: &HIRStatement) -> Result<(), String> {
+    unsafe {
+        let HIRStatement::OnError { stmt, message } = stmt else {
+            return Err("[ERR-SEM-547] Provided statement is not an error handler".into());
+        };
+        // global initializers are constants, nothing can fail there at runtime
+        let Some(fun) = c.current_function else {
+            return codegen_statement(c, stmt);
+        };
+
+        let handler_bb = LLVMAppendBasicBlockInContext(c.context, fun, b"err.handler\0".as_ptr() as _);
+        let done_bb = LLVMAppendBasicBlockInContext(c.context, fun, b"err.done\0".as_ptr() as _);
+
+        c.error_handlers.push(handler_bb);
+        let res = codegen_statement(c, stmt);
+        c.error_handlers.pop();
+        res?;
+        if LLVMGetBasicBlockTerminator(LLVMGetInsertBlock(c.builder)).is_null() {
+            LLVMBuildBr(c.builder, done_bb);
+        }
+
+        LLVMPositionBuilderAtEnd(c.builder, handler_bb);
+        let (printf_func, printf_type, _) = c.functions.get("printf").cloned().unwrap();
+        let (fmt_ptr, _) = c.codegen_expr(&HIRExpr::String("%s\n".to_string()))?;
+        let (message_ptr, _) = c.codegen_expr(&HIRExpr::String(message.clone()))?;
+        let mut args = [fmt_ptr, message_ptr];
+        LLVMBuildCall2(
+            c.builder,
+            printf_type,
+            printf_func,
+            args.as_mut_ptr(),
+            2,
+            b"err_print\0".as_ptr() as _,
+        );
+        LLVMBuildBr(c.builder, done_bb);
+
+        LLVMPositionBuilderAtEnd(c.builder, done_bb);
+        Ok(())
+    }
+}
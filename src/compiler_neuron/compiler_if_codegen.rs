@@ -1,7 +1,7 @@
 //llvm ir generation for conditions
 
 use super::{codegen_statement, compiler_context::Compiler};
-use crate::high_level_ir::{HIRStatement, HIRType};
+use crate::high_level_ir::{HIRExpr, HIROperator, HIRStatement, HIRType};
 use llvm_sys::core::*;
 use llvm_sys::prelude::LLVMValueRef;
 use llvm_sys::{LLVMIntPredicate, LLVMRealPredicate};
@@ -83,4 +83,45 @@ pub fn codegen_if(c: &mut Compiler, stmt: &HIRStatement) -> Result<(), String> {
         LLVMPositionBuilderAtEnd(c.builder, merge_bb);
         Ok(())
     }
+}
+
+// `a and b` / `a or b`, the right side only runs when the left one does not decide the result
+pub fn codegen_logical(
+    c: &mut Compiler,
+    left: &HIRExpr,
+    op: &HIROperator,
+    right: &HIRExpr,
+) -> Result<(LLVMValueRef, HIRType), String> {
+    unsafe {
+        let fun = c
+            .current_function
+            .ok_or("\x1b[31m[ERR-SEM-527] 'and'/'or' cannot be used outside of a function\x1b[0m")?;
+
+        let (left_val, _) = c.codegen_expr(left)?;
+        let left_bb = LLVMGetInsertBlock(c.builder);
+        let rhs_bb = LLVMAppendBasicBlockInContext(c.context, fun, b"logic.rhs\0".as_ptr() as _);
+        let merge_bb = LLVMAppendBasicBlockInContext(c.context, fun, b"logic.merge\0".as_ptr() as _);
+
+        // `or` is decided by a true left side, `and` by a false one
+        let decided = matches!(op, HIROperator::Or);
+        if decided {
+            LLVMBuildCondBr(c.builder, left_val, merge_bb, rhs_bb);
+        } else {
+            LLVMBuildCondBr(c.builder, left_val, rhs_bb, merge_bb);
+        }
+
+        LLVMPositionBuilderAtEnd(c.builder, rhs_bb);
+        let (right_val, _) = c.codegen_expr(right)?;
+        // the right side may have added blocks of its own
+        let rhs_end_bb = LLVMGetInsertBlock(c.builder);
+        LLVMBuildBr(c.builder, merge_bb);
+
+        LLVMPositionBuilderAtEnd(c.builder, merge_bb);
+        let bool_ty = c.hir_type_to_llvm_type(&HIRType::Bool);
+        let phi = LLVMBuildPhi(c.builder, bool_ty, b"logic\0".as_ptr() as _);
+        let mut values = [LLVMConstInt(bool_ty, decided as u64, 0), right_val];
+        let mut blocks = [left_bb, rhs_end_bb];
+        LLVMAddIncoming(phi, values.as_mut_ptr(), blocks.as_mut_ptr(), 2);
+        Ok((phi, HIRType::Bool))
+    }
 }
\ No newline at end of file
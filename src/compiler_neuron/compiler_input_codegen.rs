@@ -3,7 +3,9 @@
 
 use super::compiler_context::Compiler;
 use crate::high_level_ir::{HIRExpr, HIRStatement, HIRType};
+use llvm_sys::LLVMIntPredicate;
 use llvm_sys::core::*;
+use llvm_sys::prelude::LLVMValueRef;
 use std::ffi::CString;
 
 pub fn codegen_input(compiler: &mut Compiler, stmt: &HIRStatement) -> Result<(), String> {
@@ -21,14 +23,15 @@ pub fn codegen_input(compiler: &mut Compiler, stmt: &HIRStatement) -> Result<(),
             let (var_ptr, var_ty) = compiler
                 .variables
                 .get(name)
+                .cloned()
                 .ok_or("\x1b[31m[ERR-SEM-543] variable not declared for input\x1b[0m")?;
-            if LLVMIsAGlobalVariable(*var_ptr) != std::ptr::null_mut() {
+            if LLVMIsAGlobalVariable(var_ptr) != std::ptr::null_mut() {
                 return Err(format!(
                     "\x1b[31m[ERR-SEM-548] input on global variable '{}' is UB: use only local (alloca) variables!\x1b[0m",
                     name
                 ));
             }
-            if (*var_ptr).is_null() {
+            if var_ptr.is_null() {
                 return Err(format!(
                     "\x1b[31m[ERR-SEM-690] null ptr for '{}'\x1b[0m",
                     name
@@ -59,7 +62,8 @@ pub fn codegen_input(compiler: &mut Compiler, stmt: &HIRStatement) -> Result<(),
                 HIRType::I64 => "%lld",
                 HIRType::F32 => "%f",
                 HIRType::F64 => "%lf",
-                HIRType::String => "%s",
+                // the buffer below is 1024 bytes, scanf never writes past it
+                HIRType::String => "%1023s",
                 _ => return Err("\x1b[31m[ERR-SEM-544] unsupported input type\x1b[0m".into()),
             };
             let fmt_c = CString::new(fmt_str).unwrap();
@@ -69,7 +73,7 @@ pub fn codegen_input(compiler: &mut Compiler, stmt: &HIRStatement) -> Result<(),
                 b"scanf_fmt\0".as_ptr() as _,
             );
 
-            if *var_ty == HIRType::String {
+            if var_ty == HIRType::String {
                 let malloc_name = CString::new("malloc").unwrap();
                 let malloc_ty = LLVMFunctionType(
                     i8_ptr_type,
@@ -116,9 +120,40 @@ pub fn codegen_input(compiler: &mut Compiler, stmt: &HIRStatement) -> Result<(),
                         name
                     ));
                 }
-                LLVMBuildStore(compiler.builder, buf_ptr, *var_ptr);
+                guard_scanf(compiler, res);
+                if compiler.runtime_checks() {
+                    // a word that filled the buffer was most likely cut off
+                    let strlen_ty = LLVMFunctionType(
+                        LLVMInt64TypeInContext(compiler.context),
+                        [i8_ptr_type].as_ptr() as *mut _,
+                        1,
+                        0,
+                    );
+                    let strlen_func = compiler.get_or_declare_function("strlen", strlen_ty);
+                    let mut strlen_args = [buf_ptr];
+                    let len = LLVMBuildCall2(
+                        compiler.builder,
+                        strlen_ty,
+                        strlen_func,
+                        strlen_args.as_mut_ptr(),
+                        1,
+                        b"input_len\0".as_ptr() as _,
+                    );
+                    let full = LLVMBuildICmp(
+                        compiler.builder,
+                        LLVMIntPredicate::LLVMIntEQ,
+                        len,
+                        LLVMConstInt(LLVMInt64TypeInContext(compiler.context), 1023, 0),
+                        b"input_full\0".as_ptr() as _,
+                    );
+                    compiler.build_guard(
+                        full,
+                        "\n[ERR-RT-033] Runtime error: input does not fit the 1024 byte string buffer\n",
+                    );
+                }
+                LLVMBuildStore(compiler.builder, buf_ptr, var_ptr);
             } else {
-                if *var_ty == HIRType::Bool {
+                if var_ty == HIRType::Bool {
                     let tmp_ptr = LLVMBuildAlloca(
                         compiler.builder,
                         LLVMInt32TypeInContext(compiler.context),
@@ -139,6 +174,7 @@ pub fn codegen_input(compiler: &mut Compiler, stmt: &HIRStatement) -> Result<(),
                             name
                         ));
                     }
+                    guard_scanf(compiler, res);
                     let loaded = LLVMBuildLoad2(
                         compiler.builder,
                         LLVMInt32TypeInContext(compiler.context),
@@ -151,9 +187,9 @@ pub fn codegen_input(compiler: &mut Compiler, stmt: &HIRStatement) -> Result<(),
                         LLVMInt1TypeInContext(compiler.context),
                         b"bool_trunc\0".as_ptr() as _,
                     );
-                    LLVMBuildStore(compiler.builder, truncated, *var_ptr);
+                    LLVMBuildStore(compiler.builder, truncated, var_ptr);
                 } else {
-                    let mut args = [fmt_ptr, *var_ptr];
+                    let mut args = [fmt_ptr, var_ptr];
                     let res = LLVMBuildCall2(
                         compiler.builder,
                         scanf_ty,
@@ -168,6 +204,7 @@ pub fn codegen_input(compiler: &mut Compiler, stmt: &HIRStatement) -> Result<(),
                             name
                         ));
                     }
+                    guard_scanf(compiler, res);
                 }
             }
             Ok(())
@@ -176,3 +213,53 @@ pub fn codegen_input(compiler: &mut Compiler, stmt: &HIRStatement) -> Result<(),
         }
     }
 }
+
+// scanf returns how many values it matched, with a handler a failed read drops the rest
+// of the line (so the next `in` starts fresh) and jumps to the handler
+unsafe fn guard_scanf(compiler: &Compiler, matched: LLVMValueRef) { unsafe {
+    let Some(&handler) = compiler.error_handlers.last() else {
+        return;
+    };
+    let i32_type = LLVMInt32TypeInContext(compiler.context);
+    let failed = LLVMBuildICmp(
+        compiler.builder,
+        LLVMIntPredicate::LLVMIntNE,
+        matched,
+        LLVMConstInt(i32_type, 1, 0),
+        b"scanf_failed\0".as_ptr() as _,
+    );
+    let fun = LLVMGetBasicBlockParent(LLVMGetInsertBlock(compiler.builder));
+    let flush_bb = LLVMAppendBasicBlockInContext(compiler.context, fun, b"in.flush\0".as_ptr() as _);
+    let ok_bb = LLVMAppendBasicBlockInContext(compiler.context, fun, b"in.ok\0".as_ptr() as _);
+    LLVMBuildCondBr(compiler.builder, failed, flush_bb, ok_bb);
+
+    LLVMPositionBuilderAtEnd(compiler.builder, flush_bb);
+    let getchar_ty = LLVMFunctionType(i32_type, std::ptr::null_mut(), 0, 0);
+    let getchar_func = compiler.get_or_declare_function("getchar", getchar_ty);
+    let ch = LLVMBuildCall2(
+        compiler.builder,
+        getchar_ty,
+        getchar_func,
+        std::ptr::null_mut(),
+        0,
+        b"getchar_call\0".as_ptr() as _,
+    );
+    let is_newline = LLVMBuildICmp(
+        compiler.builder,
+        LLVMIntPredicate::LLVMIntEQ,
+        ch,
+        LLVMConstInt(i32_type, b'\n' as u64, 0),
+        b"is_newline\0".as_ptr() as _,
+    );
+    let is_eof = LLVMBuildICmp(
+        compiler.builder,
+        LLVMIntPredicate::LLVMIntEQ,
+        ch,
+        LLVMConstAllOnes(i32_type),
+        b"is_eof\0".as_ptr() as _,
+    );
+    let line_done = LLVMBuildOr(compiler.builder, is_newline, is_eof, b"line_done\0".as_ptr() as _);
+    LLVMBuildCondBr(compiler.builder, line_done, handler, flush_bb);
+
+    LLVMPositionBuilderAtEnd(compiler.builder, ok_bb);
+}}
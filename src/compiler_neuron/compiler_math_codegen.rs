@@ -80,6 +80,10 @@ pub fn codegen_math_expr(
         } else {
             left_ty.clone()
         };
+        // with runtime checks integer math is overflow checked and integer divisors are checked for zero,
+        // float division by zero only counts inside a `.Err(...)` handler (it is inf otherwise)
+        let checked_int = compiler.runtime_checks() && matches!(left_ty, HIRType::I32 | HIRType::I64);
+        let guarded = compiler.runtime_checks() && !compiler.error_handlers.is_empty();
         let result = match op {
             HIROperator::Plus => {
                 if is_float {
@@ -89,6 +93,8 @@ pub fn codegen_math_expr(
                         right_val,
                         b"fadd\0".as_ptr() as *const _,
                     )
+                } else if checked_int {
+                    build_overflow_op(compiler, op, left_val, right_val)
                 } else {
                     LLVMBuildAdd(
                         compiler.builder,
@@ -106,6 +112,8 @@ pub fn codegen_math_expr(
                         right_val,
                         b"fsub\0".as_ptr() as *const _,
                     )
+                } else if checked_int {
+                    build_overflow_op(compiler, op, left_val, right_val)
                 } else {
                     LLVMBuildSub(
                         compiler.builder,
@@ -123,6 +131,8 @@ pub fn codegen_math_expr(
                         right_val,
                         b"fmul\0".as_ptr() as *const _,
                     )
+                } else if checked_int {
+                    build_overflow_op(compiler, op, left_val, right_val)
                 } else {
                     LLVMBuildMul(
                         compiler.builder,
@@ -134,11 +144,8 @@ pub fn codegen_math_expr(
             }
             HIROperator::Divide => {
                 if is_float {
-                    match right {
-                        HIRExpr::Float32(0.0) | HIRExpr::Float64(0.0) => {
-                            return Err("\x1b[31m[ERR-SEM-550] Division by zero\x1b[0m".to_string());
-                        }
-                        _ => {}
+                    if guarded {
+                        guard_float_division(compiler, right_val);
                     }
                     LLVMBuildFDiv(
                         compiler.builder,
@@ -147,11 +154,8 @@ pub fn codegen_math_expr(
                         b"fdiv\0".as_ptr() as *const _,
                     )
                 } else {
-                    match right {
-                        HIRExpr::Int32(0) | HIRExpr::Int64(0) => {
-                            return Err("\x1b[31m[ERR-SEM-550] Division by zero\x1b[0m".to_string());
-                        }
-                        _ => {}
+                    if checked_int {
+                        guard_int_division(compiler, left_val, right_val);
                     }
                     LLVMBuildSDiv(
                         compiler.builder,
@@ -161,6 +165,29 @@ pub fn codegen_math_expr(
                     )
                 }
             }
+            HIROperator::Modulo => {
+                if is_float {
+                    if guarded {
+                        guard_float_division(compiler, right_val);
+                    }
+                    LLVMBuildFRem(
+                        compiler.builder,
+                        left_val,
+                        right_val,
+                        b"frem\0".as_ptr() as *const _,
+                    )
+                } else {
+                    if checked_int {
+                        guard_int_division(compiler, left_val, right_val);
+                    }
+                    LLVMBuildSRem(
+                        compiler.builder,
+                        left_val,
+                        right_val,
+                        b"srem\0".as_ptr() as *const _,
+                    )
+                }
+            }
             cmp_op => {
                 if is_float {
                     let float_predicate = match cmp_op {
@@ -180,6 +207,11 @@ pub fn codegen_math_expr(
                         b"fcmp\0".as_ptr() as *const _,
                     )
                 } else {
+                    // strings compare by content, `a == b` becomes `strcmp(a, b) == 0`
+                    if left_ty == HIRType::String {
+                        left_val = build_strcmp(compiler, left_val, right_val);
+                        right_val = LLVMConstInt(LLVMTypeOf(left_val), 0, 0);
+                    }
                     let int_predicate = match cmp_op {
                         HIROperator::Equals => LLVMIntPredicate::LLVMIntEQ,
                         HIROperator::NotEquals => LLVMIntPredicate::LLVMIntNE,
@@ -227,4 +259,103 @@ pub fn codegen_math_expr(
         }
         Ok((result, result_type))
     }
-}
\ No newline at end of file
+}
+
+// `llvm.sadd.with.overflow.i32` and friends, the overflow bit is guarded
+unsafe fn build_overflow_op(
+    compiler: &mut Compiler,
+    op: &HIROperator,
+    left: LLVMValueRef,
+    right: LLVMValueRef,
+) -> LLVMValueRef { unsafe {
+    let int_type = LLVMTypeOf(left);
+    let intrinsic = format!(
+        "llvm.{}.with.overflow.i{}",
+        match op {
+            HIROperator::Plus => "sadd",
+            HIROperator::Minus => "ssub",
+            _ => "smul",
+        },
+        LLVMGetIntTypeWidth(int_type)
+    );
+    let mut fields = [int_type, LLVMInt1TypeInContext(compiler.context)];
+    let result_type = LLVMStructTypeInContext(compiler.context, fields.as_mut_ptr(), 2, 0);
+    let func_type = LLVMFunctionType(result_type, [int_type, int_type].as_ptr() as *mut _, 2, 0);
+    let func = compiler.get_or_declare_function(&intrinsic, func_type);
+    let mut args = [left, right];
+    let pair = LLVMBuildCall2(
+        compiler.builder,
+        func_type,
+        func,
+        args.as_mut_ptr(),
+        2,
+        b"ovf\0".as_ptr() as _,
+    );
+    let overflowed = LLVMBuildExtractValue(compiler.builder, pair, 1, b"ovf_flag\0".as_ptr() as _);
+    compiler.build_guard(overflowed, "\n[ERR-RT-030] Runtime error: integer overflow\n");
+    LLVMBuildExtractValue(compiler.builder, pair, 0, b"ovf_value\0".as_ptr() as _)
+}}
+
+// a zero divisor and MIN / -1 (which overflows) are both guarded
+unsafe fn guard_int_division(compiler: &mut Compiler, left: LLVMValueRef, right: LLVMValueRef) { unsafe {
+    let int_type = LLVMTypeOf(right);
+    let zero = LLVMConstInt(int_type, 0, 0);
+    let is_zero = LLVMBuildICmp(
+        compiler.builder,
+        LLVMIntPredicate::LLVMIntEQ,
+        right,
+        zero,
+        b"div_zero\0".as_ptr() as _,
+    );
+    compiler.build_guard(is_zero, "\n[ERR-RT-031] Runtime error: division by zero\n");
+    let min = LLVMConstInt(int_type, 1 << (LLVMGetIntTypeWidth(int_type) - 1), 0);
+    let minus_one = LLVMConstAllOnes(int_type);
+    let is_min = LLVMBuildICmp(
+        compiler.builder,
+        LLVMIntPredicate::LLVMIntEQ,
+        left,
+        min,
+        b"div_min\0".as_ptr() as _,
+    );
+    let is_minus_one = LLVMBuildICmp(
+        compiler.builder,
+        LLVMIntPredicate::LLVMIntEQ,
+        right,
+        minus_one,
+        b"div_minus_one\0".as_ptr() as _,
+    );
+    let overflows = LLVMBuildAnd(compiler.builder, is_min, is_minus_one, b"div_ovf\0".as_ptr() as _);
+    compiler.build_guard(overflows, "\n[ERR-RT-030] Runtime error: integer overflow\n");
+}}
+
+unsafe fn guard_float_division(compiler: &mut Compiler, right: LLVMValueRef) { unsafe {
+    let zero = LLVMConstReal(LLVMTypeOf(right), 0.0);
+    let is_zero = LLVMBuildFCmp(
+        compiler.builder,
+        llvm_sys::LLVMRealPredicate::LLVMRealOEQ,
+        right,
+        zero,
+        b"div_zero\0".as_ptr() as _,
+    );
+    compiler.build_guard(is_zero, "\n[ERR-RT-031] Runtime error: division by zero\n");
+}}
+
+unsafe fn build_strcmp(compiler: &Compiler, left: LLVMValueRef, right: LLVMValueRef) -> LLVMValueRef { unsafe {
+    let i8_ptr_type = LLVMPointerType(LLVMInt8TypeInContext(compiler.context), 0);
+    let strcmp_type = LLVMFunctionType(
+        LLVMInt32TypeInContext(compiler.context),
+        [i8_ptr_type, i8_ptr_type].as_ptr() as *mut _,
+        2,
+        0,
+    );
+    let strcmp_func = compiler.get_or_declare_function("strcmp", strcmp_type);
+    let mut args = [left, right];
+    LLVMBuildCall2(
+        compiler.builder,
+        strcmp_type,
+        strcmp_func,
+        args.as_mut_ptr(),
+        2,
+        b"strcmp\0".as_ptr() as _,
+    )
+}}
\ No newline at end of file
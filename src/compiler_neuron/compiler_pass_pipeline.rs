@@ -0,0 +1,54 @@
+//optimization levels and the llvm pass pipeline,
+//runs on the finished module before the JIT takes it or the target machine writes it out
+
+use crate::backend::OptLevel;
+use llvm_sys::error::{LLVMDisposeErrorMessage, LLVMGetErrorMessage};
+use llvm_sys::prelude::LLVMModuleRef;
+use llvm_sys::target_machine::{LLVMCodeGenOptLevel, LLVMTargetMachineRef};
+use llvm_sys::transforms::pass_builder::{
+    LLVMCreatePassBuilderOptions, LLVMDisposePassBuilderOptions,
+    LLVMPassBuilderOptionsSetLoopUnrolling, LLVMPassBuilderOptionsSetLoopVectorization,
+    LLVMPassBuilderOptionsSetSLPVectorization, LLVMRunPasses,
+};
+use std::ffi::{CStr, CString};
+
+impl OptLevel {
+    pub fn codegen_level(&self) -> LLVMCodeGenOptLevel {
+        match self {
+            OptLevel::O0 => LLVMCodeGenOptLevel::LLVMCodeGenLevelNone,
+            OptLevel::O1 => LLVMCodeGenOptLevel::LLVMCodeGenLevelLess,
+            OptLevel::O2 | OptLevel::Os => LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+            OptLevel::O3 => LLVMCodeGenOptLevel::LLVMCodeGenLevelAggressive,
+        }
+    }
+}
+
+// `target_machine` may be null for the JIT, the passes then fall back to generic cost models
+pub fn run_passes(
+    module: LLVMModuleRef,
+    target_machine: LLVMTargetMachineRef,
+    level: OptLevel,
+) -> Result<(), String> {
+    // O0 would only add attributes, the module is left exactly as codegen built it
+    if level == OptLevel::O0 {
+        return Ok(());
+    }
+    let pipeline = CString::new(format!("default<{}>", level.name())).unwrap();
+    unsafe {
+        let options = LLVMCreatePassBuilderOptions();
+        let vectorize = matches!(level, OptLevel::O2 | OptLevel::O3);
+        LLVMPassBuilderOptionsSetLoopVectorization(options, vectorize as i32);
+        LLVMPassBuilderOptionsSetSLPVectorization(options, vectorize as i32);
+        LLVMPassBuilderOptionsSetLoopUnrolling(options, (level != OptLevel::Os) as i32);
+
+        let error = LLVMRunPasses(module, pipeline.as_ptr(), target_machine, options);
+        LLVMDisposePassBuilderOptions(options);
+        if !error.is_null() {
+            let msg_ptr = LLVMGetErrorMessage(error);
+            let msg = CStr::from_ptr(msg_ptr).to_string_lossy().into_owned();
+            LLVMDisposeErrorMessage(msg_ptr);
+            return Err(format!("Optimization pipeline {} failed: {}", level.name(), msg));
+        }
+    }
+    Ok(())
+}
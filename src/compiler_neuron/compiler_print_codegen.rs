@@ -2,6 +2,7 @@
 //to implement this I used the printf method
 
 use super::compiler_context::Compiler;
+use super::compiler_tensor_codegen::{codegen_tensor_print, codegen_tensor_release, owned_tensor};
 use crate::high_level_ir::{HIRStatement, HIRType};
 use llvm_sys::core::*;
 use llvm_sys::prelude::LLVMValueRef;
@@ -78,28 +79,37 @@ pub fn codegen_print(compiler: &mut Compiler, stmt: &HIRStatement) -> Result<(),
             for (i, expr) in params.iter().enumerate() {
                 let (mut value, ty) = compiler.codegen_expr(expr)?;
 
-                if ty == HIRType::F32 {
-                    value = LLVMBuildFPExt(
-                        compiler.builder,
-                        value,
-                        compiler.hir_type_to_llvm_type(&HIRType::F64),
-                        b"fpext\0".as_ptr() as *const _,
-                    );
-                }
-
                 let is_last = i == params.len() - 1;
-                let format_string = get_format_string(compiler, &ty, is_last)?;
 
-                let mut args = vec![format_string, value];
+                // tensors print themselves through the runtime library
+                if let HIRType::Tensor(_) = ty {
+                    codegen_tensor_print(compiler, value, is_last);
+                    if owned_tensor(expr, &ty) {
+                        codegen_tensor_release(compiler, value);
+                    }
+                } else {
+                    if ty == HIRType::F32 {
+                        value = LLVMBuildFPExt(
+                            compiler.builder,
+                            value,
+                            compiler.hir_type_to_llvm_type(&HIRType::F64),
+                            b"fpext\0".as_ptr() as *const _,
+                        );
+                    }
+
+                    let format_string = get_format_string(compiler, &ty, is_last)?;
+
+                    let mut args = vec![format_string, value];
 
-                LLVMBuildCall2(
-                    compiler.builder,
-                    printf_type,
-                    printf_func,
-                    args.as_mut_ptr(),
-                    args.len() as u32,
-                    b"printcall\0".as_ptr() as *const _,
-                );
+                    LLVMBuildCall2(
+                        compiler.builder,
+                        printf_type,
+                        printf_func,
+                        args.as_mut_ptr(),
+                        args.len() as u32,
+                        b"printcall\0".as_ptr() as *const _,
+                    );
+                }
 
                 if !is_last {
                     let fmt_space = get_format_string(compiler, &HIRType::String, false)?;
@@ -0,0 +1,59 @@
+//runtime library glue for the JIT,
+//build.rs compiles runtime/axon_runtime.c into the compiler itself, so the JIT maps
+//the declared runtime functions straight to the symbols in crate::runtime,
+//while `axon build` writes RUNTIME_SOURCE next to the object file and links it into the binary.
+//in the JIT a runtime error must not end the compiler's process, `exit` is mapped to axon_jit_exit
+//(runtime/axon_jit.c) and generated code only runs inside axon_jit_main / axon_jit_call
+
+use crate::runtime::*;
+use llvm_sys::core::LLVMGetNamedFunction;
+use llvm_sys::execution_engine::{LLVMAddGlobalMapping, LLVMExecutionEngineRef};
+use llvm_sys::prelude::LLVMModuleRef;
+use std::ffi::{CString, c_void};
+
+// runtime/axon_jit.c, compiled into the compiler only
+unsafe extern "C" {
+    pub fn axon_jit_exit(code: i32);
+    pub fn axon_jit_main(main: extern "C" fn() -> i32) -> i32;
+    pub fn axon_jit_call(entry: extern "C" fn()) -> i32;
+}
+
+fn runtime_symbols() -> Vec<(&'static str, *mut c_void)> {
+    vec![
+        ("axon_tensor_fill", axon_tensor_fill as *mut c_void),
+        ("axon_tensor_from", axon_tensor_from as *mut c_void),
+        ("axon_tensor_rand", axon_tensor_rand as *mut c_void),
+        ("axon_tensor_binary", axon_tensor_binary as *mut c_void),
+        ("axon_tensor_matmul", axon_tensor_matmul as *mut c_void),
+        ("axon_tensor_reshape", axon_tensor_reshape as *mut c_void),
+        ("axon_tensor_transpose", axon_tensor_transpose as *mut c_void),
+        ("axon_tensor_reduce", axon_tensor_reduce as *mut c_void),
+        ("axon_tensor_reduce_axis", axon_tensor_reduce_axis as *mut c_void),
+        ("axon_tensor_unary", axon_tensor_unary as *mut c_void),
+        ("axon_tensor_softmax", axon_tensor_softmax as *mut c_void),
+        ("axon_tensor_get", axon_tensor_get as *mut c_void),
+        ("axon_tensor_item", axon_tensor_item as *mut c_void),
+        ("axon_tensor_print", axon_tensor_print as *mut c_void),
+        ("axon_tensor_backward", axon_tensor_backward as *mut c_void),
+        ("axon_tensor_grad", axon_tensor_grad as *mut c_void),
+        ("axon_tensor_grad_of", axon_tensor_grad_of as *mut c_void),
+        ("axon_tensor_detach", axon_tensor_detach as *mut c_void),
+        ("axon_tensor_retain", axon_tensor_retain as *mut c_void),
+        ("axon_tensor_release", axon_tensor_release as *mut c_void),
+        ("axon_tensor_at", axon_tensor_at as *mut c_void),
+        ("exit", axon_jit_exit as *mut c_void),
+    ]
+}
+
+// maps every runtime function the module actually declares to its address in this process
+pub fn register_runtime_symbols(ee: LLVMExecutionEngineRef, module: LLVMModuleRef) {
+    unsafe {
+        for (name, addr) in runtime_symbols() {
+            let c_name = CString::new(name).unwrap();
+            let func = LLVMGetNamedFunction(module, c_name.as_ptr());
+            if !func.is_null() {
+                LLVMAddGlobalMapping(ee, func, addr);
+            }
+        }
+    }
+}
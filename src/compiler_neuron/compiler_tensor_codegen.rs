@@ -0,0 +1,428 @@
+//llvm ir generation for tensors,
+//a tensor is an opaque pointer owned by the runtime library (runtime/axon_runtime.c),
+//so every operation here is a call into it, shapes were already checked by the semantic pass.
+//every tensor variable holds one reference: a new value releases the old one, a function releases
+//its own before each `ret`, and a temporary is released by whatever used it
+
+use super::compiler_context::Compiler;
+use crate::high_level_ir::{HIRExpr, HIRType, TensorFunc};
+use crate::runtime::{
+    OP_ADD, OP_DIV, OP_MUL, OP_SUB, REDUCE_MAX, REDUCE_MEAN, REDUCE_SUM, UNARY_EXP, UNARY_RELU, UNARY_SIGMOID, UNARY_TANH,
+};
+use llvm_sys::{LLVMLinkage, LLVMOpcode};
+use llvm_sys::core::*;
+use llvm_sys::prelude::{LLVMTypeRef, LLVMValueRef};
+use std::ffi::CString;
+
+enum Arg {
+    Ptr,
+    I64,
+    I32,
+    F32,
+    Void,
+}
+
+unsafe fn arg_type(compiler: &Compiler, arg: &Arg) -> LLVMTypeRef { unsafe {
+    match arg {
+        Arg::Ptr => LLVMPointerType(LLVMInt8TypeInContext(compiler.context), 0),
+        Arg::I64 => LLVMInt64TypeInContext(compiler.context),
+        Arg::I32 => LLVMInt32TypeInContext(compiler.context),
+        Arg::F32 => LLVMFloatTypeInContext(compiler.context),
+        Arg::Void => LLVMVoidTypeInContext(compiler.context),
+    }
+}}
+
+unsafe fn call_runtime(
+    compiler: &Compiler,
+    name: &str,
+    ret: Arg,
+    params: &[Arg],
+    args: &mut [LLVMValueRef],
+) -> LLVMValueRef { unsafe {
+    let param_types: Vec<LLVMTypeRef> = params.iter().map(|p| arg_type(compiler, p)).collect();
+    let func_type = LLVMFunctionType(
+        arg_type(compiler, &ret),
+        param_types.as_ptr() as *mut _,
+        param_types.len() as u32,
+        0,
+    );
+    let func = compiler.get_or_declare_function(name, func_type);
+    // void calls cannot carry a name
+    let call_name: &[u8] = if let Arg::Void = ret { b"\0" } else { b"tensor_call\0" };
+    LLVMBuildCall2(
+        compiler.builder,
+        func_type,
+        func,
+        args.as_mut_ptr(),
+        args.len() as u32,
+        call_name.as_ptr() as _,
+    )
+}}
+
+unsafe fn const_i64(compiler: &Compiler, v: u64) -> LLVMValueRef { unsafe {
+    LLVMConstInt(LLVMInt64TypeInContext(compiler.context), v, 0)
+}}
+
+unsafe fn const_i32(compiler: &Compiler, v: u64) -> LLVMValueRef { unsafe {
+    LLVMConstInt(LLVMInt32TypeInContext(compiler.context), v, 0)
+}}
+
+// shapes are known at compile time, so they become private constant i64 arrays
+unsafe fn shape_global(compiler: &mut Compiler, shape: &[usize]) -> LLVMValueRef { unsafe {
+    let i64_type = LLVMInt64TypeInContext(compiler.context);
+    let mut dims: Vec<LLVMValueRef> = shape.iter().map(|d| LLVMConstInt(i64_type, *d as u64, 0)).collect();
+    // rank-0 tensors still get a valid (unused) pointer
+    if dims.is_empty() {
+        dims.push(LLVMConstInt(i64_type, 1, 0));
+    }
+    let array = LLVMConstArray2(i64_type, dims.as_mut_ptr(), dims.len() as u64);
+    let name = CString::new(format!(".shape{}", compiler.string_counter)).unwrap();
+    compiler.string_counter += 1;
+    let global = LLVMAddGlobal(compiler.module, LLVMTypeOf(array), name.as_ptr());
+    LLVMSetLinkage(global, LLVMLinkage::LLVMPrivateLinkage);
+    LLVMSetInitializer(global, array);
+    LLVMSetGlobalConstant(global, 1);
+    global
+}}
+
+// points a runtime error from the next call at the statement being compiled, as build_runtime_error does
+unsafe fn tensor_location(compiler: &Compiler) { unsafe {
+    let position = compiler
+        .current_span
+        .as_ref()
+        .and_then(|span| compiler.sources.position(span.start));
+    let location = match position {
+        Some(position) => {
+            let position_c = CString::new(position).unwrap();
+            LLVMBuildGlobalString(compiler.builder, position_c.as_ptr(), b"tensor_at\0".as_ptr() as _)
+        }
+        None => LLVMConstNull(arg_type(compiler, &Arg::Ptr)),
+    };
+    call_runtime(compiler, "axon_tensor_at", Arg::Void, &[Arg::Ptr], &mut [location]);
+}}
+
+// whether `expr` gives a reference nothing else holds, only a variable's tensor belongs to someone already
+pub fn owned_tensor(expr: &HIRExpr, ty: &HIRType) -> bool {
+    matches!(ty, HIRType::Tensor(_)) && !matches!(expr, HIRExpr::Identifier(_))
+}
+
+pub fn codegen_tensor_retain(compiler: &Compiler, tensor: LLVMValueRef) {
+    unsafe {
+        call_runtime(compiler, "axon_tensor_retain", Arg::Void, &[Arg::Ptr], &mut [tensor]);
+    }
+}
+
+pub fn codegen_tensor_release(compiler: &Compiler, tensor: LLVMValueRef) {
+    unsafe {
+        call_runtime(compiler, "axon_tensor_release", Arg::Void, &[Arg::Ptr], &mut [tensor]);
+    }
+}
+
+// a local of the running function, null until its first assignment so the release before `ret` is safe
+// on paths that never got there
+pub fn tensor_local(compiler: &mut Compiler, name: &str) -> Result<LLVMValueRef, String> {
+    unsafe {
+        let ptr_type = arg_type(compiler, &Arg::Ptr);
+        let alloca = compiler.build_entry_alloca(ptr_type, name)?;
+        let init = LLVMCreateBuilderInContext(compiler.context);
+        let next = LLVMGetNextInstruction(alloca);
+        if next.is_null() {
+            LLVMPositionBuilderAtEnd(init, LLVMGetInstructionParent(alloca));
+        } else {
+            LLVMPositionBuilderBefore(init, next);
+        }
+        LLVMBuildStore(init, LLVMConstNull(ptr_type), alloca);
+        LLVMDisposeBuilder(init);
+        compiler.tensor_locals.push(alloca);
+        Ok(alloca)
+    }
+}
+
+// stores `value` (already a reference of its own) into a tensor variable and releases what it held
+pub fn codegen_tensor_store(compiler: &Compiler, value: LLVMValueRef, ptr: LLVMValueRef) {
+    unsafe {
+        let ptr_type = arg_type(compiler, &Arg::Ptr);
+        let old = LLVMBuildLoad2(compiler.builder, ptr_type, ptr, b"old_tensor\0".as_ptr() as _);
+        LLVMBuildStore(compiler.builder, value, ptr);
+        codegen_tensor_release(compiler, old);
+    }
+}
+
+// releases the function's tensor variables before every `ret` in it, the returned tensor has a reference of its own
+pub fn release_tensor_locals(compiler: &mut Compiler, function: LLVMValueRef) {
+    unsafe {
+        let locals = std::mem::take(&mut compiler.tensor_locals);
+        if locals.is_empty() {
+            return;
+        }
+        let ptr_type = arg_type(compiler, &Arg::Ptr);
+        let mut block = LLVMGetFirstBasicBlock(function);
+        while !block.is_null() {
+            let ret = LLVMGetBasicBlockTerminator(block);
+            if !ret.is_null() && LLVMGetInstructionOpcode(ret) == LLVMOpcode::LLVMRet {
+                LLVMPositionBuilderBefore(compiler.builder, ret);
+                for local in &locals {
+                    let tensor = LLVMBuildLoad2(compiler.builder, ptr_type, *local, b"local_tensor\0".as_ptr() as _);
+                    codegen_tensor_release(compiler, tensor);
+                }
+            }
+            block = LLVMGetNextBasicBlock(block);
+        }
+    }
+}
+
+fn result_shape(ty: &HIRType) -> Result<&[usize], String> {
+    match ty {
+        HIRType::Tensor(shape) => Ok(shape),
+        other => Err(format!(
+            "\x1b[31m[ERR-SEM-243] Expected a tensor type, found {:?}\x1b[0m",
+            other
+        )),
+    }
+}
+
+pub fn codegen_tensor_literal(
+    compiler: &mut Compiler,
+    elements: &[HIRExpr],
+    shape: &[usize],
+) -> Result<(LLVMValueRef, HIRType), String> {
+    unsafe {
+        let f32_type = LLVMFloatTypeInContext(compiler.context);
+        let data = compiler.build_entry_alloca(LLVMArrayType2(f32_type, elements.len() as u64), "tensor_data")?;
+        for (i, element) in elements.iter().enumerate() {
+            let (value, _) = compiler.codegen_expr(element)?;
+            let mut indices = [const_i64(compiler, i as u64)];
+            let slot = LLVMBuildGEP2(
+                compiler.builder,
+                f32_type,
+                data,
+                indices.as_mut_ptr(),
+                1,
+                b"tensor_elem\0".as_ptr() as _,
+            );
+            LLVMBuildStore(compiler.builder, value, slot);
+        }
+        let shape_ptr = shape_global(compiler, shape);
+        tensor_location(compiler);
+        let tensor = call_runtime(
+            compiler,
+            "axon_tensor_from",
+            Arg::Ptr,
+            &[Arg::I64, Arg::Ptr, Arg::Ptr],
+            &mut [const_i64(compiler, shape.len() as u64), shape_ptr, data],
+        );
+        Ok((tensor, HIRType::Tensor(shape.to_vec())))
+    }
+}
+
+pub fn codegen_tensor_call(
+    compiler: &mut Compiler,
+    func: &TensorFunc,
+    args: &[HIRExpr],
+    ty: &HIRType,
+) -> Result<(LLVMValueRef, HIRType), String> {
+    unsafe {
+        let mut values = Vec::with_capacity(args.len());
+        let mut temporaries = Vec::new();
+        for arg in args {
+            let (value, arg_ty) = compiler.codegen_expr(arg)?;
+            if owned_tensor(arg, &arg_ty) {
+                temporaries.push(value);
+            }
+            values.push(value);
+        }
+        // item() is the only call that cannot fail
+        if !matches!(func, TensorFunc::Item) {
+            tensor_location(compiler);
+        }
+        let value = match func {
+            TensorFunc::Fill => {
+                let shape = result_shape(ty)?;
+                let shape_ptr = shape_global(compiler, shape);
+                call_runtime(
+                    compiler,
+                    "axon_tensor_fill",
+                    Arg::Ptr,
+                    &[Arg::I64, Arg::Ptr, Arg::F32],
+                    &mut [const_i64(compiler, shape.len() as u64), shape_ptr, values[0]],
+                )
+            }
+            TensorFunc::Rand => {
+                let shape = result_shape(ty)?;
+                let shape_ptr = shape_global(compiler, shape);
+                call_runtime(
+                    compiler,
+                    "axon_tensor_rand",
+                    Arg::Ptr,
+                    &[Arg::I64, Arg::Ptr],
+                    &mut [const_i64(compiler, shape.len() as u64), shape_ptr],
+                )
+            }
+            TensorFunc::Add | TensorFunc::Sub | TensorFunc::Mul | TensorFunc::Div => {
+                let op = match func {
+                    TensorFunc::Add => OP_ADD,
+                    TensorFunc::Sub => OP_SUB,
+                    TensorFunc::Mul => OP_MUL,
+                    _ => OP_DIV,
+                };
+                call_runtime(
+                    compiler,
+                    "axon_tensor_binary",
+                    Arg::Ptr,
+                    &[Arg::I32, Arg::Ptr, Arg::Ptr],
+                    &mut [const_i32(compiler, op), values[0], values[1]],
+                )
+            }
+            TensorFunc::MatMul => call_runtime(
+                compiler,
+                "axon_tensor_matmul",
+                Arg::Ptr,
+                &[Arg::Ptr, Arg::Ptr],
+                &mut [values[0], values[1]],
+            ),
+            TensorFunc::Reshape => {
+                let shape = result_shape(ty)?;
+                let shape_ptr = shape_global(compiler, shape);
+                call_runtime(
+                    compiler,
+                    "axon_tensor_reshape",
+                    Arg::Ptr,
+                    &[Arg::Ptr, Arg::I64, Arg::Ptr],
+                    &mut [values[0], const_i64(compiler, shape.len() as u64), shape_ptr],
+                )
+            }
+            TensorFunc::Transpose => call_runtime(
+                compiler,
+                "axon_tensor_transpose",
+                Arg::Ptr,
+                &[Arg::Ptr],
+                &mut [values[0]],
+            ),
+            TensorFunc::Sum(axis) | TensorFunc::Mean(axis) | TensorFunc::Max(axis) => {
+                let op = match func {
+                    TensorFunc::Sum(_) => REDUCE_SUM,
+                    TensorFunc::Mean(_) => REDUCE_MEAN,
+                    _ => REDUCE_MAX,
+                };
+                match axis {
+                    Some(axis) => call_runtime(
+                        compiler,
+                        "axon_tensor_reduce_axis",
+                        Arg::Ptr,
+                        &[Arg::I32, Arg::Ptr, Arg::I64],
+                        &mut [const_i32(compiler, op), values[0], const_i64(compiler, *axis as u64)],
+                    ),
+                    None => call_runtime(
+                        compiler,
+                        "axon_tensor_reduce",
+                        Arg::Ptr,
+                        &[Arg::I32, Arg::Ptr],
+                        &mut [const_i32(compiler, op), values[0]],
+                    ),
+                }
+            }
+            TensorFunc::Relu | TensorFunc::Sigmoid | TensorFunc::Tanh | TensorFunc::Exp => {
+                let op = match func {
+                    TensorFunc::Relu => UNARY_RELU,
+                    TensorFunc::Sigmoid => UNARY_SIGMOID,
+                    TensorFunc::Tanh => UNARY_TANH,
+                    _ => UNARY_EXP,
+                };
+                call_runtime(
+                    compiler,
+                    "axon_tensor_unary",
+                    Arg::Ptr,
+                    &[Arg::I32, Arg::Ptr],
+                    &mut [const_i32(compiler, op), values[0]],
+                )
+            }
+            TensorFunc::Softmax => call_runtime(
+                compiler,
+                "axon_tensor_softmax",
+                Arg::Ptr,
+                &[Arg::Ptr],
+                &mut [values[0]],
+            ),
+            TensorFunc::Backward => call_runtime(
+                compiler,
+                "axon_tensor_backward",
+                Arg::Void,
+                &[Arg::Ptr],
+                &mut [values[0]],
+            ),
+            TensorFunc::Grad => call_runtime(
+                compiler,
+                "axon_tensor_grad",
+                Arg::Ptr,
+                &[Arg::Ptr],
+                &mut [values[0]],
+            ),
+            TensorFunc::GradOf => call_runtime(
+                compiler,
+                "axon_tensor_grad_of",
+                Arg::Ptr,
+                &[Arg::Ptr, Arg::Ptr],
+                &mut [values[0], values[1]],
+            ),
+            TensorFunc::Detach => call_runtime(
+                compiler,
+                "axon_tensor_detach",
+                Arg::Ptr,
+                &[Arg::Ptr],
+                &mut [values[0]],
+            ),
+            TensorFunc::Item => call_runtime(
+                compiler,
+                "axon_tensor_item",
+                Arg::F32,
+                &[Arg::Ptr],
+                &mut [values[0]],
+            ),
+            TensorFunc::Get => {
+                let i64_type = LLVMInt64TypeInContext(compiler.context);
+                let rank = args.len() - 1;
+                let index = compiler.build_entry_alloca(LLVMArrayType2(i64_type, rank as u64), "tensor_index")?;
+                for (axis, value) in values[1..].iter().enumerate() {
+                    let mut widened = *value;
+                    if LLVMTypeOf(widened) != i64_type {
+                        widened = LLVMBuildSExt(compiler.builder, widened, i64_type, b"idx_sext\0".as_ptr() as _);
+                    }
+                    let mut indices = [const_i64(compiler, axis as u64)];
+                    let slot = LLVMBuildGEP2(
+                        compiler.builder,
+                        i64_type,
+                        index,
+                        indices.as_mut_ptr(),
+                        1,
+                        b"tensor_idx\0".as_ptr() as _,
+                    );
+                    LLVMBuildStore(compiler.builder, widened, slot);
+                }
+                call_runtime(
+                    compiler,
+                    "axon_tensor_get",
+                    Arg::F32,
+                    &[Arg::Ptr, Arg::Ptr],
+                    &mut [values[0], index],
+                )
+            }
+        };
+        // the result's tape holds its own references to the inputs it needs
+        for temporary in temporaries {
+            codegen_tensor_release(compiler, temporary);
+        }
+        Ok((value, ty.clone()))
+    }
+}
+
+pub fn codegen_tensor_print(compiler: &mut Compiler, tensor: LLVMValueRef, newline: bool) {
+    unsafe {
+        call_runtime(
+            compiler,
+            "axon_tensor_print",
+            Arg::Void,
+            &[Arg::Ptr, Arg::I32],
+            &mut [tensor, const_i32(compiler, newline as u64)],
+        );
+    }
+}
@@ -2,6 +2,7 @@
 //llvm ir generation for creating variables
 
 use super::compiler_context::Compiler;
+use super::compiler_tensor_codegen::{codegen_tensor_retain, codegen_tensor_store, owned_tensor, tensor_local};
 use crate::high_level_ir::{HIRStatement, HIRType};
 use llvm_sys::core::*;
 use std::ffi::CString;
@@ -14,7 +15,7 @@ pub fn codegen_assignment(compiler: &mut Compiler, stmt: &HIRStatement) -> Resul
                 return Err("\x1b[31m[ERR-SEM-695] Null value reference\x1b[0m".to_string());
             }
             let var_type_ref = compiler.hir_type_to_llvm_type(&val_type);
-            let var_name_c = CString::new(name.as_str()).unwrap();
+            let var_name_c = CString::new(compiler.global_name(name)).unwrap();
 
             let (ptr_to_store_to, is_global) = if let Some((existing_ptr, existing_type)) = compiler.variables.get(name) {
             
@@ -25,7 +26,16 @@ pub fn codegen_assignment(compiler: &mut Compiler, stmt: &HIRStatement) -> Resul
                     ));
                 }
                 (*existing_ptr, true) 
-            } else if compiler.current_function.is_some() {
+            } else if let HIRType::Tensor(_) = val_type
+                && compiler.current_function.is_some()
+                && compiler.current_function != compiler.repl_entry
+            {
+                // a tensor declared inside a loop is assigned again on every pass and releases the last value
+                let alloca = tensor_local(compiler, &compiler.global_name(name))?;
+                compiler.variables.insert(name.clone(), (alloca, val_type.clone()));
+                compiler.debug_declare_variable(name, &val_type, alloca);
+                (alloca, false)
+            } else if compiler.current_function.is_some() && compiler.current_function != compiler.repl_entry {
             
                 let current_block = LLVMGetInsertBlock(compiler.builder);
                 if current_block.is_null() {
@@ -36,6 +46,7 @@ pub fn codegen_assignment(compiler: &mut Compiler, stmt: &HIRStatement) -> Resul
                     return Err("\x1b[31m[ERR-SEM-671] Failed to allocate local variable\x1b[0m".to_string());
                 }
                 compiler.variables.insert(name.clone(), (alloca, val_type.clone()));
+                compiler.debug_declare_variable(name, &val_type, alloca);
                 (alloca, false)
             } else {
         
@@ -51,6 +62,11 @@ pub fn codegen_assignment(compiler: &mut Compiler, stmt: &HIRStatement) -> Resul
 
             if is_global && compiler.current_function.is_none() {
                 LLVMSetInitializer(ptr_to_store_to, val_ref);
+            } else if let HIRType::Tensor(_) = val_type {
+                if !owned_tensor(value, &val_type) {
+                    codegen_tensor_retain(compiler, val_ref);
+                }
+                codegen_tensor_store(compiler, val_ref, ptr_to_store_to);
             } else {
                 let store = LLVMBuildStore(compiler.builder, val_ref, ptr_to_store_to);
                 if store.is_null() {
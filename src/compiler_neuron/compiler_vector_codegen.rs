@@ -0,0 +1,347 @@
+//llvm ir generation for vectors,
+//a vector is a pointer to a heap header { i64 len, i64 cap, ptr data },
+//elements are stored in a malloc'ed buffer that grows with realloc on push
+
+use super::compiler_context::Compiler;
+use crate::high_level_ir::{HIRExpr, HIRStatement, HIRType};
+use llvm_sys::LLVMIntPredicate;
+use llvm_sys::core::*;
+use llvm_sys::prelude::{LLVMTypeRef, LLVMValueRef};
+
+const LEN_FIELD: u32 = 0;
+const CAP_FIELD: u32 = 1;
+const DATA_FIELD: u32 = 2;
+const MIN_CAPACITY: u64 = 4;
+
+unsafe fn malloc_type(compiler: &Compiler) -> LLVMTypeRef { unsafe {
+    let i8_ptr_type = LLVMPointerType(LLVMInt8TypeInContext(compiler.context), 0);
+    LLVMFunctionType(
+        i8_ptr_type,
+        [LLVMInt64TypeInContext(compiler.context)].as_ptr() as *mut _,
+        1,
+        0,
+    )
+}}
+
+unsafe fn build_malloc(compiler: &Compiler, size: LLVMValueRef, name: &[u8]) -> LLVMValueRef { unsafe {
+    let malloc_ty = malloc_type(compiler);
+    let malloc_func = compiler.get_or_declare_function("malloc", malloc_ty);
+    let mut args = [size];
+    LLVMBuildCall2(
+        compiler.builder,
+        malloc_ty,
+        malloc_func,
+        args.as_mut_ptr(),
+        1,
+        name.as_ptr() as _,
+    )
+}}
+
+unsafe fn field_ptr(compiler: &Compiler, header: LLVMValueRef, field: u32) -> LLVMValueRef { unsafe {
+    LLVMBuildStructGEP2(
+        compiler.builder,
+        compiler.vector_header_type(),
+        header,
+        field,
+        b"vec_field\0".as_ptr() as _,
+    )
+}}
+
+unsafe fn load_field(compiler: &Compiler, header: LLVMValueRef, field: u32) -> LLVMValueRef { unsafe {
+    let ty = if field == DATA_FIELD {
+        LLVMPointerType(LLVMInt8TypeInContext(compiler.context), 0)
+    } else {
+        LLVMInt64TypeInContext(compiler.context)
+    };
+    LLVMBuildLoad2(
+        compiler.builder,
+        ty,
+        field_ptr(compiler, header, field),
+        b"vec_load\0".as_ptr() as _,
+    )
+}}
+
+unsafe fn element_ptr(
+    compiler: &Compiler,
+    data: LLVMValueRef,
+    elem_llvm_ty: LLVMTypeRef,
+    index: LLVMValueRef,
+) -> LLVMValueRef { unsafe {
+    let mut indices = [index];
+    LLVMBuildGEP2(
+        compiler.builder,
+        elem_llvm_ty,
+        data,
+        indices.as_mut_ptr(),
+        1,
+        b"vec_elem\0".as_ptr() as _,
+    )
+}}
+
+fn element_type(ty: &HIRType) -> Result<HIRType, String> {
+    match ty {
+        HIRType::Vector(elem) => Ok((**elem).clone()),
+        other => Err(format!(
+            "\x1b[31m[ERR-SEM-233] Cannot use {:?} as a vector\x1b[0m",
+            other
+        )),
+    }
+}
+
+unsafe fn codegen_index_value(compiler: &mut Compiler, index: &HIRExpr) -> Result<LLVMValueRef, String> { unsafe {
+    let (index_val, index_ty) = compiler.codegen_expr(index)?;
+    match index_ty {
+        HIRType::I64 => Ok(index_val),
+        HIRType::I32 => Ok(LLVMBuildSExt(
+            compiler.builder,
+            index_val,
+            LLVMInt64TypeInContext(compiler.context),
+            b"idx_sext\0".as_ptr() as _,
+        )),
+        other => Err(format!(
+            "\x1b[31m[ERR-SEM-234] Vector index must be an integer, found {:?}\x1b[0m",
+            other
+        )),
+    }
+}}
+
+// emits the bounds check and returns a pointer to the element,
+// a negative index wraps to a huge unsigned value so one `uge` covers both ends
+unsafe fn checked_element_ptr(
+    compiler: &mut Compiler,
+    header: LLVMValueRef,
+    index: LLVMValueRef,
+    elem_llvm_ty: LLVMTypeRef,
+) -> Result<LLVMValueRef, String> { unsafe {
+    let fun = compiler
+        .current_function
+        .ok_or("\x1b[31m[ERR-SEM-237] Vectors can only be used inside a function\x1b[0m")?;
+    let len = load_field(compiler, header, LEN_FIELD);
+    let out_of_bounds = LLVMBuildICmp(
+        compiler.builder,
+        LLVMIntPredicate::LLVMIntUGE,
+        index,
+        len,
+        b"oob\0".as_ptr() as _,
+    );
+    let oob_bb = LLVMAppendBasicBlockInContext(compiler.context, fun, b"vec.oob\0".as_ptr() as _);
+    let ok_bb = LLVMAppendBasicBlockInContext(compiler.context, fun, b"vec.ok\0".as_ptr() as _);
+    LLVMBuildCondBr(compiler.builder, out_of_bounds, oob_bb, ok_bb);
+
+    LLVMPositionBuilderAtEnd(compiler.builder, oob_bb);
+    compiler.build_runtime_error(
+        "\n[ERR-RT-001] Runtime error: index %lld is out of bounds for vector of length %lld\n",
+        &[index, len],
+    );
+
+    LLVMPositionBuilderAtEnd(compiler.builder, ok_bb);
+    let data = load_field(compiler, header, DATA_FIELD);
+    Ok(element_ptr(compiler, data, elem_llvm_ty, index))
+}}
+
+pub fn codegen_vector_literal(
+    compiler: &mut Compiler,
+    elements: &[HIRExpr],
+    elem_type: &HIRType,
+) -> Result<(LLVMValueRef, HIRType), String> {
+    unsafe {
+        if compiler.current_function.is_none() {
+            return Err(
+                "\x1b[31m[ERR-SEM-237] Vectors can only be created inside a function\x1b[0m".to_string(),
+            );
+        }
+        let i64_type = LLVMInt64TypeInContext(compiler.context);
+        let elem_llvm_ty = compiler.hir_type_to_llvm_type(elem_type);
+        let header_ty = compiler.vector_header_type();
+
+        let header = build_malloc(compiler, LLVMSizeOf(header_ty), b"vec_header\0");
+        let len = elements.len() as u64;
+        let cap = LLVMConstInt(i64_type, len.max(MIN_CAPACITY), 0);
+        let data_size = LLVMBuildMul(
+            compiler.builder,
+            cap,
+            LLVMSizeOf(elem_llvm_ty),
+            b"vec_bytes\0".as_ptr() as _,
+        );
+        let data = build_malloc(compiler, data_size, b"vec_data\0");
+
+        LLVMBuildStore(compiler.builder, LLVMConstInt(i64_type, len, 0), field_ptr(compiler, header, LEN_FIELD));
+        LLVMBuildStore(compiler.builder, cap, field_ptr(compiler, header, CAP_FIELD));
+        LLVMBuildStore(compiler.builder, data, field_ptr(compiler, header, DATA_FIELD));
+
+        for (i, element) in elements.iter().enumerate() {
+            let (value, _) = compiler.codegen_expr(element)?;
+            let slot = element_ptr(compiler, data, elem_llvm_ty, LLVMConstInt(i64_type, i as u64, 0));
+            LLVMBuildStore(compiler.builder, value, slot);
+        }
+
+        Ok((header, HIRType::Vector(Box::new(elem_type.clone()))))
+    }
+}
+
+pub fn codegen_index(
+    compiler: &mut Compiler,
+    target: &HIRExpr,
+    index: &HIRExpr,
+) -> Result<(LLVMValueRef, HIRType), String> {
+    unsafe {
+        let (header, vec_ty) = compiler.codegen_expr(target)?;
+        let elem_ty = element_type(&vec_ty)?;
+        let elem_llvm_ty = compiler.hir_type_to_llvm_type(&elem_ty);
+        let index_val = codegen_index_value(compiler, index)?;
+        let slot = checked_element_ptr(compiler, header, index_val, elem_llvm_ty)?;
+        let value = LLVMBuildLoad2(compiler.builder, elem_llvm_ty, slot, b"vec_get\0".as_ptr() as _);
+        Ok((value, elem_ty))
+    }
+}
+
+pub fn codegen_len(compiler: &mut Compiler, target: &HIRExpr) -> Result<(LLVMValueRef, HIRType), String> {
+    unsafe {
+        let (header, vec_ty) = compiler.codegen_expr(target)?;
+        element_type(&vec_ty)?;
+        let len = load_field(compiler, header, LEN_FIELD);
+        let len_i32 = LLVMBuildTrunc(
+            compiler.builder,
+            len,
+            LLVMInt32TypeInContext(compiler.context),
+            b"vec_len\0".as_ptr() as _,
+        );
+        Ok((len_i32, HIRType::I32))
+    }
+}
+
+pub fn codegen_push(
+    compiler: &mut Compiler,
+    target: &HIRExpr,
+    value: &HIRExpr,
+) -> Result<(LLVMValueRef, HIRType), String> {
+    unsafe {
+        let fun = compiler
+            .current_function
+            .ok_or("\x1b[31m[ERR-SEM-237] Vectors can only be used inside a function\x1b[0m")?;
+        let (header, vec_ty) = compiler.codegen_expr(target)?;
+        let elem_ty = element_type(&vec_ty)?;
+        let elem_llvm_ty = compiler.hir_type_to_llvm_type(&elem_ty);
+        let (value_val, _) = compiler.codegen_expr(value)?;
+
+        let i64_type = LLVMInt64TypeInContext(compiler.context);
+        let len = load_field(compiler, header, LEN_FIELD);
+        let cap = load_field(compiler, header, CAP_FIELD);
+        let full = LLVMBuildICmp(
+            compiler.builder,
+            LLVMIntPredicate::LLVMIntEQ,
+            len,
+            cap,
+            b"vec_full\0".as_ptr() as _,
+        );
+        let grow_bb = LLVMAppendBasicBlockInContext(compiler.context, fun, b"vec.grow\0".as_ptr() as _);
+        let store_bb = LLVMAppendBasicBlockInContext(compiler.context, fun, b"vec.store\0".as_ptr() as _);
+        LLVMBuildCondBr(compiler.builder, full, grow_bb, store_bb);
+
+        LLVMPositionBuilderAtEnd(compiler.builder, grow_bb);
+        let new_cap = LLVMBuildMul(
+            compiler.builder,
+            cap,
+            LLVMConstInt(i64_type, 2, 0),
+            b"vec_new_cap\0".as_ptr() as _,
+        );
+        let new_size = LLVMBuildMul(
+            compiler.builder,
+            new_cap,
+            LLVMSizeOf(elem_llvm_ty),
+            b"vec_bytes\0".as_ptr() as _,
+        );
+        let i8_ptr_type = LLVMPointerType(LLVMInt8TypeInContext(compiler.context), 0);
+        let realloc_ty = LLVMFunctionType(i8_ptr_type, [i8_ptr_type, i64_type].as_ptr() as *mut _, 2, 0);
+        let realloc_func = compiler.get_or_declare_function("realloc", realloc_ty);
+        let old_data = load_field(compiler, header, DATA_FIELD);
+        let mut realloc_args = [old_data, new_size];
+        let new_data = LLVMBuildCall2(
+            compiler.builder,
+            realloc_ty,
+            realloc_func,
+            realloc_args.as_mut_ptr(),
+            2,
+            b"vec_realloc\0".as_ptr() as _,
+        );
+        LLVMBuildStore(compiler.builder, new_cap, field_ptr(compiler, header, CAP_FIELD));
+        LLVMBuildStore(compiler.builder, new_data, field_ptr(compiler, header, DATA_FIELD));
+        LLVMBuildBr(compiler.builder, store_bb);
+
+        LLVMPositionBuilderAtEnd(compiler.builder, store_bb);
+        let data = load_field(compiler, header, DATA_FIELD);
+        let slot = element_ptr(compiler, data, elem_llvm_ty, len);
+        LLVMBuildStore(compiler.builder, value_val, slot);
+        let new_len = LLVMBuildAdd(
+            compiler.builder,
+            len,
+            LLVMConstInt(i64_type, 1, 0),
+            b"vec_new_len\0".as_ptr() as _,
+        );
+        let store = LLVMBuildStore(compiler.builder, new_len, field_ptr(compiler, header, LEN_FIELD));
+
+        Ok((store, HIRType::Void))
+    }
+}
+
+pub fn codegen_pop(compiler: &mut Compiler, target: &HIRExpr) -> Result<(LLVMValueRef, HIRType), String> {
+    unsafe {
+        let fun = compiler
+            .current_function
+            .ok_or("\x1b[31m[ERR-SEM-237] Vectors can only be used inside a function\x1b[0m")?;
+        let (header, vec_ty) = compiler.codegen_expr(target)?;
+        let elem_ty = element_type(&vec_ty)?;
+        let elem_llvm_ty = compiler.hir_type_to_llvm_type(&elem_ty);
+
+        let i64_type = LLVMInt64TypeInContext(compiler.context);
+        let len = load_field(compiler, header, LEN_FIELD);
+        let empty = LLVMBuildICmp(
+            compiler.builder,
+            LLVMIntPredicate::LLVMIntEQ,
+            len,
+            LLVMConstInt(i64_type, 0, 0),
+            b"vec_empty\0".as_ptr() as _,
+        );
+        let empty_bb = LLVMAppendBasicBlockInContext(compiler.context, fun, b"vec.empty\0".as_ptr() as _);
+        let pop_bb = LLVMAppendBasicBlockInContext(compiler.context, fun, b"vec.pop\0".as_ptr() as _);
+        LLVMBuildCondBr(compiler.builder, empty, empty_bb, pop_bb);
+
+        LLVMPositionBuilderAtEnd(compiler.builder, empty_bb);
+        compiler.build_runtime_error("\n[ERR-RT-002] Runtime error: pop() called on an empty vector\n", &[]);
+
+        LLVMPositionBuilderAtEnd(compiler.builder, pop_bb);
+        let new_len = LLVMBuildSub(
+            compiler.builder,
+            len,
+            LLVMConstInt(i64_type, 1, 0),
+            b"vec_new_len\0".as_ptr() as _,
+        );
+        LLVMBuildStore(compiler.builder, new_len, field_ptr(compiler, header, LEN_FIELD));
+        let data = load_field(compiler, header, DATA_FIELD);
+        let slot = element_ptr(compiler, data, elem_llvm_ty, new_len);
+        let value = LLVMBuildLoad2(compiler.builder, elem_llvm_ty, slot, b"vec_popped\0".as_ptr() as _);
+        Ok((value, elem_ty))
+    }
+}
+
+pub fn codegen_index_assign(compiler: &mut Compiler, stmt: &HIRStatement) -> Result<(), String> {
+    unsafe {
+        let HIRStatement::IndexAssign { name, index, value } = stmt else {
+            return Err("\x1b[31m[ERR-SEM-238] codegen_index_assign expected HIRStatement::IndexAssign\x1b[0m".into());
+        };
+        let (header, vec_ty) = compiler.codegen_expr(&HIRExpr::Identifier(name.clone()))?;
+        let elem_ty = element_type(&vec_ty)?;
+        let elem_llvm_ty = compiler.hir_type_to_llvm_type(&elem_ty);
+        let index_val = codegen_index_value(compiler, index)?;
+        let (value_val, value_ty) = compiler.codegen_expr(value)?;
+        if value_ty != elem_ty {
+            return Err(format!(
+                "\x1b[31m[ERR-SEM-510] Type mismatch: vector of {:?} vs new value {:?}\x1b[0m",
+                elem_ty, value_ty
+            ));
+        }
+        let slot = checked_element_ptr(compiler, header, index_val, elem_llvm_ty)?;
+        LLVMBuildStore(compiler.builder, value_val, slot);
+        Ok(())
+    }
+}
@@ -3,54 +3,60 @@
 //and also here is the main logic of converting HIR to llvm ir
 
 
+use crate::backend::{CompileResult, CompilerError, EmitKind, OptLevel};
+use crate::compiler_neuron::compiler_pass_pipeline::run_passes;
 use crate::high_level_ir::{HIRStatement, HIRType};
+use crate::module_loader::SourceMap;
 use llvm_sys::analysis::{LLVMVerifierFailureAction, LLVMVerifyModule};
+use llvm_sys::bit_writer::LLVMWriteBitcodeToMemoryBuffer;
 use llvm_sys::core::*;
 use llvm_sys::execution_engine::{
     LLVMCreateExecutionEngineForModule, LLVMDisposeExecutionEngine, LLVMExecutionEngineRef,
     LLVMGetFunctionAddress,
 };
-use llvm_sys::prelude::LLVMModuleRef;
+use llvm_sys::prelude::{LLVMMemoryBufferRef, LLVMModuleRef};
 use llvm_sys::target::{
-    LLVM_InitializeAllAsmPrinters, LLVM_InitializeAllTargetInfos, LLVM_InitializeAllTargetMCs,
-    LLVM_InitializeAllTargets, LLVM_InitializeNativeAsmParser, LLVM_InitializeNativeAsmPrinter,
-    LLVM_InitializeNativeTarget,
+    LLVMDisposeTargetData, LLVMSetModuleDataLayout, LLVM_InitializeAllAsmPrinters,
+    LLVM_InitializeAllTargetInfos, LLVM_InitializeAllTargetMCs, LLVM_InitializeAllTargets,
+    LLVM_InitializeNativeAsmParser, LLVM_InitializeNativeAsmPrinter, LLVM_InitializeNativeTarget,
 };
 use llvm_sys::target_machine::{
-    LLVMCodeGenFileType, LLVMCodeGenOptLevel, LLVMCodeModel, LLVMCreateTargetMachine,
-    LLVMDisposeTargetMachine, LLVMGetTargetFromTriple, LLVMRelocMode,
-    LLVMTargetMachineEmitToFile,
+    LLVMCodeGenFileType, LLVMCodeModel, LLVMCreateTargetDataLayout,
+    LLVMCreateTargetMachine, LLVMDisposeTargetMachine, LLVMGetTargetFromTriple, LLVMRelocMode,
+    LLVMTargetMachineEmitToMemoryBuffer,
 };
 use std::collections::HashSet;
 use std::ffi::{CStr, CString};
-use std::fmt;
 
+pub mod compiler_assert_codegen;
 pub mod compiler_context;
+pub mod compiler_debug_info;
 pub mod compiler_function_codegen;
+pub mod compiler_handler_codegen;
 pub mod compiler_if_codegen;
 pub mod compiler_input_codegen;
 pub mod compiler_loop_codegen;
 pub mod compiler_math_codegen;
+pub mod compiler_pass_pipeline;
 pub mod compiler_print_codegen;
+pub mod compiler_runtime;
+pub mod compiler_tensor_codegen;
 pub mod compiler_variable_codegen;
+pub mod compiler_vector_codegen;
 
-#[derive(Debug)]
-pub struct CompilerError(pub String);
-
-impl fmt::Display for CompilerError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Compiler Error: {}", self.0)
-    }
+#[derive(Default)]
+pub struct CodegenOptions {
+    // overflow, division, conversion and input checks that stop the program, `--release` leaves them out
+    pub checked: bool,
+    // lets runtime errors say which file and line they came from
+    pub sources: SourceMap,
+    // `axon run --emit=llvm-ir` writes the module here before running it
+    pub emit_ir: Option<String>,
+    pub opt_level: OptLevel,
+    // DWARF line tables, functions and locals for gdb/lldb (`-g`)
+    pub debug_info: bool,
 }
 
-impl From<String> for CompilerError {
-    fn from(error: String) -> Self {
-        CompilerError(error)
-    }
-}
-
-pub type CompileResult<T> = Result<T, Vec<CompilerError>>;
-
 pub fn codegen_statement(
     compiler: &mut compiler_context::Compiler,
     stmt: &HIRStatement,
@@ -63,55 +69,83 @@ pub fn codegen_statement(
             compiler_variable_codegen::codegen_assignment(compiler, stmt)
         }
         HIRStatement::Print { .. } => compiler_print_codegen::codegen_print(compiler, stmt),
-        HIRStatement::ExprStatement { expr } => compiler.codegen_expr(expr).map(|_| ()),
+        HIRStatement::ExprStatement { expr } => {
+            let (value, ty) = compiler.codegen_expr(expr)?;
+            // e.g. a function returning a tensor, called for its output
+            if compiler_tensor_codegen::owned_tensor(expr, &ty) {
+                compiler_tensor_codegen::codegen_tensor_release(compiler, value);
+            }
+            Ok(())
+        }
         HIRStatement::If { .. } => compiler_if_codegen::codegen_if(compiler, stmt),
         HIRStatement::While { .. } => compiler_loop_codegen::codegen_while(compiler, stmt),
         HIRStatement::Loop { .. } => compiler_loop_codegen::codegen_loop(compiler, stmt),
         HIRStatement::Break => compiler_loop_codegen::codegen_break(compiler),
+        HIRStatement::Return { value } => {
+            compiler_function_codegen::codegen_return(compiler, value.as_ref())
+        }
         HIRStatement::Input { .. } => compiler_input_codegen::codegen_input(compiler, stmt),
+        HIRStatement::IndexAssign { .. } => {
+            compiler_vector_codegen::codegen_index_assign(compiler, stmt)
+        }
+        HIRStatement::Assert { .. } => compiler_assert_codegen::codegen_assert(compiler, stmt),
+        HIRStatement::OnError { .. } => compiler_handler_codegen::codegen_on_error(compiler, stmt),
+        HIRStatement::Located { span, stmt } => {
+            // on failure the span is left in place for the error report
+            let outer = compiler.current_span.replace(span.clone());
+            compiler.debug_location(Some(span));
+            codegen_statement(compiler, stmt)?;
+            compiler.debug_location(outer.as_ref());
+            compiler.current_span = outer;
+            Ok(())
+        }
+    }
+}
+
+// adds every function up front, so calls can come before the declaration
+pub fn declare_functions(compiler: &mut compiler_context::Compiler, hir: &[HIRStatement]) {
+    for statement in hir {
+        if let HIRStatement::Function { name, params, return_type, start, .. } = statement.unlocated() {
+            let param_types: Vec<HIRType> = params.iter().map(|(_, ty)| ty.clone()).collect();
+            if *start {
+                compiler.declare_function(name, "main", &param_types, HIRType::I32);
+            } else {
+                compiler.declare_function(name, name, &param_types, return_type.clone());
+            }
+        }
     }
 }
 
 pub fn create_llvm_module(
     hir: Vec<HIRStatement>,
     mutable_vars: HashSet<String>,
+    options: CodegenOptions,
 ) -> Result<compiler_context::Compiler, Vec<CompilerError>> {
     let mut compiler = compiler_context::Compiler::new("axon_module");
     compiler.mutable_vars = mutable_vars;
+    compiler.checked = options.checked;
+    compiler.sources = options.sources;
+    if options.debug_info {
+        let optimized = options.opt_level != OptLevel::O0;
+        compiler.debug = Some(compiler_debug_info::DebugInfo::new(
+            compiler.module,
+            compiler.context,
+            &compiler.sources,
+            optimized,
+        ));
+    }
     let mut errors: Vec<CompilerError> = Vec::new();
 
-    unsafe {
-        for statement in &hir {
-            if let HIRStatement::Function { name, params, return_type, start, .. } = statement {
-                let final_name = if *start { "main" } else { name };
-                let func_name = CString::new(final_name).unwrap();
-                let param_types_llvm: Vec<_> = params
-                    .iter()
-                    .map(|(_, ty)| compiler.hir_type_to_llvm_type(ty))
-                    .collect();
-                let ret_type_llvm = if *start {
-                    LLVMInt32TypeInContext(compiler.context)
-                } else {
-                    compiler.hir_type_to_llvm_type(return_type)
-                };
-                let func_type = LLVMFunctionType(
-                    ret_type_llvm,
-                    param_types_llvm.as_ptr() as *mut _,
-                    param_types_llvm.len() as u32,
-                    0,
-                );
-                let llvm_func = LLVMAddFunction(compiler.module, func_name.as_ptr(), func_type);
-                let recorded_ret_type = if *start { HIRType::I32 } else { return_type.clone() };
-                compiler.functions.insert(name.clone(), (llvm_func, func_type, recorded_ret_type));
-            }
-        }
+    declare_functions(&mut compiler, &hir);
 
-        for statement in hir {
-            if let Err(e) = codegen_statement(&mut compiler, &statement) {
-                errors.push(CompilerError(e));
-            }
+    for statement in hir {
+        if let Err(e) = codegen_statement(&mut compiler, &statement) {
+            errors.push(CompilerError(e, compiler.current_span.take()));
         }
     }
+    if let Some(debug) = compiler.debug.take() {
+        debug.finalize();
+    }
 
     if !errors.is_empty() {
         compiler.dispose();
@@ -121,16 +155,20 @@ pub fn create_llvm_module(
     Ok(compiler)
 }
 
+// the program's exit code, 0 when Start returned normally, a runtime error comes back as its code as well
 pub fn compile_and_run_jit(
     hir: Vec<HIRStatement>,
     mutable_vars: HashSet<String>,
-) -> CompileResult<()> {
+    options: CodegenOptions,
+) -> CompileResult<i32> {
     unsafe {
         LLVM_InitializeNativeTarget();
         LLVM_InitializeNativeAsmPrinter();
         LLVM_InitializeNativeAsmParser();
 
-        let compiler = create_llvm_module(hir, mutable_vars)?;
+        let emit_ir = options.emit_ir.clone();
+        let opt_level = options.opt_level;
+        let compiler = create_llvm_module(hir, mutable_vars, options)?;
         let module = compiler.module;
 
         let mut error_msg: *mut i8 = std::ptr::null_mut();
@@ -138,7 +176,15 @@ pub fn compile_and_run_jit(
             let msg = CStr::from_ptr(error_msg).to_string_lossy().into_owned();
             LLVMDisposeMessage(error_msg);
             compiler.dispose();
-            return Err(vec![CompilerError(format!("LLVM module verification failed: {}", msg))]);
+            return Err(vec![CompilerError(format!("LLVM module verification failed: {}", msg), None)]);
+        }
+        if let Err(e) = run_passes(module, std::ptr::null_mut(), opt_level) {
+            compiler.dispose();
+            return Err(vec![CompilerError(e, None)]);
+        }
+        if let Some(Err(e)) = emit_ir.map(|path| write_llvm_ir(module, &path)) {
+            compiler.dispose();
+            return Err(vec![CompilerError(e, None)]);
         }
         
         let mut ee: LLVMExecutionEngineRef = std::ptr::null_mut();
@@ -147,8 +193,9 @@ pub fn compile_and_run_jit(
             LLVMDisposeMessage(error_msg);
             // Do not dispose compiler here, as EE creation failed before it could take ownership
             compiler.dispose();
-            return Err(vec![CompilerError(format!("Failed to create JIT execution engine: {}", msg))]);
+            return Err(vec![CompilerError(format!("Failed to create JIT execution engine: {}", msg), None)]);
         }
+        compiler_runtime::register_runtime_symbols(ee, module);
 
         let main_func_name = CString::new("main").unwrap();
         let main_func_addr = LLVMGetFunctionAddress(ee, main_func_name.as_ptr());
@@ -156,9 +203,8 @@ pub fn compile_and_run_jit(
         let exit_code = if main_func_addr == 0 {
             -1 
         } else {
-            type MainFn = unsafe extern "C" fn() -> i32;
-            let main_fn = std::mem::transmute::<u64, MainFn>(main_func_addr);
-            main_fn()
+            let main_fn = std::mem::transmute::<u64, extern "C" fn() -> i32>(main_func_addr);
+            compiler_runtime::axon_jit_main(main_fn)
         };
         
         LLVMDisposeExecutionEngine(ee);
@@ -167,21 +213,20 @@ pub fn compile_and_run_jit(
         LLVMDisposeBuilder(compiler.builder);
         LLVMContextDispose(compiler.context);
         
-        if exit_code != 0 {
-             if main_func_addr == 0 {
-                return Err(vec![CompilerError("Main function not found".to_string())]);
-            }
-            return Err(vec![CompilerError(format!("Program exited with code: {}", exit_code))]);
+        if main_func_addr == 0 {
+            return Err(vec![CompilerError("Main function not found".to_string(), None)]);
         }
+        Ok(exit_code)
     }
-    Ok(())
 }
 
-pub fn emit_object_file(
+// the .ll text, bitcode, assembly or object file `--emit=` asks for, as bytes for the caller to write out
+pub fn emit_to_memory(
     module: LLVMModuleRef,
     target_triple_str: &str,
-    output_filename: &str,
-) -> Result<(), String> {
+    opt_level: OptLevel,
+    emit: EmitKind,
+) -> Result<Vec<u8>, String> {
     unsafe {
         LLVM_InitializeAllTargetInfos();
         LLVM_InitializeAllTargets();
@@ -211,7 +256,7 @@ pub fn emit_object_file(
             target_triple.as_ptr(),
             cpu.as_ptr(),
             features.as_ptr(),
-            LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+            opt_level.codegen_level(),
             LLVMRelocMode::LLVMRelocPIC, // <-- FIX for PIE
             LLVMCodeModel::LLVMCodeModelDefault,
         );
@@ -220,23 +265,70 @@ pub fn emit_object_file(
             return Err("Failed to create target machine".to_string());
         }
 
+        // .ll and .bc files carry the same triple and layout the object file would be built for
         LLVMSetTarget(module, target_triple.as_ptr());
+        let data_layout = LLVMCreateTargetDataLayout(target_machine);
+        LLVMSetModuleDataLayout(module, data_layout);
+        LLVMDisposeTargetData(data_layout);
 
-        let filename_c = CString::new(output_filename).unwrap();
-        if LLVMTargetMachineEmitToFile(
+        if let Err(e) = run_passes(module, target_machine, opt_level) {
+            LLVMDisposeTargetMachine(target_machine);
+            return Err(e);
+        }
+
+        let file_type = match emit {
+            EmitKind::LlvmIr => {
+                LLVMDisposeTargetMachine(target_machine);
+                let text = LLVMPrintModuleToString(module);
+                let bytes = CStr::from_ptr(text).to_bytes().to_vec();
+                LLVMDisposeMessage(text);
+                return Ok(bytes);
+            }
+            EmitKind::Bitcode => {
+                LLVMDisposeTargetMachine(target_machine);
+                return Ok(take_buffer(LLVMWriteBitcodeToMemoryBuffer(module)));
+            }
+            EmitKind::Asm => LLVMCodeGenFileType::LLVMAssemblyFile,
+            EmitKind::Obj | EmitKind::Exe => LLVMCodeGenFileType::LLVMObjectFile,
+        };
+        let mut buffer: LLVMMemoryBufferRef = std::ptr::null_mut();
+        if LLVMTargetMachineEmitToMemoryBuffer(
             target_machine,
             module,
-            filename_c.as_ptr() as *mut i8,
-            LLVMCodeGenFileType::LLVMObjectFile,
+            file_type,
             &mut error_msg,
+            &mut buffer,
         ) != 0 {
             let msg = CStr::from_ptr(error_msg).to_string_lossy().into_owned();
             LLVMDisposeMessage(error_msg);
             LLVMDisposeTargetMachine(target_machine);
-            return Err(format!("Failed to emit object file: {}", msg));
+            return Err(format!("Failed to emit {} file: {}", emit.name(), msg));
         }
 
         LLVMDisposeTargetMachine(target_machine);
+        Ok(take_buffer(buffer))
+    }
+}
+
+// copies an LLVM memory buffer out and frees it
+unsafe fn take_buffer(buffer: LLVMMemoryBufferRef) -> Vec<u8> {
+    unsafe {
+        let start = LLVMGetBufferStart(buffer) as *const u8;
+        let bytes = std::slice::from_raw_parts(start, LLVMGetBufferSize(buffer)).to_vec();
+        LLVMDisposeMemoryBuffer(buffer);
+        bytes
+    }
+}
+
+pub fn write_llvm_ir(module: LLVMModuleRef, output_filename: &str) -> Result<(), String> {
+    unsafe {
+        let filename_c = CString::new(output_filename).unwrap();
+        let mut error_msg: *mut i8 = std::ptr::null_mut();
+        if LLVMPrintModuleToFile(module, filename_c.as_ptr(), &mut error_msg) != 0 {
+            let msg = CStr::from_ptr(error_msg).to_string_lossy().into_owned();
+            LLVMDisposeMessage(error_msg);
+            return Err(format!("Failed to write LLVM IR: {}", msg));
+        }
     }
     Ok(())
-}
\ No newline at end of file
+}
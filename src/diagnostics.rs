@@ -0,0 +1,143 @@
+// what the compiler reports back to whoever is driving it,
+// every error and warning remembers the stage that raised it so a tool can tell a syntax error
+// from a failed codegen without parsing the CLI's output
+
+use crate::parser::parser_error::{ErrorKind, ParseError, Severity};
+use std::fmt;
+
+// the stages of a compile, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    // project.asml, dependencies and the entry file
+    Setup,
+    // the lexer, the parser and the module loader
+    Parsing,
+    Semantic,
+    Codegen,
+    // .ll, .bc, assembly or object file
+    Emit,
+    // compiling and running the program in the JIT
+    Execute,
+    // running the program in the interpreter
+    Interpret,
+}
+
+impl Stage {
+    // the name the CLI shows in its `Stage:` line
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Setup => "Setup",
+            Stage::Parsing => "Parsing",
+            Stage::Semantic => "Semantic",
+            Stage::Codegen => "IR Codegen",
+            Stage::Emit => "Emit",
+            Stage::Execute => "JIT Execute",
+            Stage::Interpret => "Interpret",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub stage: Stage,
+    pub error: ParseError,
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        matches!(self.error.severity, Severity::Error)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>,
+    // the program ran and ended with this non-zero code,
+    // a runtime error printed its message to stdout along with the rest of the output
+    exit_code: Option<i32>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // what `run` returns for a program that ran to a non-zero exit code
+    pub fn exited(stage: Stage, code: i32) -> Self {
+        let message = format!("Program exited with code: {}", code);
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.push(stage, ParseError::new(ErrorKind::Runtime, message, 0, 0, None, None, Severity::Error));
+        diagnostics.exit_code = Some(code);
+        diagnostics
+    }
+
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    pub fn push(&mut self, stage: Stage, error: ParseError) {
+        self.items.push(Diagnostic { stage, error });
+    }
+
+    pub fn extend(&mut self, stage: Stage, errors: impl IntoIterator<Item = ParseError>) {
+        self.items.extend(errors.into_iter().map(|error| Diagnostic { stage, error }));
+    }
+
+    // `other` happened after everything already here
+    pub fn append(&mut self, other: Diagnostics) {
+        self.items.extend(other.items);
+        self.exit_code = other.exit_code.or(self.exit_code);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.items.iter()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.items.iter().filter(|d| d.is_error())
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.items.iter().filter(|d| !d.is_error())
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.items.iter().any(Diagnostic::is_error)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    // the stages that reported anything, in pipeline order
+    pub fn stages(&self) -> Vec<Stage> {
+        let mut stages: Vec<Stage> = self.items.iter().map(|d| d.stage).collect();
+        stages.sort();
+        stages.dedup();
+        stages
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for diagnostic in &self.items {
+            writeln!(f, "{}: {}", diagnostic.stage.name(), diagnostic.error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostics {}
@@ -0,0 +1,1866 @@
+// every diagnostic code the compiler, the package manager and the runtime can print,
+// with a longer explanation for `axon explain <code>`.
+// errors are still written with their code first, `[ERR-SEM-550] Division by zero`, ParseError::new
+// splits it off and checks it against this table. codes are never reused, a retired one stays here
+
+use crate::terminal::WEBSITE;
+use console::style;
+
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub explanation: &'static str,
+    // a program that reports the code and the same program fixed,
+    // None for internal errors that no program should be able to reach
+    pub examples: Option<(&'static str, &'static str)>,
+}
+
+const fn entry(code: &'static str, title: &'static str, explanation: &'static str, failing: &'static str, fixed: &'static str) -> ErrorCode {
+    ErrorCode { code, title, explanation, examples: Some((failing, fixed)) }
+}
+
+// codegen errors for situations semantic analysis already rules out
+const fn internal(code: &'static str, title: &'static str, explanation: &'static str) -> ErrorCode {
+    ErrorCode { code, title, explanation, examples: None }
+}
+
+pub fn lookup(code: &str) -> Option<&'static ErrorCode> {
+    REGISTRY.iter().find(|entry| entry.code.eq_ignore_ascii_case(code))
+}
+
+// `axon explain ERR-SEM-550`, without a code every code is listed with its title
+pub fn run_explain(args: &[String]) -> i32 {
+    let Some(code) = args.first() else {
+        for entry in REGISTRY {
+            println!("{:<14} {}", style(entry.code).yellow(), entry.title);
+        }
+        return 0;
+    };
+    let Some(entry) = lookup(code) else {
+        println!(
+            "{} '{}' is not an AxonScript error code, run `axon explain` for the list",
+            style("✘").red().bold(),
+            code
+        );
+        return 1;
+    };
+
+    println!("{} {}\n", style(format!("[{}]", entry.code)).red().bold(), style(entry.title).bold());
+    println!("{}\n", entry.explanation);
+    match entry.examples {
+        Some((failing, fixed)) => {
+            println!("{}", style("Failing example:").red().bold());
+            println!("{}\n", indent(failing));
+            println!("{}", style("Fixed:").green().bold());
+            println!("{}", indent(fixed));
+        }
+        None => println!(
+            "This is an internal compiler error, no program should be able to cause it.\nPlease report the program that did at {}/bugs",
+            WEBSITE
+        ),
+    }
+    0
+}
+
+fn indent(example: &str) -> String {
+    example.lines().map(|line| format!("    {}", line)).collect::<Vec<_>>().join("\n")
+}
+
+pub static REGISTRY: &[ErrorCode] = &[
+    // lexing and parsing
+    entry(
+        "ERR-SYN-001",
+        "Invalid statement",
+        "A statement started with a name that is not followed by `(`, `[` or `.`, so it is not a call, \
+an element assignment or a method call. Variables are declared and changed with `set`, math results \
+are stored with `math`.",
+        r#"cast Start() >>
+    x = 5;
+<<"#,
+        r#"cast Start() >>
+    set: x(i32) = 5;
+<<"#,
+    ),
+    entry(
+        "ERR-SYN-002",
+        "Unexpected token at the start of a statement",
+        "The token cannot start a statement. Statements begin with a keyword (`set`, `out`, `math`, `if`, \
+`while`, `loop`, `return`, ...) or with a name that is called, indexed or has a method called on it.",
+        r#"cast Start() >>
+    + 1;
+<<"#,
+        r#"cast Start() >>
+    out(1);
+<<"#,
+    ),
+    entry(
+        "ERR-SYN-003",
+        "Unexpected end of input, expected a statement",
+        "The file ended where a statement was expected, for example right after `do`.",
+        r#"cast Start() >>
+    out(1);
+<<
+do"#,
+        r#"cast Start() >>
+    out(1);
+<<"#,
+    ),
+    entry(
+        "ERR-SYN-004",
+        "Unexpected token in expression",
+        "An expression was expected but the token cannot start one. Expressions are numbers, strings, \
+`yes`/`no`, names, calls, vector literals `[..]` and parenthesized expressions.",
+        r#"cast Start() >>
+    out(1 +);
+<<"#,
+        r#"cast Start() >>
+    out(1 + 2);
+<<"#,
+    ),
+    entry(
+        "ERR-SYN-005",
+        "Unexpected end of input, expected an expression",
+        "The file ended in the middle of an expression, for example right after an operator or `=`.",
+        r#"cast Start() >>
+    set: x(i32) ="#,
+        r#"cast Start() >>
+    set: x(i32) = 1;
+<<"#,
+    ),
+    entry(
+        "ERR-SYN-006",
+        "Expected a method name after '.'",
+        "A `.` after a value has to be followed by the name of a method, like `v.len()` or `t.sum()`.",
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1, 2];
+    out(v.());
+<<"#,
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1, 2];
+    out(v.len());
+<<"#,
+    ),
+    entry(
+        "ERR-SYN-007",
+        "Expected a different token",
+        "The statement is missing a token it needs at this point, most often the `;` at the end of a \
+statement or a closing `)`. The message names the token that was expected and the one that was found.",
+        r#"cast Start() >>
+    out(1)
+    out(2);
+<<"#,
+        r#"cast Start() >>
+    out(1);
+    out(2);
+<<"#,
+    ),
+    entry(
+        "ERR-SYN-008",
+        "Unexpected end of input, expected a token",
+        "The file ended in the middle of a statement, before a token it needs, like the `;` or `)` that \
+would close it.",
+        r#"cast Start() >>
+    out(1)"#,
+        r#"cast Start() >>
+    out(1);
+<<"#,
+    ),
+    entry(
+        "ERR-SYN-009",
+        "Expected '>>' to start a block",
+        "The body of a function, `if`, `else`, `while` or `loop` is a block between `>>` and `<<`.",
+        r#"cast Start()
+    out(1);
+<<"#,
+        r#"cast Start() >>
+    out(1);
+<<"#,
+    ),
+    entry(
+        "ERR-SYN-109",
+        "Expected a variable inside 'in(...)'",
+        "`in(name)` reads a line from standard input into the variable `name`, which has to be declared \
+with `set:` first.",
+        r#"cast Start() >>
+    in(5);
+<<"#,
+        r#"cast Start() >>
+    set: age(i32) = 0;
+    in(age);
+<<"#,
+    ),
+    entry(
+        "ERR-SYN-110",
+        "Only a vector variable can be indexed on the left of '='",
+        "Element assignment writes into a vector variable, `name[index] = value;`. The result of a call \
+or of another expression is a temporary and cannot be assigned to.",
+        r#"cast make() -> Vec(i32) >>
+    return [1, 2];
+<<
+cast Start() >>
+    make()[0] = 5;
+<<"#,
+        r#"cast make() -> Vec(i32) >>
+    return [1, 2];
+<<
+cast Start() >>
+    set: v(Vec(i32)) = make();
+    v[0] = 5;
+<<"#,
+    ),
+    entry(
+        "ERR-SYN-111",
+        "Expression cannot be used as a statement",
+        "An expression on its own only computes a value and throws it away. Statements that start with a \
+name have to assign to an element, call a method or call a function.",
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1, 2];
+    v[0];
+<<"#,
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1, 2];
+    out(v[0]);
+<<"#,
+    ),
+    entry(
+        "ERR-SYN-120",
+        "Expected a module path after 'use'",
+        "`use` imports another file of the project or an installed package by its dotted path, relative \
+to src/: `use util.calc;` loads src/util/calc.ax.",
+        r#"?? src/init.ax
+use;
+cast Start() >>
+    out(calc.add(1, 2));
+<<
+?? src/util/calc.ax
+cast add(a: i32, b: i32) -> i32 >>
+    return a + b;
+<<"#,
+        r#"?? src/init.ax
+use util.calc;
+cast Start() >>
+    out(calc.add(1, 2));
+<<
+?? src/util/calc.ax
+cast add(a: i32, b: i32) -> i32 >>
+    return a + b;
+<<"#,
+    ),
+    // types
+    entry(
+        "ERR-TYP-001",
+        "Expected a type",
+        "Variables and parameters need one of the types i32, i64, f32, f64, str, bool, Vec(T) or \
+Tensor(f32, [dims]).",
+        r#"cast Start() >>
+    set: x(int) = 1;
+<<"#,
+        r#"cast Start() >>
+    set: x(i32) = 1;
+<<"#,
+    ),
+    entry(
+        "ERR-TYP-002",
+        "Expected the element type of a vector",
+        "Vector types name their element type in parentheses, `Vec(i32)` or `Vec(str)`.",
+        r#"cast Start() >>
+    set: v(Vec()) = [1];
+<<"#,
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1];
+<<"#,
+    ),
+    entry(
+        "ERR-TYP-003",
+        "Parameter needs a type",
+        "Every function parameter is written as `name: type`, there is no inference for parameters.",
+        r#"cast double(n) -> i32 >>
+    return n + n;
+<<
+cast Start() >>
+    out(double(2));
+<<"#,
+        r#"cast double(n: i32) -> i32 >>
+    return n + n;
+<<
+cast Start() >>
+    out(double(2));
+<<"#,
+    ),
+    entry(
+        "ERR-TYP-004",
+        "Tensor element type must be f32",
+        "Tensors only hold 32-bit floats for now, the element type in `Tensor(f32, [..])` cannot be \
+anything else.",
+        r#"cast Start() >>
+    set t(Tensor(i32, [2])) = [1, 2];
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+<<"#,
+    ),
+    entry(
+        "ERR-TYP-005",
+        "Expected a positive tensor dimension",
+        "The shape of a tensor type is a list of integer literals greater than zero.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [0])) = [];
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [1])) = [0];
+<<"#,
+    ),
+    // parser errors for declarations, with the SEM prefix for historical reasons
+    entry(
+        "ERR-SEM-101",
+        "Expected a function name",
+        "`cast` is followed by the name of the function, then its parameters in parentheses.",
+        r#"cast (a: i32) >>
+    out(a);
+<<"#,
+        r#"cast show(a: i32) >>
+    out(a);
+<<
+cast Start() >>
+    show(1);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-102",
+        "Expected a name for the math result",
+        "`math([expr], name)` stores the result in `name`, the comma has to be followed by a variable \
+name.",
+        r#"cast Start() >>
+    math([1 + 2], 3);
+<<"#,
+        r#"cast Start() >>
+    math([1 + 2], sum);
+    out(sum);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-103",
+        "Expected a variable name",
+        "`set` and `set:` are followed by the variable name and its type, `set: name(type) = value;`.",
+        r#"cast Start() >>
+    set: (i32) = 1;
+<<"#,
+        r#"cast Start() >>
+    set: x(i32) = 1;
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-104",
+        "Expected a parameter name",
+        "Parameters are written as `name: type` and separated by commas.",
+        r#"cast add(: i32) -> i32 >>
+    return 1;
+<<"#,
+        r#"cast add(a: i32) -> i32 >>
+    return a;
+<<
+cast Start() >>
+    out(add(1));
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-105",
+        "Variable requires an initialization value",
+        "Every variable gets its value where it is declared, there are no uninitialized variables.",
+        r#"cast Start() >>
+    set: x(i32);
+<<"#,
+        r#"cast Start() >>
+    set: x(i32) = 0;
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-904",
+        "Expected 'Err' after '.' on math",
+        "The only thing that can follow `math(..)` is an error handler, `.Err(\"message\")`, which runs \
+instead of a runtime error when the math fails.",
+        r#"cast divide(a: i32, b: i32) >>
+    math([a / b], q).Fail("no");
+    out(q);
+<<
+cast Start() >>
+    divide(1, 0);
+<<"#,
+        r#"cast divide(a: i32, b: i32) >>
+    math([a / b], q).Err("cannot divide by zero");
+    out(q);
+<<
+cast Start() >>
+    divide(1, 0);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-905",
+        "Expected a string after Err on math",
+        "The error handler of `math(..)` takes the message to print as a string literal.",
+        r#"cast divide(a: i32, b: i32) >>
+    math([a / b], q).Err(1);
+    out(q);
+<<
+cast Start() >>
+    divide(1, 0);
+<<"#,
+        r#"cast divide(a: i32, b: i32) >>
+    math([a / b], q).Err("cannot divide by zero");
+    out(q);
+<<
+cast Start() >>
+    divide(1, 0);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-906",
+        "Expected a string after Err on input",
+        "The error handler of `in(..)` takes the message to print as a string literal.",
+        r#"cast Start() >>
+    set: n(i32) = 0;
+    in(n).Err(n);
+<<"#,
+        r#"cast Start() >>
+    set: n(i32) = 0;
+    in(n).Err("not a number");
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-907",
+        "Expected 'Err' after '.' on input",
+        "The only thing that can follow `in(..)` is an error handler, `.Err(\"message\")`, which runs \
+when the input cannot be read as the variable's type.",
+        r#"cast Start() >>
+    set: n(i32) = 0;
+    in(n).Fail("no");
+<<"#,
+        r#"cast Start() >>
+    set: n(i32) = 0;
+    in(n).Err("not a number");
+<<"#,
+    ),
+    // vectors and tensors
+    entry(
+        "ERR-SEM-231",
+        "Cannot infer the element type of an empty vector",
+        "`[]` has no elements to take a type from. It can only be used where the vector type is declared, \
+as the initial value of a `set: v(Vec(i32))` variable.",
+        r#"cast total(v: Vec(i32)) -> i32 >>
+    return v.len();
+<<
+cast Start() >>
+    out(total([]));
+<<"#,
+        r#"cast total(v: Vec(i32)) -> i32 >>
+    return v.len();
+<<
+cast Start() >>
+    set: none(Vec(i32)) = [];
+    out(total(none));
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-232",
+        "Vector element has the wrong type",
+        "All elements of a vector have its element type. This covers literals, `push` and element \
+assignment.",
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1, 2];
+    v.push("three");
+<<"#,
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1, 2];
+    v.push(3);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-233",
+        "Value cannot be indexed",
+        "Only vectors can be indexed with `[i]`. Tensor elements are read with `t.get(i, ..)`.",
+        r#"cast Start() >>
+    set n(i32) = 5;
+    out(n[0]);
+<<"#,
+        r#"cast Start() >>
+    set v(Vec(i32)) = [5];
+    out(v[0]);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-234",
+        "Vector index must be an integer",
+        "Vector indexes are i32 or i64 values, floats and other types are not converted.",
+        r#"cast Start() >>
+    set v(Vec(i32)) = [1, 2];
+    out(v[1.0]);
+<<"#,
+        r#"cast Start() >>
+    set v(Vec(i32)) = [1, 2];
+    out(v[1]);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-235",
+        "Unknown method",
+        "Vectors have `len()`, `push(value)` and `pop()`, tensors have the methods listed in ERR-SEM-247. \
+Other types have no methods.",
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1, 2];
+    out(v.size());
+<<"#,
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1, 2];
+    out(v.len());
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-236",
+        "Initial value does not match the declared type",
+        "A variable declared with a vector or tensor type has to be initialized with a value of exactly \
+that type. A number is widened to the declared type (i32 to i64, f32 to f64) but never narrowed: an i64 \
+or f64 value would lose part of itself in an i32 or f32 variable, only a literal that fits is accepted. \
+A tensor is never a number, read the value of a reduction like `t.sum()` with `.item()`.",
+        r#"cast Start() >>
+    set v(Vec(i32)) = 5;
+<<"#,
+        r#"cast Start() >>
+    set v(Vec(i32)) = [5];
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-237",
+        "Vectors can only be used inside a function",
+        "Vectors live on the heap and are created when the statement runs, so they cannot be top-level \
+values. Declare them inside a function.",
+        r#"set: v(Vec(i32)) = [1, 2];
+cast Start() >>
+    out(1);
+<<"#,
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1, 2];
+    out(v[0]);
+<<"#,
+    ),
+    internal(
+        "ERR-SEM-238",
+        "Element assignment given the wrong statement",
+        "Code generation for `v[i] = x;` was handed a different kind of statement.",
+    ),
+    entry(
+        "ERR-SEM-240",
+        "Tensor shapes cannot be broadcast together",
+        "Element-wise tensor operations need shapes that match from the last dimension on, where each \
+pair of dimensions is equal or one of them is 1.",
+        r#"cast Start() >>
+    set a(Tensor(f32, [2, 3])) = zeros([2, 3]);
+    set b(Tensor(f32, [2, 2])) = ones([2, 2]);
+    out(a + b);
+<<"#,
+        r#"cast Start() >>
+    set a(Tensor(f32, [2, 3])) = zeros([2, 3]);
+    set b(Tensor(f32, [1, 3])) = ones([1, 3]);
+    out(a + b);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-241",
+        "Tensor literal does not match its shape",
+        "A nested literal has to have as many elements at each depth as the shape of the declared tensor \
+type says.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [2, 2])) = [[1, 2], [3]];
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2, 2])) = [[1, 2], [3, 4]];
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-242",
+        "Tensor elements must be numbers",
+        "Tensor literals hold numbers, which are stored as f32.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, "two"];
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-243",
+        "Cannot combine a tensor with this value",
+        "Tensors combine with other tensors and with numbers, which are applied to every element.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+    out(t + "one");
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+    out(t + 1);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-244",
+        "Invalid matmul operands",
+        "`a.matmul(b)` multiplies a [m, k] tensor with a [k, n] tensor, the inner dimensions have to be \
+equal.",
+        r#"cast Start() >>
+    set a(Tensor(f32, [2, 3])) = ones([2, 3]);
+    set b(Tensor(f32, [2, 3])) = ones([2, 3]);
+    out(a.matmul(b));
+<<"#,
+        r#"cast Start() >>
+    set a(Tensor(f32, [2, 3])) = ones([2, 3]);
+    set b(Tensor(f32, [3, 2])) = ones([3, 2]);
+    out(a.matmul(b));
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-245",
+        "Invalid reshape",
+        "`t.reshape([..])` takes a shape literal with the same number of elements as the tensor.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [2, 3])) = ones([2, 3]);
+    out(t.reshape([4, 2]));
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2, 3])) = ones([2, 3]);
+    out(t.reshape([3, 2]));
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-246",
+        "Axis must be an integer literal",
+        "Reductions take the axis as an integer literal, the shape of the result depends on it and is \
+known when the program is compiled.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [2, 3])) = ones([2, 3]);
+    set axis(i32) = 1;
+    out(t.sum(axis));
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2, 3])) = ones([2, 3]);
+    out(t.sum(1));
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-247",
+        "Unknown tensor method",
+        "The message lists every tensor method with the number of arguments it takes.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+    out(t.average());
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+    out(t.mean());
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-248",
+        "Expected a shape literal",
+        "`zeros`, `ones` and `rand` take the shape of the new tensor as a literal, like `zeros([2, 3])`.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = zeros(2);
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = zeros([2]);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-249",
+        "Tensor index out of bounds",
+        "`t.get(i, ..)` with literal indexes is checked against the shape when the program is compiled.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+    out(t.get(2));
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+    out(t.get(1));
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-250",
+        "Invalid grad() arguments",
+        "`grad(loss, w)` returns the gradient of a loss tensor with respect to the parameter tensor `w`, \
+both arguments have to be tensors.",
+        r#"cast Start() >>
+    set w(Tensor(f32, [2])) = [1, 2];
+    out(grad(1, w));
+<<"#,
+        r#"cast Start() >>
+    set w(Tensor(f32, [2])) = [1, 2];
+    set loss(Tensor(f32, [2])) = w * w;
+    out(grad(loss, w));
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-252",
+        "transpose() needs a 2-D tensor",
+        "`t.transpose()` swaps rows and columns, tensors of any other rank cannot be transposed. Use \
+`reshape` to give the tensor two dimensions first.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [3])) = [1, 2, 3];
+    out(t.transpose());
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [3])) = [1, 2, 3];
+    out(t.reshape([3, 1]).transpose());
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-253",
+        "Axis out of range",
+        "The axis of a reduction counts the tensor's dimensions from 0, a tensor of shape [2, 3] has the \
+axes 0 and 1.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [2, 3])) = ones([2, 3]);
+    out(t.sum(2));
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2, 3])) = ones([2, 3]);
+    out(t.sum(1));
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-254",
+        "Method called with the wrong number of arguments",
+        "`len()` and `pop()` take no arguments, `push(value)` takes the one value to append.",
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1, 2];
+    v.push(3, 4);
+<<"#,
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1, 2];
+    v.push(3);
+    v.push(4);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-255",
+        "item() needs a single-element tensor",
+        "`t.item()` returns the one value held by a tensor as an f32, like the rank-0 tensor `t.sum()` \
+or `t.mean()` gives. Reduce a larger tensor first or read one element with `get`.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+    out(t.item());
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+    out(t.sum().item());
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-251",
+        "Operator not supported on tensors",
+        "Tensors support `+`, `-`, `*` and `/` element-wise. Comparisons and `%` are not defined for \
+them, compare single elements read with `get` instead.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+    out(t % 2);
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+    out(t.get(0) < 2.0);
+<<"#,
+    ),
+    // functions and control flow
+    entry(
+        "ERR-SEM-301",
+        "Program must have exactly one Start() function",
+        "The entry file of a program declares `cast Start()`, which is where it begins to run.",
+        r#"cast main() >>
+    out("hello");
+<<"#,
+        r#"cast Start() >>
+    out("hello");
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-302",
+        "Function declared inside another function",
+        "Functions are declared at the top level of a file, there are no nested functions.",
+        r#"cast Start() >>
+    cast helper() >>
+        out(1);
+    <<
+<<"#,
+        r#"cast helper() >>
+    out(1);
+<<
+cast Start() >>
+    helper();
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-303",
+        "Function declared more than once",
+        "Function names are unique within a program, there is no overloading.",
+        r#"cast show(a: i32) >>
+    out(a);
+<<
+cast show(a: str) >>
+    out(a);
+<<
+cast Start() >>
+    show(1);
+<<"#,
+        r#"cast show_int(a: i32) >>
+    out(a);
+<<
+cast show_str(a: str) >>
+    out(a);
+<<
+cast Start() >>
+    show_int(1);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-304",
+        "Start() cannot have parameters or a return type",
+        "`cast Start()` is called by the runtime with nothing to pass in and nothing to return to.",
+        r#"cast Start(n: i32) >>
+    out(n);
+<<"#,
+        r#"cast Start() >>
+    set n(i32) = 1;
+    out(n);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-305",
+        "Start() is not needed in the REPL",
+        "REPL entries run as soon as they are entered, write the statements directly.",
+        r#"axon> cast Start() >> out(1); <<"#,
+        r#"axon> out(1);"#,
+    ),
+    entry(
+        "ERR-SEM-310",
+        "'break' outside of a loop",
+        "`break` leaves the innermost `loop` or `while`, it has no meaning anywhere else.",
+        r#"cast Start() >>
+    out(1);
+    break;
+<<"#,
+        r#"cast Start() >>
+    loop >>
+        out(1);
+        break;
+    <<
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-312",
+        "'return' outside of a function",
+        "`return` can only be used inside a function body.",
+        r#"return;
+cast Start() >>
+    out(1);
+<<"#,
+        r#"cast Start() >>
+    out(1);
+    return;
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-313",
+        "Return value does not match the function",
+        "A function with a return type returns a value with every `return`, one without a return type \
+returns nothing. The return type is written after `->`.",
+        r#"cast answer() >>
+    return 42;
+<<
+cast Start() >>
+    out(answer());
+<<"#,
+        r#"cast answer() -> i32 >>
+    return 42;
+<<
+cast Start() >>
+    out(answer());
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-314",
+        "Returned value has the wrong type",
+        "The value of `return` has to have the function's return type, or one that widens to it.",
+        r#"cast name() -> str >>
+    return 1;
+<<
+cast Start() >>
+    out(name());
+<<"#,
+        r#"cast name() -> str >>
+    return "axon";
+<<
+cast Start() >>
+    out(name());
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-320",
+        "Call to an undeclared function",
+        "The function is not declared in this file. Functions of other modules are called through the \
+module, `calc.add(1, 2)` after `use util.calc;`.",
+        r#"cast Start() >>
+    out(add(1, 2));
+<<"#,
+        r#"cast add(a: i32, b: i32) -> i32 >>
+    return a + b;
+<<
+cast Start() >>
+    out(add(1, 2));
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-321",
+        "Wrong number of arguments",
+        "A call passes exactly as many arguments as the function has parameters.",
+        r#"cast add(a: i32, b: i32) -> i32 >>
+    return a + b;
+<<
+cast Start() >>
+    out(add(1));
+<<"#,
+        r#"cast add(a: i32, b: i32) -> i32 >>
+    return a + b;
+<<
+cast Start() >>
+    out(add(1, 2));
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-322",
+        "Argument has the wrong type",
+        "Each argument has to have the type of its parameter, or one that widens to it.",
+        r#"cast twice(n: i32) -> i32 >>
+    return n + n;
+<<
+cast Start() >>
+    out(twice("two"));
+<<"#,
+        r#"cast twice(n: i32) -> i32 >>
+    return n + n;
+<<
+cast Start() >>
+    out(twice(2));
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-323",
+        "Function does not return a value",
+        "A call to a function without a return type has no value to assign or print.",
+        r#"cast hello() >>
+    out("hello");
+<<
+cast Start() >>
+    set x(i32) = hello();
+<<"#,
+        r#"cast hello() >>
+    out("hello");
+<<
+cast Start() >>
+    hello();
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-330",
+        "Assertion compares incompatible values",
+        "`assert_eq(a, b)` compares numbers with numbers and bools with bools.",
+        r#"cast Start() >>
+    assert_eq(1, "1");
+<<"#,
+        r#"cast Start() >>
+    assert_eq(1, 1);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-340",
+        "Condition must be a bool",
+        "`if`, `while` and `assert` take a bool, numbers are not treated as true or false.",
+        r#"cast Start() >>
+    set n(i32) = 1;
+    if (n) >>
+        out(n);
+    <<
+<<"#,
+        r#"cast Start() >>
+    set n(i32) = 1;
+    if (n != 0) >>
+        out(n);
+    <<
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-341",
+        "'and'/'or' need bool operands",
+        "Both sides of `and` and `or` are bools.",
+        r#"cast Start() >>
+    out(1 and yes);
+<<"#,
+        r#"cast Start() >>
+    out(1 == 1 and yes);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-342",
+        "'not' needs a bool",
+        "`not` negates a bool, it does not work on numbers or strings.",
+        r#"cast Start() >>
+    out(not 0);
+<<"#,
+        r#"cast Start() >>
+    out(not no);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-343",
+        "Values cannot be compared",
+        "Comparisons work between numbers, between strings and between bools. `<`, `>`, `<=` and `>=` \
+are only defined for numbers.",
+        r#"cast Start() >>
+    out("a" < 1);
+<<"#,
+        r#"cast Start() >>
+    out("a" == "b");
+<<"#,
+    ),
+    // code generation
+    internal(
+        "ERR-SEM-510",
+        "Type mismatch in code generation",
+        "Code generation met two values whose types semantic analysis should have made agree.",
+    ),
+    internal(
+        "ERR-SEM-511",
+        "Unsupported coercion",
+        "Code generation was asked to convert between two types that have no conversion.",
+    ),
+    entry(
+        "ERR-SEM-520",
+        "Function is missing a return statement",
+        "A function with a return type has to end with `return value;` on every path.",
+        r#"cast sign(n: i32) -> i32 >>
+    if (n < 0) >>
+        return 0 - 1;
+    <<
+<<
+cast Start() >>
+    out(sign(5));
+<<"#,
+        r#"cast sign(n: i32) -> i32 >>
+    if (n < 0) >>
+        return 0 - 1;
+    <<
+    return 1;
+<<
+cast Start() >>
+    out(sign(5));
+<<"#,
+    ),
+    internal("ERR-SEM-521", "If given the wrong statement", "Code generation for `if` was handed a different kind of statement."),
+    internal("ERR-SEM-522", "Unsupported type in if condition", "An `if` condition reached code generation with a type other than bool."),
+    internal("ERR-SEM-523", "Function declaration given the wrong statement", "Code generation for `cast` was handed a different kind of statement."),
+    internal("ERR-SEM-524", "Condition outside of a function", "An `if` or an assertion reached code generation outside of a function body."),
+    internal("ERR-SEM-525", "'return' outside of a function", "A `return` reached code generation outside of a function body."),
+    internal("ERR-SEM-526", "Return type mismatch", "A `return` value reached code generation with a type other than the function's return type."),
+    internal("ERR-SEM-527", "'and'/'or' outside of a function", "A short-circuiting `and` or `or` reached code generation outside of a function body."),
+    internal("ERR-SEM-530", "Loop given the wrong statement", "Code generation for `loop` was handed a different kind of statement."),
+    internal("ERR-SEM-531", "No loop for 'break'", "A `break` reached code generation outside of a loop."),
+    internal("ERR-SEM-532", "Loop outside of a function", "A `loop` or `while` reached code generation outside of a function body."),
+    internal("ERR-SEM-533", "While given the wrong statement", "Code generation for `while` was handed a different kind of statement."),
+    internal("ERR-SEM-534", "While condition is not a bool", "A `while` condition reached code generation with a type other than bool."),
+    internal("ERR-SEM-540", "Unsupported type for printing", "`out` reached code generation with a value it has no print routine for."),
+    internal("ERR-SEM-541", "Assignment given the wrong statement", "Code generation for `set` or `in` was handed a different kind of statement."),
+    internal("ERR-SEM-542", "Input target is not a name", "`in(..)` reached code generation with something other than a variable name."),
+    internal("ERR-SEM-543", "Input into an undeclared variable", "`in(..)` reached code generation for a variable that was never declared."),
+    internal("ERR-SEM-544", "Unsupported input type", "`in(..)` reached code generation for a variable type it cannot read."),
+    internal("ERR-SEM-545", "Print given the wrong statement", "Code generation for `out` was handed a different kind of statement."),
+    internal("ERR-SEM-546", "Assertion given the wrong statement", "Code generation for `assert` was handed a different kind of statement."),
+    internal("ERR-SEM-547", "Error handler given the wrong statement", "Code generation for `.Err(..)` was handed a different kind of statement."),
+    entry(
+        "ERR-SEM-548",
+        "Input into a global variable",
+        "`in(..)` reads into a local variable. Variables declared directly in REPL entries are globals, \
+so read inside a function and return the value.",
+        r#"axon> set: n(i32) = 0;
+axon> in(n);"#,
+        r#"axon> cast read() -> i32 >> set: n(i32) = 0; in(n); return n; <<
+axon> set n(i32) = read();"#,
+    ),
+    entry(
+        "ERR-SEM-549",
+        "Input into an immutable variable",
+        "`in(..)` overwrites the variable, so it has to be declared mutable with `set:`.",
+        r#"cast Start() >>
+    set n(i32) = 0;
+    in(n);
+<<"#,
+        r#"cast Start() >>
+    set: n(i32) = 0;
+    in(n);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-550",
+        "Division by zero",
+        "The divisor is the constant zero, so the division can never succeed. Divisions by a variable \
+are checked when the program runs instead, see ERR-RT-031.",
+        r#"cast Start() >>
+    math([10 / 0], q);
+    out(q);
+<<"#,
+        r#"cast Start() >>
+    math([10 / 2], q);
+    out(q);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-551",
+        "Remainder of division by zero",
+        "The divisor of `%` is the constant zero, so the remainder is undefined.",
+        r#"cast Start() >>
+    math([10 % 0], r);
+    out(r);
+<<"#,
+        r#"cast Start() >>
+    math([10 % 3], r);
+    out(r);
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-560",
+        "Cannot reassign an immutable variable",
+        "A mutable variable, declared with `set:`, is changed with `set:` again. `set` without the colon \
+declares an immutable variable and cannot take over a name that is already mutable.",
+        r#"cast Start() >>
+    set: n(i32) = 1;
+    set n(i32) = 2;
+<<"#,
+        r#"cast Start() >>
+    set: n(i32) = 1;
+    set: n(i32) = 2;
+<<"#,
+    ),
+    entry(
+        "ERR-SEM-561",
+        "Cannot modify an immutable vector",
+        "`push`, `pop` and element assignment change the vector, which has to be declared with `set:`.",
+        r#"cast Start() >>
+    set v(Vec(i32)) = [1];
+    v.push(2);
+<<"#,
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1];
+    v.push(2);
+<<"#,
+    ),
+    internal("ERR-SEM-670", "No insertion point", "Code generation tried to emit an instruction outside of any function or basic block."),
+    internal("ERR-SEM-671", "Failed to allocate a local variable", "LLVM did not create the stack slot for a local variable."),
+    internal("ERR-SEM-680", "Failed to create a global variable", "LLVM did not create a top-level variable."),
+    internal("ERR-SEM-690", "Input variable has no storage", "`in(..)` found a null pointer for its variable."),
+    internal("ERR-SEM-691", "Input buffer allocation failed", "The call to malloc for a string read by `in(..)` could not be built."),
+    internal("ERR-SEM-692", "Input read failed to build", "The scanf call for a string read by `in(..)` could not be built."),
+    internal("ERR-SEM-693", "Input read failed to build", "The scanf call for a number read by `in(..)` could not be built."),
+    internal("ERR-SEM-694", "Failed to store a value", "LLVM did not create the store into a variable."),
+    internal("ERR-SEM-695", "Null value", "An expression produced no LLVM value."),
+    internal("ERR-SEM-696", "Failed to generate a math instruction", "LLVM did not create the instruction for an arithmetic operator."),
+    internal("ERR-SEM-697", "Store to a global outside of a function", "A `math` result was stored into a global with no function to run the store in."),
+    internal("ERR-SEM-698", "Global variable not found", "A `math` result was stored into a global that code generation does not know."),
+    entry(
+        "ERR-SEM-999",
+        "Variable used before declaration",
+        "Every variable is declared with `set` or `set:` before it is used, `math` also declares its \
+result variable.",
+        r#"cast Start() >>
+    out(total);
+<<"#,
+        r#"cast Start() >>
+    set total(i32) = 0;
+    out(total);
+<<"#,
+    ),
+    // warnings
+    entry(
+        "WARN-SEM-002",
+        "Empty print statement",
+        "`out()` without arguments prints nothing.",
+        r#"cast Start() >>
+    out();
+<<"#,
+        r#"cast Start() >>
+    out("");
+<<"#,
+    ),
+    entry(
+        "WARN-SEM-003",
+        "Math without a destination",
+        "`math([expr])` without a result variable computes a value that is never used.",
+        r#"cast Start() >>
+    math([1 + 2]);
+<<"#,
+        r#"cast Start() >>
+    math([1 + 2], sum);
+    out(sum);
+<<"#,
+    ),
+    entry(
+        "WARN-SEM-006",
+        "Empty vector literal",
+        "`[]` has no elements. With a declared vector type it compiles, the warning points out a vector \
+that may have been meant to start with elements. Without a declared type it is an error, see ERR-SEM-231.",
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [];
+    v.push(1);
+    out(v[0]);
+<<"#,
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1];
+    out(v[0]);
+<<"#,
+    ),
+    // modules
+    entry(
+        "ERR-MOD-001",
+        "Module not found",
+        "`use a.b;` loads src/a/b.ax, or the file b.ax of an installed package `a`. The message lists \
+every path that was tried.",
+        r#"?? src/init.ax
+use util.calc;
+cast Start() >>
+    out(calc.add(1, 2));
+<<"#,
+        r#"?? src/init.ax
+use util.calc;
+cast Start() >>
+    out(calc.add(1, 2));
+<<
+?? src/util/calc.ax
+cast add(a: i32, b: i32) -> i32 >>
+    return a + b;
+<<"#,
+    ),
+    entry(
+        "ERR-MOD-002",
+        "Import cycle",
+        "Modules are loaded before the file that uses them, so two modules cannot use each other. Move \
+what both need into a third module.",
+        r#"?? src/init.ax
+use a;
+cast Start() >>
+    out(a.one());
+<<
+?? src/a.ax
+use b;
+cast one() -> i32 >>
+    return b.two() - 1;
+<<
+?? src/b.ax
+use a;
+cast two() -> i32 >>
+    return 2;
+<<"#,
+        r#"?? src/init.ax
+use a;
+cast Start() >>
+    out(a.one());
+<<
+?? src/a.ax
+use b;
+cast one() -> i32 >>
+    return b.two() - 1;
+<<
+?? src/b.ax
+cast two() -> i32 >>
+    return 2;
+<<"#,
+    ),
+    entry(
+        "ERR-MOD-003",
+        "Module name already used",
+        "Modules are referred to by the last part of their path, so two imports that end the same way \
+would be ambiguous. Rename one of the files.",
+        r#"?? src/init.ax
+use util.calc;
+use extra.calc;
+cast Start() >>
+    out(calc.add(1, 2));
+<<
+?? src/util/calc.ax
+cast add(a: i32, b: i32) -> i32 >>
+    return a + b;
+<<
+?? src/extra/calc.ax
+cast sub(a: i32, b: i32) -> i32 >>
+    return a - b;
+<<"#,
+        r#"?? src/init.ax
+use util.calc;
+use extra.minus;
+cast Start() >>
+    out(calc.add(1, 2), minus.sub(3, 1));
+<<
+?? src/util/calc.ax
+cast add(a: i32, b: i32) -> i32 >>
+    return a + b;
+<<
+?? src/extra/minus.ax
+cast sub(a: i32, b: i32) -> i32 >>
+    return a - b;
+<<"#,
+    ),
+    entry(
+        "ERR-MOD-004",
+        "Not an imported module",
+        "`name.item` refers to a function or global of a module, which has to be imported by this file \
+with `use`.",
+        r#"?? src/init.ax
+cast Start() >>
+    out(calc.pi);
+<<
+?? src/util/calc.ax
+set pi(f64) = 3.14;"#,
+        r#"?? src/init.ax
+use util.calc;
+cast Start() >>
+    out(calc.pi);
+<<
+?? src/util/calc.ax
+set pi(f64) = 3.14;"#,
+    ),
+    entry(
+        "ERR-MOD-005",
+        "Module has no such item",
+        "The module was found but does not declare the function or global that is used.",
+        r#"?? src/init.ax
+use util.calc;
+cast Start() >>
+    out(calc.sub(1, 2));
+<<
+?? src/util/calc.ax
+cast add(a: i32, b: i32) -> i32 >>
+    return a + b;
+<<"#,
+        r#"?? src/init.ax
+use util.calc;
+cast Start() >>
+    out(calc.add(1, 2));
+<<
+?? src/util/calc.ax
+cast add(a: i32, b: i32) -> i32 >>
+    return a + b;
+<<"#,
+    ),
+    entry(
+        "ERR-MOD-006",
+        "Module declares Start()",
+        "Only the entry file may declare `cast Start()`, modules only provide functions and globals.",
+        r#"?? src/init.ax
+use util.calc;
+cast Start() >>
+    out(1);
+<<
+?? src/util/calc.ax
+cast Start() >>
+    out(2);
+<<"#,
+        r#"?? src/init.ax
+use util.calc;
+cast Start() >>
+    out(1);
+    calc.show();
+<<
+?? src/util/calc.ax
+cast show() >>
+    out(2);
+<<"#,
+    ),
+    entry(
+        "ERR-MOD-007",
+        "'use' inside a function",
+        "Imports are resolved before the program is analyzed, so they are written at the top level of \
+the file.",
+        r#"?? src/init.ax
+cast Start() >>
+    use util.calc;
+    out(calc.add(1, 2));
+<<
+?? src/util/calc.ax
+cast add(a: i32, b: i32) -> i32 >>
+    return a + b;
+<<"#,
+        r#"?? src/init.ax
+use util.calc;
+cast Start() >>
+    out(calc.add(1, 2));
+<<
+?? src/util/calc.ax
+cast add(a: i32, b: i32) -> i32 >>
+    return a + b;
+<<"#,
+    ),
+    // project.asml
+    entry(
+        "ERR-ASML-001",
+        "Unknown section",
+        "project.asml has the sections __Project__, __Build__ and __Dependencies__.",
+        r#"__Packages__
+_tensors_ = "1.2.0""#,
+        r#"__Dependencies__
+_tensors_ = "1.2.0""#,
+    ),
+    entry(
+        "ERR-ASML-002",
+        "Setting outside of a section",
+        "Every setting belongs to a section, which starts with its __Name__ line.",
+        r#"_name_ = "demo""#,
+        r#"__Project__
+_name_ = "demo""#,
+    ),
+    entry(
+        "ERR-ASML-003",
+        "Malformed setting",
+        "Settings are written as `_key_ = value`, with the key between underscores.",
+        r#"__Project__
+name: "demo""#,
+        r#"__Project__
+_name_ = "demo""#,
+    ),
+    entry(
+        "ERR-ASML-004",
+        "Unknown setting",
+        "__Project__ knows _name_, _version_ and _entry_, __Build__ knows _output_, _target_ and \
+_opt_level_. Dependencies can have any package name.",
+        r#"__Project__
+_name_ = "demo"
+_author_ = "me""#,
+        r#"__Project__
+_name_ = "demo""#,
+    ),
+    entry(
+        "ERR-ASML-005",
+        "Invalid value",
+        "Values are quoted strings (\"...\") or whole numbers, anything else cannot be read.",
+        r#"__Project__
+_name_ = demo"#,
+        r#"__Project__
+_name_ = "demo""#,
+    ),
+    entry(
+        "ERR-ASML-006",
+        "Setting appears twice",
+        "Each setting is given once, a second one would silently win.",
+        r#"__Project__
+_name_ = "demo"
+_name_ = "other""#,
+        r#"__Project__
+_name_ = "demo""#,
+    ),
+    entry(
+        "ERR-ASML-007",
+        "Section appears twice",
+        "Each section is written once, with all of its settings.",
+        r#"__Project__
+_name_ = "demo"
+__Project__
+_version_ = "0.1.0""#,
+        r#"__Project__
+_name_ = "demo"
+_version_ = "0.1.0""#,
+    ),
+    entry(
+        "ERR-ASML-008",
+        "Project has no name",
+        "_name_ under __Project__ is required, it names the build output and the package.",
+        r#"__Project__
+_version_ = "0.1.0""#,
+        r#"__Project__
+_name_ = "demo"
+_version_ = "0.1.0""#,
+    ),
+    entry(
+        "ERR-ASML-009",
+        "Invalid version",
+        "Versions are major.minor.patch. Requirements are versions with an optional ^ (compatible, the \
+default) or = (exact) in front, or * for any version.",
+        r#"__Project__
+_name_ = "demo"
+_version_ = "first""#,
+        r#"__Project__
+_name_ = "demo"
+_version_ = "0.1.0""#,
+    ),
+    entry(
+        "ERR-ASML-010",
+        "Dependency not installed",
+        "A dependency is listed in project.asml but is missing from packages/, or the installed version \
+does not match. Run `axon install` to fetch it.",
+        r#"$ axon run
+[ERR-ASML-010] Dependency 'tensors' (^1.2.0) is not installed"#,
+        r#"$ axon install
+$ axon run"#,
+    ),
+    entry(
+        "ERR-ASML-011",
+        "Invalid dependency name",
+        "A dependency is imported with `use <name>;` and installed into packages/<name>/, so its name is \
+made of letters, digits and _ and is not a keyword.",
+        r#"__Dependencies__
+_my-tensors_ = "1.2.0""#,
+        r#"__Dependencies__
+_my_tensors_ = "1.2.0""#,
+    ),
+    entry(
+        "ERR-ASML-012",
+        "Setting must be a string",
+        "_name_, _version_, _entry_, _output_, _target_ and the dependency requirements are quoted \
+strings, only _opt_level_ can be a number.",
+        r#"__Project__
+_name_ = "demo"
+_version_ = 1"#,
+        r#"__Project__
+_name_ = "demo"
+_version_ = "1.0.0""#,
+    ),
+    entry(
+        "ERR-ASML-013",
+        "Empty project name",
+        "The name is used for the build output and as the package name, it cannot be empty.",
+        r#"__Project__
+_name_ = """#,
+        r#"__Project__
+_name_ = "demo""#,
+    ),
+    entry(
+        "ERR-ASML-014",
+        "Invalid entry file",
+        "_entry_ is the .ax file the program starts in, given relative to the project and inside src/.",
+        r#"__Project__
+_name_ = "demo"
+_entry_ = "main.ax""#,
+        r#"__Project__
+_name_ = "demo"
+_entry_ = "src/main.ax""#,
+    ),
+    entry(
+        "ERR-ASML-015",
+        "Invalid optimization level",
+        "_opt_level_ is 0, 1, 2 or 3 like `-O`, or \"s\" to optimize for size.",
+        r#"__Build__
+_opt_level_ = 5"#,
+        r#"__Build__
+_opt_level_ = 2"#,
+    ),
+    // packages
+    entry(
+        "ERR-PKG-001",
+        "Package not in the registry",
+        "No version of the package was published to the local registry. Publish it from its project \
+with `axon publish`.",
+        r#"$ axon install tensorz"#,
+        r#"$ axon install tensors"#,
+    ),
+    entry(
+        "ERR-PKG-002",
+        "No matching version",
+        "The registry has the package, but no version satisfies the requirement. The message lists the \
+versions that are available.",
+        r#"$ axon install tensors@9.0.0"#,
+        r#"$ axon install tensors@1.2.0"#,
+    ),
+    entry(
+        "ERR-PKG-003",
+        "Version conflict",
+        "Two packages need versions of the same dependency that no single version satisfies. Every \
+package is installed once per project. In the example plots 2.0.0 itself depends on tensors ^2.0.0.",
+        r#"?? project.asml
+__Dependencies__
+_tensors_ = "1.0.0"
+_plots_ = "2.0.0""#,
+        r#"?? project.asml
+__Dependencies__
+_tensors_ = "2.0.0"
+_plots_ = "2.0.0""#,
+    ),
+    entry(
+        "ERR-PKG-004",
+        "Version already published",
+        "Published versions never change. Raise _version_ in project.asml, or overwrite it with \
+`axon publish --force`.",
+        r#"$ axon publish
+$ axon publish"#,
+        r#"$ axon publish
+$ axon publish --force"#,
+    ),
+    entry(
+        "ERR-PKG-005",
+        "Package could not be published",
+        "`axon publish` copies project.asml and src/ into the registry, the copy failed. The message has \
+the error from the file system.",
+        r#"$ chmod -w ~/.axon/registry
+$ axon publish"#,
+        r#"$ chmod +w ~/.axon/registry
+$ axon publish"#,
+    ),
+    entry(
+        "ERR-PKG-006",
+        "Invalid package name",
+        "Package names are imported with `use <name>;`, so they are made of letters, digits and _ and \
+are not keywords.",
+        r#"$ axon install my-tensors"#,
+        r#"$ axon install my_tensors"#,
+    ),
+    entry(
+        "ERR-PKG-007",
+        "project.asml could not be updated",
+        "`axon install <name>` writes the new dependency into project.asml once it resolved, writing the \
+file failed. Nothing was installed.",
+        r#"$ chmod -w project.asml
+$ axon install tensors"#,
+        r#"$ chmod +w project.asml
+$ axon install tensors"#,
+    ),
+    entry(
+        "ERR-PKG-008",
+        "Package could not be installed",
+        "Copying a resolved package from the registry into packages/ failed, the message has the error \
+from the file system.",
+        r#"$ chmod -w packages
+$ axon install"#,
+        r#"$ chmod +w packages
+$ axon install"#,
+    ),
+    entry(
+        "ERR-PKG-009",
+        "Lockfile could not be written",
+        "The packages were installed but project.lock, which pins their versions, could not be written.",
+        r#"$ chmod -w project.lock
+$ axon install"#,
+        r#"$ chmod +w project.lock
+$ axon install"#,
+    ),
+    entry(
+        "ERR-PKG-010",
+        "Invalid version requirement",
+        "The part after @ is a version with an optional ^ (compatible, the default) or = (exact) in \
+front, or * for any version.",
+        r#"$ axon install tensors@latest"#,
+        r#"$ axon install tensors@1.2.0"#,
+    ),
+    // tools
+    internal(
+        "ERR-FMT-001",
+        "Formatting would change the program",
+        "`axon fmt` only changes whitespace and checks that the reformatted file has the same tokens and \
+comments. The check failed, so the file was left untouched.",
+    ),
+    entry(
+        "ERR-REPL-001",
+        "'use' in the REPL",
+        "The REPL is not part of a project, so there are no modules to import. Paste the functions into \
+the session instead.",
+        r#"axon> use util.calc;"#,
+        r#"axon> cast add(a: i32, b: i32) -> i32 >> return a + b; <<"#,
+    ),
+    // runtime
+    entry(
+        "ERR-RT-001",
+        "Vector index out of bounds",
+        "The program read or wrote an element past the end of a vector. Check the index against \
+`v.len()`.",
+        r#"cast Start() >>
+    set v(Vec(i32)) = [1, 2];
+    out(v[2]);
+<<"#,
+        r#"cast Start() >>
+    set v(Vec(i32)) = [1, 2];
+    out(v[1]);
+<<"#,
+    ),
+    entry(
+        "ERR-RT-002",
+        "pop() on an empty vector",
+        "There was no element left to remove. Check `v.len()` first.",
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1];
+    out(v.pop());
+    out(v.pop());
+<<"#,
+        r#"cast Start() >>
+    set: v(Vec(i32)) = [1];
+    out(v.pop());
+    if (v.len() > 0) >>
+        out(v.pop());
+    <<
+<<"#,
+    ),
+    entry(
+        "ERR-RT-010",
+        "Tensor runtime error",
+        "The tensor runtime stopped the program. Shapes are checked when the program is compiled, so \
+this is almost always the runtime running out of memory for a large tensor.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [100000, 100000])) = zeros([100000, 100000]);
+    out(t.sum());
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [1000, 1000])) = zeros([1000, 1000]);
+    out(t.sum());
+<<"#,
+    ),
+    entry(
+        "ERR-RT-011",
+        "Tensor index out of bounds",
+        "`t.get(i, ..)` was called with an index past the end of one of the tensor's axes.",
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+    set: i(i32) = 2;
+    out(t.get(i));
+<<"#,
+        r#"cast Start() >>
+    set t(Tensor(f32, [2])) = [1, 2];
+    set: i(i32) = 1;
+    out(t.get(i));
+<<"#,
+    ),
+    entry(
+        "ERR-RT-020",
+        "Assertion failed",
+        "An `assert` or `assert_eq` did not hold. The message shows the line and both sides of the \
+comparison.",
+        r#"cast Start() >>
+    assert_eq(2 + 2, 5);
+<<"#,
+        r#"cast Start() >>
+    assert_eq(2 + 2, 4);
+<<"#,
+    ),
+    entry(
+        "ERR-RT-030",
+        "Integer overflow",
+        "An integer operation did not fit its type. Use i64 for larger values, or handle it with \
+`math(..).Err(\"..\")`. Builds check this only with --checked.",
+        r#"cast double(n: i32) >>
+    math([n + n], s);
+    out(s);
+<<
+cast Start() >>
+    double(2000000000);
+<<"#,
+        r#"cast double(n: i64) >>
+    math([n + n], s);
+    out(s);
+<<
+cast Start() >>
+    double(2000000000);
+<<"#,
+    ),
+    entry(
+        "ERR-RT-031",
+        "Division by zero",
+        "An integer division or remainder had a zero divisor at run time. Handle it with \
+`math(..).Err(\"..\")`.",
+        r#"cast divide(a: i32, b: i32) >>
+    math([a / b], q);
+    out(q);
+<<
+cast Start() >>
+    divide(10, 0);
+<<"#,
+        r#"cast divide(a: i32, b: i32) >>
+    math([a / b], q).Err("cannot divide by zero");
+    out(q);
+<<
+cast Start() >>
+    divide(10, 0);
+<<"#,
+    ),
+    entry(
+        "ERR-RT-032",
+        "Value out of range",
+        "A value was converted to a type that cannot hold it, like an i64 above 2147483647 stored into an \
+i32.",
+        r#"cast narrow(k: i64) >>
+    math([k * 1000], big);
+    set n(i32) = big;
+    out(n);
+<<
+cast Start() >>
+    narrow(3000000);
+<<"#,
+        r#"cast narrow(k: i64) >>
+    math([k * 1000], big);
+    set n(i64) = big;
+    out(n);
+<<
+cast Start() >>
+    narrow(3000000);
+<<"#,
+    ),
+    entry(
+        "ERR-RT-033",
+        "Input too long",
+        "Strings read with `in(..)` are limited to 1024 bytes.",
+        r#"$ python3 -c 'print("a" * 2000)' | axon run"#,
+        r#"$ python3 -c 'print("a" * 100)' | axon run"#,
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::fs;
+    use std::path::Path;
+
+    // `[ERR-SEM-550]` anywhere in the compiler or runtime sources,
+    // a code put together with format! (`[ERR-SEM-{}]`) is kept as written so it shows up as unregistered
+    fn codes_in(dir: &Path, found: &mut HashSet<String>) {
+        for entry in fs::read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                codes_in(&path, found);
+                continue;
+            }
+            if !matches!(path.extension().and_then(|e| e.to_str()), Some("rs" | "c")) || path.ends_with("error_codes.rs") {
+                continue;
+            }
+            let text = fs::read_to_string(&path).unwrap();
+            for (i, _) in text.match_indices('[') {
+                let rest = &text[i + 1..];
+                let Some(end) = rest.find(']') else { continue };
+                let code = &rest[..end];
+                let parts: Vec<&str> = code.split('-').collect();
+                if parts.len() == 3
+                    && matches!(parts[0], "ERR" | "WARN")
+                    && parts[1].chars().all(|c| c.is_ascii_uppercase())
+                    && (parts[2].chars().all(|c| c.is_ascii_digit()) || parts[2].starts_with('{'))
+                {
+                    found.insert(code.to_string());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_emitted_code_is_registered() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut found = HashSet::new();
+        codes_in(&root.join("src"), &mut found);
+        codes_in(&root.join("runtime"), &mut found);
+        assert!(found.len() > 100, "only found {} codes, is the scan broken?", found.len());
+        let mut missing: Vec<_> = found.iter().filter(|code| lookup(code).is_none()).collect();
+        missing.sort();
+        assert!(missing.is_empty(), "codes without an entry in REGISTRY: {:?}", missing);
+    }
+
+    #[test]
+    fn registry_codes_are_unique() {
+        let mut seen = HashSet::new();
+        for entry in REGISTRY {
+            assert!(seen.insert(entry.code), "{} is registered twice", entry.code);
+            assert!(!entry.title.is_empty() && !entry.explanation.is_empty(), "{} is not explained", entry.code);
+        }
+    }
+}
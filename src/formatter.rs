@@ -0,0 +1,262 @@
+// `axon fmt`,
+// the formatter works on the token stream with comments kept (lex_with_trivia),
+// it only ever changes the whitespace between tokens: one statement per line, four spaces
+// per open `>>` block, single spaces around operators and after commas, blank lines kept
+// but never more than one in a row. the result is lexed again and has to give the same
+// tokens and comments, so the AST cannot change
+
+use crate::lexer_tokenizer::{lex_with_span, lex_with_trivia, PositionedToken, Token};
+use crate::parser::parser_error::{ErrorKind, ParseError, Severity};
+use crate::parser::parser_kernel::Parser as AxonParser;
+use crate::terminal::print_error;
+use crate::test_runner::collect_sources;
+use console::style;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const INDENT: &str = "    ";
+
+pub fn run_fmt(args: &[String]) -> i32 {
+    let check = args.iter().any(|a| a == "--check");
+    let mut files: Vec<PathBuf> = args.iter().filter(|a| !a.starts_with("--")).map(PathBuf::from).collect();
+    if files.is_empty() {
+        let src_dir = Path::new("src");
+        if !src_dir.is_dir() {
+            println!(
+                "{} No src/ directory here, run `axon fmt` in a project or pass the files to format",
+                style("✘").red().bold()
+            );
+            return 1;
+        }
+        collect_sources(src_dir, &mut files);
+        files.sort();
+    }
+
+    let mut changed = 0;
+    let mut failed = 0;
+    for file in &files {
+        let code = match fs::read_to_string(file) {
+            Ok(code) => code,
+            Err(e) => {
+                println!("{} Cannot read '{}': {}", style("✘").red().bold(), file.display(), e);
+                failed += 1;
+                continue;
+            }
+        };
+        let formatted = match format_source(&code) {
+            Ok(formatted) => formatted,
+            Err(mut errors) => {
+                errors.iter_mut().for_each(|e| e.file = Some(file.display().to_string()));
+                print_error(&format!("Formatting {}", file.display()), &errors);
+                failed += 1;
+                continue;
+            }
+        };
+        if formatted == code {
+            continue;
+        }
+        changed += 1;
+        if check {
+            println!("{} {}", style("Would reformat").yellow().bold(), file.display());
+        } else if let Err(e) = fs::write(file, formatted) {
+            println!("{} Cannot write '{}': {}", style("✘").red().bold(), file.display(), e);
+            failed += 1;
+        } else {
+            println!("{} {}", style("Formatted").green().bold(), file.display());
+        }
+    }
+
+    if check && changed > 0 {
+        println!(
+            "\n{} {} file(s) need formatting, run `axon fmt` to fix them",
+            style("✘").red().bold(),
+            changed
+        );
+    } else if failed == 0 && check {
+        println!("{} {} file(s) already formatted", style("✔").green().bold(), files.len());
+    } else if failed == 0 {
+        println!("{} {} file(s) checked, {} reformatted", style("✔").green().bold(), files.len(), changed);
+    }
+    if failed > 0 || (check && changed > 0) { 1 } else { 0 }
+}
+
+// the canonical layout of `src`, which has to parse
+pub fn format_source(src: &str) -> Result<String, Vec<ParseError>> {
+    let tokens = lex_with_span(src);
+    let parsed = AxonParser::new(&tokens, Some(src.to_string())).parse_program();
+    let errors: Vec<ParseError> = parsed.errors.into_iter().filter(|e| e.severity == Severity::Error).collect();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let formatted = Formatter::default().format(src, &lex_with_trivia(src));
+    if trivia(src) != trivia(&formatted) {
+        return Err(vec![ParseError::new(
+            ErrorKind::Syntax,
+            "\x1b[31m[ERR-FMT-001] Formatting would change the tokens of this file, it was left as it is.\x1b[0m"
+                .to_string(),
+            0,
+            0,
+            None,
+            Some("This is a formatter bug, please report the file.".to_string()),
+            Severity::Error,
+        )]);
+    }
+    Ok(formatted)
+}
+
+// every token with the comment text, what the round trip has to keep
+fn trivia(src: &str) -> Vec<(Token, &str)> {
+    lex_with_trivia(src)
+        .into_iter()
+        .map(|t| {
+            let text = if t.token == Token::Comment { src[t.span.clone()].trim_end() } else { "" };
+            (t.token, text)
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Formatter {
+    out: String,
+    depth: usize,
+    // the current line is finished, the next token goes on a new one
+    line_done: bool,
+}
+
+impl Formatter {
+    fn format(mut self, src: &str, tokens: &[PositionedToken]) -> String {
+        let mut prev: Option<&Token> = None;
+        let mut last_end = 0;
+        for (i, token) in tokens.iter().enumerate() {
+            let newlines = src[last_end..token.span.start].matches('\n').count();
+            last_end = token.span.end;
+            let text = &src[token.span.clone()];
+
+            if token.token == Token::Comment {
+                let text = text.trim_end();
+                if newlines == 0 && !self.out.is_empty() {
+                    // trailing comment, stays on the line of the code before it
+                    self.out.push(' ');
+                } else {
+                    self.new_line(newlines > 1 && !matches!(prev, Some(Token::DoubleGt)));
+                }
+                self.out.push_str(text);
+                self.line_done = true;
+                continue;
+            }
+
+            if token.token == Token::DoubleLt {
+                self.depth = self.depth.saturating_sub(1);
+            }
+            let else_branch = prev == Some(&Token::DoubleLt) && token.token == Token::Else;
+            if self.out.is_empty() {
+                self.indent();
+            } else if (self.line_done || token.token == Token::DoubleLt) && !else_branch {
+                let blank = newlines > 1
+                    && !matches!(prev, Some(Token::DoubleGt))
+                    && token.token != Token::DoubleLt;
+                self.new_line(blank);
+            } else if prev.is_some_and(|p| space_between(p, &token.token)) {
+                self.out.push(' ');
+            }
+            self.out.push_str(text);
+
+            self.line_done = match token.token {
+                Token::EndStr => true,
+                Token::DoubleGt => {
+                    self.depth += 1;
+                    true
+                }
+                // `<< else >>` stays on one line
+                Token::DoubleLt => tokens.get(i + 1).is_none_or(|next| next.token != Token::Else),
+                _ => false,
+            };
+            prev = Some(&token.token);
+        }
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out
+    }
+
+    fn new_line(&mut self, blank: bool) {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+            if blank {
+                self.out.push('\n');
+            }
+        }
+        self.indent();
+        self.line_done = false;
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.depth {
+            self.out.push_str(INDENT);
+        }
+    }
+}
+
+fn space_between(prev: &Token, next: &Token) -> bool {
+    use Token::*;
+    match (prev, next) {
+        (_, RParen | RBracket | RBrace | Comma | EndStr | Dot | Colon) => false,
+        (LParen | LBracket | LBrace | Dot, _) => false,
+        // calls, `out(..)`, `math(..)`, `Vec(i32)`
+        (Identifier(_) | Print | Math | Input | Assert | AssertEq | Vector | Tensor, LParen) => false,
+        // indexing
+        (Identifier(_) | RParen | RBracket, LBracket) => false,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_source;
+    use std::fs;
+    use std::path::Path;
+
+    fn ax_files(dir: &Path, found: &mut Vec<std::path::PathBuf>) {
+        for path in fs::read_dir(dir).unwrap().flatten().map(|e| e.path()) {
+            if path.is_dir() {
+                ax_files(&path, found);
+            } else if path.extension().is_some_and(|ext| ext == "ax") {
+                found.push(path);
+            }
+        }
+    }
+
+    // formatting a formatted file changes nothing
+    #[test]
+    fn formatting_is_idempotent_on_the_test_programs() {
+        let mut files = Vec::new();
+        ax_files(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests"), &mut files);
+        assert!(!files.is_empty(), "no .ax files under tests/");
+        for file in files {
+            let src = fs::read_to_string(&file).unwrap();
+            let once = format_source(&src).unwrap_or_else(|_| panic!("{} does not format", file.display()));
+            let twice = format_source(&once).unwrap();
+            assert_eq!(once, twice, "{} is not stable under formatting", file.display());
+        }
+    }
+
+    #[test]
+    fn comments_keep_their_place() {
+        let src = "?? header comment\n\n\nset limit(i32)=3;   ?? trailing after a global\n\
+                   cast Start() >>   ?? after the opening\n    ?? standalone, first in the block\n\
+                   \x20 set: x(i32) = limit+1; ?? trailing\n    if (x > 2) >>\n        out(x);\n    ?? before the closing\n\
+                   \x20   << else >>\n   out( 0 ); ?? in else\n    <<\n\n\n    ?? after a blank line\n    return;\n<<\n?? end of file";
+        let expected = "?? header comment\n\nset limit(i32) = 3; ?? trailing after a global\n\
+                        cast Start() >> ?? after the opening\n    ?? standalone, first in the block\n\
+                        \x20   set: x(i32) = limit + 1; ?? trailing\n    if (x > 2) >>\n        out(x);\n        ?? before the closing\n\
+                        \x20   << else >>\n        out(0); ?? in else\n    <<\n\n    ?? after a blank line\n    return;\n<<\n?? end of file\n";
+        assert_eq!(format_source(src).unwrap(), expected);
+        assert_eq!(format_source(expected).unwrap(), expected);
+    }
+
+    #[test]
+    fn a_file_that_does_not_parse_is_left_alone() {
+        assert!(format_source("cast Start() >>\n    out(1\n<<\n").is_err());
+    }
+}
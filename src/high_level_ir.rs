@@ -1,6 +1,8 @@
 //HIR(high level IR) is an AST that has already undergone semantic analysis, 
 //and it is also lower-level
 
+use crate::ast::Span;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum HIRType {
     I32,
@@ -9,21 +11,73 @@ pub enum HIRType {
     F64,
     String,
     Bool,
+    Vector(Box<HIRType>),
+    Tensor(Vec<usize>),
     Void,
 }
 
+// written the way a declaration spells it, `Vec(i32)`, `Tensor(f32, [2, 3])`
+impl std::fmt::Display for HIRType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HIRType::I32 => write!(f, "i32"),
+            HIRType::I64 => write!(f, "i64"),
+            HIRType::F32 => write!(f, "f32"),
+            HIRType::F64 => write!(f, "f64"),
+            HIRType::String => write!(f, "str"),
+            HIRType::Bool => write!(f, "bool"),
+            HIRType::Vector(elem) => write!(f, "Vec({})", elem),
+            HIRType::Tensor(shape) => write!(f, "Tensor(f32, {:?})", shape),
+            HIRType::Void => write!(f, "void"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum HIROperator {
     Plus,
     Minus,
     Multiply,
     Divide,
+    Modulo,
     Equals,
     NotEquals,
     GreaterThan,
     LessThan,
     GreaterEqual,
     LessEqual,
+    // short-circuiting, both sides are bool
+    And,
+    Or,
+}
+
+// operations backed by the tensor runtime (runtime/axon_runtime.c),
+// reductions carry the axis they reduce over, or None for the whole tensor
+#[derive(Debug, Clone, PartialEq)]
+pub enum TensorFunc {
+    Fill,
+    Rand,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    MatMul,
+    Reshape,
+    Transpose,
+    Sum(Option<usize>),
+    Mean(Option<usize>),
+    Max(Option<usize>),
+    Relu,
+    Sigmoid,
+    Tanh,
+    Exp,
+    Softmax,
+    Get,
+    Item,
+    Backward,
+    Grad,
+    GradOf,
+    Detach,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -44,10 +98,40 @@ pub enum HIRExpr {
         name: String,
         args: Vec<HIRExpr>,
     },
+    Not {
+        expr: Box<HIRExpr>,
+    },
     Coerce {
         expr: Box<HIRExpr>,
         target: HIRType,
     },
+    Vector {
+        elements: Vec<HIRExpr>,
+        elem_type: HIRType,
+    },
+    Index {
+        target: Box<HIRExpr>,
+        index: Box<HIRExpr>,
+    },
+    VectorLen {
+        target: Box<HIRExpr>,
+    },
+    VectorPush {
+        target: Box<HIRExpr>,
+        value: Box<HIRExpr>,
+    },
+    VectorPop {
+        target: Box<HIRExpr>,
+    },
+    Tensor {
+        elements: Vec<HIRExpr>,
+        shape: Vec<usize>,
+    },
+    TensorCall {
+        func: TensorFunc,
+        args: Vec<HIRExpr>,
+        ty: HIRType,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -82,7 +166,205 @@ pub enum HIRStatement {
         body: Vec<HIRStatement>,
     },
     Break,
+    Return {
+        value: Option<HIRExpr>,
+    },
+    IndexAssign {
+        name: String,
+        index: HIRExpr,
+        value: HIRExpr,
+    },
     Input {
         target: HIRExpr,
     },
+    // `math(...).Err("...")` / `in(...).Err("...")`, a runtime check failing inside `stmt`
+    // prints `message` and skips the rest of the statement
+    OnError {
+        stmt: Box<HIRStatement>,
+        message: String,
+    },
+    // source span of the statement it was lowered from, so codegen errors can point at it
+    Located {
+        span: Span,
+        stmt: Box<HIRStatement>,
+    },
+    // both operands are already coerced to `ty`
+    Assert {
+        left: HIRExpr,
+        op: HIROperator,
+        right: HIRExpr,
+        ty: HIRType,
+        source: String,
+    },
+}
+
+impl HIRStatement {
+    pub fn unlocated(&self) -> &HIRStatement {
+        match self {
+            HIRStatement::Located { stmt, .. } => stmt.unlocated(),
+            other => other,
+        }
+    }
+}
+
+impl std::fmt::Display for HIROperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            HIROperator::Plus => "+",
+            HIROperator::Minus => "-",
+            HIROperator::Multiply => "*",
+            HIROperator::Divide => "/",
+            HIROperator::Modulo => "%",
+            HIROperator::Equals => "==",
+            HIROperator::NotEquals => "!=",
+            HIROperator::GreaterThan => ">",
+            HIROperator::LessThan => "<",
+            HIROperator::GreaterEqual => ">=",
+            HIROperator::LessEqual => "<=",
+            HIROperator::And => "and",
+            HIROperator::Or => "or",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+// close to the source, with every binary operation in parentheses, i64 and f64 literals
+// carry their type (i32 and f32 are what a plain literal is) and conversions are written as `as`
+impl std::fmt::Display for HIRExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |exprs: &[HIRExpr]| exprs.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(", ");
+        match self {
+            HIRExpr::Int32(v) => write!(f, "{}", v),
+            HIRExpr::Int64(v) => write!(f, "{}i64", v),
+            HIRExpr::Float32(v) => write!(f, "{:?}", v),
+            HIRExpr::Float64(v) => write!(f, "{:?}f64", v),
+            HIRExpr::String(s) => write!(f, "{:?}", s),
+            HIRExpr::Bool(b) => write!(f, "{}", b),
+            HIRExpr::Identifier(name) => write!(f, "{}", name),
+            HIRExpr::BinaryOp { left, op, right } => write!(f, "({} {} {})", left, op, right),
+            HIRExpr::FunctionCall { name, args } => write!(f, "{}({})", name, list(args)),
+            HIRExpr::Not { expr } => write!(f, "not {}", expr),
+            HIRExpr::Coerce { expr, target } => write!(f, "({} as {})", expr, target),
+            HIRExpr::Vector { elements, .. } => write!(f, "[{}]", list(elements)),
+            HIRExpr::Index { target, index } => write!(f, "{}[{}]", target, index),
+            HIRExpr::VectorLen { target } => write!(f, "{}.len()", target),
+            HIRExpr::VectorPush { target, value } => write!(f, "{}.push({})", target, value),
+            HIRExpr::VectorPop { target } => write!(f, "{}.pop()", target),
+            HIRExpr::Tensor { elements, shape } => write!(f, "tensor([{}], {:?})", list(elements), shape),
+            HIRExpr::TensorCall { func, args, .. } => {
+                let (name, axis) = match func {
+                    TensorFunc::Sum(axis) => ("sum", axis),
+                    TensorFunc::Mean(axis) => ("mean", axis),
+                    TensorFunc::Max(axis) => ("max", axis),
+                    other => (tensor_func_name(other), &None),
+                };
+                let axis = axis.map(|a| format!(", axis {}", a)).unwrap_or_default();
+                write!(f, "{}({}{})", name, list(args), axis)
+            }
+        }
+    }
+}
+
+fn tensor_func_name(func: &TensorFunc) -> &'static str {
+    match func {
+        TensorFunc::Fill => "fill",
+        TensorFunc::Rand => "rand",
+        TensorFunc::Add => "add",
+        TensorFunc::Sub => "sub",
+        TensorFunc::Mul => "mul",
+        TensorFunc::Div => "div",
+        TensorFunc::MatMul => "matmul",
+        TensorFunc::Reshape => "reshape",
+        TensorFunc::Transpose => "transpose",
+        TensorFunc::Sum(_) => "sum",
+        TensorFunc::Mean(_) => "mean",
+        TensorFunc::Max(_) => "max",
+        TensorFunc::Relu => "relu",
+        TensorFunc::Sigmoid => "sigmoid",
+        TensorFunc::Tanh => "tanh",
+        TensorFunc::Exp => "exp",
+        TensorFunc::Softmax => "softmax",
+        TensorFunc::Get => "get",
+        TensorFunc::Item => "item",
+        TensorFunc::Backward => "backward",
+        TensorFunc::Grad => "grad",
+        TensorFunc::GradOf => "grad_of",
+        TensorFunc::Detach => "detach",
+    }
+}
+
+// the program as text, for `--dump-hir`
+pub fn dump(hir: &[HIRStatement]) -> String {
+    let mut out = String::new();
+    dump_block(hir, 0, &mut out);
+    out
+}
+
+// a binary operation brings its own parentheses
+fn parenthesized(expr: &HIRExpr) -> String {
+    match expr {
+        HIRExpr::BinaryOp { .. } => expr.to_string(),
+        _ => format!("({})", expr),
+    }
+}
+
+fn dump_block(stmts: &[HIRStatement], depth: usize, out: &mut String) {
+    for stmt in stmts {
+        dump_statement(stmt, depth, out);
+    }
+}
+
+fn dump_statement(stmt: &HIRStatement, depth: usize, out: &mut String) {
+    let indent = "    ".repeat(depth);
+    let line = match stmt {
+        HIRStatement::Located { stmt, .. } => return dump_statement(stmt, depth, out),
+        HIRStatement::Function { name, params, return_type, body, .. } => {
+            let params: Vec<String> = params.iter().map(|(name, ty)| format!("{}: {}", name, ty)).collect();
+            let returns = match return_type {
+                HIRType::Void => String::new(),
+                ty => format!(" -> {}", ty),
+            };
+            out.push_str(&format!("{}cast {}({}){} >>\n", indent, name, params.join(", "), returns));
+            dump_block(body, depth + 1, out);
+            out.push_str(&format!("{}<<\n", indent));
+            return;
+        }
+        HIRStatement::If { condition, body, else_body } => {
+            out.push_str(&format!("{}if {} >>\n", indent, parenthesized(condition)));
+            dump_block(body, depth + 1, out);
+            if let Some(else_body) = else_body {
+                out.push_str(&format!("{}<< else >>\n", indent));
+                dump_block(else_body, depth + 1, out);
+            }
+            out.push_str(&format!("{}<<\n", indent));
+            return;
+        }
+        HIRStatement::Loop { body } | HIRStatement::While { body, .. } => {
+            let head = match stmt {
+                HIRStatement::While { condition, .. } => format!("while {}", parenthesized(condition)),
+                _ => "loop".to_string(),
+            };
+            out.push_str(&format!("{}{} >>\n", indent, head));
+            dump_block(body, depth + 1, out);
+            out.push_str(&format!("{}<<\n", indent));
+            return;
+        }
+        HIRStatement::OnError { stmt, message } => {
+            let mut inner = String::new();
+            dump_statement(stmt, 0, &mut inner);
+            format!("{}.Err({:?});", inner.trim_end().trim_end_matches(';'), message)
+        }
+        HIRStatement::Assignment { name, value } => format!("{} = {};", name, value),
+        HIRStatement::Print { params } => {
+            format!("out({});", params.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", "))
+        }
+        HIRStatement::ExprStatement { expr } => format!("{};", expr),
+        HIRStatement::Break => "break;".to_string(),
+        HIRStatement::Return { value: Some(value) } => format!("return {};", value),
+        HIRStatement::Return { value: None } => "return;".to_string(),
+        HIRStatement::IndexAssign { name, index, value } => format!("{}[{}] = {};", name, index, value),
+        HIRStatement::Input { target } => format!("in({});", target),
+        HIRStatement::Assert { left, op, right, .. } => format!("assert({} {} {});", left, op, right),
+    };
+    out.push_str(&format!("{}{}\n", indent, line));
 }
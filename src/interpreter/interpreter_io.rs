@@ -0,0 +1,231 @@
+//out(..), in(..), assertions and runtime errors in the interpreter,
+//everything goes through the C library's printf and scanf like in compiled code,
+//so the program shares stdout and stdin buffering with the tensor runtime and prints what the JIT prints
+
+use super::interpreter_math::compare;
+use super::{Eval, Interpreter, Stop, Value};
+use crate::high_level_ir::{HIRExpr, HIRStatement, HIRType};
+use std::ffi::{c_char, c_double, c_int, c_longlong, CStr, CString};
+use std::rc::Rc;
+
+// a printf argument, as the C side sees it
+#[derive(Clone, Copy)]
+pub enum CArg {
+    Int(c_int),
+    LongLong(c_longlong),
+    Double(c_double),
+    Str(*const c_char),
+}
+
+impl CArg {
+    // bools go out as 0 / 1 and f32 is widened, like in compiler_print_codegen
+    fn of(value: &Value) -> Option<CArg> {
+        match value {
+            Value::I32(v) => Some(CArg::Int(*v)),
+            Value::Bool(v) => Some(CArg::Int(*v as c_int)),
+            Value::I64(v) => Some(CArg::LongLong(*v)),
+            Value::F32(v) => Some(CArg::Double(*v as c_double)),
+            Value::F64(v) => Some(CArg::Double(*v)),
+            Value::Str(v) => Some(CArg::Str(v.as_ptr())),
+            _ => None,
+        }
+    }
+
+    fn spec(&self) -> &'static str {
+        match self {
+            CArg::Int(_) => "%d",
+            CArg::LongLong(_) => "%lld",
+            CArg::Double(_) => "%f",
+            CArg::Str(_) => "%s",
+        }
+    }
+}
+
+// the formats here take up to three arguments of the same kind
+pub fn printf(format: &str, args: &[CArg]) {
+    let format = CString::new(format).unwrap();
+    let f = format.as_ptr();
+    unsafe {
+        match *args {
+            [] => libc::printf(f),
+            [CArg::Int(a)] => libc::printf(f, a),
+            [CArg::LongLong(a)] => libc::printf(f, a),
+            [CArg::Double(a)] => libc::printf(f, a),
+            [CArg::Str(a)] => libc::printf(f, a),
+            [CArg::Int(a), CArg::Int(b)] => libc::printf(f, a, b),
+            [CArg::LongLong(a), CArg::LongLong(b)] => libc::printf(f, a, b),
+            [CArg::Double(a), CArg::Double(b)] => libc::printf(f, a, b),
+            [CArg::LongLong(a), CArg::LongLong(b), CArg::LongLong(c)] => libc::printf(f, a, b, c),
+            _ => unreachable!(),
+        };
+    }
+}
+
+impl Interpreter<'_> {
+    // prints the message and stops the program with exit code 1, see Compiler::build_runtime_error
+    pub(super) fn runtime_error(&self, format: &str, args: &[CArg]) -> Stop {
+        let located = self
+            .current_span
+            .as_ref()
+            .and_then(|span| self.sources.position(span.start))
+            .map(|position| format!("{}  --> {}\n", format, position.replace('%', "%%")));
+        printf(located.as_deref().unwrap_or(format), args);
+        Stop::Exit(1)
+    }
+
+    // when `failed` is set, goes to the innermost `.Err(...)` handler or, without one, stops the program with `trap`
+    pub(super) fn guard(&self, failed: bool, trap: &str) -> Eval<()> {
+        if !failed {
+            return Ok(());
+        }
+        if self.handlers > 0 {
+            return Err(Stop::Handled);
+        }
+        Err(self.runtime_error(trap, &[]))
+    }
+
+    // the message of a `.Err(...)` handler that fired
+    pub(super) fn print_message(&self, message: &str) {
+        let message = CString::new(message).unwrap_or_default();
+        printf("%s\n", &[CArg::Str(message.as_ptr())]);
+    }
+
+    pub(super) fn print(&mut self, params: &[HIRExpr]) -> Eval<()> {
+        for (i, expr) in params.iter().enumerate() {
+            let value = self.eval(expr)?;
+            let is_last = i == params.len() - 1;
+
+            // tensors print themselves through the runtime library
+            if let Value::Tensor(tensor, _) = value {
+                self.tensor_print(tensor.0, is_last);
+            } else {
+                let arg = CArg::of(&value)
+                    .ok_or_else(|| format!("[ERR-SEM-540] Unsupported type for printing: {:?}", value.ty()))?;
+                let newline = if is_last { "\n" } else { "" };
+                printf(&format!("{}{}", arg.spec(), newline), &[arg]);
+            }
+
+            if !is_last {
+                printf("%s", &[CArg::Str(c" ".as_ptr())]);
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn input(&mut self, target: &HIRExpr) -> Eval<()> {
+        let HIRExpr::Identifier(name) = target else {
+            return Err("[ERR-SEM-542] input target must be identifier".into());
+        };
+        if !self.mutable_vars.contains(name) {
+            return Err(format!("[ERR-SEM-549] cannot input into immutable variable '{}'", name).into());
+        }
+        let current = match self.locals.as_ref().and_then(|locals| locals.get(name)) {
+            Some(value) => value.clone(),
+            None if self.globals.contains_key(name) => {
+                return Err(format!(
+                    "[ERR-SEM-548] input on global variable '{}' is UB: use only local (alloca) variables!",
+                    name
+                )
+                .into());
+            }
+            None => return Err("[ERR-SEM-543] variable not declared for input".into()),
+        };
+
+        // a failed read leaves the variable as it was
+        let value = unsafe {
+            match current {
+                Value::I32(mut v) => {
+                    self.guard_scanf(libc::scanf(c"%d".as_ptr(), &mut v as *mut c_int))?;
+                    Value::I32(v)
+                }
+                Value::Bool(v) => {
+                    let mut read = v as c_int;
+                    self.guard_scanf(libc::scanf(c"%d".as_ptr(), &mut read as *mut c_int))?;
+                    // truncated to an i1
+                    Value::Bool(read & 1 != 0)
+                }
+                Value::I64(mut v) => {
+                    self.guard_scanf(libc::scanf(c"%lld".as_ptr(), &mut v as *mut c_longlong))?;
+                    Value::I64(v)
+                }
+                Value::F32(mut v) => {
+                    self.guard_scanf(libc::scanf(c"%f".as_ptr(), &mut v as *mut f32))?;
+                    Value::F32(v)
+                }
+                Value::F64(mut v) => {
+                    self.guard_scanf(libc::scanf(c"%lf".as_ptr(), &mut v as *mut c_double))?;
+                    Value::F64(v)
+                }
+                Value::Str(_) => {
+                    // compiled code reads into a fresh 1024 byte buffer, scanf never writes past it
+                    let mut buffer = [0 as c_char; 1024];
+                    self.guard_scanf(libc::scanf(c"%1023s".as_ptr(), buffer.as_mut_ptr()))?;
+                    let read = CStr::from_ptr(buffer.as_ptr());
+                    if self.runtime_checks() {
+                        // a word that filled the buffer was most likely cut off
+                        self.guard(
+                            read.to_bytes().len() == 1023,
+                            "\n[ERR-RT-033] Runtime error: input does not fit the 1024 byte string buffer\n",
+                        )?;
+                    }
+                    Value::Str(Rc::from(read))
+                }
+                _ => return Err("[ERR-SEM-544] unsupported input type".into()),
+            }
+        };
+        if let Some(locals) = self.locals.as_mut() {
+            locals.insert(name.clone(), value);
+        }
+        Ok(())
+    }
+
+    // scanf returns how many values it matched, with a handler a failed read drops the rest
+    // of the line (so the next `in` starts fresh) and goes to the handler
+    fn guard_scanf(&self, matched: c_int) -> Eval<()> {
+        if self.handlers == 0 || matched == 1 {
+            return Ok(());
+        }
+        loop {
+            let ch = unsafe { libc::getchar() };
+            if ch == b'\n' as c_int || ch == libc::EOF {
+                break;
+            }
+        }
+        Err(Stop::Handled)
+    }
+
+    // a failed assertion prints where it happened and both values, then stops the program with exit code 1
+    pub(super) fn assert(&mut self, stmt: &HIRStatement) -> Eval<()> {
+        self.function_only("[ERR-SEM-524]")?;
+        let HIRStatement::Assert { left, op, right, ty, source } = stmt else {
+            return Err("[ERR-SEM-546] Provided statement is not an assertion".into());
+        };
+        let left = self.eval(left)?;
+        let right = self.eval(right)?;
+        let ordering = match (&left, &right) {
+            (Value::F32(a), Value::F32(b)) => a.partial_cmp(b),
+            (Value::F64(a), Value::F64(b)) => a.partial_cmp(b),
+            (Value::I32(a), Value::I32(b)) => Some(a.cmp(b)),
+            (Value::I64(a), Value::I64(b)) => Some(a.cmp(b)),
+            // an i1, true is -1 to a signed comparison
+            (Value::Bool(a), Value::Bool(b)) => Some((-(*a as i8)).cmp(&-(*b as i8))),
+            _ => return Err(format!("Cannot compare {:?} with {:?}", left.ty(), right.ty()).into()),
+        };
+        if compare(op, ordering) {
+            return Ok(());
+        }
+        let spec = match ty {
+            HIRType::I64 => "%lld",
+            HIRType::F32 | HIRType::F64 => "%f",
+            _ => "%d",
+        };
+        let format = format!(
+            "\n[ERR-RT-020] Assertion failed: {}\n  left:  {}\n  right: {}\n",
+            source.replace('%', "%%"),
+            spec,
+            spec
+        );
+        let args: Vec<CArg> = [&left, &right].into_iter().filter_map(CArg::of).collect();
+        Err(self.runtime_error(&format, &args))
+    }
+}
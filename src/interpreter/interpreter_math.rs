@@ -0,0 +1,164 @@
+//math and conversions in the interpreter, with the results and runtime checks of compiler_math_codegen:
+//unchecked integer math wraps, checked math stops on overflow and zero divisors,
+//comparisons are ordered (NaN compares false) and strings compare by content
+
+use super::{Eval, Interpreter, Value};
+use crate::high_level_ir::{HIRExpr, HIROperator, HIRType};
+use std::cmp::Ordering;
+
+const INTEGER_OVERFLOW: &str = "\n[ERR-RT-030] Runtime error: integer overflow\n";
+const DIVISION_BY_ZERO: &str = "\n[ERR-RT-031] Runtime error: division by zero\n";
+
+impl Interpreter<'_> {
+    pub(super) fn math(&mut self, left: &HIRExpr, op: &HIROperator, right: &HIRExpr) -> Eval<Value> {
+        let left_val = self.eval(left)?;
+        let right_val = self.eval(right)?;
+        match promote(left_val, right_val)? {
+            (Value::I32(a), Value::I32(b)) => self.int_math(op, a as i64, b as i64, 32),
+            (Value::I64(a), Value::I64(b)) => self.int_math(op, a, b, 64),
+            (Value::F32(a), Value::F32(b)) => self.float_math(op, a as f64, b as f64, true),
+            (Value::F64(a), Value::F64(b)) => self.float_math(op, a, b, false),
+            // an i1 in compiled code, true is -1 to a signed comparison
+            (Value::Bool(a), Value::Bool(b)) if is_comparison(op) => {
+                Ok(Value::Bool(compare(op, Some((-(a as i64)).cmp(&-(b as i64))))))
+            }
+            (Value::Str(a), Value::Str(b)) if is_comparison(op) => {
+                Ok(Value::Bool(compare(op, Some(a.to_bytes().cmp(b.to_bytes())))))
+            }
+            (value, _) => Err(format!("Cannot apply {:?} to {:?}", op, value.ty()).into()),
+        }
+    }
+
+    // `bits` is the width of the operands, both are already sign extended
+    fn int_math(&mut self, op: &HIROperator, a: i64, b: i64, bits: u32) -> Eval<Value> {
+        let (min, max) = if bits == 32 { (i32::MIN as i64, i32::MAX as i64) } else { (i64::MIN, i64::MAX) };
+        let checked = self.runtime_checks();
+        let wide = match op {
+            HIROperator::Plus => a as i128 + b as i128,
+            HIROperator::Minus => a as i128 - b as i128,
+            HIROperator::Multiply => a as i128 * b as i128,
+            HIROperator::Divide | HIROperator::Modulo => {
+                if checked {
+                    self.guard(b == 0, DIVISION_BY_ZERO)?;
+                    self.guard(a == min && b == -1, INTEGER_OVERFLOW)?;
+                } else if b == 0 {
+                    // compiled code dies of SIGFPE here, the checked message says more
+                    return Err(self.runtime_error(DIVISION_BY_ZERO, &[]));
+                }
+                if *op == HIROperator::Divide {
+                    a.wrapping_div(b) as i128
+                } else {
+                    a.wrapping_rem(b) as i128
+                }
+            }
+            cmp_op => return Ok(Value::Bool(compare(cmp_op, Some(a.cmp(&b))))),
+        };
+        if checked {
+            self.guard(wide < min as i128 || wide > max as i128, INTEGER_OVERFLOW)?;
+        }
+        // without checks the result wraps around like the llvm instruction
+        Ok(if bits == 32 { Value::I32(wide as i32) } else { Value::I64(wide as i64) })
+    }
+
+    // f32 math is done in f64 and rounded back, which gives the same result for + - * / and %
+    fn float_math(&mut self, op: &HIROperator, a: f64, b: f64, single: bool) -> Eval<Value> {
+        let result = match op {
+            HIROperator::Plus => a + b,
+            HIROperator::Minus => a - b,
+            HIROperator::Multiply => a * b,
+            HIROperator::Divide | HIROperator::Modulo => {
+                // float division by zero only counts inside a `.Err(...)` handler (it is inf otherwise)
+                if self.runtime_checks() && self.handlers > 0 {
+                    self.guard(b == 0.0, DIVISION_BY_ZERO)?;
+                }
+                if *op == HIROperator::Divide { a / b } else { a % b }
+            }
+            cmp_op => return Ok(Value::Bool(compare(cmp_op, a.partial_cmp(&b)))),
+        };
+        Ok(if single { Value::F32(result as f32) } else { Value::F64(result) })
+    }
+
+    pub(super) fn coerce(&self, value: Value, target: &HIRType) -> Eval<Value> {
+        Ok(match (value, target) {
+            (Value::I32(v), HIRType::I64) => Value::I64(v as i64),
+            (Value::I32(v), HIRType::F64) => Value::F64(v as f64),
+            (Value::I64(v), HIRType::F64) => Value::F64(v as f64),
+            (Value::F32(v), HIRType::F64) => Value::F64(v as f64),
+            (Value::I32(v), HIRType::F32) => Value::F32(v as f32),
+            (Value::I64(v), HIRType::F32) => Value::F32(v as f32),
+            (Value::F64(v), HIRType::F32) => {
+                if self.runtime_checks() {
+                    // finite doubles past f32::MAX would silently become inf
+                    self.guard(
+                        v > f32::MAX as f64 || v < f32::MIN as f64,
+                        "\n[ERR-RT-032] Runtime error: value is out of range for f32\n",
+                    )?;
+                }
+                Value::F32(v as f32)
+            }
+            (Value::I64(v), HIRType::I32) => {
+                if self.runtime_checks() {
+                    self.guard(v as i32 as i64 != v, "\n[ERR-RT-032] Runtime error: value is out of range for i32\n")?;
+                }
+                Value::I32(v as i32)
+            }
+            (Value::Bool(v), HIRType::I32) => Value::I32(v as i32),
+            (Value::Bool(v), HIRType::I64) => Value::I64(v as i64),
+            (value, target) => {
+                return Err(format!(
+                    "[ERR-SEM-511] Unsupported coercion from {:?} to {:?}",
+                    value.ty(),
+                    target
+                )
+                .into());
+            }
+        })
+    }
+}
+
+// i32 meets i64 and f32 meets f64 in the wider type, anything else has to match already
+fn promote(left: Value, right: Value) -> Eval<(Value, Value)> {
+    Ok(match (left, right) {
+        (Value::I32(a), Value::I64(b)) => (Value::I64(a as i64), Value::I64(b)),
+        (Value::I64(a), Value::I32(b)) => (Value::I64(a), Value::I64(b as i64)),
+        (Value::F32(a), Value::F64(b)) => (Value::F64(a as f64), Value::F64(b)),
+        (Value::F64(a), Value::F32(b)) => (Value::F64(a), Value::F64(b as f64)),
+        (left, right) if left.ty() == right.ty() => (left, right),
+        (left, right) => {
+            return Err(format!(
+                "[ERR-SEM-510] Type mismatch: {:?} vs {:?}",
+                left.ty(),
+                right.ty()
+            )
+            .into());
+        }
+    })
+}
+
+fn is_comparison(op: &HIROperator) -> bool {
+    matches!(
+        op,
+        HIROperator::Equals
+            | HIROperator::NotEquals
+            | HIROperator::GreaterThan
+            | HIROperator::LessThan
+            | HIROperator::GreaterEqual
+            | HIROperator::LessEqual
+    )
+}
+
+// None is an unordered pair (a NaN), every comparison with it is false, `!=` included
+pub(super) fn compare(op: &HIROperator, ordering: Option<Ordering>) -> bool {
+    let Some(ordering) = ordering else {
+        return false;
+    };
+    match op {
+        HIROperator::Equals => ordering.is_eq(),
+        HIROperator::NotEquals => ordering.is_ne(),
+        HIROperator::GreaterThan => ordering.is_gt(),
+        HIROperator::LessThan => ordering.is_lt(),
+        HIROperator::GreaterEqual => ordering.is_ge(),
+        HIROperator::LessEqual => ordering.is_le(),
+        _ => unreachable!(),
+    }
+}
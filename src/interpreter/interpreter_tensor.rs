@@ -0,0 +1,170 @@
+//tensors in the interpreter, every operation calls the runtime library (runtime/axon_runtime.c)
+//with the same arguments compiler_tensor_codegen passes it, shapes were already checked by the semantic pass
+
+use super::interpreter_io::CArg;
+use super::interpreter_value::TensorRef;
+use super::{Eval, Interpreter, Value};
+use crate::high_level_ir::{HIRExpr, HIRType, TensorFunc};
+use crate::runtime::{
+    axon_tensor_at, axon_tensor_backward, axon_tensor_binary, axon_tensor_detach, axon_tensor_fill, axon_tensor_from, axon_tensor_get,
+    axon_tensor_grad, axon_tensor_grad_of, axon_tensor_item, axon_tensor_matmul, axon_tensor_print, axon_tensor_rand,
+    axon_tensor_reduce, axon_tensor_reduce_axis, axon_tensor_reshape, axon_tensor_softmax, axon_tensor_transpose,
+    axon_tensor_unary, Tensor, OP_ADD, OP_DIV, OP_MUL, OP_SUB, REDUCE_MAX, REDUCE_MEAN, REDUCE_SUM, UNARY_EXP,
+    UNARY_RELU, UNARY_SIGMOID, UNARY_TANH,
+};
+use std::ffi::CString;
+use std::rc::Rc;
+
+const ALLOCATE_OUTSIDE: &str = "[ERR-SEM-670] Cannot allocate outside of a function";
+
+fn result_shape(ty: &HIRType) -> Eval<&[usize]> {
+    match ty {
+        HIRType::Tensor(shape) => Ok(shape),
+        other => Err(format!("[ERR-SEM-243] Expected a tensor type, found {:?}", other).into()),
+    }
+}
+
+// rank-0 tensors still get a valid (unused) pointer
+fn dims(shape: &[usize]) -> Vec<i64> {
+    match shape {
+        [] => vec![1],
+        _ => shape.iter().map(|d| *d as i64).collect(),
+    }
+}
+
+fn tensor_arg(value: &Value) -> Eval<Tensor> {
+    match value {
+        Value::Tensor(tensor, _) => Ok(tensor.0),
+        other => Err(format!("[ERR-SEM-243] Expected a tensor type, found {:?}", other.ty()).into()),
+    }
+}
+
+fn f32_arg(value: &Value) -> Eval<f32> {
+    match value {
+        Value::F32(v) => Ok(*v),
+        other => Err(format!("Expected an f32 tensor element, found {:?}", other.ty()).into()),
+    }
+}
+
+impl Interpreter<'_> {
+    // runs a runtime call with its errors pointing at the current statement, as tensor_location does in the JIT
+    fn located<T>(&self, call: impl FnOnce() -> T) -> T {
+        let location = self
+            .current_span
+            .as_ref()
+            .and_then(|span| self.sources.position(span.start))
+            .and_then(|position| CString::new(position).ok());
+        unsafe { axon_tensor_at(location.as_ref().map_or(std::ptr::null(), |location| location.as_ptr())) };
+        let result = call();
+        // the runtime must not keep a pointer to the string past this call
+        unsafe { axon_tensor_at(std::ptr::null()) };
+        result
+    }
+
+    pub(super) fn tensor_literal(&mut self, elements: &[HIRExpr], shape: &[usize]) -> Eval<Value> {
+        self.function_only(ALLOCATE_OUTSIDE)?;
+        let mut data = Vec::with_capacity(elements.len());
+        for element in elements {
+            data.push(f32_arg(&self.eval(element)?)?);
+        }
+        let dims = dims(shape);
+        let tensor = self.located(|| unsafe { axon_tensor_from(shape.len() as i64, dims.as_ptr(), data.as_ptr()) });
+        Ok(Value::Tensor(Rc::new(TensorRef(tensor)), shape.to_vec()))
+    }
+
+    pub(super) fn tensor_call(&mut self, func: &TensorFunc, args: &[HIRExpr], ty: &HIRType) -> Eval<Value> {
+        let mut values = Vec::with_capacity(args.len());
+        for arg in args {
+            values.push(self.eval(arg)?);
+        }
+        // every call hands back a new reference for the value to own
+        let tensor = |t: Tensor| -> Eval<Value> { Ok(Value::Tensor(Rc::new(TensorRef(t)), result_shape(ty)?.to_vec())) };
+        self.located(|| unsafe {
+            match func {
+                TensorFunc::Fill => {
+                    let shape = result_shape(ty)?;
+                    let dims = dims(shape);
+                    tensor(axon_tensor_fill(shape.len() as i64, dims.as_ptr(), f32_arg(&values[0])?))
+                }
+                TensorFunc::Rand => {
+                    let shape = result_shape(ty)?;
+                    let dims = dims(shape);
+                    tensor(axon_tensor_rand(shape.len() as i64, dims.as_ptr()))
+                }
+                TensorFunc::Add | TensorFunc::Sub | TensorFunc::Mul | TensorFunc::Div => {
+                    let op = match func {
+                        TensorFunc::Add => OP_ADD,
+                        TensorFunc::Sub => OP_SUB,
+                        TensorFunc::Mul => OP_MUL,
+                        _ => OP_DIV,
+                    };
+                    tensor(axon_tensor_binary(op as i32, tensor_arg(&values[0])?, tensor_arg(&values[1])?))
+                }
+                TensorFunc::MatMul => tensor(axon_tensor_matmul(tensor_arg(&values[0])?, tensor_arg(&values[1])?)),
+                TensorFunc::Reshape => {
+                    let shape = result_shape(ty)?;
+                    let dims = dims(shape);
+                    tensor(axon_tensor_reshape(tensor_arg(&values[0])?, shape.len() as i64, dims.as_ptr()))
+                }
+                TensorFunc::Transpose => tensor(axon_tensor_transpose(tensor_arg(&values[0])?)),
+                TensorFunc::Sum(axis) | TensorFunc::Mean(axis) | TensorFunc::Max(axis) => {
+                    let op = match func {
+                        TensorFunc::Sum(_) => REDUCE_SUM,
+                        TensorFunc::Mean(_) => REDUCE_MEAN,
+                        _ => REDUCE_MAX,
+                    } as i32;
+                    let t = tensor_arg(&values[0])?;
+                    match axis {
+                        Some(axis) => tensor(axon_tensor_reduce_axis(op, t, *axis as i64)),
+                        None => tensor(axon_tensor_reduce(op, t)),
+                    }
+                }
+                TensorFunc::Relu | TensorFunc::Sigmoid | TensorFunc::Tanh | TensorFunc::Exp => {
+                    let op = match func {
+                        TensorFunc::Relu => UNARY_RELU,
+                        TensorFunc::Sigmoid => UNARY_SIGMOID,
+                        TensorFunc::Tanh => UNARY_TANH,
+                        _ => UNARY_EXP,
+                    };
+                    tensor(axon_tensor_unary(op as i32, tensor_arg(&values[0])?))
+                }
+                TensorFunc::Softmax => tensor(axon_tensor_softmax(tensor_arg(&values[0])?)),
+                TensorFunc::Backward => {
+                    axon_tensor_backward(tensor_arg(&values[0])?);
+                    Ok(Value::Void)
+                }
+                TensorFunc::Grad => tensor(axon_tensor_grad(tensor_arg(&values[0])?)),
+                TensorFunc::GradOf => tensor(axon_tensor_grad_of(tensor_arg(&values[0])?, tensor_arg(&values[1])?)),
+                TensorFunc::Detach => tensor(axon_tensor_detach(tensor_arg(&values[0])?)),
+                TensorFunc::Item => Ok(Value::F32(axon_tensor_item(tensor_arg(&values[0])?))),
+                TensorFunc::Get => {
+                    self.function_only(ALLOCATE_OUTSIDE)?;
+                    let mut index = Vec::with_capacity(values.len() - 1);
+                    for value in &values[1..] {
+                        index.push(match value {
+                            Value::I64(v) => *v,
+                            Value::I32(v) => *v as i64,
+                            other => return Err(format!("Tensor index must be an integer, found {:?}", other.ty()).into()),
+                        });
+                    }
+                    // the runtime library would exit the process, the same message stops only the program here
+                    if let Value::Tensor(_, shape) = &values[0] {
+                        for (axis, (&i, &size)) in index.iter().zip(shape).enumerate() {
+                            if i < 0 || i >= size as i64 {
+                                return Err(self.runtime_error(
+                                    "\n[ERR-RT-011] Runtime error: index %lld is out of bounds for tensor axis %lld of size %lld\n",
+                                    &[CArg::LongLong(i), CArg::LongLong(axis as i64), CArg::LongLong(size as i64)],
+                                ));
+                            }
+                        }
+                    }
+                    Ok(Value::F32(axon_tensor_get(tensor_arg(&values[0])?, index.as_ptr())))
+                }
+            }
+        })
+    }
+
+    pub(super) fn tensor_print(&self, tensor: Tensor, newline: bool) {
+        unsafe { axon_tensor_print(tensor, newline as i32) }
+    }
+}
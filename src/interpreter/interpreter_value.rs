@@ -0,0 +1,74 @@
+//values the interpreter works with, shaped like their compiled counterparts:
+//a vector is shared by every variable holding it (compiled code passes a pointer to its header),
+//strings are C strings so printf and strcmp see the same bytes, tensors stay on the C side
+//and the values holding one share a single reference, released when the last of them goes away
+
+use super::{Eval, Stop};
+use crate::high_level_ir::HIRType;
+use crate::runtime::{axon_tensor_release, Tensor};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::rc::Rc;
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    Str(Rc<CStr>),
+    // the elements and their type
+    Vector(Rc<RefCell<Vec<Value>>>, HIRType),
+    // owned by the runtime library, with the shape the semantic pass gave it
+    Tensor(Rc<TensorRef>, Vec<usize>),
+    // what calling a function without a return type gives
+    Void,
+}
+
+// the reference a runtime call hands back, what compiled code releases by hand happens on drop here
+#[derive(Debug)]
+pub struct TensorRef(pub Tensor);
+
+impl Drop for TensorRef {
+    fn drop(&mut self) {
+        unsafe { axon_tensor_release(self.0) }
+    }
+}
+
+impl Value {
+    pub fn string(text: &str) -> Value {
+        Value::Str(Rc::from(CString::new(text).unwrap_or_default()))
+    }
+
+    pub fn ty(&self) -> HIRType {
+        match self {
+            Value::I32(_) => HIRType::I32,
+            Value::I64(_) => HIRType::I64,
+            Value::F32(_) => HIRType::F32,
+            Value::F64(_) => HIRType::F64,
+            Value::Bool(_) => HIRType::Bool,
+            Value::Str(_) => HIRType::String,
+            Value::Vector(_, elem_ty) => HIRType::Vector(Box::new(elem_ty.clone())),
+            Value::Tensor(_, shape) => HIRType::Tensor(shape.clone()),
+            Value::Void => HIRType::Void,
+        }
+    }
+
+    pub(super) fn into_bool(self) -> Eval<bool> {
+        match self {
+            Value::Bool(value) => Ok(value),
+            other => Err(Stop::Error(format!("Expected a bool, found {:?}", other.ty()))),
+        }
+    }
+
+    pub(super) fn into_vector(self) -> Eval<(Rc<RefCell<Vec<Value>>>, HIRType)> {
+        match self {
+            Value::Vector(elements, elem_ty) => Ok((elements, elem_ty)),
+            other => Err(Stop::Error(format!(
+                "[ERR-SEM-233] Cannot use {:?} as a vector",
+                other.ty()
+            ))),
+        }
+    }
+}
@@ -0,0 +1,498 @@
+//tree-walking interpreter for the HIR,
+//`axon run --backend=interp` evaluates the program here instead of compiling it for the JIT,
+//every statement does what the code from the matching compiler_neuron module does:
+//output goes through printf and the tensor runtime, runtime errors print the same message and end the program with code 1,
+//and what codegen would refuse to compile stops the program with the same error
+
+use crate::ast::Span;
+use crate::backend::{CompileResult, CompilerError};
+use crate::high_level_ir::{HIRExpr, HIROperator, HIRStatement, HIRType};
+use crate::module_loader::SourceMap;
+use interpreter_io::CArg;
+use interpreter_value::Value;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+pub mod interpreter_io;
+pub mod interpreter_math;
+pub mod interpreter_tensor;
+pub mod interpreter_value;
+
+// every call of the program is a few nested calls here,
+// the default 8 MiB would run out long before the stack of a compiled program does
+const STACK_SIZE: usize = 512 * 1024 * 1024;
+
+const VECTOR_OUTSIDE: &str = "[ERR-SEM-237] Vectors can only be used inside a function";
+
+// how a statement finished
+enum Flow {
+    Next,
+    Break,
+    Return(Value),
+}
+
+// why evaluation stopped before the end of a statement
+enum Stop {
+    // a runtime check failed inside a `.Err(...)` statement, its handler takes over
+    Handled,
+    // what codegen reports as a compiler error
+    Error(String),
+    // a runtime error ended the program with this exit code, its message is already printed
+    Exit(i32),
+}
+
+impl From<String> for Stop {
+    fn from(error: String) -> Self {
+        Stop::Error(error)
+    }
+}
+
+impl From<&str> for Stop {
+    fn from(error: &str) -> Self {
+        Stop::Error(error.to_string())
+    }
+}
+
+type Eval<T> = Result<T, Stop>;
+
+pub struct Interpreter<'a> {
+    program: &'a [HIRStatement],
+    functions: HashMap<&'a str, &'a HIRStatement>,
+    mutable_vars: HashSet<String>,
+    globals: HashMap<String, Value>,
+    // the variables of the running function, None for the top level
+    locals: Option<HashMap<String, Value>>,
+    // what `return` hands back in the running function, `Start` returns an i32 exit code
+    return_type: Option<HIRType>,
+    // `.Err(...)` statements around the current one, like codegen's error_handlers they do not reach into calls
+    handlers: usize,
+    current_span: Option<Span>,
+    checked: bool,
+    sources: SourceMap,
+}
+
+// what CodegenOptions says about running a program, the rest of it is about building the LLVM module
+pub struct InterpreterOptions {
+    // overflow, division, conversion and input checks that stop the program, `--release` leaves them out
+    pub checked: bool,
+    // lets runtime errors say which file and line they came from
+    pub sources: SourceMap,
+}
+
+// runs the program the way compile_and_run_jit does, on a thread with room for deep recursion,
+// Ok holds the program's exit code
+pub fn run_interpreter(
+    hir: Vec<HIRStatement>,
+    mutable_vars: HashSet<String>,
+    options: InterpreterOptions,
+) -> CompileResult<i32> {
+    let thread = std::thread::Builder::new()
+        .name("axon-interp".to_string())
+        .stack_size(STACK_SIZE)
+        .spawn(move || Interpreter::new(&hir, mutable_vars, options).run());
+    let result = match thread {
+        Ok(handle) => handle
+            .join()
+            .unwrap_or_else(|_| Err(vec![CompilerError("The interpreter panicked".to_string(), None)])),
+        Err(e) => Err(vec![CompilerError(format!("Failed to start the interpreter: {}", e), None)]),
+    };
+    // the program printed through libc, like axon_jit_main its output is out before the caller goes on
+    unsafe { libc::fflush(std::ptr::null_mut()) };
+    result
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(program: &'a [HIRStatement], mutable_vars: HashSet<String>, options: InterpreterOptions) -> Self {
+        // every function is known up front, so calls can come before the declaration
+        let functions = program
+            .iter()
+            .filter_map(|statement| match statement.unlocated() {
+                function @ HIRStatement::Function { name, .. } => Some((name.as_str(), function)),
+                _ => None,
+            })
+            .collect();
+        Interpreter {
+            program,
+            functions,
+            mutable_vars,
+            globals: HashMap::new(),
+            locals: None,
+            return_type: None,
+            handlers: 0,
+            current_span: None,
+            checked: options.checked,
+            sources: options.sources,
+        }
+    }
+
+    // the top level sets up the globals, then `Start` runs
+    pub fn run(mut self) -> CompileResult<i32> {
+        let mut errors = Vec::new();
+        for statement in self.program {
+            match self.exec(statement) {
+                Ok(Flow::Next) => {}
+                Ok(_) => errors.push(CompilerError(
+                    "[ERR-SEM-531] No loop context for break".to_string(),
+                    self.current_span.take(),
+                )),
+                Err(Stop::Exit(code)) => return Ok(code),
+                Err(stop) => errors.push(self.error(stop)),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let start = self
+            .functions
+            .values()
+            .copied()
+            .find(|function| matches!(function, HIRStatement::Function { start: true, .. }));
+        let Some(start) = start else {
+            return Err(vec![CompilerError("Main function not found".to_string(), None)]);
+        };
+        match self.call(start, Vec::new()) {
+            Ok(Value::I32(code)) | Err(Stop::Exit(code)) => Ok(code),
+            Ok(_) => Ok(0),
+            Err(stop) => Err(vec![self.error(stop)]),
+        }
+    }
+
+    fn error(&mut self, stop: Stop) -> CompilerError {
+        match stop {
+            Stop::Error(message) => CompilerError(message, self.current_span.take()),
+            // handlers never reach past the function they are in, run() takes the exit code
+            Stop::Handled | Stop::Exit(_) => unreachable!(),
+        }
+    }
+
+    // whether math, conversions and input are checked, see Compiler::runtime_checks
+    fn runtime_checks(&self) -> bool {
+        self.locals.is_some() && (self.checked || self.handlers > 0)
+    }
+
+    fn function_only(&self, error: &str) -> Eval<()> {
+        match self.locals {
+            Some(_) => Ok(()),
+            None => Err(error.into()),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Value> {
+        self.locals
+            .as_ref()
+            .and_then(|locals| locals.get(name))
+            .or_else(|| self.globals.get(name))
+    }
+
+    fn call(&mut self, function: &'a HIRStatement, args: Vec<Value>) -> Eval<Value> {
+        let HIRStatement::Function { name, params, return_type, start, body } = function else {
+            return Err("[ERR-SEM-523] Provided statement is not a function".into());
+        };
+        let locals = params.iter().map(|(param, _)| param.clone()).zip(args).collect();
+        let outer_locals = self.locals.replace(locals);
+        // `Start` is `main` and hands an exit code back
+        let outer_return = self.return_type.replace(if *start { HIRType::I32 } else { return_type.clone() });
+        let outer_handlers = std::mem::take(&mut self.handlers);
+        let flow = self.exec_block(body);
+        self.locals = outer_locals;
+        self.return_type = outer_return;
+        self.handlers = outer_handlers;
+
+        match flow? {
+            Flow::Return(value) => Ok(value),
+            Flow::Break => Err("[ERR-SEM-531] No loop context for break".into()),
+            Flow::Next if *start => Ok(Value::I32(0)),
+            Flow::Next if *return_type == HIRType::Void => Ok(Value::Void),
+            Flow::Next => Err(format!(
+                "[ERR-SEM-520] Function '{}' with return type {:?} is missing a return statement at the end of its body.",
+                name, return_type
+            )
+            .into()),
+        }
+    }
+
+    // runs until a statement breaks or returns
+    fn exec_block(&mut self, statements: &'a [HIRStatement]) -> Eval<Flow> {
+        for statement in statements {
+            match self.exec(statement)? {
+                Flow::Next => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn exec(&mut self, stmt: &'a HIRStatement) -> Eval<Flow> {
+        match stmt {
+            // already known, see Interpreter::new
+            HIRStatement::Function { .. } => {}
+            HIRStatement::Assignment { name, value } => {
+                let value = self.eval(value)?;
+                self.assign(name, value)?;
+            }
+            HIRStatement::Print { params } => self.print(params)?,
+            HIRStatement::ExprStatement { expr } => {
+                self.eval(expr)?;
+            }
+            HIRStatement::If { condition, body, else_body } => {
+                self.function_only("[ERR-SEM-524]")?;
+                let taken = match self.eval(condition)? {
+                    Value::Bool(value) => value,
+                    Value::I32(value) => value != 0,
+                    Value::I64(value) => value != 0,
+                    // ordered, NaN is false
+                    Value::F32(value) => !value.is_nan() && value != 0.0,
+                    Value::F64(value) => !value.is_nan() && value != 0.0,
+                    _ => return Err("[ERR-SEM-522] Unsupported type in if condition".into()),
+                };
+                if taken {
+                    return self.exec_block(body);
+                }
+                if let Some(else_body) = else_body {
+                    return self.exec_block(else_body);
+                }
+            }
+            HIRStatement::Loop { body } => {
+                self.function_only("[ERR-SEM-532] No active function")?;
+                loop {
+                    match self.exec_block(body)? {
+                        Flow::Next => {}
+                        Flow::Break => break,
+                        flow => return Ok(flow),
+                    }
+                }
+            }
+            HIRStatement::While { condition, body } => {
+                self.function_only("[ERR-SEM-532] No active function")?;
+                loop {
+                    let Value::Bool(holds) = self.eval(condition)? else {
+                        return Err("[ERR-SEM-534] while condition must be a boolean expression".into());
+                    };
+                    if !holds {
+                        break;
+                    }
+                    match self.exec_block(body)? {
+                        Flow::Next => {}
+                        Flow::Break => break,
+                        flow => return Ok(flow),
+                    }
+                }
+            }
+            HIRStatement::Break => return Ok(Flow::Break),
+            HIRStatement::Return { value } => return self.exec_return(value.as_ref()),
+            HIRStatement::Input { target } => self.input(target)?,
+            HIRStatement::IndexAssign { name, index, value } => self.index_assign(name, index, value)?,
+            HIRStatement::Assert { .. } => self.assert(stmt)?,
+            HIRStatement::OnError { stmt, message } => {
+                // global initializers are constants, nothing can fail there at runtime
+                if self.locals.is_none() {
+                    return self.exec(stmt);
+                }
+                let span = self.current_span.clone();
+                self.handlers += 1;
+                let result = self.exec(stmt);
+                self.handlers -= 1;
+                return match result {
+                    Err(Stop::Handled) => {
+                        self.current_span = span;
+                        self.print_message(message);
+                        Ok(Flow::Next)
+                    }
+                    other => other,
+                };
+            }
+            HIRStatement::Located { span, stmt } => {
+                // on failure the span is left in place for the error report
+                let outer = self.current_span.replace(span.clone());
+                let flow = self.exec(stmt)?;
+                self.current_span = outer;
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn exec_return(&mut self, value: Option<&HIRExpr>) -> Eval<Flow> {
+        let return_type = self
+            .return_type
+            .clone()
+            .ok_or("[ERR-SEM-525] 'return' outside of a function")?;
+        let value = match value {
+            Some(expr) => {
+                let value = self.eval(expr)?;
+                if value.ty() != return_type {
+                    return Err(format!(
+                        "[ERR-SEM-526] Return type mismatch: expected {:?}, found {:?}",
+                        return_type,
+                        value.ty()
+                    )
+                    .into());
+                }
+                value
+            }
+            None if return_type == HIRType::Void => Value::Void,
+            None if return_type == HIRType::I64 => Value::I64(0),
+            None => Value::I32(0),
+        };
+        Ok(Flow::Return(value))
+    }
+
+    // an existing variable is overwritten, a new one is local inside a function and global outside
+    fn assign(&mut self, name: &str, value: Value) -> Eval<()> {
+        let existing = match self.locals.as_mut() {
+            Some(locals) if locals.contains_key(name) => locals.get_mut(name),
+            _ => self.globals.get_mut(name),
+        };
+        if let Some(existing) = existing {
+            if existing.ty() != value.ty() {
+                return Err(format!(
+                    "[ERR-SEM-510] Type mismatch: existing var {:?} vs new value {:?}",
+                    existing.ty(),
+                    value.ty()
+                )
+                .into());
+            }
+            *existing = value;
+            return Ok(());
+        }
+        match self.locals.as_mut() {
+            Some(locals) => locals.insert(name.to_string(), value),
+            None => self.globals.insert(name.to_string(), value),
+        };
+        Ok(())
+    }
+
+    fn eval(&mut self, expr: &HIRExpr) -> Eval<Value> {
+        match expr {
+            HIRExpr::Int32(value) => Ok(Value::I32(*value)),
+            HIRExpr::Int64(value) => Ok(Value::I64(*value)),
+            HIRExpr::Float32(value) => Ok(Value::F32(*value)),
+            HIRExpr::Float64(value) => Ok(Value::F64(*value)),
+            HIRExpr::Bool(value) => Ok(Value::Bool(*value)),
+            HIRExpr::String(value) => Ok(Value::string(value)),
+            HIRExpr::Identifier(name) => self
+                .lookup(name)
+                .cloned()
+                .ok_or_else(|| format!("Unknown variable: {}", name).into()),
+            HIRExpr::BinaryOp { left, op: op @ (HIROperator::And | HIROperator::Or), right } => {
+                self.logical(left, op, right)
+            }
+            HIRExpr::BinaryOp { left, op, right } => self.math(left, op, right),
+            HIRExpr::Not { expr } => match self.eval(expr)? {
+                Value::Bool(value) => Ok(Value::Bool(!value)),
+                other => Err(format!("'not' needs a bool, found {:?}", other.ty()).into()),
+            },
+            HIRExpr::FunctionCall { name, args } => {
+                let function = *self
+                    .functions
+                    .get(name.as_str())
+                    .ok_or_else(|| format!("Unknown function: {}", name))?;
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    values.push(self.eval(arg)?);
+                }
+                self.call(function, values)
+            }
+            HIRExpr::Coerce { expr, target } => {
+                let value = self.eval(expr)?;
+                self.coerce(value, target)
+            }
+            HIRExpr::Vector { elements, elem_type } => {
+                self.function_only("[ERR-SEM-237] Vectors can only be created inside a function")?;
+                let mut values = Vec::with_capacity(elements.len());
+                for element in elements {
+                    values.push(self.eval(element)?);
+                }
+                Ok(Value::Vector(Rc::new(RefCell::new(values)), elem_type.clone()))
+            }
+            HIRExpr::Index { target, index } => {
+                let (elements, _) = self.eval(target)?.into_vector()?;
+                let index = self.eval_index(index)?;
+                let slot = self.bounds_check(index, elements.borrow().len())?;
+                let value = elements.borrow()[slot].clone();
+                Ok(value)
+            }
+            HIRExpr::VectorLen { target } => {
+                let (elements, _) = self.eval(target)?.into_vector()?;
+                let len = elements.borrow().len();
+                Ok(Value::I32(len as i32))
+            }
+            HIRExpr::VectorPush { target, value } => {
+                self.function_only(VECTOR_OUTSIDE)?;
+                let (elements, _) = self.eval(target)?.into_vector()?;
+                let value = self.eval(value)?;
+                elements.borrow_mut().push(value);
+                Ok(Value::Void)
+            }
+            HIRExpr::VectorPop { target } => {
+                self.function_only(VECTOR_OUTSIDE)?;
+                let (elements, _) = self.eval(target)?.into_vector()?;
+                let popped = elements.borrow_mut().pop();
+                match popped {
+                    Some(value) => Ok(value),
+                    None => Err(self.runtime_error("\n[ERR-RT-002] Runtime error: pop() called on an empty vector\n", &[])),
+                }
+            }
+            HIRExpr::Tensor { elements, shape } => self.tensor_literal(elements, shape),
+            HIRExpr::TensorCall { func, args, ty } => self.tensor_call(func, args, ty),
+        }
+    }
+
+    // `a and b` / `a or b`, the right side only runs when the left one does not decide the result
+    fn logical(&mut self, left: &HIRExpr, op: &HIROperator, right: &HIRExpr) -> Eval<Value> {
+        self.function_only("[ERR-SEM-527] 'and'/'or' cannot be used outside of a function")?;
+        // `or` is decided by a true left side, `and` by a false one
+        let decided = matches!(op, HIROperator::Or);
+        if self.eval(left)?.into_bool()? == decided {
+            return Ok(Value::Bool(decided));
+        }
+        Ok(Value::Bool(self.eval(right)?.into_bool()?))
+    }
+
+    fn eval_index(&mut self, index: &HIRExpr) -> Eval<i64> {
+        match self.eval(index)? {
+            Value::I64(value) => Ok(value),
+            Value::I32(value) => Ok(value as i64),
+            other => Err(format!(
+                "[ERR-SEM-234] Vector index must be an integer, found {:?}",
+                other.ty()
+            )
+            .into()),
+        }
+    }
+
+    // a negative index is out of bounds too
+    fn bounds_check(&self, index: i64, len: usize) -> Eval<usize> {
+        self.function_only(VECTOR_OUTSIDE)?;
+        if index < 0 || index >= len as i64 {
+            return Err(self.runtime_error(
+                "\n[ERR-RT-001] Runtime error: index %lld is out of bounds for vector of length %lld\n",
+                &[CArg::LongLong(index), CArg::LongLong(len as i64)],
+            ));
+        }
+        Ok(index as usize)
+    }
+
+    fn index_assign(&mut self, name: &str, index: &HIRExpr, value: &HIRExpr) -> Eval<()> {
+        let target = self
+            .lookup(name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown variable: {}", name))?;
+        let (elements, elem_ty) = target.into_vector()?;
+        let index = self.eval_index(index)?;
+        let value = self.eval(value)?;
+        if value.ty() != elem_ty {
+            return Err(format!(
+                "[ERR-SEM-510] Type mismatch: vector of {:?} vs new value {:?}",
+                elem_ty,
+                value.ty()
+            )
+            .into());
+        }
+        let slot = self.bounds_check(index, elements.borrow().len())?;
+        elements.borrow_mut()[slot] = value;
+        Ok(())
+    }
+}
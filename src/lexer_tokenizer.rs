@@ -23,6 +23,8 @@ pub enum Token {
     Bool,
     #[token("Vec")]
     Vector,
+    #[token("Tensor")]
+    Tensor,
 
     // Functions
     #[token("do")]
@@ -37,6 +39,10 @@ pub enum Token {
     When,
     #[token("not")]
     Not,
+    #[token("and")]
+    And,
+    #[token("or")]
+    Or,
     #[token("else")]
     Else,
     #[token("yes")]
@@ -53,8 +59,16 @@ pub enum Token {
     While,
     #[token("break")]
     Break,
+    #[token("return")]
+    Return,
     #[token("in")]
     Input,
+    #[token("assert")]
+    Assert,
+    #[token("assert_eq")]
+    AssertEq,
+    #[token("use")]
+    Use,
 
     // Punctuation
     #[token("(")]
@@ -79,6 +93,8 @@ pub enum Token {
     RBrace,
     #[token(",")]
     Comma,
+    #[token("->")]
+    Arrow,
 
     // Arithmetic
     #[token("+")]
@@ -162,6 +178,34 @@ pub fn lex_with_span(source: &str) -> Vec<PositionedToken> {
     tokens
 }
 
+// Same as lex_with_span, but `??` comments are kept as Token::Comment so tools like
+// the formatter can put them back, the parser never sees these
+pub fn lex_with_trivia(source: &str) -> Vec<PositionedToken> {
+    let mut tokens = Vec::new();
+    let mut last_end = 0;
+    for token in lex_with_span(source) {
+        push_comments(source, last_end..token.span.start, &mut tokens);
+        last_end = token.span.end;
+        tokens.push(token);
+    }
+    push_comments(source, last_end..source.len(), &mut tokens);
+    tokens
+}
+
+// between two tokens there is only whitespace and comments
+fn push_comments(source: &str, gap: std::ops::Range<usize>, tokens: &mut Vec<PositionedToken>) {
+    let mut pos = gap.start;
+    while let Some(found) = source[pos..gap.end].find("??") {
+        let start = pos + found;
+        let end = source[start..gap.end].find(['\r', '\n']).map_or(gap.end, |i| start + i);
+        tokens.push(PositionedToken {
+            token: Token::Comment,
+            span: start..end,
+        });
+        pos = end;
+    }
+}
+
 // Converts a string with escapes like \n, \t, \" etc.
 fn unescape(s: &str) -> String {
     let mut chars = s[1..s.len() - 1].chars(); // cut quotes
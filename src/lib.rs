@@ -0,0 +1,416 @@
+// AxonScript as a library,
+// the CLI in main.rs is one user of it, editors and build tools drive the same stages in-process:
+// `parse` loads a program and everything it imports, `analyze` runs the semantic pass and the HIR optimizations,
+// `emit` takes the result through codegen, `run` executes it in the JIT or the interpreter,
+// and `compile` / `run_jit` do all of it in one call,
+// a build without the default `llvm` feature has only the interpreter, `emit` and the JIT report that they are missing,
+// nothing here prints, sleeps or clears the screen, errors and warnings come back as Diagnostics
+
+pub mod ast;
+mod backend;
+// raw LLVM handles, reached through `emit` and `run`, left out of a build without the `llvm` feature
+#[cfg(feature = "llvm")]
+mod compiler_neuron;
+pub mod diagnostics;
+pub mod error_codes;
+pub mod high_level_ir;
+// the LLVM-free backend of `run`
+mod interpreter;
+pub mod lexer_tokenizer;
+pub mod manifest;
+pub mod module_loader;
+pub mod optimizer;
+pub mod parser;
+mod runtime;
+pub mod semantic;
+
+// the axon subcommands, the ones that print and return exit codes are only for main.rs (see `cli`)
+pub mod formatter;
+mod lsp;
+mod package_manager;
+// only the JIT runs entries, the reading side is still built for its tests
+#[cfg(any(feature = "llvm", test))]
+mod repl;
+mod terminal;
+mod test_runner;
+
+// what the CLI in main.rs calls, not part of the library API
+#[doc(hidden)]
+pub mod cli {
+    pub use crate::lsp::run_lsp;
+    pub use crate::package_manager::{create_pack, install, publish};
+    #[cfg(feature = "llvm")]
+    pub use crate::repl::run_repl;
+    pub use crate::terminal::{
+        clear_screen, interactive, json_messages, print_error, print_header, report, set_json_messages, set_quiet,
+        Progress, VERSION, WEBSITE,
+    };
+    pub use crate::test_runner::run_tests;
+}
+
+pub use crate::backend::{EmitKind, OptLevel};
+pub use crate::diagnostics::{Diagnostic, Diagnostics, Stage};
+pub use crate::parser::parser_error::{ErrorKind, ParseError, Severity};
+pub use crate::runtime::RUNTIME_SOURCE;
+
+use crate::ast::Statement;
+#[cfg(feature = "llvm")]
+use crate::compiler_neuron::{compile_and_run_jit, create_llvm_module, emit_to_memory, CodegenOptions};
+use crate::high_level_ir::HIRStatement;
+use crate::interpreter::{run_interpreter, InterpreterOptions};
+use crate::manifest::Manifest;
+use crate::module_loader::{load_program_with, SourceMap};
+use crate::optimizer::optimize_hir;
+use crate::semantic::ast_to_hir;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "llvm")]
+use target_lexicon::HOST;
+
+// where a program comes from
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub entry: PathBuf,
+    // `use util.calc;` is looked up here
+    pub src_dir: PathBuf,
+    // dependency name -> the src/ of that package
+    pub packages: HashMap<String, PathBuf>,
+    // text that takes the place of what is saved, unsaved editor buffers or code that never was a file
+    pub open: HashMap<PathBuf, String>,
+}
+
+impl Source {
+    // a program held in memory, shown as `main.ax` in diagnostics,
+    // it can still `use` modules from the current directory
+    pub fn code(code: &str) -> Source {
+        let entry = PathBuf::from("main.ax");
+        Source {
+            open: HashMap::from([(entry.clone(), code.to_string())]),
+            entry,
+            src_dir: PathBuf::from("."),
+            packages: HashMap::new(),
+        }
+    }
+
+    // the project in `dir` as `axon run` sees it, after checking project.asml, its dependencies and the entry file
+    pub fn project(dir: &Path) -> Result<(Source, Manifest), Diagnostics> {
+        let mut diagnostics = Diagnostics::new();
+        let manifest = match Manifest::load(dir) {
+            Ok(manifest) => manifest,
+            Err(errors) => {
+                diagnostics.extend(Stage::Setup, errors);
+                return Err(diagnostics);
+            }
+        };
+        let entry = dir.join(&manifest.entry);
+        diagnostics.extend(Stage::Setup, manifest.missing_dependencies(dir));
+        if !entry.exists() {
+            diagnostics.push(
+                Stage::Setup,
+                ParseError::new(
+                    ErrorKind::Syntax,
+                    format!("Entry file '{}' does not exist.", manifest.entry.display()),
+                    0,
+                    0,
+                    None,
+                    Some("Set _entry_ under __Project__ in project.asml".to_string()),
+                    Severity::Error,
+                ),
+            );
+        }
+        if diagnostics.has_errors() {
+            return Err(diagnostics);
+        }
+        let source = Source {
+            entry,
+            src_dir: dir.join("src"),
+            packages: manifest.package_roots(dir),
+            open: HashMap::new(),
+        };
+        Ok((source, manifest))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompileOptions {
+    // overflow, division, conversion and input checks that stop the program
+    pub checked: bool,
+    pub opt_level: OptLevel,
+    // DWARF line tables for gdb/lldb, only used by `emit`
+    pub debug_info: bool,
+    // a target triple, the host when None
+    pub target: Option<String>,
+    // what `emit` stops at, anything but Exe, linking is left to the caller
+    pub emit: EmitKind,
+    // `run` writes the module's IR here before running it, JIT only
+    pub emit_ir: Option<String>,
+    // what `run` executes the program with
+    pub backend: Backend,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        CompileOptions {
+            checked: true,
+            opt_level: OptLevel::default(),
+            debug_info: false,
+            target: None,
+            emit: EmitKind::Obj,
+            emit_ir: None,
+            backend: Backend::default(),
+        }
+    }
+}
+
+// `axon run --backend=..`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    // compiled by LLVM and run in its JIT
+    Jit,
+    // the HIR evaluated as it is, no LLVM involved
+    Interp,
+}
+
+// the JIT, unless the build has no LLVM to run it with
+impl Default for Backend {
+    fn default() -> Self {
+        if cfg!(feature = "llvm") { Backend::Jit } else { Backend::Interp }
+    }
+}
+
+impl Backend {
+    pub fn from_flag(value: &str) -> Option<Self> {
+        match value {
+            "jit" => Some(Backend::Jit),
+            "interp" => Some(Backend::Interp),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Jit => "jit",
+            Backend::Interp => "interp",
+        }
+    }
+}
+
+// a program that parsed, every import resolved into one flat list of statements
+pub struct Parsed {
+    pub ast: Vec<Statement>,
+    pub sources: SourceMap,
+    // warnings from the parser and the module loader
+    pub diagnostics: Diagnostics,
+}
+
+// a program that passed the semantic checks, its HIR already optimized
+pub struct Analyzed {
+    pub hir: Vec<HIRStatement>,
+    pub mutable_vars: HashSet<String>,
+    pub sources: SourceMap,
+    // warnings from the semantic pass, the ones from parsing stay with Parsed
+    pub diagnostics: Diagnostics,
+}
+
+// what `emit` produced
+#[derive(Debug, Clone)]
+pub struct Artifact {
+    pub emit: EmitKind,
+    pub target: String,
+    // the .ll text, bitcode, assembly or object file, byte for byte what `axon build --emit=..` writes
+    pub bytes: Vec<u8>,
+    // warnings from the stages before, filled in by `compile`
+    pub diagnostics: Diagnostics,
+}
+
+pub fn parse(source: &Source) -> Result<Parsed, Diagnostics> {
+    let (parse_result, sources) = load_program_with(&source.src_dir, &source.packages, &source.entry, &source.open);
+    let mut diagnostics = Diagnostics::new();
+    diagnostics.extend(Stage::Parsing, parse_result.errors);
+    if diagnostics.has_errors() {
+        return Err(diagnostics);
+    }
+    match parse_result.result {
+        Some(ast) => Ok(Parsed { ast, sources, diagnostics }),
+        None => {
+            diagnostics.push(
+                Stage::Parsing,
+                ParseError::new(
+                    ErrorKind::Syntax,
+                    "Invalid AST".to_string(),
+                    0,
+                    0,
+                    None,
+                    Some("Check syntax".to_string()),
+                    Severity::Error,
+                ),
+            );
+            Err(diagnostics)
+        }
+    }
+}
+
+pub fn analyze(parsed: Parsed) -> Result<Analyzed, Diagnostics> {
+    let Parsed { ast, sources, .. } = parsed;
+    let sem_result = ast_to_hir(ast, None);
+    let mut diagnostics = Diagnostics::new();
+    diagnostics.extend(
+        Stage::Semantic,
+        sem_result.errors.iter().map(|e| sources.localize(ParseError::from(e))),
+    );
+    if diagnostics.has_errors() {
+        return Err(diagnostics);
+    }
+    let hir = optimize_hir(sem_result.result, &sem_result.mutable_vars);
+    Ok(Analyzed { hir, mutable_vars: sem_result.mutable_vars, sources, diagnostics })
+}
+
+// codegen and `options.emit`, for the target in `options.target`
+#[cfg(feature = "llvm")]
+pub fn emit(analyzed: Analyzed, options: &CompileOptions) -> Result<Artifact, Diagnostics> {
+    let mut diagnostics = Diagnostics::new();
+    if options.emit == EmitKind::Exe {
+        diagnostics.push(
+            Stage::Emit,
+            ParseError::new(
+                ErrorKind::Codegen,
+                "An executable needs the runtime linked in, emit an object file and link it instead.".to_string(),
+                0,
+                0,
+                None,
+                Some("Use EmitKind::Obj, or `axon build` to get an executable".to_string()),
+                Severity::Error,
+            ),
+        );
+        return Err(diagnostics);
+    }
+    let Analyzed { hir, mutable_vars, sources, .. } = analyzed;
+    let codegen = CodegenOptions {
+        checked: options.checked,
+        sources: sources.clone(),
+        emit_ir: None,
+        opt_level: options.opt_level,
+        debug_info: options.debug_info,
+    };
+    let compiler = match create_llvm_module(hir, mutable_vars, codegen) {
+        Ok(compiler) => compiler,
+        Err(errors) => {
+            diagnostics.extend(
+                Stage::Codegen,
+                errors.iter().map(|e| sources.localize(ParseError::from_compiler_error(e))),
+            );
+            return Err(diagnostics);
+        }
+    };
+    let target = options.target.clone().unwrap_or_else(|| HOST.to_string());
+    let emitted = emit_to_memory(compiler.module, &target, options.opt_level, options.emit);
+    compiler.dispose();
+    match emitted {
+        Ok(bytes) => Ok(Artifact { emit: options.emit, target, bytes, diagnostics }),
+        Err(e) => {
+            diagnostics.push(Stage::Emit, ParseError::new(ErrorKind::Codegen, e, 0, 0, None, None, Severity::Error));
+            Err(diagnostics)
+        }
+    }
+}
+
+#[cfg(not(feature = "llvm"))]
+pub fn emit(_analyzed: Analyzed, _options: &CompileOptions) -> Result<Artifact, Diagnostics> {
+    Err(without_llvm(Stage::Emit))
+}
+
+// what needs LLVM reports in a build without the `llvm` feature
+#[cfg(not(feature = "llvm"))]
+fn without_llvm(stage: Stage) -> Diagnostics {
+    let mut diagnostics = Diagnostics::new();
+    diagnostics.push(
+        stage,
+        ParseError::new(
+            ErrorKind::Codegen,
+            "This build of AxonScript has no LLVM backend, programs can only run in the interpreter.".to_string(),
+            0,
+            0,
+            None,
+            Some("Use `--backend=interp`, or build AxonScript with its default `llvm` feature".to_string()),
+            Severity::Error,
+        ),
+    );
+    diagnostics
+}
+
+// runs the program with `options.backend`, its output goes to stdout,
+// a runtime error or a non-zero code from Start comes back as Diagnostics::exit_code, the process goes on
+pub fn run(analyzed: Analyzed, options: &CompileOptions) -> Result<(), Diagnostics> {
+    let Analyzed { hir, mutable_vars, sources, .. } = analyzed;
+    let (stage, result) = match options.backend {
+        #[cfg(feature = "llvm")]
+        Backend::Jit => {
+            let codegen = CodegenOptions {
+                checked: options.checked,
+                sources: sources.clone(),
+                emit_ir: options.emit_ir.clone(),
+                opt_level: options.opt_level,
+                debug_info: false,
+            };
+            (Stage::Execute, compile_and_run_jit(hir, mutable_vars, codegen))
+        }
+        #[cfg(not(feature = "llvm"))]
+        Backend::Jit => return Err(without_llvm(Stage::Execute)),
+        Backend::Interp => {
+            let interp = InterpreterOptions { checked: options.checked, sources: sources.clone() };
+            (Stage::Interpret, run_interpreter(hir, mutable_vars, interp))
+        }
+    };
+    match result {
+        Ok(0) => Ok(()),
+        Ok(code) => Err(Diagnostics::exited(stage, code)),
+        Err(errors) => {
+            let mut diagnostics = Diagnostics::new();
+            diagnostics.extend(
+                stage,
+                errors.iter().map(|e| sources.localize(ParseError::from_compiler_error(e))),
+            );
+            Err(diagnostics)
+        }
+    }
+}
+
+// every stage up to `options.emit`, the artifact carries the warnings and a failure carries the warnings before it
+pub fn compile(source: &Source, options: &CompileOptions) -> Result<Artifact, Diagnostics> {
+    let (analyzed, mut warnings) = front_end(source)?;
+    match emit(analyzed, options) {
+        Ok(mut artifact) => {
+            warnings.append(artifact.diagnostics);
+            artifact.diagnostics = warnings;
+            Ok(artifact)
+        }
+        Err(errors) => Err(after(warnings, errors)),
+    }
+}
+
+// parses, checks and runs a program, Ok holds the warnings
+pub fn run_jit(source: &Source, options: &CompileOptions) -> Result<Diagnostics, Diagnostics> {
+    let (analyzed, warnings) = front_end(source)?;
+    match run(analyzed, options) {
+        Ok(()) => Ok(warnings),
+        Err(errors) => Err(after(warnings, errors)),
+    }
+}
+
+// parse and analyze, keeping the warnings of both
+fn front_end(source: &Source) -> Result<(Analyzed, Diagnostics), Diagnostics> {
+    let parsed = parse(source)?;
+    let mut warnings = parsed.diagnostics.clone();
+    match analyze(parsed) {
+        Ok(mut analyzed) => {
+            warnings.append(std::mem::take(&mut analyzed.diagnostics));
+            Ok((analyzed, warnings))
+        }
+        Err(errors) => Err(after(warnings, errors)),
+    }
+}
+
+fn after(mut before: Diagnostics, errors: Diagnostics) -> Diagnostics {
+    before.append(errors);
+    before
+}
@@ -0,0 +1,494 @@
+// `axon lsp`, a language server over stdio,
+// every request runs the open file's project through the same loader, parser and semantic
+// analysis as `axon check`, with the editor's unsaved buffers in place of the files on disk.
+// diagnostics carry the ERR-* code and suggestion, hover/definition/symbols come from the
+// declarations semantic analysis records along the way
+
+use crate::ast::Span;
+use crate::high_level_ir::HIRType;
+use crate::lexer_tokenizer::{lex_with_span, Token};
+use crate::manifest::{Manifest, MANIFEST_FILE};
+use crate::module_loader::{load_program_with, SourceFile, SourceMap};
+use crate::parser::parser_error::{ParseError, Severity};
+use crate::semantic::{ast_to_hir_with_symbols, Symbol, SymbolKind};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+const KEYWORDS: &[&str] = &[
+    "set", "cast", "if", "else", "when", "while", "loop", "break", "return", "out", "math", "in", "do",
+    "use", "assert", "assert_eq", "and", "or", "not", "yes", "no",
+];
+const TYPES: &[&str] = &["i32", "i64", "f32", "f64", "str", "bool", "Vec", "Tensor"];
+
+// LSP enums
+const SEVERITY_ERROR: u8 = 1;
+const SEVERITY_WARNING: u8 = 2;
+const COMPLETION_FUNCTION: u8 = 3;
+const COMPLETION_VARIABLE: u8 = 6;
+const COMPLETION_KEYWORD: u8 = 14;
+const COMPLETION_TYPE: u8 = 25;
+const SYMBOL_FUNCTION: u8 = 12;
+const SYMBOL_VARIABLE: u8 = 13;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+pub fn run_lsp() -> i32 {
+    let mut input = io::stdin().lock();
+    let mut server = Server::default();
+    while let Some(message) = read_message(&mut input) {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        match (message.get("id"), method) {
+            (_, "exit") => return if server.shutdown { 0 } else { 1 },
+            // a response to something we never ask the client
+            (_, "") => {}
+            (Some(id), method) => {
+                let response = match server.request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err(message) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": METHOD_NOT_FOUND, "message": message },
+                    }),
+                };
+                send(&response);
+            }
+            (None, method) => server.notification(method, params),
+        }
+    }
+    // stdin closed without `exit`
+    1
+}
+
+fn read_message(input: &mut impl BufRead) -> Option<Value> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() && length.is_some() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0; length?];
+    input.read_exact(&mut body).ok()?;
+    // a body that is not JSON is skipped, the next message may be fine
+    Some(serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn send(message: &Value) {
+    let body = message.to_string();
+    let mut out = io::stdout().lock();
+    write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body).ok();
+    out.flush().ok();
+}
+
+fn notify(method: &str, params: Value) {
+    send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+}
+
+#[derive(Default)]
+struct Server {
+    // text of every open document, it wins over the file on disk
+    open: HashMap<PathBuf, String>,
+    shutdown: bool,
+}
+
+// one run of the front end over the project a document belongs to
+struct Analysis {
+    sources: SourceMap,
+    // localized, errors without a position belong to the entry file
+    errors: Vec<ParseError>,
+    symbols: Vec<Symbol>,
+    entry: PathBuf,
+}
+
+impl Server {
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, String> {
+        let path = params["textDocument"]["uri"].as_str().and_then(uri_to_path);
+        let result = match (method, path) {
+            ("initialize", _) => json!({
+                "capabilities": {
+                    // full text on every change
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": {},
+                    "documentSymbolProvider": true,
+                },
+                "serverInfo": { "name": "axon-lsp", "version": env!("CARGO_PKG_VERSION") },
+            }),
+            ("shutdown", _) => {
+                self.shutdown = true;
+                Value::Null
+            }
+            ("textDocument/hover", Some(path)) => self.hover(&path, &params["position"]),
+            ("textDocument/definition", Some(path)) => self.definition(&path, &params["position"]),
+            ("textDocument/completion", Some(path)) => self.completion(&path, &params["position"]),
+            ("textDocument/documentSymbol", Some(path)) => self.document_symbols(&path),
+            _ => return Err(format!("Unsupported request '{}'", method)),
+        };
+        Ok(result)
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) {
+        let Some(path) = params["textDocument"]["uri"].as_str().and_then(uri_to_path) else {
+            return;
+        };
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.open.insert(path.clone(), text.to_string());
+                self.publish(&path);
+            }
+            "textDocument/didChange" => {
+                if let Some(text) = params["contentChanges"].as_array().and_then(|c| c.last()) {
+                    let text = text["text"].as_str().unwrap_or_default();
+                    self.open.insert(path.clone(), text.to_string());
+                }
+                self.publish(&path);
+            }
+            "textDocument/didSave" => self.publish(&path),
+            "textDocument/didClose" => {
+                self.open.remove(&path);
+                notify(
+                    "textDocument/publishDiagnostics",
+                    json!({ "uri": path_to_uri(&path), "diagnostics": [] }),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn analyze(&self, path: &Path) -> Analysis {
+        let root = path.ancestors().skip(1).find(|dir| dir.join(MANIFEST_FILE).exists());
+        let manifest = root.and_then(|root| Some((root, Manifest::load(root).ok()?)));
+        let (src_dir, packages) = match &manifest {
+            Some((root, manifest)) => (root.join("src"), manifest.package_roots(root)),
+            None => (path.parent().unwrap_or(Path::new(".")).to_path_buf(), HashMap::new()),
+        };
+        if let Some((root, manifest)) = &manifest {
+            let analysis = self.load(&src_dir, &packages, &root.join(&manifest.entry));
+            if analysis.sources.global(path, 0).is_some() {
+                return analysis;
+            }
+        }
+        // a file outside a project, or a module nothing imports yet, is checked on its own
+        let mut analysis = self.load(&src_dir, &packages, path);
+        analysis.errors.retain(|e| e.code.as_deref() != Some("ERR-SEM-301"));
+        analysis
+    }
+
+    fn load(&self, src_dir: &Path, packages: &HashMap<String, PathBuf>, entry: &Path) -> Analysis {
+        let (parsed, sources) = load_program_with(src_dir, packages, entry, &self.open);
+        let mut errors = parsed.errors;
+        let mut symbols = Vec::new();
+        if let Some(ast) = parsed.result {
+            let (sem, declared) = ast_to_hir_with_symbols(ast, None);
+            symbols = declared;
+            // after a syntax error the semantic errors are mostly follow-ups, the symbols still help
+            if !errors.iter().any(|e| e.severity == Severity::Error) {
+                errors.extend(sem.errors.iter().map(|e| sources.localize(ParseError::from(e))));
+            }
+        }
+        Analysis { sources, errors, symbols, entry: entry.to_path_buf() }
+    }
+
+    // diagnostics for every file the analysis went through, so fixing a module clears its users
+    fn publish(&self, path: &Path) {
+        let analysis = self.analyze(path);
+        let mut files: Vec<PathBuf> = analysis.sources.files().iter().map(|f| f.path.clone()).collect();
+        if !files.iter().any(|f| f == path) {
+            files.push(path.to_path_buf());
+        }
+        for file in files {
+            let text = analysis.text(&file).or_else(|| self.open.get(&file).cloned()).unwrap_or_default();
+            let diagnostics: Vec<Value> = analysis
+                .errors
+                .iter()
+                .filter(|e| e.file.as_ref().map_or(analysis.entry == file, |f| Path::new(f) == file))
+                .map(|e| diagnostic(e, &text))
+                .collect();
+            notify(
+                "textDocument/publishDiagnostics",
+                json!({ "uri": path_to_uri(&file), "diagnostics": diagnostics }),
+            );
+        }
+    }
+
+    // the analysis, the text of the document and the project-wide offset of `position`
+    fn at(&self, path: &Path, position: &Value) -> Option<(Analysis, String, usize, usize)> {
+        let text = match self.open.get(path) {
+            Some(text) => text.clone(),
+            None => std::fs::read_to_string(path).ok()?,
+        };
+        let local = offset(&text, position);
+        let analysis = self.analyze(path);
+        let global = analysis.sources.global(path, local)?;
+        Some((analysis, text, local, global))
+    }
+
+    fn hover(&self, path: &Path, position: &Value) -> Value {
+        let Some((analysis, text, local, global)) = self.at(path, position) else {
+            return Value::Null;
+        };
+        let Some((name, span)) = identifier_at(&text, local) else {
+            return Value::Null;
+        };
+        match resolve(&analysis.symbols, &name, global) {
+            Some(symbol) => json!({
+                "contents": { "kind": "markdown", "value": format!("```axon\n{}\n```", signature(symbol)) },
+                "range": range(&text, span.start, span.end),
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn definition(&self, path: &Path, position: &Value) -> Value {
+        let Some((analysis, text, local, global)) = self.at(path, position) else {
+            return Value::Null;
+        };
+        let declaration = identifier_at(&text, local)
+            .and_then(|(name, _)| resolve(&analysis.symbols, &name, global))
+            .and_then(|symbol| name_location(&analysis.sources, symbol));
+        match declaration {
+            Some((file, span)) => json!({
+                "uri": path_to_uri(&file.path),
+                "range": range(&file.code, span.start, span.end),
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn completion(&self, path: &Path, position: &Value) -> Value {
+        let mut items: Vec<Value> = KEYWORDS
+            .iter()
+            .map(|k| json!({ "label": k, "kind": COMPLETION_KEYWORD }))
+            .chain(TYPES.iter().map(|t| json!({ "label": t, "kind": COMPLETION_TYPE })))
+            .collect();
+        let Some((analysis, _, _, global)) = self.at(path, position) else {
+            return json!(items);
+        };
+        let mut seen = HashSet::new();
+        for symbol in &analysis.symbols {
+            let visible = symbol.scope.as_ref().is_none_or(|scope| scope.contains(&global));
+            if !visible {
+                continue;
+            }
+            // names from other modules are written through their alias, `calc.add`
+            let same_file = analysis.sources.local(symbol.span.start).is_some_and(|(f, _)| f.path == path);
+            let label = match symbol.name.rsplitn(3, '.').collect::<Vec<_>>()[..] {
+                [item, module, ..] if !same_file => format!("{}.{}", module, item),
+                [item, ..] => item.to_string(),
+                [] => continue,
+            };
+            if !seen.insert(label.clone()) {
+                continue;
+            }
+            let kind = match symbol.kind {
+                SymbolKind::Function { .. } => COMPLETION_FUNCTION,
+                _ => COMPLETION_VARIABLE,
+            };
+            items.push(json!({ "label": label, "kind": kind, "detail": signature(symbol) }));
+        }
+        json!(items)
+    }
+
+    // functions with their parameters and locals underneath, then the globals
+    fn document_symbols(&self, path: &Path) -> Value {
+        let analysis = self.analyze(path);
+        let in_file: Vec<&Symbol> = analysis
+            .symbols
+            .iter()
+            .filter(|s| analysis.sources.local(s.span.start).is_some_and(|(f, _)| f.path == path))
+            .collect();
+        let mut top_level = HashSet::new();
+        let mut symbols = Vec::new();
+        for symbol in in_file.iter().filter(|s| s.scope.is_none()) {
+            if !top_level.insert(&symbol.name) {
+                continue;
+            }
+            let mut locals = HashSet::new();
+            let children: Vec<Value> = in_file
+                .iter()
+                .filter(|s| s.scope.as_ref() == Some(&symbol.span) && locals.insert(&s.name))
+                .filter_map(|s| document_symbol(&analysis.sources, s, Vec::new()))
+                .collect();
+            symbols.extend(document_symbol(&analysis.sources, symbol, children));
+        }
+        json!(symbols)
+    }
+}
+
+impl Analysis {
+    fn text(&self, path: &Path) -> Option<String> {
+        self.sources.files().iter().find(|f| f.path == path).map(|f| f.code.clone())
+    }
+}
+
+fn diagnostic(error: &ParseError, text: &str) -> Value {
+    let mut message = error.message.clone();
+    if let Some(suggestion) = &error.suggestion {
+        message.push_str("\nSuggestion: ");
+        message.push_str(suggestion);
+    }
+    let severity = match error.severity {
+        Severity::Error => SEVERITY_ERROR,
+        Severity::Warning => SEVERITY_WARNING,
+    };
+    let mut diagnostic = Map::new();
+    diagnostic.insert("range".to_string(), range(text, error.start, error.end));
+    diagnostic.insert("severity".to_string(), json!(severity));
+    if let Some(code) = &error.code {
+        diagnostic.insert("code".to_string(), json!(code));
+    }
+    diagnostic.insert("source".to_string(), json!("axon"));
+    diagnostic.insert("message".to_string(), json!(message));
+    Value::Object(diagnostic)
+}
+
+fn document_symbol(sources: &SourceMap, symbol: &Symbol, children: Vec<Value>) -> Option<Value> {
+    let (file, start) = sources.local(symbol.span.start)?;
+    let (_, name) = name_location(sources, symbol)?;
+    let kind = match symbol.kind {
+        SymbolKind::Function { .. } => SYMBOL_FUNCTION,
+        _ => SYMBOL_VARIABLE,
+    };
+    Some(json!({
+        "name": symbol.name.rsplit('.').next().unwrap_or(&symbol.name),
+        "detail": signature(symbol),
+        "kind": kind,
+        "range": range(&file.code, start, start + symbol.span.len()),
+        "selectionRange": range(&file.code, name.start, name.end),
+        "children": children,
+    }))
+}
+
+// the identifier token under (or right after) the cursor
+fn identifier_at(text: &str, offset: usize) -> Option<(String, Span)> {
+    lex_with_span(text).into_iter().find_map(|t| match t.token {
+        Token::Identifier(name) if t.span.start <= offset && offset <= t.span.end => Some((name, t.span)),
+        _ => None,
+    })
+}
+
+// the declaration `name` refers to at project-wide offset `at`,
+// the latest local declared before it in the same function, else a function or global,
+// else one from another module (`calc.add` is looked up as `add`)
+fn resolve<'a>(symbols: &'a [Symbol], name: &str, at: usize) -> Option<&'a Symbol> {
+    symbols
+        .iter()
+        .rev()
+        .filter(|s| s.name == name && s.span.start <= at)
+        .find(|s| s.scope.as_ref().is_some_and(|scope| scope.contains(&at)))
+        .or_else(|| symbols.iter().find(|s| s.scope.is_none() && s.name == name))
+        .or_else(|| symbols.iter().find(|s| s.scope.is_none() && s.name.rsplit('.').next() == Some(name)))
+}
+
+// where the name itself sits in the declaring statement, as a span inside its file
+fn name_location<'a>(sources: &'a SourceMap, symbol: &Symbol) -> Option<(&'a SourceFile, Span)> {
+    let (file, start) = sources.local(symbol.span.start)?;
+    let end = start + symbol.span.len();
+    let short = symbol.name.rsplit('.').next()?;
+    let name = lex_with_span(&file.code)
+        .into_iter()
+        .find(|t| t.span.start >= start && t.span.end <= end && t.token == Token::Identifier(short.to_string()))
+        .map_or(start..start, |t| t.span);
+    Some((file, name))
+}
+
+fn signature(symbol: &Symbol) -> String {
+    match &symbol.kind {
+        SymbolKind::Function { params } => {
+            let params: Vec<String> = params.iter().map(|(name, ty)| format!("{}: {}", name, ty)).collect();
+            let returns = match symbol.ty {
+                HIRType::Void => String::new(),
+                ref ty => format!(" -> {}", ty),
+            };
+            format!("cast {}({}){}", symbol.name, params.join(", "), returns)
+        }
+        SymbolKind::Parameter => format!("{}: {}", symbol.name, symbol.ty),
+        SymbolKind::Variable => format!("set: {}({})", symbol.name, symbol.ty),
+    }
+}
+
+// byte offsets -> an LSP range, lines split on '\n' and columns in UTF-16 code units
+fn range(text: &str, start: usize, end: usize) -> Value {
+    json!({ "start": position(text, start), "end": position(text, end.max(start)) })
+}
+
+fn position(text: &str, offset: usize) -> Value {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    json!({
+        "line": before.matches('\n').count(),
+        "character": before[line_start..].encode_utf16().count(),
+    })
+}
+
+fn offset(text: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or(0) as usize;
+    let character = position["character"].as_u64().unwrap_or(0) as usize;
+    let line_start = match line {
+        0 => 0,
+        _ => match text.match_indices('\n').nth(line - 1) {
+            Some((i, _)) => i + 1,
+            None => return text.len(),
+        },
+    };
+    let mut units = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if units >= character || c == '\n' {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let hex = tail.get(..2).and_then(|h| std::str::from_utf8(h).ok());
+        match (b, hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(decoded)) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+    // file:///C:/project -> C:/project
+    match path.as_bytes() {
+        [b'/', _, b':', ..] => Some(PathBuf::from(&path[1..])),
+        _ => Some(PathBuf::from(path)),
+    }
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let path = path.display().to_string().replace('\\', "/");
+    let mut uri = String::from(if path.starts_with('/') { "file://" } else { "file:///" });
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' | b':' => uri.push(b as char),
+            _ => uri.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    uri
+}
@@ -1,63 +1,46 @@
-// AxonScript CLI
-
-
-mod ast;
-mod compiler_neuron;
-mod high_level_ir;
-mod lexer_tokenizer;
-mod parser;
-mod semantic;
-
-use crate::compiler_neuron::{compile_and_run_jit, create_llvm_module, emit_object_file, CompilerError};
-use crate::high_level_ir::HIRStatement;
-use crate::lexer_tokenizer::lex_with_span;
-use crate::parser::parser_error::{ErrorKind, ParseError, Severity};
-use crate::parser::parser_kernel::Parser as AxonParser;
-use crate::semantic::{ast_to_hir, semantic_error::SemanticError};
+// AxonScript CLI,
+// a thin layer over the axonscript library that adds the header, the progress bar and the build directory
+
+
+use axonscript::high_level_ir;
+use axonscript::manifest::DEFAULT_ENTRY;
+use axonscript::cli::{
+    self, clear_screen, interactive, json_messages, print_error, print_header, report, set_json_messages, set_quiet,
+    Progress, VERSION, WEBSITE,
+};
+use axonscript::{
+    analyze, emit as emit_artifact, error_codes, formatter, parse, run, Backend, CompileOptions, EmitKind, ErrorKind,
+    OptLevel, ParseError, Severity, Source, RUNTIME_SOURCE,
+};
 use console::style;
-use std::collections::HashSet;
 use std::fs::{self, File};
-use std::io::{stdout, Write};
+use std::io::{stdout, IsTerminal, Write};
 use std::path::Path;
-use std::process::Command;
-use std::thread::sleep;
-use std::time::Duration;
+use std::process::{exit, Command};
 use target_lexicon::HOST;
 
-const VERSION: &str = "AxonScript Build #0001 Pre-Alpha Demo";
-const WEBSITE: &str = "https://axonscript.org";
-
-impl From<&SemanticError> for ParseError {
-    fn from(e: &SemanticError) -> Self {
-        ParseError {
-            kind: ErrorKind::Semantic,
-            message: e.message.clone(),
-            start: e.start,
-            end: e.end,
-            src: e.src.clone(),
-            suggestion: None,
-            severity: Severity::Error,
-        }
-    }
-}
-
-impl ParseError {
-    pub fn from_compiler_error(e: &CompilerError, src: &str) -> Self {
-        ParseError::new(
-            ErrorKind::Codegen,
-            e.0.clone(),
-            0,
-            0,
-            Some(src.to_string()),
-            None,
-            Severity::Error,
-        )
-    }
-}
-
+// exit status of the commands: 0 when it worked, 1 when the project or the build failed,
+// a program that fails under `axon run` passes on its own code (1 for a runtime error)
+const EXIT_FAILURE: i32 = 1;
+// ... and 2 when the command line itself was wrong
+const EXIT_USAGE: i32 = 2;
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    let mut args: Vec<String> = std::env::args().collect();
+    if let Some(format) = args.iter().find_map(|a| a.strip_prefix("--message-format=")) {
+        match format {
+            "human" => {}
+            "json" => set_json_messages(true),
+            _ => {
+                println!("{}", style(format!("Error: Unknown message format '{}' (expected human or json).", format)).red());
+                exit(EXIT_USAGE);
+            }
+        }
+    }
+    // a pipe or a CI log gets plain output without the animation
+    set_quiet(args.iter().any(|a| a == "--quiet" || a == "--ci") || !stdout().is_terminal());
+    // the flags for every command are taken out before the command sees its arguments
+    args.retain(|a| a != "--quiet" && a != "--ci" && !a.starts_with("--message-format="));
     if args.len() == 1 {
         print_help();
         return;
@@ -67,7 +50,7 @@ fn main() {
             let name = args[3..].join(" ");
             if name.is_empty() {
                 println!("Project name cannot be empty!");
-                return;
+                exit(EXIT_USAGE);
             }
             if Path::new(&name).exists() {
                 print_error(
@@ -82,7 +65,7 @@ fn main() {
                         Severity::Error,
                     )],
                 );
-                return;
+                exit(EXIT_FAILURE);
             }
             let src_path = format!("{}/src", name);
             let project_path = format!("{}/project.asml", name);
@@ -92,6 +75,9 @@ fn main() {
             writeln!(project_file, "__Project__").unwrap();
             writeln!(project_file, "_name_ = \"{}\"", name).unwrap();
             writeln!(project_file, "_version_ = \"0.1.0\"").unwrap();
+            writeln!(project_file, "_entry_ = \"{}\"", DEFAULT_ENTRY).unwrap();
+            writeln!(project_file, "\n__Build__\n_opt_level_ = 2").unwrap();
+            writeln!(project_file, "\n__Dependencies__").unwrap();
             let mut main_ax = File::create(&ax_path).unwrap();
             writeln!(main_ax, "cast Start() >>\nout(\"Hello World!\");\n<<").unwrap();
             println!(
@@ -100,7 +86,12 @@ fn main() {
                 style(&name).yellow().bold()
             );
         }
-        "create" if args.len() >= 4 && (args[2] == "ai" || args[2] == "pack") => {
+        "create" if args.len() >= 4 && args[2] == "pack" => {
+            exit(cli::create_pack(&args[3]))
+        }
+        "install" => exit(cli::install(&args[2..])),
+        "publish" => exit(cli::publish(&args[2..])),
+        "create" if args.len() >= 4 && args[2] == "ai" => {
             println!("{} Coming soon!", style("🚀").yellow().bold());
         }
         "build" => {
@@ -110,7 +101,7 @@ fn main() {
                     output_filename = Some(filename.clone());
                 } else {
                     println!("{}", style("Error: The --output flag requires a filename.").red());
-                    return;
+                    exit(EXIT_USAGE);
                 }
             }
 
@@ -124,39 +115,115 @@ fn main() {
                         style("Error: The --target flag requires a value (e.g., windows, linux).")
                             .red()
                     );
-                    return;
+                    exit(EXIT_USAGE);
                 }
             }
-            run_pipeline("build", output_filename, target);
+            let Some(emit) = parse_emit(&args) else { exit(EXIT_USAGE) };
+            let Some(opt_level) = parse_opt_level(&args) else { exit(EXIT_USAGE) };
+            // runtime checks are opt-in for builds, `--release` wins over `--checked`
+            let checked = args.iter().any(|a| a == "--checked") && !args.iter().any(|a| a == "--release");
+            let debug_info = args.iter().any(|a| a == "-g");
+            let dump_hir = args.iter().any(|a| a == "--dump-hir");
+            exit(run_pipeline(
+                "build",
+                PipelineFlags {
+                    output_filename,
+                    target,
+                    checked,
+                    emit: emit.unwrap_or(EmitKind::Exe),
+                    opt_level,
+                    debug_info,
+                    backend: Backend::Jit,
+                    dump_hir,
+                },
+            ));
         }
-        "run" | "check" => run_pipeline(&args[1], None, None),
+        "run" | "check" => {
+            let Some(emit) = parse_emit(&args) else { exit(EXIT_USAGE) };
+            // a run only has the IR to show, everything after it happens in the JIT
+            if emit.is_some_and(|emit| emit != EmitKind::LlvmIr) {
+                println!("{}", style(format!("Error: axon {} only supports --emit=llvm-ir.", args[1])).red());
+                exit(EXIT_USAGE);
+            }
+            let Some(opt_level) = parse_opt_level(&args) else { exit(EXIT_USAGE) };
+            let Some(backend) = parse_backend(&args) else { exit(EXIT_USAGE) };
+            // the interpreter never builds an LLVM module
+            if backend == Backend::Interp && emit.is_some() {
+                println!("{}", style("Error: --emit=llvm-ir needs --backend=jit.").red());
+                exit(EXIT_USAGE);
+            }
+            let checked = !args.iter().any(|a| a == "--release");
+            let dump_hir = args.iter().any(|a| a == "--dump-hir");
+            exit(run_pipeline(
+                &args[1],
+                PipelineFlags {
+                    output_filename: None,
+                    target: None,
+                    checked,
+                    emit: emit.unwrap_or(EmitKind::Exe),
+                    opt_level,
+                    debug_info: false,
+                    backend,
+                    dump_hir,
+                },
+            ));
+        }
+        "test" => exit(cli::run_tests(&args[2..])),
+        #[cfg(feature = "llvm")]
+        "repl" => exit(cli::run_repl()),
+        // every entry of the REPL is compiled for the JIT
+        #[cfg(not(feature = "llvm"))]
+        "repl" => {
+            print_error(
+                "Invalid",
+                &[ParseError::new(
+                    ErrorKind::Syntax,
+                    "axon repl needs the LLVM backend, this build of AxonScript has none.".to_string(),
+                    0,
+                    0,
+                    None,
+                    Some("Use axon run --backend=interp, or build AxonScript with its default `llvm` feature".to_string()),
+                    Severity::Error,
+                )],
+            );
+            exit(EXIT_FAILURE);
+        }
+        "lsp" => exit(cli::run_lsp()),
+        "fmt" => exit(formatter::run_fmt(&args[2..])),
+        "explain" => exit(error_codes::run_explain(&args[2..])),
         "--help" | "-h" => print_help(),
         "--version" | "-v" => println!("{}\nDocs: {}\n", VERSION, WEBSITE),
-        _ => print_error(
-            "Invalid",
-            &[ParseError::new(
-                ErrorKind::Syntax,
-                "Unknown command. Use: axon --help".to_string(),
-                0,
-                0,
-                None,
-                None,
-                Severity::Error,
-            )],
-        ),
+        _ => {
+            print_error(
+                "Invalid",
+                &[ParseError::new(
+                    ErrorKind::Syntax,
+                    "Unknown command. Use: axon --help".to_string(),
+                    0,
+                    0,
+                    None,
+                    None,
+                    Severity::Error,
+                )],
+            );
+            exit(EXIT_USAGE);
+        }
     }
 }
 
 fn print_help() {
     println!(
-        "{}\n\nUsage:\n  axon create project <name>      Create new AxonScript project\n  axon create ai <name>           Create new AI [coming soon]\n  axon create pack <name>         Create new package [coming soon]\n  axon install                    Install package [coming soon]\n  axon run                        Run project\n  axon build [--output <f>] [--target <os>] Build project\n  axon check                      Check syntax\n  axon test                       Run tests\n\nOptions:\n  --output <file>                 Specify output file name for build\n  --target <os>                   Specify target OS for build (windows, linux)\n  --help, -h                      Show help\n  --version, -v                   Show version\n\nDocs: {}\nCommunity: {}",
+        "{}\n\nUsage:\n  axon create project <name>      Create new AxonScript project\n  axon create ai <name>           Create new AI [coming soon]\n  axon create pack <name>         Create new package\n  axon install [name[@version]]   Install dependencies (or add one) from the registry\n  axon publish [--force]          Publish this package to the local registry\n  axon run [-O<n>] [--release] [--emit=llvm-ir] [--backend=<b>] Run project (with runtime checks unless --release)\n  axon build [-O<n>] [-g] [--output <f>] [--target <os>] [--checked] [--emit=<kind>] Build project\n  axon check [--dump-hir]         Check syntax\n  axon test [filter]              Run test_* functions in src/\n  axon fmt [--check] [files]      Format .ax files (all of src/ by default)\n  axon repl                       Start an interactive session\n  axon lsp                        Start the language server on stdin/stdout\n  axon explain [code]             Explain an error code, e.g. axon explain ERR-SEM-550\n\nOptions:\n  --output <file>                 Specify output file name for build\n  --target <os>                   Specify target OS for build (windows, linux)\n  --checked                       Trap on integer overflow, division by zero and out-of-range conversions\n  --release                       Leave the runtime checks out\n  -O0, -O1, -O2, -O3, -Os         Optimization level, overrides _opt_level_ in project.asml\n  -g                              Include DWARF debug info for gdb/lldb in the build\n  --emit=<kind>                   Stop the build at llvm-ir (.ll), bc, asm, obj or exe (default)\n  --backend=<jit|interp>          Run through the LLVM JIT (default) or the HIR interpreter\n  --dump-hir                      Print the HIR after the optimization passes (run, check, build)\n  --message-format=json           Print errors and warnings as JSON lines (code, span, line/col, ...)
+  --quiet, --ci                   No screen clearing, progress bar or pauses (the default when output is not a terminal)
+  --help, -h                      Show help\n  --version, -v                   Show version\n\nExit status: 0 on success, 1 when the project or build fails, the program's own code when it fails, 2 for a bad command line\n\nDocs: {}\nCommunity: {}",
         style("AxonScript CLI").cyan().bold(),
         format!("{}/docs", WEBSITE),
         format!("{}/community", WEBSITE)
     );
 }
 
-fn link_object_file(obj_path: &str, exe_path: &str, target_triple: &str) -> Result<(), String> {
+// the runtime library is shipped as C source and compiled together with the object file
+fn link_object_file(obj_path: &str, runtime_path: &str, exe_path: &str, target_triple: &str) -> Result<(), String> {
     let linker_cmd = if target_triple.contains("windows-msvc") {
         "link.exe"
     } else if target_triple.contains("windows-gnu") {
@@ -166,7 +233,21 @@ fn link_object_file(obj_path: &str, exe_path: &str, target_triple: &str) -> Resu
     };
 
     let mut command = Command::new(linker_cmd);
-    command.args([obj_path, "-o", exe_path]);
+    if target_triple.contains("windows-msvc") {
+        let runtime_obj = Path::new(runtime_path).with_extension("obj");
+        let status = Command::new("cl.exe")
+            .args(["/nologo", "/c", "/O2", runtime_path])
+            .arg(format!("/Fo{}", runtime_obj.display()))
+            .status()
+            .map_err(|e| format!("Failed to compile the runtime library: {}. Is cl.exe in your PATH?", e))?;
+        if !status.success() {
+            return Err(format!("Runtime library compilation exited with status: {}", status));
+        }
+        command.args([obj_path, runtime_obj.to_str().unwrap()]);
+        command.arg(format!("/OUT:{}", exe_path));
+    } else {
+        command.args([obj_path, runtime_path, "-O2", "-o", exe_path, "-lm"]);
+    }
 
     if target_triple.contains("windows-gnu") {
         command.arg("-static");
@@ -183,359 +264,281 @@ fn link_object_file(obj_path: &str, exe_path: &str, target_triple: &str) -> Resu
 
 
 
-fn run_pipeline(cmd: &str, output_filename: Option<String>, target: Option<String>) {
+// `--emit=<kind>`, None when the flag is missing and an outer None when it has a bad value
+fn parse_emit(args: &[String]) -> Option<Option<EmitKind>> {
+    let Some(value) = args.iter().find_map(|a| a.strip_prefix("--emit=")) else {
+        return Some(None);
+    };
+    match EmitKind::from_flag(value) {
+        Some(emit) => Some(Some(emit)),
+        None => {
+            println!(
+                "{}",
+                style(format!("Error: Unknown --emit value '{}' (expected llvm-ir, bc, asm, obj or exe).", value)).red()
+            );
+            None
+        }
+    }
+}
+
+// `-O0`..`-O3` or `-Os`, the same shape as parse_emit
+fn parse_opt_level(args: &[String]) -> Option<Option<OptLevel>> {
+    let Some(flag) = args.iter().find(|a| a.starts_with("-O")) else {
+        return Some(None);
+    };
+    match OptLevel::from_flag(flag) {
+        Some(level) => Some(Some(level)),
+        None => {
+            println!("{}", style(format!("Error: Unknown optimization level '{}' (expected -O0, -O1, -O2, -O3 or -Os).", flag)).red());
+            None
+        }
+    }
+}
+
+// `--backend=jit` or `--backend=interp`, the same shape as parse_emit but with the default filled in
+fn parse_backend(args: &[String]) -> Option<Backend> {
+    let Some(value) = args.iter().find_map(|a| a.strip_prefix("--backend=")) else {
+        return Some(Backend::default());
+    };
+    let backend = Backend::from_flag(value);
+    if backend.is_none() {
+        println!("{}", style(format!("Error: Unknown --backend value '{}' (expected jit or interp).", value)).red());
+    }
+    backend
+}
+
+// what the command line asked of `run_pipeline`
+struct PipelineFlags {
+    output_filename: Option<String>,
+    target: Option<String>,
+    checked: bool,
+    emit: EmitKind,
+    // overrides _opt_level_ from project.asml
+    opt_level: Option<OptLevel>,
+    debug_info: bool,
+    // what `run` executes the program with
+    backend: Backend,
+    // print the HIR the backends get, after the optimizations
+    dump_hir: bool,
+}
+
+
+// returns the exit status of the command
+fn run_pipeline(cmd: &str, flags: PipelineFlags) -> i32 {
+    let PipelineFlags { output_filename, target, checked, emit, opt_level, debug_info, backend, dump_hir } = flags;
     clear_screen();
     print_header();
 
-    let project_dir = Path::new(".");
-    let asml_path = project_dir.join("project.asml");
-    let src_file = project_dir.join("src/init.ax");
-    if !asml_path.exists() || !src_file.exists() {
-        print_error(
-            "Setup",
-            &[ParseError::new(
-                ErrorKind::Syntax,
-                format!("No project found in '{}'.", project_dir.display()),
-                0,
-                0,
-                None,
-                None,
-                Severity::Error,
-            )],
-        );
-        return;
-    }
+    let (source, manifest) = match Source::project(Path::new(".")) {
+        Ok(project) => project,
+        Err(diagnostics) => {
+            report(&diagnostics);
+            return EXIT_FAILURE;
+        }
+    };
+    let opt_level = opt_level.unwrap_or(manifest.opt_level);
 
     let pipeline_run: &[&str] = &["Lexer", "Parser", "Semantic", "IR Codegen"];
     let pipeline_build: &[&str] = &["Lexer", "Parser", "Semantic", "IR Codegen", "Obj Gen", "Linking"];
-    let pipeline = if cmd == "build" { pipeline_build } else { pipeline_run };
-
-    print_progress(0, pipeline);
-    let mut stage = 0;
+    let pipeline_emit: &[&str] = &["Lexer", "Parser", "Semantic", "IR Codegen", "Emit"];
+    let pipeline_interp: &[&str] = &["Lexer", "Parser", "Semantic", "Interpreter"];
+    let pipeline = match cmd {
+        "build" if emit != EmitKind::Exe => pipeline_emit,
+        "build" => pipeline_build,
+        "run" if backend == Backend::Interp => pipeline_interp,
+        _ => pipeline_run,
+    };
 
-    let code = match fs::read_to_string(&src_file) {
-        Ok(c) => c,
-        Err(_) => {
-            print_error(
-                "Lexing",
-                &[ParseError::new(
-                    ErrorKind::Syntax,
-                    "Source not found".to_string(),
-                    0,
-                    0,
-                    None,
-                    None,
-                    Severity::Error,
-                )],
-            );
-            return;
-        }
+    let progress = Progress::new(pipeline);
+    // a failed stage leaves the bar where it stopped, the errors go below it
+    let fail = |errors: &dyn Fn()| {
+        progress.stop();
+        errors();
+        EXIT_FAILURE
     };
 
-    sleep(Duration::from_millis(100));
-    let tokens = lex_with_span(&code);
-    stage += 1;
-    print_progress(stage, pipeline);
+    // every file is lexed as the module loader reaches it
+    progress.pause();
+    progress.advance();
 
-    sleep(Duration::from_millis(100));
-    let mut parser = AxonParser {
-        tokens: &tokens,
-        pos: 0,
-        src: Some(code.clone()),
+    progress.pause();
+    let parsed = match parse(&source) {
+        Ok(parsed) => parsed,
+        Err(diagnostics) => return fail(&|| report(&diagnostics)),
     };
-    let parse_result = parser.parse_program();
-    let (parse_warnings, parse_errors): (Vec<_>, Vec<_>) = parse_result
-        .errors
-        .into_iter()
-        .partition(|e| matches!(e.severity, Severity::Warning));
-
-    if !parse_warnings.is_empty() {
-        print_warning("Parsing", &parse_warnings);
-    }
-    if !parse_errors.is_empty() {
-        print_error("Parsing", &parse_errors);
-        return;
-    }
-    let ast = match parse_result.result {
-        Some(ast) => ast,
-        None => {
-            print_error(
-                "Parsing",
-                &[ParseError::new(
-                    ErrorKind::Syntax,
-                    "Invalid AST".to_string(),
-                    0,
-                    0,
-                    Some(code.clone()),
-                    Some("Check syntax".to_string()),
-                    Severity::Error,
-                )],
-            );
-            return;
-        }
+    progress.suspend(|| report(&parsed.diagnostics));
+    progress.advance();
+
+    progress.pause();
+    let analyzed = match analyze(parsed) {
+        Ok(analyzed) => analyzed,
+        Err(diagnostics) => return fail(&|| report(&diagnostics)),
     };
-    stage += 1;
-    print_progress(stage, pipeline);
-
-    sleep(Duration::from_millis(100));
-    let sem_result = ast_to_hir(ast, Some(code.clone()));
-    let sem_parse_errs: Vec<ParseError> =
-        sem_result.errors.iter().map(ParseError::from).collect();
-    let (sem_warnings, sem_errors): (Vec<_>, Vec<_>) =
-        sem_parse_errs.into_iter().partition(|e| matches!(e.severity, Severity::Warning));
-
-    if !sem_warnings.is_empty() {
-        print_warning("Semantic", &sem_warnings);
-    }
-    if !sem_errors.is_empty() {
-        print_error("Semantic", &sem_errors);
-        return;
+    progress.suspend(|| report(&analyzed.diagnostics));
+    if dump_hir {
+        progress.suspend(|| print!("{}", high_level_ir::dump(&analyzed.hir)));
     }
-    let hir: Vec<HIRStatement> = sem_result.result;
-    let mut_vars: HashSet<String> = sem_result.mutable_vars.clone();
-    stage += 1;
-    print_progress(stage, pipeline);
+    progress.advance();
 
     if cmd == "check" {
-        println!();
-        println!("{} Syntax check passed successfully!", style("✔").green().bold());
-        println!("💻 Community: {}/community", WEBSITE);
-        return;
+        progress.stop();
+        if !json_messages() {
+            println!();
+            println!("{} Syntax check passed successfully!", style("✔").green().bold());
+        }
+        if interactive() {
+            println!("💻 Community: {}/community", WEBSITE);
+        }
+        return 0;
     }
     
-    sleep(Duration::from_millis(100));
+    progress.pause();
 
     if cmd == "build" {
-        let compiler = match create_llvm_module(hir, mut_vars) {
-            Ok(c) => c,
-            Err(errors) => {
-                print_error("IR Codegen", &errors.iter().map(|e| ParseError::from_compiler_error(e, &code)).collect::<Vec<_>>());
-                return;
-            }
+        // command line flags win over the __Build__ section
+        let target_triple = target.or(manifest.target.clone()).unwrap_or_else(|| HOST.to_string());
+        let options = CompileOptions {
+            checked,
+            opt_level,
+            debug_info,
+            target: Some(target_triple.clone()),
+            // an executable is the object file linked with the runtime below
+            emit: if emit == EmitKind::Exe { EmitKind::Obj } else { emit },
+            emit_ir: None,
+            backend,
+        };
+        let artifact = match emit_artifact(analyzed, &options) {
+            Ok(artifact) => artifact,
+            Err(diagnostics) => return fail(&|| report(&diagnostics)),
         };
-        stage += 1;
-        print_progress(stage, pipeline);
+        progress.advance();
         
-        sleep(Duration::from_millis(100));
+        progress.pause();
 
         let build_dir = Path::new("release");
         if !build_dir.exists() {
             fs::create_dir_all(build_dir).unwrap();
         }
-        let project_name = get_project_name().unwrap_or_else(|| "output".to_string());
+        let project_name = manifest.name.clone();
 
-        let target_triple = target.unwrap_or_else(|| HOST.to_string());
-        let obj_ext = if target_triple.contains("windows") { "obj" } else { "o" };
-        let obj_path = build_dir.join(format!("{}.{}", project_name, obj_ext));
+        // anything short of an executable is written straight into release/ and the build stops there
+        if emit != EmitKind::Exe {
+            let emit_path = build_dir.join(
+                output_filename.unwrap_or_else(|| format!("{}.{}", project_name, emit.extension(&target_triple))),
+            );
+            if let Err(e) = fs::write(&emit_path, &artifact.bytes) {
+                let message = format!("Failed to write {} file '{}': {}", emit.name(), emit_path.display(), e);
+                let error = ParseError::new(ErrorKind::Codegen, message, 0, 0, None, None, Severity::Error);
+                return fail(&|| print_error("Emit", std::slice::from_ref(&error)));
+            }
+            progress.advance();
+            progress.stop();
+            if !json_messages() {
+                println!();
+                println!(
+                    "{} Build of {} v{} finished successfully! Output ({}) at: {}",
+                    style("✔").green().bold(),
+                    manifest.name,
+                    manifest.version,
+                    emit.name(),
+                    style(emit_path.display()).yellow()
+                );
+            }
+            if interactive() {
+                println!("💻 Community: {}/community", WEBSITE);
+            }
+            return 0;
+        }
+
+        let obj_path = build_dir.join(format!("{}.{}", project_name, emit.extension(&target_triple)));
 
-        if let Err(e) = emit_object_file(compiler.module, &target_triple, obj_path.to_str().unwrap()) {
-            print_error("Obj Gen", &[ParseError::new(ErrorKind::Codegen, e, 0, 0, Some(code), None, Severity::Error)]);
-            compiler.dispose();
-            return;
+        if let Err(e) = fs::write(&obj_path, &artifact.bytes) {
+            let message = format!("Failed to write obj file '{}': {}", obj_path.display(), e);
+            let error = ParseError::new(ErrorKind::Codegen, message, 0, 0, None, None, Severity::Error);
+            return fail(&|| print_error("Obj Gen", std::slice::from_ref(&error)));
         }
-        compiler.dispose();
-        stage += 1;
-        print_progress(stage, pipeline);
+        progress.advance();
         
-        sleep(Duration::from_millis(100));
+        progress.pause();
 
         let exe_ext = if target_triple.contains("windows") { ".exe" } else { "" };
-        let exe_name = output_filename.unwrap_or_else(|| format!("{}{}", project_name, exe_ext));
+        let exe_name = output_filename
+            .or(manifest.output.clone().map(|output| format!("{}{}", output, exe_ext)))
+            .unwrap_or_else(|| format!("{}{}", project_name, exe_ext));
         let exe_path = build_dir.join(&exe_name);
 
-        stage += 1;
-        print_progress(stage, pipeline);
+        progress.advance();
         
-        if let Err(e) = link_object_file(obj_path.to_str().unwrap(), exe_path.to_str().unwrap(), &target_triple) {
-            print_error("Linking", &[ParseError::new(ErrorKind::Linker, e, 0, 0, None, None, Severity::Error)]);
+        let runtime_path = build_dir.join("axon_runtime.c");
+        let runtime_obj_path = runtime_path.with_extension("obj");
+        if let Err(e) = fs::write(&runtime_path, RUNTIME_SOURCE) {
             fs::remove_file(&obj_path).ok();
-            return;
+            let message = format!("Failed to write the runtime library: {}", e);
+            let error = ParseError::new(ErrorKind::Linker, message, 0, 0, None, None, Severity::Error);
+            return fail(&|| print_error("Linking", std::slice::from_ref(&error)));
         }
 
+        let linked = link_object_file(
+            obj_path.to_str().unwrap(),
+            runtime_path.to_str().unwrap(),
+            exe_path.to_str().unwrap(),
+            &target_triple,
+        );
         fs::remove_file(&obj_path).ok();
+        fs::remove_file(&runtime_path).ok();
+        fs::remove_file(&runtime_obj_path).ok();
+        progress.stop();
+        if let Err(e) = linked {
+            print_error("Linking", &[ParseError::new(ErrorKind::Linker, e, 0, 0, None, None, Severity::Error)]);
+            return EXIT_FAILURE;
+        }
 
-        println!();
-        println!(
-            "{} Build finished successfully! Binary at: {}",
-            style("✔").green().bold(),
-            style(exe_path.display()).yellow()
-        );
+        if !json_messages() {
+            println!();
+            println!(
+                "{} Build of {} v{} finished successfully! Binary at: {}",
+                style("✔").green().bold(),
+                manifest.name,
+                manifest.version,
+                style(exe_path.display()).yellow()
+            );
+        }
 
     } else if cmd == "run" {
-        stage += 1;
-        print_progress(stage, pipeline);
-        println!();
-
-        match compile_and_run_jit(hir, mut_vars) {
+        progress.advance();
+        progress.stop();
+
+        let emit_ir = (emit == EmitKind::LlvmIr).then(|| {
+            fs::create_dir_all("release").ok();
+            format!("release/{}.ll", manifest.name)
+        });
+        let options = CompileOptions { checked, opt_level, emit_ir: emit_ir.clone(), backend, ..CompileOptions::default() };
+        match run(analyzed, &options) {
+            Ok(()) if json_messages() => {}
             Ok(()) => {
                 println!(
                     "\n{} Program executed successfully!",
                     style("✔").green().bold()
                 );
-            }
-            Err(errors) => {
-                print_error("JIT Execute", &errors.iter().map(|e| ParseError::from_compiler_error(e, &code)).collect::<Vec<_>>());
-                return;
-            }
-        }
-    }
-    println!("💻 Community: {}/community", WEBSITE);
-}
-
-fn print_header() {
-    let title = format!(
-        "{} {} {}",
-        style("Neuron — AxonScript Compiler").cyan().bold(),
-        style("•").white().bold(),
-        style("Build #0001 Pre-Alpha Demo").yellow().bold()
-    );
-    println!(
-        "{line}\n{title}\n    💻 Community: {website}/community\n{line}",
-        line = style("━").dim().to_string().repeat(60),
-        title = title,
-        website = WEBSITE
-    );
-}
-
-fn print_progress(stage: usize, pipeline: &[&str]) {
-    let bar_width: usize = 40;
-    let progress_ratio = if !pipeline.is_empty() {
-        stage as f32 / pipeline.len() as f32
-    } else {
-        0.0
-    };
-    let filled_width = (bar_width as f32 * progress_ratio).round() as usize;
-
-    let bar = format!(
-        "{}{}",
-        "━".repeat(filled_width),
-        "-".repeat(bar_width.saturating_sub(filled_width))
-    );
-
-    let stage_name = if stage > 0 && stage <= pipeline.len() {
-        pipeline[stage - 1]
-    } else if stage >= pipeline.len() {
-        "Done"
-    } else {
-        "Starting..."
-    };
-
-    let line = format!(
-        "PIPELINE [{}] {}",
-        style(&bar).green(),
-        style(stage_name).bold()
-    );
-
-    print!("\r\x1b[K{}", line);
-    stdout().flush().unwrap();
-}
-
-fn print_error(phase: &str, errors: &[ParseError]) {
-    println!();
-    println!(
-        "\n{} Stage: {}",
-        style("✘").red().bold(),
-        style(phase).red().bold()
-    );
-
-    for error in errors {
-        println!("{} {}", style("").red().bold(), error.message);
-
-        if let Some(src) = &error.src {
-            if error.start < src.len() && error.end <= src.len() {
-                let line_start = src[..error.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
-                let line_end = src[error.start..]
-                    .find('\n')
-                    .map(|i| error.start + i)
-                    .unwrap_or(src.len());
-                if line_start < line_end {
-                    let line_text = &src[line_start..line_end];
-                    println!("{}", style(line_text).dim());
-                    println!(
-                        "{}",
-                        " ".repeat(error.start - line_start) + &style("^").red().bold().to_string()
-                    );
+                if let Some(path) = emit_ir {
+                    println!("LLVM IR written to: {}", style(path).yellow());
                 }
             }
-        }
-
-        if let Some(suggestion) = &error.suggestion {
-            println!(
-                "{} {}",
-                style("Hint:").cyan().bold(),
-                style(suggestion).cyan()
-            );
-        }
-    }
-
-    println!(
-        "\n📚 Docs: {}/docs\n🐞 Bugs: {}/bugs\n💻 Community: {}/community\n",
-        WEBSITE, WEBSITE, WEBSITE
-    );
-}
-
-fn print_warning(phase: &str, warnings: &[ParseError]) {
-    println!();
-    println!(
-        "\n{} Stage: {}",
-        style("!").yellow().bold(),
-        style(phase).yellow().bold()
-    );
-
-    for warning in warnings {
-        println!(
-            "{} [{:?}] {}",
-            style("").yellow().bold(),
-            style(warning.kind).yellow(),
-            warning.message
-        );
-
-        if let Some(src) = &warning.src {
-             if warning.start < src.len() && warning.end <= src.len() {
-                let line_start = src[..warning.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
-                let line_end = src[warning.start..]
-                    .find('\n')
-                    .map(|i| warning.start + i)
-                    .unwrap_or(src.len());
-                if line_start < line_end {
-                    let line_text = &src[line_start..line_end];
-                    println!("{}", style(line_text).dim());
-                    println!(
-                        "{}",
-                        " ".repeat(warning.start - line_start) + &style("^").yellow().bold().to_string()
-                    );
+            // the program printed its own runtime error, its exit code is the result like for a built binary
+            Err(diagnostics) if diagnostics.exit_code().is_some() => {
+                if json_messages() {
+                    report(&diagnostics);
                 }
+                return diagnostics.exit_code().unwrap_or(EXIT_FAILURE);
             }
-        }
-
-        if let Some(suggestion) = &warning.suggestion {
-            println!(
-                "{} {}",
-                style("Hint:").cyan().bold(),
-                style(suggestion).cyan()
-            );
-        }
-    }
-}
-
-fn clear_screen() {
-    print!("\x1b[2J\x1b[1;1H");
-    stdout().flush().unwrap();
-}
-
-fn get_project_name() -> Option<String> {
-    let path = Path::new("project.asml");
-    if !path.exists() {
-        return None;
-    }
-    let content = fs::read_to_string(path).ok()?;
-    for line in content.lines() {
-        if let Some(name) = line.trim().strip_prefix("_name_ = ") {
-            let name = name.trim().trim_matches('"');
-            if !name.is_empty() {
-                return Some(name.to_string());
+            Err(diagnostics) => {
+                report(&diagnostics);
+                return EXIT_FAILURE;
             }
         }
     }
-    None
-}
\ No newline at end of file
+    if interactive() {
+        println!("💻 Community: {}/community", WEBSITE);
+    }
+    0
+}
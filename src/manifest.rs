@@ -0,0 +1,504 @@
+// project.asml,
+// an ini-like manifest split into sections, every setting is a `_key_ = value` line:
+//
+//   __Project__
+//   _name_ = "demo"
+//   _version_ = "0.1.0"
+//   _entry_ = "src/init.ax"
+//
+//   __Build__
+//   _output_ = "demo"
+//   _target_ = "x86_64-unknown-linux-gnu"
+//   _opt_level_ = 2          ?? 0, 1, 2, 3 or "s"
+//
+//   __Dependencies__
+//   _tensors_ = "1.2.0"
+//
+// `??` starts a comment, like in .ax files
+
+use crate::backend::OptLevel;
+use crate::lexer_tokenizer::{lex_with_span, Token};
+// a dependency's requirement is part of the manifest
+pub use crate::package_manager::version::{Version, VersionReq};
+use crate::parser::parser_error::{ErrorKind, ParseError, Severity};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+
+pub const MANIFEST_FILE: &str = "project.asml";
+pub const DEFAULT_ENTRY: &str = "src/init.ax";
+// installed dependencies live in packages/<name>/, each one is a project with its own src/
+pub const PACKAGES_DIR: &str = "packages";
+
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    pub name: String,
+    pub req: VersionReq,
+    // where the dependency is declared in project.asml
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    pub entry: PathBuf,
+    pub output: Option<String>,
+    pub target: Option<String>,
+    pub opt_level: OptLevel,
+    pub dependencies: Vec<Dependency>,
+    // the manifest text, so later errors can point into it
+    pub src: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Section {
+    Project,
+    Build,
+    Dependencies,
+}
+
+enum Value {
+    Str(String),
+    Int(i64),
+}
+
+impl Manifest {
+    pub fn load(project_dir: &Path) -> Result<Manifest, Vec<ParseError>> {
+        let path = project_dir.join(MANIFEST_FILE);
+        match fs::read_to_string(&path) {
+            Ok(src) => parse_manifest(&src),
+            Err(_) => Err(vec![ParseError::new(
+                ErrorKind::Syntax,
+                format!("No project found in '{}'.", project_dir.display()),
+                0,
+                0,
+                None,
+                Some(format!("Create one with: axon create project <name>, or add a {}", MANIFEST_FILE)),
+                Severity::Error,
+            )]),
+        }
+    }
+
+    // package name -> the src/ directory `use name.module;` resolves against,
+    // every installed package counts, so packages can import their own dependencies
+    pub fn package_roots(&self, project_dir: &Path) -> HashMap<String, PathBuf> {
+        let Ok(entries) = fs::read_dir(project_dir.join(PACKAGES_DIR)) else {
+            return HashMap::new();
+        };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|dir| dir.join(MANIFEST_FILE).exists())
+            .filter_map(|dir| Some((dir.file_name()?.to_string_lossy().into_owned(), dir.join("src"))))
+            .collect()
+    }
+
+    // dependencies declared in the manifest without an installed copy that satisfies them
+    pub fn missing_dependencies(&self, project_dir: &Path) -> Vec<ParseError> {
+        self.dependencies
+            .iter()
+            .filter_map(|dep| {
+                let installed = Manifest::load(&project_dir.join(PACKAGES_DIR).join(&dep.name))
+                    .ok()
+                    .and_then(|m| Version::parse(&m.version));
+                let message = match installed {
+                    Some(v) if dep.req.matches(&v) => return None,
+                    Some(v) => format!(
+                        "\x1b[31m[ERR-ASML-010] Dependency '{}' needs {} but {} is installed\x1b[0m",
+                        dep.name, dep.req, v
+                    ),
+                    None => format!(
+                        "\x1b[31m[ERR-ASML-010] Dependency '{}' ({}) is not installed\x1b[0m",
+                        dep.name, dep.req
+                    ),
+                };
+                Some(self.error(message, dep.span.clone(), Some("Run: axon install".to_string())))
+            })
+            .collect()
+    }
+
+    fn error(&self, message: String, span: Range<usize>, suggestion: Option<String>) -> ParseError {
+        manifest_error(&self.src, message, span, suggestion)
+    }
+}
+
+fn manifest_error(src: &str, message: String, span: Range<usize>, suggestion: Option<String>) -> ParseError {
+    let mut error = ParseError::new(
+        ErrorKind::Syntax,
+        message,
+        span.start,
+        span.end,
+        Some(src.to_string()),
+        suggestion,
+        Severity::Error,
+    );
+    error.file = Some(MANIFEST_FILE.to_string());
+    error
+}
+
+// package names end up in `use` paths and in directory names under packages/ and the registry,
+// so they have to lex as a single identifier
+pub fn is_package_name(name: &str) -> bool {
+    let tokens = lex_with_span(name);
+    matches!(tokens.as_slice(), [t] if matches!(&t.token, Token::Identifier(id) if id == name && !id.contains(['-', '*', '$'])))
+}
+
+pub fn parse_manifest(src: &str) -> Result<Manifest, Vec<ParseError>> {
+    let mut errors = Vec::new();
+    let mut section: Option<Section> = None;
+    let mut seen_sections = HashSet::new();
+    let mut seen_keys = HashSet::new();
+
+    let mut name = None;
+    let mut version = None;
+    let mut entry = None;
+    let mut output = None;
+    let mut target = None;
+    let mut opt_level = None;
+    let mut dependencies: Vec<Dependency> = Vec::new();
+
+    let mut offset = 0;
+    for raw in src.split_inclusive('\n') {
+        let line_start = offset;
+        offset += raw.len();
+        let without_comment = comment_start(raw).map_or(raw, |i| &raw[..i]);
+        let line = without_comment.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let indent = without_comment.len() - without_comment.trim_start().len();
+        let line_span = line_start + indent..line_start + indent + line.len();
+
+        if line.starts_with("__") && line.ends_with("__") && !line.contains('=') {
+            let found = match line.trim_matches('_') {
+                "Project" => Some(Section::Project),
+                "Build" => Some(Section::Build),
+                "Dependencies" => Some(Section::Dependencies),
+                _ => None,
+            };
+            match found {
+                Some(found) if seen_sections.contains(&found) => {
+                    errors.push(manifest_error(
+                        src,
+                        format!("\x1b[31m[ERR-ASML-007] Section {} appears more than once\x1b[0m", line),
+                        line_span,
+                        Some("Merge both sections into one.".to_string()),
+                    ));
+                    section = Some(found);
+                }
+                Some(found) => {
+                    seen_sections.insert(found);
+                    section = Some(found);
+                }
+                None => {
+                    errors.push(manifest_error(
+                        src,
+                        format!("\x1b[31m[ERR-ASML-001] Unknown section '{}'\x1b[0m", line),
+                        line_span,
+                        Some("Known sections: __Project__, __Build__, __Dependencies__".to_string()),
+                    ));
+                    section = None;
+                }
+            }
+            continue;
+        }
+
+        let Some(eq) = line.find('=') else {
+            errors.push(manifest_error(
+                src,
+                format!("\x1b[31m[ERR-ASML-003] Expected `_key_ = value`, found '{}'\x1b[0m", line),
+                line_span,
+                None,
+            ));
+            continue;
+        };
+        let raw_key = line[..eq].trim();
+        let raw_value = line[eq + 1..].trim();
+        let key_span = line_span.start..line_span.start + raw_key.len();
+        let value_start = line_span.start + eq + 1 + (line[eq + 1..].len() - line[eq + 1..].trim_start().len());
+        let value_span = value_start..value_start + raw_value.len();
+
+        let key = match raw_key.strip_prefix('_').and_then(|k| k.strip_suffix('_')) {
+            Some(key) if !key.is_empty() => key.to_string(),
+            _ => {
+                errors.push(manifest_error(
+                    src,
+                    format!("\x1b[31m[ERR-ASML-003] Keys are written as _key_, found '{}'\x1b[0m", raw_key),
+                    key_span,
+                    None,
+                ));
+                continue;
+            }
+        };
+        let Some(current) = section else {
+            errors.push(manifest_error(
+                src,
+                format!("\x1b[31m[ERR-ASML-002] '{}' is not inside a section\x1b[0m", raw_key),
+                line_span,
+                Some("Put project settings under __Project__.".to_string()),
+            ));
+            continue;
+        };
+        if !seen_keys.insert((current, key.clone())) {
+            errors.push(manifest_error(
+                src,
+                format!("\x1b[31m[ERR-ASML-006] '{}' is set more than once\x1b[0m", raw_key),
+                key_span,
+                None,
+            ));
+            continue;
+        }
+        let value = match parse_value(raw_value) {
+            Some(value) => value,
+            None => {
+                errors.push(manifest_error(
+                    src,
+                    format!("\x1b[31m[ERR-ASML-005] Invalid value '{}' for {}\x1b[0m", raw_value, raw_key),
+                    value_span,
+                    Some("Values are quoted strings (\"...\") or whole numbers.".to_string()),
+                ));
+                continue;
+            }
+        };
+
+        let expect_string = |value: Value, errors: &mut Vec<ParseError>| match value {
+            Value::Str(s) => Some(s),
+            Value::Int(_) => {
+                errors.push(manifest_error(
+                    src,
+                    format!("\x1b[31m[ERR-ASML-012] {} must be a quoted string\x1b[0m", raw_key),
+                    value_span.clone(),
+                    None,
+                ));
+                None
+            }
+        };
+
+        match (current, key.as_str()) {
+            (Section::Project, "name") => {
+                if let Some(s) = expect_string(value, &mut errors) {
+                    if s.trim().is_empty() {
+                        errors.push(manifest_error(
+                            src,
+                            "\x1b[31m[ERR-ASML-013] _name_ cannot be empty\x1b[0m".to_string(),
+                            value_span,
+                            None,
+                        ));
+                    } else {
+                        name = Some(s);
+                    }
+                }
+            }
+            (Section::Project, "version") => {
+                if let Some(s) = expect_string(value, &mut errors) {
+                    if Version::parse(&s).is_some() {
+                        version = Some(s);
+                    } else {
+                        errors.push(version_error(src, &s, value_span));
+                    }
+                }
+            }
+            (Section::Project, "entry") => {
+                if let Some(s) = expect_string(value, &mut errors) {
+                    let path = Path::new(&s);
+                    if s.ends_with(".ax") && path.starts_with("src") && !path.components().any(|c| c == Component::ParentDir) {
+                        entry = Some(PathBuf::from(s));
+                    } else {
+                        errors.push(manifest_error(
+                            src,
+                            format!("\x1b[31m[ERR-ASML-014] _entry_ must be an .ax file inside src/, found \"{}\"\x1b[0m", s),
+                            value_span,
+                            Some(format!("The default is \"{}\"", DEFAULT_ENTRY)),
+                        ));
+                    }
+                }
+            }
+            (Section::Build, "output") => output = expect_string(value, &mut errors),
+            (Section::Build, "target") => target = expect_string(value, &mut errors).map(|t| t.to_lowercase()),
+            (Section::Build, "opt_level") => match value {
+                Value::Int(level @ 0..=3) => opt_level = OptLevel::parse(&level.to_string()),
+                Value::Str(level) if level == "s" => opt_level = Some(OptLevel::Os),
+                _ => errors.push(manifest_error(
+                    src,
+                    format!("\x1b[31m[ERR-ASML-015] _opt_level_ must be 0, 1, 2, 3 or \"s\", found {}\x1b[0m", raw_value),
+                    value_span,
+                    None,
+                )),
+            },
+            (Section::Dependencies, _) if !is_package_name(&key) => errors.push(manifest_error(
+                src,
+                format!("\x1b[31m[ERR-ASML-011] '{}' cannot be used as a package name\x1b[0m", key),
+                key_span,
+                Some("Package names are imported with `use <name>;`, so use letters, digits and _ and avoid keywords.".to_string()),
+            )),
+            (Section::Dependencies, _) => {
+                if let Some(s) = expect_string(value, &mut errors) {
+                    match VersionReq::parse(&s) {
+                        Some(req) => dependencies.push(Dependency { name: key, req, span: line_span }),
+                        None => errors.push(manifest_error(
+                            src,
+                            format!("\x1b[31m[ERR-ASML-009] '{}' is not a version requirement\x1b[0m", s),
+                            value_span,
+                            Some("For example: \"1.2.0\" (compatible releases), \"=1.2.0\" (exactly) or \"*\"".to_string()),
+                        )),
+                    }
+                }
+            }
+            (Section::Project, _) | (Section::Build, _) => {
+                let known = if current == Section::Project { "_name_, _version_, _entry_" } else { "_output_, _target_, _opt_level_" };
+                errors.push(manifest_error(
+                    src,
+                    format!("\x1b[31m[ERR-ASML-004] Unknown setting {}\x1b[0m", raw_key),
+                    key_span,
+                    Some(format!("This section accepts: {}", known)),
+                ));
+            }
+        }
+    }
+
+    if name.is_none() && errors.is_empty() {
+        errors.push(manifest_error(
+            src,
+            "\x1b[31m[ERR-ASML-008] project.asml has no _name_\x1b[0m".to_string(),
+            0..0,
+            Some("Add it under __Project__: _name_ = \"my_project\"".to_string()),
+        ));
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(Manifest {
+        name: name.unwrap_or_default(),
+        version: version.unwrap_or_else(|| "0.1.0".to_string()),
+        entry: entry.unwrap_or_else(|| PathBuf::from(DEFAULT_ENTRY)),
+        output,
+        target,
+        opt_level: opt_level.unwrap_or_default(),
+        dependencies,
+        src: src.to_string(),
+    })
+}
+
+// where the `??` comment on a line starts, `??` inside a quoted value is part of the value
+fn comment_start(line: &str) -> Option<usize> {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '?' if !quoted && line[i..].starts_with("??") => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_value(raw: &str) -> Option<Value> {
+    if raw.len() >= 2 && raw.starts_with('"') && raw.ends_with('"') {
+        let inner = &raw[1..raw.len() - 1];
+        return (!inner.contains('"')).then(|| Value::Str(inner.to_string()));
+    }
+    raw.parse().ok().map(Value::Int)
+}
+
+fn version_error(src: &str, found: &str, span: Range<usize>) -> ParseError {
+    manifest_error(
+        src,
+        format!("\x1b[31m[ERR-ASML-009] '{}' is not a version, expected major.minor.patch\x1b[0m", found),
+        span,
+        Some("For example: \"0.1.0\"".to_string()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the code of every error in `src`
+    fn error_codes(src: &str) -> Vec<String> {
+        match parse_manifest(src) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors.into_iter().filter_map(|e| e.code).collect(),
+        }
+    }
+
+    #[test]
+    fn dependency_names_are_package_names() {
+        let src = "__Project__\n_name_ = \"demo\"\n\n__Dependencies__\n_../../evil_ = \"1.0.0\"\n_my-tensors_ = \"1.0.0\"\n_tensors_ = \"1.0.0\"\n";
+        assert_eq!(error_codes(src), vec!["ERR-ASML-011", "ERR-ASML-011"]);
+    }
+
+    #[test]
+    fn every_setting_is_read() {
+        let src = "?? a comment\n__Project__\n_name_ = \"demo\"\n_version_ = \"0.2.0\"\n_entry_ = \"src/main.ax\"\n\n\
+                   __Build__\n_output_ = \"app\"\n_target_ = \"X86_64-unknown-linux-gnu\"\n_opt_level_ = \"s\"\n\n\
+                   __Dependencies__\n_tensors_ = \"^1.2.0\"\n";
+        let manifest = parse_manifest(src).ok().unwrap();
+        assert_eq!((manifest.name.as_str(), manifest.version.as_str()), ("demo", "0.2.0"));
+        assert_eq!(manifest.entry, PathBuf::from("src/main.ax"));
+        assert_eq!(manifest.output.as_deref(), Some("app"));
+        assert_eq!(manifest.target.as_deref(), Some("x86_64-unknown-linux-gnu"));
+        assert_eq!(manifest.opt_level, OptLevel::Os);
+        assert_eq!(manifest.dependencies.len(), 1);
+        assert_eq!(manifest.dependencies[0].name, "tensors");
+        assert_eq!(manifest.dependencies[0].req.to_string(), "^1.2.0");
+        assert_eq!(&src[manifest.dependencies[0].span.clone()], "_tensors_ = \"^1.2.0\"");
+    }
+
+    #[test]
+    fn comments_start_outside_quoted_values() {
+        let src = "__Project__\n_name_ = \"demo\" ?? \"not\" the name\n_version_ = \"0.1.0\"\n\n__Build__\n_output_ = \"a??b\" ?? the binary\n";
+        let manifest = parse_manifest(src).ok().unwrap();
+        assert_eq!(manifest.name, "demo");
+        assert_eq!(manifest.output.as_deref(), Some("a??b"));
+    }
+
+    #[test]
+    fn each_mistake_has_its_own_code() {
+        let project = "__Project__\n_name_ = \"demo\"\n_version_ = \"0.1.0\"\n";
+        let cases = [
+            (format!("{}__Tests__\n", project), "ERR-ASML-001"),
+            ("_name_ = \"demo\"\n__Project__\n_version_ = \"0.1.0\"\n".to_string(), "ERR-ASML-002"),
+            (format!("{}name = \"other\"\n", project), "ERR-ASML-003"),
+            (format!("{}_author_ = \"ada\"\n", project), "ERR-ASML-004"),
+            (format!("{}_entry_ = src/init.ax\n", project), "ERR-ASML-005"),
+            (format!("{}_name_ = \"again\"\n", project), "ERR-ASML-006"),
+            (format!("{}__Project__\n", project), "ERR-ASML-007"),
+            ("__Project__\n_version_ = \"0.1.0\"\n".to_string(), "ERR-ASML-008"),
+            ("__Project__\n_name_ = \"demo\"\n_version_ = \"1.0\"\n".to_string(), "ERR-ASML-009"),
+            (format!("{}__Dependencies__\n_tensors_ = \"latest\"\n", project), "ERR-ASML-009"),
+            (format!("{}__Dependencies__\n_use_ = \"1.0.0\"\n", project), "ERR-ASML-011"),
+            (format!("{}__Build__\n_output_ = 3\n", project), "ERR-ASML-012"),
+            ("__Project__\n_name_ = \"\"\n_version_ = \"0.1.0\"\n".to_string(), "ERR-ASML-013"),
+            (format!("{}_entry_ = \"../init.ax\"\n", project), "ERR-ASML-014"),
+            (format!("{}_entry_ = \"src/../../etc/x.ax\"\n", project), "ERR-ASML-014"),
+            (format!("{}__Build__\n_opt_level_ = 4\n", project), "ERR-ASML-015"),
+        ];
+        for (src, code) in cases {
+            assert_eq!(error_codes(&src), vec![code], "for\n{}", src);
+        }
+        assert_eq!(error_codes(project), Vec::<String>::new());
+    }
+
+    #[test]
+    fn dependencies_are_checked_against_what_is_installed() {
+        let dir = std::env::temp_dir().join(format!("axon_manifest_{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        let installed = dir.join(PACKAGES_DIR).join("tensors");
+        fs::create_dir_all(&installed).unwrap();
+        fs::write(installed.join(MANIFEST_FILE), "__Project__\n_name_ = \"tensors\"\n_version_ = \"1.4.0\"\n").unwrap();
+
+        let manifest = |deps: &str| {
+            parse_manifest(&format!("__Project__\n_name_ = \"demo\"\n\n__Dependencies__\n{}", deps)).ok().unwrap()
+        };
+        let missing = |deps: &str| {
+            manifest(deps).missing_dependencies(&dir).into_iter().filter_map(|e| e.code).collect::<Vec<_>>()
+        };
+        assert!(missing("_tensors_ = \"1.2.0\"\n").is_empty());
+        assert_eq!(missing("_tensors_ = \"2.0.0\"\n"), vec!["ERR-ASML-010"]);
+        assert_eq!(missing("_plots_ = \"*\"\n"), vec!["ERR-ASML-010"]);
+        assert_eq!(manifest("").package_roots(&dir), HashMap::from([("tensors".to_string(), installed.join("src"))]));
+        fs::remove_dir_all(&dir).ok();
+    }
+}
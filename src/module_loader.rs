@@ -0,0 +1,509 @@
+// module loader,
+// `use util.calc;` pulls src/util/calc.ax into the program, every file is lexed and parsed on its own,
+// then the top-level functions and globals of each imported module are renamed to `util.calc.name`
+// so files cannot clash, and `calc.add(..)` / `calc.pi` in the importing file are rewritten to match,
+// all files share one range of byte offsets, so any span can be traced back to the file it came from
+
+use crate::ast::*;
+use crate::lexer_tokenizer::lex_with_span;
+use crate::parser::parser_error::{ErrorKind, ParseError, ParseResult, Severity};
+use crate::parser::parser_kernel::Parser as AxonParser;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub code: String,
+    base: usize,
+}
+
+#[derive(Default, Clone)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceFile {
+    // the path as the user would write it, without a leading `./`
+    pub fn display_path(&self) -> String {
+        self.path.display().to_string().trim_start_matches("./").to_string()
+    }
+}
+
+impl SourceMap {
+    // the gap of one keeps the end of one file from being the start of the next
+    fn add(&mut self, path: PathBuf, code: String) -> usize {
+        let base = self.files.last().map_or(0, |f| f.base + f.code.len() + 1);
+        self.files.push(SourceFile { path, code, base });
+        base
+    }
+
+    pub fn file_at(&self, offset: usize) -> Option<&SourceFile> {
+        self.files.iter().rev().find(|f| f.base <= offset)
+    }
+
+    // the file an offset falls in, with the 1-based line and byte column
+    pub fn line_col(&self, offset: usize) -> Option<(&SourceFile, usize, usize)> {
+        let file = self.file_at(offset)?;
+        let before = file.code.get(..offset - file.base)?;
+        let line = before.matches('\n').count() + 1;
+        let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+        Some((file, line, column))
+    }
+
+    // `src/util/calc.ax:3:9` for a project-wide offset
+    pub fn position(&self, offset: usize) -> Option<String> {
+        let (file, line, column) = self.line_col(offset)?;
+        Some(format!("{}:{}:{}", file.display_path(), line, column))
+    }
+
+    pub fn entry(&self) -> Option<&SourceFile> {
+        self.files.first()
+    }
+
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    // project-wide offset -> the file and the byte offset inside it
+    pub fn local(&self, offset: usize) -> Option<(&SourceFile, usize)> {
+        let file = self.file_at(offset)?;
+        Some((file, offset - file.base))
+    }
+
+    // byte offset inside `path` -> project-wide offset
+    pub fn global(&self, path: &Path, offset: usize) -> Option<usize> {
+        self.files.iter().find(|f| f.path == path).map(|f| f.base + offset)
+    }
+
+    // turns project-wide offsets back into a position inside the right file
+    pub fn localize(&self, mut error: ParseError) -> ParseError {
+        if error.start == 0 && error.end == 0 {
+            return error;
+        }
+        if let Some(file) = self.file_at(error.start) {
+            error.start -= file.base;
+            error.end = error.end.saturating_sub(file.base).max(error.start);
+            error.src = Some(file.code.clone());
+            error.file = Some(file.path.display().to_string());
+        }
+        error
+    }
+}
+
+struct Module {
+    // `util.calc`, the path of the file under src/ with dots
+    name: String,
+    // the entry file keeps its names as they are (Start, test_*)
+    root: bool,
+    // where this module's own `use` paths start, src/ or the src/ of the package it belongs to
+    dir: PathBuf,
+    // the package this module belongs to, None for the project itself
+    package: Option<String>,
+    functions: HashSet<String>,
+    globals: HashSet<String>,
+    // alias (last path segment) -> module name
+    imports: HashMap<String, String>,
+    ast: Vec<Statement>,
+}
+
+impl Module {
+    fn qualify(&self, name: &str) -> String {
+        if self.root { name.to_string() } else { format!("{}.{}", self.name, name) }
+    }
+
+    // `calc` or `util.calc` as written in this file -> the module it refers to
+    fn resolve_module(&self, written: &str) -> Option<&String> {
+        self.imports
+            .get(written)
+            .or_else(|| self.imports.values().find(|name| *name == written))
+    }
+}
+
+struct Loader<'a> {
+    // dependency name -> its installed src/ directory
+    packages: &'a HashMap<String, PathBuf>,
+    sources: SourceMap,
+    modules: HashMap<String, Module>,
+    // modules in the order they finished loading, dependencies come before their users
+    order: Vec<String>,
+    // modules currently being loaded, an import of one of these is a cycle
+    stack: Vec<String>,
+    errors: Vec<ParseError>,
+    // unsaved editor buffers, read instead of the file on disk
+    open: &'a HashMap<PathBuf, String>,
+}
+
+// loads `entry` and everything it imports, the result is one flat program,
+// `use name...;` where name is a dependency resolves inside that package instead of src/
+pub fn load_program(
+    src_dir: &Path,
+    packages: &HashMap<String, PathBuf>,
+    entry: &Path,
+) -> (ParseResult<Vec<Statement>>, SourceMap) {
+    load_program_with(src_dir, packages, entry, &HashMap::new())
+}
+
+// the same, with the text of files open in an editor taking the place of what is saved
+pub fn load_program_with(
+    src_dir: &Path,
+    packages: &HashMap<String, PathBuf>,
+    entry: &Path,
+    open: &HashMap<PathBuf, String>,
+) -> (ParseResult<Vec<Statement>>, SourceMap) {
+    let mut loader = Loader {
+        packages,
+        sources: SourceMap::default(),
+        modules: HashMap::new(),
+        order: Vec::new(),
+        stack: Vec::new(),
+        errors: Vec::new(),
+        open,
+    };
+    let name = module_name(src_dir, entry);
+    loader.load(&name, entry, (src_dir.to_path_buf(), None), true, None);
+
+    let mut program = Vec::new();
+    for name in &loader.order {
+        let module = &loader.modules[name];
+        let mut resolver = Resolver {
+            modules: &loader.modules,
+            module,
+            locals: HashSet::new(),
+            in_function: false,
+            span: 0..0,
+            errors: Vec::new(),
+        };
+        for stmt in &module.ast {
+            program.push(resolver.statement(stmt.clone()));
+        }
+        loader.errors.extend(resolver.errors);
+    }
+
+    let sources = loader.sources;
+    let errors = loader.errors.into_iter().map(|e| sources.localize(e)).collect();
+    let result = if loader.order.is_empty() { None } else { Some(program) };
+    (ParseResult { result, errors }, sources)
+}
+
+// src/util/calc.ax -> util.calc
+fn module_name(src_dir: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(src_dir).unwrap_or(file).with_extension("");
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn module_error(message: String, span: Option<&Span>, suggestion: Option<String>) -> ParseError {
+    let (start, end) = span.map_or((0, 0), |s| (s.start, s.end));
+    ParseError::new(ErrorKind::Semantic, message, start, end, None, suggestion, Severity::Error)
+}
+
+impl Loader<'_> {
+    fn load(
+        &mut self,
+        name: &str,
+        path: &Path,
+        (dir, package): (PathBuf, Option<String>),
+        root: bool,
+        used_at: Option<&Span>,
+    ) -> bool {
+        if let Some(pos) = self.stack.iter().position(|m| m == name) {
+            let mut cycle = self.stack[pos..].to_vec();
+            cycle.push(name.to_string());
+            self.errors.push(module_error(
+                format!("\x1b[31m[ERR-MOD-002] Import cycle: {}\x1b[0m", cycle.join(" -> ")),
+                used_at,
+                Some("Move the shared functions into a module that neither file imports.".to_string()),
+            ));
+            return false;
+        }
+        if self.modules.contains_key(name) {
+            return true;
+        }
+        let code = match self.open.get(path).cloned().map_or_else(|| fs::read_to_string(path), Ok) {
+            Ok(code) => code,
+            Err(_) => {
+                self.errors.push(module_error(
+                    format!(
+                        "\x1b[31m[ERR-MOD-001] Module '{}' not found, looked for {}\x1b[0m",
+                        name,
+                        path.display()
+                    ),
+                    used_at,
+                    None,
+                ));
+                return false;
+            }
+        };
+
+        let base = self.sources.add(path.to_path_buf(), code.clone());
+        let mut tokens = lex_with_span(&code);
+        for token in &mut tokens {
+            token.span.start += base;
+            token.span.end += base;
+        }
+        let mut parser = AxonParser::new(&tokens, Some(code));
+        parser.base = base;
+        let parsed = parser.parse_program();
+        self.errors.extend(parsed.errors);
+        let Some(ast) = parsed.result else {
+            return false;
+        };
+
+        self.stack.push(name.to_string());
+        let mut module = Module {
+            name: name.to_string(),
+            root,
+            dir,
+            package,
+            functions: HashSet::new(),
+            globals: HashSet::new(),
+            imports: HashMap::new(),
+            ast: Vec::with_capacity(ast.len()),
+        };
+        for stmt in ast {
+            let span = match &stmt {
+                Statement::Located { span, .. } => Some(span.clone()),
+                _ => None,
+            };
+            match stmt.unlocated() {
+                Statement::Use { path } => {
+                    let (location, file, target) = self.locate(&module, path);
+                    let alias = path.last().cloned().unwrap_or_default();
+                    if let Some(existing) = module.imports.get(&alias).filter(|m| **m != target) {
+                        self.errors.push(module_error(
+                            format!(
+                                "\x1b[31m[ERR-MOD-003] '{}' already refers to module '{}' in this file\x1b[0m",
+                                alias, existing
+                            ),
+                            span.as_ref(),
+                            Some(format!("Call into '{}' with its full path instead, e.g. {}.name(...)", target, target)),
+                        ));
+                        continue;
+                    }
+                    if self.load(&target, &file, location, false, span.as_ref()) {
+                        module.imports.insert(alias, target);
+                    }
+                    continue;
+                }
+                Statement::FunctionCall { name: fn_name, start, .. } => {
+                    if *start && !root {
+                        self.errors.push(module_error(
+                            format!(
+                                "\x1b[31m[ERR-MOD-006] Module '{}' declares Start(), only the entry file may\x1b[0m",
+                                name
+                            ),
+                            span.as_ref(),
+                            None,
+                        ));
+                    }
+                    module.functions.insert(fn_name.clone());
+                }
+                Statement::Assignment { name: var, .. } => {
+                    module.globals.insert(var.clone());
+                }
+                _ => {}
+            }
+            module.ast.push(stmt);
+        }
+        self.stack.pop();
+        self.modules.insert(name.to_string(), module);
+        self.order.push(name.to_string());
+        true
+    }
+
+    // `use` path -> (where the imported module resolves its own imports, its file, its module name)
+    fn locate(&self, from: &Module, path: &[String]) -> ((PathBuf, Option<String>), PathBuf, String) {
+        let (dir, package, rest) = match self.packages.get(&path[0]) {
+            // `use tensors;` is the package's init.ax, `use tensors.ops;` its src/ops.ax
+            Some(package_src) => {
+                let rest = if path.len() == 1 { vec!["init".to_string()] } else { path[1..].to_vec() };
+                (package_src.clone(), Some(path[0].clone()), rest)
+            }
+            _ => (from.dir.clone(), from.package.clone(), path.to_vec()),
+        };
+        let file = rest.iter().fold(dir.clone(), |dir, part| dir.join(part)).with_extension("ax");
+        let name = match &package {
+            Some(package) => format!("{}.{}", package, rest.join(".")),
+            None => rest.join("."),
+        };
+        ((dir, package), file, name)
+    }
+}
+
+// rewrites one module's names into their project-wide form
+struct Resolver<'a> {
+    modules: &'a HashMap<String, Module>,
+    module: &'a Module,
+    // parameters and `set` declarations of the function being rewritten, they shadow globals,
+    // a variable declared in a nested block belongs to the whole function like anywhere else
+    locals: HashSet<String>,
+    in_function: bool,
+    span: Span,
+    errors: Vec<ParseError>,
+}
+
+impl Resolver<'_> {
+    fn variable(&self, name: String) -> String {
+        if self.module.globals.contains(&name) && !self.locals.contains(&name) {
+            self.module.qualify(&name)
+        } else {
+            name
+        }
+    }
+
+    fn error(&mut self, message: String) {
+        let span = self.span.clone();
+        self.errors.push(module_error(message, Some(&span), None));
+    }
+
+    fn statements(&mut self, stmts: Vec<Statement>) -> Vec<Statement> {
+        stmts.into_iter().map(|s| self.statement(s)).collect()
+    }
+
+    fn exprs(&mut self, exprs: Vec<Expr>) -> Vec<Expr> {
+        exprs.into_iter().map(|e| self.expr(e)).collect()
+    }
+
+    fn statement(&mut self, stmt: Statement) -> Statement {
+        match stmt {
+            Statement::Located { span, stmt } => {
+                let outer = std::mem::replace(&mut self.span, span.clone());
+                let stmt = self.statement(*stmt);
+                self.span = outer;
+                Statement::Located { span, stmt: Box::new(stmt) }
+            }
+            Statement::Do(inner) => Statement::Do(Box::new(self.statement(*inner))),
+            Statement::FunctionCall { name, params, return_type, start, body } => {
+                let outer = std::mem::replace(&mut self.locals, params.iter().map(|(p, _)| p.clone()).collect());
+                let outer_function = std::mem::replace(&mut self.in_function, true);
+                let body = self.statements(body);
+                self.locals = outer;
+                self.in_function = outer_function;
+                Statement::FunctionCall { name: self.module.qualify(&name), params, return_type, start, body }
+            }
+            // the value is read before the local it declares exists
+            Statement::Assignment { name, mutable, type_var, value } => {
+                let value = self.expr(value);
+                if self.in_function {
+                    self.locals.insert(name.clone());
+                }
+                Statement::Assignment { name: self.variable(name), mutable, type_var, value }
+            }
+            Statement::Print { params } => Statement::Print { params: self.exprs(params) },
+            Statement::Math { expression, destination, err } => Statement::Math {
+                expression: self.expr(expression),
+                destination: self.variable(destination),
+                err,
+            },
+            Statement::If { condition, body, else_body } => Statement::If {
+                condition: self.expr(condition),
+                body: self.statements(body),
+                else_body: else_body.map(|b| self.statements(b)),
+            },
+            Statement::Loop { body } => Statement::Loop { body: self.statements(body) },
+            Statement::While { condition, body } => Statement::While {
+                condition: self.expr(condition),
+                body: self.statements(body),
+            },
+            Statement::Return { value } => Statement::Return { value: value.map(|v| self.expr(v)) },
+            Statement::IndexAssign { name, index, value } => Statement::IndexAssign {
+                name: self.variable(name),
+                index: self.expr(index),
+                value: self.expr(value),
+            },
+            Statement::ExprStatement { expr } => Statement::ExprStatement { expr: self.expr(expr) },
+            Statement::Input { target, err } => Statement::Input { target: self.expr(target), err },
+            Statement::Assert { logic, args, source } => {
+                Statement::Assert { logic, args: self.exprs(args), source }
+            }
+            Statement::Use { path } => {
+                self.error(format!(
+                    "\x1b[31m[ERR-MOD-007] 'use {}' must be at the top level of the file\x1b[0m",
+                    path.join(".")
+                ));
+                Statement::Use { path }
+            }
+            Statement::Break => Statement::Break,
+        }
+    }
+
+    fn expr(&mut self, expr: Expr) -> Expr {
+        match expr {
+            Expr::Located { span, expr } => {
+                let outer = std::mem::replace(&mut self.span, span.clone());
+                let expr = self.expr(*expr);
+                self.span = outer;
+                Expr::Located { span, expr: Box::new(expr) }
+            }
+            Expr::Identifier(name) => match name.rsplit_once('.') {
+                Some((module, item)) => Expr::Identifier(self.qualified(module, item, false)),
+                None => Expr::Identifier(self.variable(name)),
+            },
+            Expr::Call { name, args } => {
+                let name = if self.module.functions.contains(&name) { self.module.qualify(&name) } else { name };
+                Expr::Call { name, args: self.exprs(args) }
+            }
+            Expr::MethodCall { target, method, args } => {
+                let module = match target.unlocated() {
+                    Expr::Identifier(written)
+                        if !self.locals.contains(written) && !self.module.globals.contains(written) =>
+                    {
+                        self.module.resolve_module(written).map(|_| written.clone())
+                    }
+                    _ => None,
+                };
+                match module {
+                    Some(written) => Expr::Call {
+                        name: self.qualified(&written, &method, true),
+                        args: self.exprs(args),
+                    },
+                    None => Expr::MethodCall {
+                        target: Box::new(self.expr(*target)),
+                        method,
+                        args: self.exprs(args),
+                    },
+                }
+            }
+            Expr::BinaryOp { left, op, right } => Expr::BinaryOp {
+                left: Box::new(self.expr(*left)),
+                op,
+                right: Box::new(self.expr(*right)),
+            },
+            Expr::Not { expr } => Expr::Not { expr: Box::new(self.expr(*expr)) },
+            Expr::Index { target, index } => Expr::Index {
+                target: Box::new(self.expr(*target)),
+                index: Box::new(self.expr(*index)),
+            },
+            Expr::Vector(elements) => Expr::Vector(self.exprs(elements)),
+            literal => literal,
+        }
+    }
+
+    // `calc.add` / `util.calc.pi` -> the renamed item, reports unknown modules and items
+    fn qualified(&mut self, written: &str, item: &str, function: bool) -> String {
+        let Some(target) = self.module.resolve_module(written).and_then(|name| self.modules.get(name)) else {
+            self.error(format!(
+                "\x1b[31m[ERR-MOD-004] '{}' is not a module imported by this file\x1b[0m",
+                written
+            ));
+            return format!("{}.{}", written, item);
+        };
+        let (found, kind) = if function {
+            (target.functions.contains(item), "function")
+        } else {
+            (target.globals.contains(item), "global")
+        };
+        if !found {
+            self.error(format!(
+                "\x1b[31m[ERR-MOD-005] Module '{}' has no {} named '{}'\x1b[0m",
+                target.name, kind, item
+            ));
+        }
+        target.qualify(item)
+    }
+}
@@ -0,0 +1,113 @@
+// HIR optimizations, they run on the output of the semantic pass before either backend sees it:
+// constants are folded and propagated, an `if` with a known condition is replaced by the branch that runs
+// and statements after a `break` or `return` are dropped,
+// every pass keeps what the program prints, including its runtime errors
+
+pub mod optimizer_branches;
+pub mod optimizer_fold;
+pub mod optimizer_propagate;
+pub mod optimizer_unreachable;
+
+use crate::high_level_ir::HIRStatement;
+use crate::optimizer::optimizer_branches::eliminate_constant_branches;
+use crate::optimizer::optimizer_propagate::propagate_constants;
+use crate::optimizer::optimizer_unreachable::remove_unreachable;
+use std::collections::HashSet;
+
+// what a pass knows about the whole program
+pub struct PassContext<'a> {
+    pub mutable_vars: &'a HashSet<String>,
+    // variables set at the top level, a function call can change them between any two statements
+    pub globals: HashSet<String>,
+}
+
+type Pass = fn(Vec<HIRStatement>, &PassContext) -> Vec<HIRStatement>;
+
+// in order, a later pass can give an earlier one more to do
+// (an `if` that went away no longer hides the constants set in it), so the list is run until nothing changes
+const PASSES: &[Pass] = &[propagate_constants, eliminate_constant_branches, remove_unreachable];
+const MAX_ROUNDS: usize = 8;
+
+pub fn optimize_hir(mut hir: Vec<HIRStatement>, mutable_vars: &HashSet<String>) -> Vec<HIRStatement> {
+    let globals = hir
+        .iter()
+        .filter_map(|stmt| match stmt.unlocated() {
+            HIRStatement::Assignment { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    let context = PassContext { mutable_vars, globals };
+    for _ in 0..MAX_ROUNDS {
+        let before = hir.clone();
+        for pass in PASSES {
+            hir = pass(hir, &context);
+        }
+        if hir == before {
+            break;
+        }
+    }
+    hir
+}
+
+// `stmt` with `block` applied to every block directly inside it, for the passes that only rearrange statements
+fn map_blocks(stmt: HIRStatement, block: fn(Vec<HIRStatement>) -> Vec<HIRStatement>) -> HIRStatement {
+    match stmt {
+        HIRStatement::Function { name, params, return_type, start, body } => {
+            HIRStatement::Function { name, params, return_type, start, body: block(body) }
+        }
+        HIRStatement::If { condition, body, else_body } => {
+            HIRStatement::If { condition, body: block(body), else_body: else_body.map(block) }
+        }
+        HIRStatement::Loop { body } => HIRStatement::Loop { body: block(body) },
+        HIRStatement::While { condition, body } => HIRStatement::While { condition, body: block(body) },
+        HIRStatement::Located { span, stmt } => {
+            HIRStatement::Located { span, stmt: Box::new(map_blocks(*stmt, block)) }
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::high_level_ir::dump;
+    use crate::{analyze, parse, Source};
+
+    // the body of Start after the passes
+    fn optimized_start(body: &str) -> String {
+        let code = format!("cast Start() >>\n{}\n    return;\n<<\n", body);
+        let analyzed = analyze(parse(&Source::code(&code)).ok().unwrap()).ok().unwrap();
+        let text = dump(&analyzed.hir);
+        let start = text.find("cast Start() >>\n").unwrap();
+        text[start..].lines().skip(1).filter(|l| *l != "<<" && *l != "    return;").collect::<Vec<_>>().join("\n")
+    }
+
+    #[test]
+    fn folds_and_propagates_constants() {
+        let body = "    set a(i32) = 6;\n    set b(i64) = a * 7;\n    out(b + 1, 1.5 * 2.0, not (a > 2));";
+        assert_eq!(optimized_start(body), "    a = 6;\n    b = 42i64;\n    out(43i64, 3.0, false);");
+    }
+
+    #[test]
+    fn leaves_runtime_checks_alone() {
+        let body = "    set big(i32) = 2147483647;\n    set z(f64) = 0.0;\n    out(big + 1, 1.0 / z);";
+        assert_eq!(
+            optimized_start(body),
+            "    big = 2147483647;\n    z = 0.0f64;\n    out((2147483647 + 1), (1.0f64 / z));"
+        );
+    }
+
+    #[test]
+    fn mutable_and_loop_assigned_variables_stay() {
+        let body = "    set: m(i32) = 1;\n    set k(i32) = 2;\n    while (m < 10) >>\n        set k(i32) = m;\n        math([m + k], m);\n    <<\n    out(m, k);";
+        assert_eq!(
+            optimized_start(body),
+            "    m = 1;\n    k = 2;\n    while (m < 10) >>\n        k = m;\n        m = (m + k);\n    <<\n    out(m, k);"
+        );
+    }
+
+    #[test]
+    fn removes_constant_branches_and_unreachable_code() {
+        let body = "    set debug(bool) = 1 > 2;\n    if (debug) >>\n        out(1);\n    << else >>\n        out(2);\n    <<\n    loop >>\n        break;\n        out(3);\n    <<";
+        assert_eq!(optimized_start(body), "    debug = false;\n    out(2);\n    loop >>\n        break;\n    <<");
+    }
+}
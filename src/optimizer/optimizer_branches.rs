@@ -0,0 +1,33 @@
+// an `if` whose condition folded to a literal is replaced by the statements of the branch that runs,
+// variables belong to the function rather than the block, so they mean the same in their new place
+
+use super::{map_blocks, PassContext};
+use crate::high_level_ir::{HIRExpr, HIRStatement};
+
+pub fn eliminate_constant_branches(hir: Vec<HIRStatement>, _context: &PassContext) -> Vec<HIRStatement> {
+    block(hir)
+}
+
+fn block(stmts: Vec<HIRStatement>) -> Vec<HIRStatement> {
+    let mut out = Vec::with_capacity(stmts.len());
+    for stmt in stmts {
+        statement(stmt, &mut out);
+    }
+    out
+}
+
+fn statement(stmt: HIRStatement, out: &mut Vec<HIRStatement>) {
+    match stmt {
+        HIRStatement::If { condition: HIRExpr::Bool(taken), body, else_body } => {
+            let branch = if taken { Some(body) } else { else_body };
+            out.extend(block(branch.unwrap_or_default()));
+        }
+        // the span goes with the `if`, the statements left behind have their own
+        HIRStatement::Located { stmt, .. }
+            if matches!(stmt.unlocated(), HIRStatement::If { condition: HIRExpr::Bool(_), .. }) =>
+        {
+            statement(*stmt, out)
+        }
+        other => out.push(map_blocks(other, block)),
+    }
+}
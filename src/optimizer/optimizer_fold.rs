@@ -0,0 +1,173 @@
+// constant folding, an operation on literals becomes the literal it evaluates to,
+// but only where that is exactly what running it gives: anything that overflows, divides by zero
+// or leaves the range of its type is left alone for the runtime checks (or the wrap around) to handle
+
+use crate::high_level_ir::{HIRExpr, HIROperator, HIRType};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+// folds `expr` bottom up, reading the variables in `known` as the literals they hold
+pub fn fold_expr(expr: HIRExpr, known: &HashMap<String, HIRExpr>) -> HIRExpr {
+    let fold = |expr: Box<HIRExpr>| Box::new(fold_expr(*expr, known));
+    let fold_all = |exprs: Vec<HIRExpr>| exprs.into_iter().map(|e| fold_expr(e, known)).collect();
+    match expr {
+        HIRExpr::Identifier(name) => known.get(&name).cloned().unwrap_or(HIRExpr::Identifier(name)),
+        HIRExpr::BinaryOp { left, op, right } => {
+            let left = fold_expr(*left, known);
+            // a divisor that only turns out to be zero here stays as written:
+            // a literal zero is a compile error, the variable is a runtime check (or inf for floats)
+            let folded_right = fold_expr((*right).clone(), known);
+            let right = match op {
+                HIROperator::Divide | HIROperator::Modulo if is_zero(&folded_right) => *right,
+                _ => folded_right,
+            };
+            fold_binary(&left, &op, &right).unwrap_or(HIRExpr::BinaryOp {
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+            })
+        }
+        HIRExpr::Not { expr } => {
+            let expr = fold_expr(*expr, known);
+            match expr {
+                HIRExpr::Bool(value) => HIRExpr::Bool(!value),
+                expr => HIRExpr::Not { expr: Box::new(expr) },
+            }
+        }
+        HIRExpr::Coerce { expr, target } => {
+            let expr = fold_expr(*expr, known);
+            fold_coerce(&expr, &target).unwrap_or(HIRExpr::Coerce { expr: Box::new(expr), target })
+        }
+        HIRExpr::FunctionCall { name, args } => HIRExpr::FunctionCall { name, args: fold_all(args) },
+        HIRExpr::Vector { elements, elem_type } => HIRExpr::Vector { elements: fold_all(elements), elem_type },
+        HIRExpr::Index { target, index } => HIRExpr::Index { target: fold(target), index: fold(index) },
+        HIRExpr::VectorLen { target } => HIRExpr::VectorLen { target: fold(target) },
+        HIRExpr::VectorPush { target, value } => HIRExpr::VectorPush { target: fold(target), value: fold(value) },
+        HIRExpr::VectorPop { target } => HIRExpr::VectorPop { target: fold(target) },
+        HIRExpr::Tensor { elements, shape } => HIRExpr::Tensor { elements: fold_all(elements), shape },
+        HIRExpr::TensorCall { func, args, ty } => HIRExpr::TensorCall { func, args: fold_all(args), ty },
+        literal => literal,
+    }
+}
+
+// what a variable can be replaced with
+pub fn is_literal(expr: &HIRExpr) -> bool {
+    matches!(
+        expr,
+        HIRExpr::Int32(_) | HIRExpr::Int64(_) | HIRExpr::Float32(_) | HIRExpr::Float64(_) | HIRExpr::Bool(_)
+    )
+}
+
+fn is_zero(expr: &HIRExpr) -> bool {
+    match expr {
+        HIRExpr::Int32(v) => *v == 0,
+        HIRExpr::Int64(v) => *v == 0,
+        HIRExpr::Float32(v) => *v == 0.0,
+        HIRExpr::Float64(v) => *v == 0.0,
+        _ => false,
+    }
+}
+
+fn fold_binary(left: &HIRExpr, op: &HIROperator, right: &HIRExpr) -> Option<HIRExpr> {
+    match (left, right) {
+        // `and` / `or` never look at the right side when the left one decides
+        (HIRExpr::Bool(a), _) if matches!(op, HIROperator::And | HIROperator::Or) => {
+            Some(match (op, a) {
+                (HIROperator::And, false) => HIRExpr::Bool(false),
+                (HIROperator::Or, true) => HIRExpr::Bool(true),
+                _ => right.clone(),
+            })
+        }
+        // `x and true` / `x or false` is `x`, the other two would drop whatever `x` does
+        (_, HIRExpr::Bool(b)) if matches!((op, b), (HIROperator::And, true) | (HIROperator::Or, false)) => {
+            Some(left.clone())
+        }
+        (HIRExpr::Int32(a), HIRExpr::Int32(b)) => match op {
+            HIROperator::Plus => a.checked_add(*b),
+            HIROperator::Minus => a.checked_sub(*b),
+            HIROperator::Multiply => a.checked_mul(*b),
+            HIROperator::Divide => a.checked_div(*b),
+            HIROperator::Modulo => a.checked_rem(*b),
+            op => return compare(op, Some(a.cmp(b))),
+        }
+        .map(HIRExpr::Int32),
+        (HIRExpr::Int64(a), HIRExpr::Int64(b)) => match op {
+            HIROperator::Plus => a.checked_add(*b),
+            HIROperator::Minus => a.checked_sub(*b),
+            HIROperator::Multiply => a.checked_mul(*b),
+            HIROperator::Divide => a.checked_div(*b),
+            HIROperator::Modulo => a.checked_rem(*b),
+            op => return compare(op, Some(a.cmp(b))),
+        }
+        .map(HIRExpr::Int64),
+        (HIRExpr::Float32(a), HIRExpr::Float32(b)) => match op {
+            HIROperator::Plus => finite(a + b),
+            HIROperator::Minus => finite(a - b),
+            HIROperator::Multiply => finite(a * b),
+            HIROperator::Divide if *b != 0.0 => finite(a / b),
+            HIROperator::Modulo if *b != 0.0 => finite(a % b),
+            HIROperator::Divide | HIROperator::Modulo => None,
+            op => return compare(op, a.partial_cmp(b)),
+        }
+        .map(HIRExpr::Float32),
+        (HIRExpr::Float64(a), HIRExpr::Float64(b)) => match op {
+            HIROperator::Plus => finite(a + b),
+            HIROperator::Minus => finite(a - b),
+            HIROperator::Multiply => finite(a * b),
+            HIROperator::Divide if *b != 0.0 => finite(a / b),
+            HIROperator::Modulo if *b != 0.0 => finite(a % b),
+            HIROperator::Divide | HIROperator::Modulo => None,
+            op => return compare(op, a.partial_cmp(b)),
+        }
+        .map(HIRExpr::Float64),
+        // bools are ordered as i1s (true is -1), only equality is folded
+        (HIRExpr::Bool(a), HIRExpr::Bool(b)) => equality(op, a == b),
+        (HIRExpr::String(a), HIRExpr::String(b)) => equality(op, a == b),
+        _ => None,
+    }
+}
+
+// the comparisons are ordered, nothing is equal to a NaN
+fn compare(op: &HIROperator, ordering: Option<Ordering>) -> Option<HIRExpr> {
+    let ordering = ordering?;
+    let result = match op {
+        HIROperator::Equals => ordering.is_eq(),
+        HIROperator::NotEquals => ordering.is_ne(),
+        HIROperator::GreaterThan => ordering.is_gt(),
+        HIROperator::LessThan => ordering.is_lt(),
+        HIROperator::GreaterEqual => ordering.is_ge(),
+        HIROperator::LessEqual => ordering.is_le(),
+        _ => return None,
+    };
+    Some(HIRExpr::Bool(result))
+}
+
+fn equality(op: &HIROperator, equal: bool) -> Option<HIRExpr> {
+    match op {
+        HIROperator::Equals => Some(HIRExpr::Bool(equal)),
+        HIROperator::NotEquals => Some(HIRExpr::Bool(!equal)),
+        _ => None,
+    }
+}
+
+// an inf or NaN stays a computation, there is no literal to write it as
+fn finite<T: Into<f64> + Copy>(value: T) -> Option<T> {
+    value.into().is_finite().then_some(value)
+}
+
+// the same conversions as codegen, the narrowing ones only when the value fits
+fn fold_coerce(expr: &HIRExpr, target: &HIRType) -> Option<HIRExpr> {
+    Some(match (expr, target) {
+        (HIRExpr::Int32(v), HIRType::I64) => HIRExpr::Int64(*v as i64),
+        (HIRExpr::Int32(v), HIRType::F64) => HIRExpr::Float64(*v as f64),
+        (HIRExpr::Int64(v), HIRType::F64) => HIRExpr::Float64(*v as f64),
+        (HIRExpr::Float32(v), HIRType::F64) => HIRExpr::Float64(*v as f64),
+        (HIRExpr::Int32(v), HIRType::F32) => HIRExpr::Float32(*v as f32),
+        (HIRExpr::Int64(v), HIRType::F32) => HIRExpr::Float32(*v as f32),
+        (HIRExpr::Float64(v), HIRType::F32) if v.abs() <= f32::MAX as f64 => HIRExpr::Float32(*v as f32),
+        (HIRExpr::Int64(v), HIRType::I32) => HIRExpr::Int32(i32::try_from(*v).ok()?),
+        (HIRExpr::Bool(v), HIRType::I32) => HIRExpr::Int32(*v as i32),
+        (HIRExpr::Bool(v), HIRType::I64) => HIRExpr::Int64(*v as i64),
+        _ => return None,
+    })
+}
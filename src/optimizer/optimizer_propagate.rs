@@ -0,0 +1,130 @@
+// constant propagation, an immutable variable set to a literal is read as that literal
+// until something sets it again, every expression is folded on the way (optimizer_fold)
+// so `set b(i32) = a * 2;` becomes a constant as well once `a` is one,
+// what a loop body or a branch assigns is unknown after it (and inside a loop, from the start)
+
+use super::optimizer_fold::{fold_expr, is_literal};
+use super::PassContext;
+use crate::high_level_ir::{HIRExpr, HIRStatement};
+use std::collections::{HashMap, HashSet};
+
+// variable -> the literal it holds
+type Known = HashMap<String, HIRExpr>;
+
+pub fn propagate_constants(hir: Vec<HIRStatement>, context: &PassContext) -> Vec<HIRStatement> {
+    let mut propagation = Propagation { context, in_function: false };
+    // the top level runs once, in order, before Start
+    propagation.block(hir, &mut Known::new())
+}
+
+struct Propagation<'a> {
+    context: &'a PassContext<'a>,
+    in_function: bool,
+}
+
+impl Propagation<'_> {
+    fn block(&mut self, stmts: Vec<HIRStatement>, known: &mut Known) -> Vec<HIRStatement> {
+        stmts.into_iter().map(|stmt| self.statement(stmt, known)).collect()
+    }
+
+    // a global read inside a function holds whatever the last call left in it
+    fn trackable(&self, name: &str) -> bool {
+        let global = self.in_function && self.context.globals.contains(name);
+        !global && !self.context.mutable_vars.contains(name)
+    }
+
+    fn statement(&mut self, stmt: HIRStatement, known: &mut Known) -> HIRStatement {
+        match stmt {
+            HIRStatement::Assignment { name, value } => {
+                let value = fold_expr(value, known);
+                if self.trackable(&name) && is_literal(&value) {
+                    known.insert(name.clone(), value.clone());
+                } else {
+                    known.remove(&name);
+                }
+                HIRStatement::Assignment { name, value }
+            }
+            HIRStatement::Function { name, params, return_type, start, body } => {
+                // every call starts from its parameters, nothing set elsewhere is known
+                let outer = std::mem::replace(&mut self.in_function, true);
+                let body = self.block(body, &mut Known::new());
+                self.in_function = outer;
+                HIRStatement::Function { name, params, return_type, start, body }
+            }
+            HIRStatement::Print { params } => {
+                HIRStatement::Print { params: params.into_iter().map(|e| fold_expr(e, known)).collect() }
+            }
+            HIRStatement::ExprStatement { expr } => HIRStatement::ExprStatement { expr: fold_expr(expr, known) },
+            HIRStatement::If { condition, body, else_body } => {
+                let condition = fold_expr(condition, known);
+                let body = self.block(body, &mut known.clone());
+                let else_body = else_body.map(|else_body| self.block(else_body, &mut known.clone()));
+                forget_assigned(known, body.iter().chain(else_body.iter().flatten()));
+                HIRStatement::If { condition, body, else_body }
+            }
+            HIRStatement::Loop { body } => {
+                forget_assigned(known, &body);
+                HIRStatement::Loop { body: self.block(body, &mut known.clone()) }
+            }
+            HIRStatement::While { condition, body } => {
+                forget_assigned(known, &body);
+                let condition = fold_expr(condition, known);
+                HIRStatement::While { condition, body: self.block(body, &mut known.clone()) }
+            }
+            HIRStatement::Return { value } => HIRStatement::Return { value: value.map(|v| fold_expr(v, known)) },
+            HIRStatement::IndexAssign { name, index, value } => {
+                HIRStatement::IndexAssign { name, index: fold_expr(index, known), value: fold_expr(value, known) }
+            }
+            // the target is written, not read
+            HIRStatement::Input { target } => {
+                if let HIRExpr::Identifier(name) = &target {
+                    known.remove(name);
+                }
+                HIRStatement::Input { target }
+            }
+            // a handled failure skips the assignment, the variable may hold either value
+            HIRStatement::OnError { stmt, message } => {
+                let stmt = self.statement(*stmt, known);
+                forget_assigned(known, std::slice::from_ref(&stmt));
+                HIRStatement::OnError { stmt: Box::new(stmt), message }
+            }
+            HIRStatement::Located { span, stmt } => {
+                HIRStatement::Located { span, stmt: Box::new(self.statement(*stmt, known)) }
+            }
+            HIRStatement::Assert { left, op, right, ty, source } => HIRStatement::Assert {
+                left: fold_expr(left, known),
+                op,
+                right: fold_expr(right, known),
+                ty,
+                source,
+            },
+            HIRStatement::Break => HIRStatement::Break,
+        }
+    }
+}
+
+fn forget_assigned<'a>(known: &mut Known, stmts: impl IntoIterator<Item = &'a HIRStatement>) {
+    let mut names = HashSet::new();
+    collect_assigned(stmts, &mut names);
+    known.retain(|name, _| !names.contains(name));
+}
+
+// every variable the statements can set, nested blocks included
+fn collect_assigned<'a>(stmts: impl IntoIterator<Item = &'a HIRStatement>, names: &mut HashSet<String>) {
+    for stmt in stmts {
+        match stmt.unlocated() {
+            HIRStatement::Assignment { name, .. } => {
+                names.insert(name.clone());
+            }
+            HIRStatement::Input { target: HIRExpr::Identifier(name) } => {
+                names.insert(name.clone());
+            }
+            HIRStatement::If { body, else_body, .. } => {
+                collect_assigned(body.iter().chain(else_body.iter().flatten()), names);
+            }
+            HIRStatement::Loop { body } | HIRStatement::While { body, .. } => collect_assigned(body, names),
+            HIRStatement::OnError { stmt, .. } => collect_assigned([stmt.as_ref()], names),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,34 @@
+// statements after a `break` or `return` never run and are dropped,
+// so is everything after an `if` / `else` that ends in one on both sides
+
+use super::{map_blocks, PassContext};
+use crate::high_level_ir::HIRStatement;
+
+pub fn remove_unreachable(hir: Vec<HIRStatement>, _context: &PassContext) -> Vec<HIRStatement> {
+    block(hir)
+}
+
+fn block(stmts: Vec<HIRStatement>) -> Vec<HIRStatement> {
+    let mut out = Vec::with_capacity(stmts.len());
+    for stmt in stmts {
+        let stmt = map_blocks(stmt, block);
+        let ends = ends_block(&stmt);
+        out.push(stmt);
+        if ends {
+            break;
+        }
+    }
+    out
+}
+
+// control never reaches the statement after this one,
+// the blocks inside were already cut, so only their last statement can end them
+fn ends_block(stmt: &HIRStatement) -> bool {
+    match stmt.unlocated() {
+        HIRStatement::Break | HIRStatement::Return { .. } => true,
+        HIRStatement::If { body, else_body: Some(else_body), .. } => {
+            body.last().is_some_and(ends_block) && else_body.last().is_some_and(ends_block)
+        }
+        _ => false,
+    }
+}
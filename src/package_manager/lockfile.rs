@@ -0,0 +1,70 @@
+//project.lock, the exact versions `axon install` picked for every package (dependencies of
+//dependencies included), later installs reuse them as long as the manifest still allows them
+
+use super::version::Version;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const LOCK_FILE: &str = "project.lock";
+
+#[derive(Default)]
+pub struct Lockfile {
+    pub packages: BTreeMap<String, Version>,
+}
+
+impl Lockfile {
+    // a missing lockfile is an empty one, lines that do not parse are ignored
+    pub fn load(project_dir: &Path) -> Lockfile {
+        let mut lock = Lockfile::default();
+        let Ok(content) = fs::read_to_string(project_dir.join(LOCK_FILE)) else {
+            return lock;
+        };
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim();
+            let name = key.strip_prefix('_').and_then(|k| k.strip_suffix('_')).unwrap_or(key);
+            let version = Version::parse(value.trim().trim_matches('"'));
+            if let (false, Some(version)) = (name.is_empty(), version) {
+                lock.packages.insert(name.to_string(), version);
+            }
+        }
+        lock
+    }
+
+    pub fn save(&self, project_dir: &Path) -> io::Result<()> {
+        let mut out = String::from("?? generated by axon install, do not edit by hand\n__Locked__\n");
+        for (name, version) in &self.packages {
+            out.push_str(&format!("_{}_ = \"{}\"\n", name, version));
+        }
+        fs::write(project_dir.join(LOCK_FILE), out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_saved_lockfile_loads_back() {
+        let dir = std::env::temp_dir().join(format!("axon_lockfile_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        assert!(Lockfile::load(&dir).packages.is_empty());
+
+        let mut lock = Lockfile::default();
+        lock.packages.insert("tensors".to_string(), Version::parse("1.2.0").unwrap());
+        lock.packages.insert("plots".to_string(), Version::parse("0.3.10").unwrap());
+        lock.save(&dir).unwrap();
+        assert_eq!(Lockfile::load(&dir).packages, lock.packages);
+
+        // a line that was edited into something else is dropped, the rest still loads
+        let text = fs::read_to_string(dir.join(LOCK_FILE)).unwrap().replace("0.3.10", "latest");
+        fs::write(dir.join(LOCK_FILE), text).unwrap();
+        let loaded = Lockfile::load(&dir).packages;
+        assert_eq!(loaded.keys().collect::<Vec<_>>(), vec!["tensors"]);
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,498 @@
+//package manager,
+//a package is an ordinary project without a Start(), `axon publish` copies it into the local
+//registry (~/.axon/registry/<name>/<version>/, or $AXON_REGISTRY), `axon install` resolves the
+//manifest's dependencies against that registry, copies the chosen versions into packages/
+//and pins them in project.lock, the module loader then finds them through `use <package>;`
+
+pub mod lockfile;
+pub mod version;
+
+use crate::manifest::{is_package_name, parse_manifest, Manifest, DEFAULT_ENTRY, MANIFEST_FILE, PACKAGES_DIR};
+use crate::parser::parser_error::{ErrorKind, ParseError, Severity};
+use crate::terminal::print_error;
+use console::style;
+use lockfile::Lockfile;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use version::{Version, VersionReq};
+
+const REGISTRY_ENV: &str = "AXON_REGISTRY";
+
+fn pkg_error(message: String, suggestion: Option<String>) -> ParseError {
+    ParseError::new(ErrorKind::Syntax, message, 0, 0, None, suggestion, Severity::Error)
+}
+
+fn registry_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var(REGISTRY_ENV) {
+        return PathBuf::from(dir);
+    }
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    Path::new(&home).join(".axon").join("registry")
+}
+
+// published versions of a package, newest first
+fn available_versions(registry: &Path, name: &str) -> Vec<Version> {
+    let Ok(entries) = fs::read_dir(registry.join(name)) else {
+        return Vec::new();
+    };
+    let mut versions: Vec<Version> = entries
+        .flatten()
+        .filter(|entry| entry.path().join(MANIFEST_FILE).exists())
+        .filter_map(|entry| Version::parse(&entry.file_name().to_string_lossy()))
+        .collect();
+    versions.sort_by(|a, b| b.cmp(a));
+    versions
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+// only what another project needs to compile against the package
+fn copy_package(from: &Path, to: &Path) -> io::Result<()> {
+    if to.exists() {
+        fs::remove_dir_all(to)?;
+    }
+    fs::create_dir_all(to)?;
+    fs::copy(from.join(MANIFEST_FILE), to.join(MANIFEST_FILE))?;
+    copy_dir(&from.join("src"), &to.join("src"))
+}
+
+pub fn create_pack(name: &str) -> i32 {
+    if !is_package_name(name) {
+        print_error(
+            "Create",
+            &[pkg_error(
+                format!("\x1b[31m[ERR-PKG-006] '{}' cannot be used as a package name\x1b[0m", name),
+                Some("Package names are imported with `use <name>;`, so use letters, digits and _ and avoid keywords.".to_string()),
+            )],
+        );
+        return 1;
+    }
+    if Path::new(name).exists() {
+        print_error("Create", &[pkg_error(format!("Package '{}' already exists.", name), None)]);
+        return 1;
+    }
+    let manifest = format!(
+        "__Project__\n_name_ = \"{}\"\n_version_ = \"0.1.0\"\n_entry_ = \"{}\"\n\n__Dependencies__\n",
+        name, DEFAULT_ENTRY
+    );
+    let init = format!(
+        "?? everything declared here is available as {name}.<item> after `use {name};`\n\
+cast answer() -> i32 >>\n    return 42;\n<<\n\n\
+cast test_answer() >>\n    assert_eq(answer(), 42);\n<<\n"
+    );
+    let written = fs::create_dir_all(Path::new(name).join("src"))
+        .and_then(|_| fs::write(Path::new(name).join(MANIFEST_FILE), manifest))
+        .and_then(|_| fs::write(Path::new(name).join(DEFAULT_ENTRY), init));
+    if let Err(e) = written {
+        print_error("Create", &[pkg_error(format!("Failed to create package '{}': {}", name, e), None)]);
+        return 1;
+    }
+    println!(
+        "{} Package '{}' created successfully! Publish it with: axon publish",
+        style("✔").green().bold(),
+        style(name).yellow().bold()
+    );
+    0
+}
+
+pub fn publish(args: &[String]) -> i32 {
+    let manifest = match Manifest::load(Path::new(".")) {
+        Ok(manifest) => manifest,
+        Err(errors) => {
+            print_error("Publish", &errors);
+            return 1;
+        }
+    };
+    // the name becomes a directory in the registry
+    if !is_package_name(&manifest.name) {
+        print_error(
+            "Publish",
+            &[pkg_error(
+                format!("\x1b[31m[ERR-PKG-006] '{}' cannot be used as a package name\x1b[0m", manifest.name),
+                Some("Rename the project in project.asml, it is installed and imported under its _name_.".to_string()),
+            )],
+        );
+        return 1;
+    }
+    let force = args.iter().any(|a| a == "--force");
+    let dest = registry_dir().join(&manifest.name).join(&manifest.version);
+    if dest.exists() && !force {
+        print_error(
+            "Publish",
+            &[pkg_error(
+                format!(
+                    "\x1b[31m[ERR-PKG-004] {} v{} is already in the registry\x1b[0m",
+                    manifest.name, manifest.version
+                ),
+                Some("Bump _version_ in project.asml, or overwrite it with: axon publish --force".to_string()),
+            )],
+        );
+        return 1;
+    }
+    if let Err(e) = copy_package(Path::new("."), &dest) {
+        print_error(
+            "Publish",
+            &[pkg_error(format!("\x1b[31m[ERR-PKG-005] Failed to copy the package: {}\x1b[0m", e), None)],
+        );
+        return 1;
+    }
+    println!(
+        "{} Published {} v{} to {}",
+        style("✔").green().bold(),
+        style(&manifest.name).yellow().bold(),
+        manifest.version,
+        dest.display()
+    );
+    0
+}
+
+// `axon install` installs what the manifest asks for, `axon install name[@req]` adds a dependency first
+pub fn install(args: &[String]) -> i32 {
+    let project_dir = Path::new(".");
+    let registry = registry_dir();
+    let mut manifest = match Manifest::load(project_dir) {
+        Ok(manifest) => manifest,
+        Err(errors) => {
+            print_error("Install", &errors);
+            return 1;
+        }
+    };
+
+    // the new dependency is only written to project.asml once it resolved
+    let mut updated_manifest = None;
+    if let Some(spec) = args.iter().find(|a| !a.starts_with("--")) {
+        let parsed = add_dependency(&manifest, &registry, spec).and_then(|src| parse_manifest(&src));
+        match parsed {
+            Ok(parsed) => {
+                updated_manifest = Some(parsed.src.clone());
+                manifest = parsed;
+            }
+            Err(errors) => {
+                print_error("Install", &errors);
+                return 1;
+            }
+        }
+    }
+
+    // a lockfile that no longer fits (say a new dependency needs a newer release) is ignored
+    let lock = Lockfile::load(project_dir);
+    let resolved = resolve(&manifest, &registry, &lock).or_else(|_| resolve(&manifest, &registry, &Lockfile::default()));
+    let resolved = match resolved {
+        Ok(resolved) => resolved,
+        Err(errors) => {
+            print_error("Install", &errors);
+            return 1;
+        }
+    };
+
+    if let Some(Err(e)) = updated_manifest.map(|src| fs::write(project_dir.join(MANIFEST_FILE), src)) {
+        print_error(
+            "Install",
+            &[pkg_error(format!("\x1b[31m[ERR-PKG-007] Failed to update {}: {}\x1b[0m", MANIFEST_FILE, e), None)],
+        );
+        return 1;
+    }
+
+    let packages_dir = project_dir.join(PACKAGES_DIR);
+    for (name, version) in &resolved {
+        let dest = packages_dir.join(name);
+        let installed = Manifest::load(&dest).ok().and_then(|m| Version::parse(&m.version));
+        if installed == Some(*version) {
+            println!("{} {} v{} is up to date", style("•").dim(), name, version);
+            continue;
+        }
+        if let Err(e) = copy_package(&registry.join(name).join(version.to_string()), &dest) {
+            print_error(
+                "Install",
+                &[pkg_error(format!("\x1b[31m[ERR-PKG-008] Failed to install {}: {}\x1b[0m", name, e), None)],
+            );
+            return 1;
+        }
+        println!("{} Installed {} v{}", style("✔").green().bold(), style(name).yellow().bold(), version);
+    }
+
+    // packages that nothing depends on anymore
+    if let Ok(entries) = fs::read_dir(&packages_dir) {
+        for dir in entries.flatten().map(|e| e.path()) {
+            let name = dir.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            if dir.join(MANIFEST_FILE).exists() && !resolved.contains_key(&name) {
+                fs::remove_dir_all(&dir).ok();
+                println!("{} Removed {}", style("-").red().bold(), name);
+            }
+        }
+    }
+
+    if let Err(e) = (Lockfile { packages: resolved }).save(project_dir) {
+        print_error(
+            "Install",
+            &[pkg_error(format!("\x1b[31m[ERR-PKG-009] Failed to write the lockfile: {}\x1b[0m", e), None)],
+        );
+        return 1;
+    }
+    0
+}
+
+// picks one version per package for the manifest's dependencies and everything they depend on,
+// a locked version is kept while it still satisfies every requirement, otherwise the newest match wins
+fn resolve(
+    manifest: &Manifest,
+    registry: &Path,
+    lock: &Lockfile,
+) -> Result<BTreeMap<String, Version>, Vec<ParseError>> {
+    let mut queue: VecDeque<(String, VersionReq, String)> = manifest
+        .dependencies
+        .iter()
+        .map(|dep| (dep.name.clone(), dep.req, manifest.name.clone()))
+        .collect();
+    let mut resolved: BTreeMap<String, (Version, String)> = BTreeMap::new();
+    let mut errors = Vec::new();
+
+    while let Some((name, req, requester)) = queue.pop_front() {
+        // the name is joined onto registry and packages/ paths, a manifest in the registry could have been
+        // written by hand (or by an older axon), so it is checked here too
+        if !is_package_name(&name) {
+            errors.push(pkg_error(
+                format!(
+                    "\x1b[31m[ERR-PKG-006] '{}' (needed by {}) cannot be used as a package name\x1b[0m",
+                    name, requester
+                ),
+                None,
+            ));
+            continue;
+        }
+        if let Some((picked, picked_for)) = resolved.get(&name) {
+            if !req.matches(picked) {
+                errors.push(pkg_error(
+                    format!(
+                        "\x1b[31m[ERR-PKG-003] {} needs {} {}, but {} v{} was already picked for {}\x1b[0m",
+                        requester, name, req, name, picked, picked_for
+                    ),
+                    Some("Loosen one of the two requirements so a single version satisfies both.".to_string()),
+                ));
+            }
+            continue;
+        }
+        let versions = available_versions(registry, &name);
+        if versions.is_empty() {
+            errors.push(pkg_error(
+                format!(
+                    "\x1b[31m[ERR-PKG-001] Package '{}' (needed by {}) is not in the registry at {}\x1b[0m",
+                    name,
+                    requester,
+                    registry.display()
+                ),
+                Some(format!("Publish it from the package's directory with: axon publish (or set {})", REGISTRY_ENV)),
+            ));
+            continue;
+        }
+        let locked = lock.packages.get(&name).filter(|v| req.matches(v) && versions.contains(v));
+        let Some(version) = locked.or_else(|| versions.iter().find(|v| req.matches(v))).copied() else {
+            let list: Vec<String> = versions.iter().map(|v| v.to_string()).collect();
+            errors.push(pkg_error(
+                format!(
+                    "\x1b[31m[ERR-PKG-002] No version of '{}' matches {} (needed by {}), available: {}\x1b[0m",
+                    name,
+                    req,
+                    requester,
+                    list.join(", ")
+                ),
+                None,
+            ));
+            continue;
+        };
+        match Manifest::load(&registry.join(&name).join(version.to_string())) {
+            Ok(package) => queue.extend(
+                package
+                    .dependencies
+                    .iter()
+                    .map(|dep| (dep.name.clone(), dep.req, format!("{} v{}", name, version))),
+            ),
+            Err(mut package_errors) => errors.append(&mut package_errors),
+        }
+        resolved.insert(name, (version, requester));
+    }
+
+    if errors.is_empty() {
+        Ok(resolved.into_iter().map(|(name, (version, _))| (name, version)).collect())
+    } else {
+        Err(errors)
+    }
+}
+
+// the manifest text with `_name_ = "req"` in __Dependencies__, replacing an existing entry for the same package
+fn add_dependency(manifest: &Manifest, registry: &Path, spec: &str) -> Result<String, Vec<ParseError>> {
+    let (name, req) = spec.split_once('@').map_or((spec, None), |(name, req)| (name, Some(req)));
+    // before the name is used to look into the registry
+    if !is_package_name(name) {
+        return Err(vec![pkg_error(
+            format!("\x1b[31m[ERR-PKG-006] '{}' cannot be used as a package name\x1b[0m", name),
+            None,
+        )]);
+    }
+    let req = match req {
+        Some(req) => match VersionReq::parse(req) {
+            Some(_) => req.to_string(),
+            None => {
+                return Err(vec![pkg_error(
+                    format!("\x1b[31m[ERR-PKG-010] '{}' is not a version requirement\x1b[0m", req),
+                    Some("For example: axon install tensors@1.2.0".to_string()),
+                )]);
+            }
+        },
+        None => match available_versions(registry, name).first() {
+            Some(latest) => latest.to_string(),
+            None => {
+                return Err(vec![pkg_error(
+                    format!(
+                        "\x1b[31m[ERR-PKG-001] Package '{}' is not in the registry at {}\x1b[0m",
+                        spec,
+                        registry.display()
+                    ),
+                    None,
+                )]);
+            }
+        },
+    };
+
+    let line = format!("_{}_ = \"{}\"", name, req);
+    let src = &manifest.src;
+    Ok(if let Some(dep) = manifest.dependencies.iter().find(|d| d.name == name) {
+        format!("{}{}{}", &src[..dep.span.start], line, &src[dep.span.end..])
+    } else if let Some(pos) = src.find("__Dependencies__") {
+        let insert_at = src[pos..].find('\n').map_or(src.len(), |i| pos + i + 1);
+        let newline = if insert_at == src.len() && !src.ends_with('\n') { "\n" } else { "" };
+        format!("{}{}{}\n{}", &src[..insert_at], newline, line, &src[insert_at..])
+    } else {
+        let separator = if src.ends_with('\n') { "\n" } else { "\n\n" };
+        format!("{}{}__Dependencies__\n{}\n", src, separator, line)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a registry of its own per test, the tests run in parallel
+    fn registry(test: &str, packages: &[(&str, &str, &str)]) -> PathBuf {
+        let registry = std::env::temp_dir().join(format!("axon_registry_{}_{}", std::process::id(), test));
+        fs::remove_dir_all(&registry).ok();
+        for (name, version, deps) in packages {
+            let dir = registry.join(name).join(version);
+            fs::create_dir_all(&dir).unwrap();
+            let manifest = format!(
+                "__Project__\n_name_ = \"{}\"\n_version_ = \"{}\"\n\n__Dependencies__\n{}",
+                name, version, deps
+            );
+            fs::write(dir.join(MANIFEST_FILE), manifest).unwrap();
+        }
+        registry
+    }
+
+    fn project(deps: &str) -> Manifest {
+        parse_manifest(&format!("__Project__\n_name_ = \"app\"\n\n__Dependencies__\n{}", deps)).ok().unwrap()
+    }
+
+    fn picked(resolved: &BTreeMap<String, Version>) -> Vec<String> {
+        resolved.iter().map(|(name, version)| format!("{} {}", name, version)).collect()
+    }
+
+    fn error_codes(result: Result<BTreeMap<String, Version>, Vec<ParseError>>) -> Vec<String> {
+        result.err().unwrap_or_default().into_iter().filter_map(|e| e.code).collect()
+    }
+
+    #[test]
+    fn the_newest_matching_versions_are_picked_for_every_package() {
+        let registry = registry(
+            "newest",
+            &[
+                ("tensors", "1.2.0", "_blas_ = \"0.3.0\"\n"),
+                ("tensors", "1.4.1", "_blas_ = \"0.3.0\"\n"),
+                ("tensors", "2.0.0", ""),
+                ("blas", "0.3.2", ""),
+                ("blas", "0.4.0", ""),
+            ],
+        );
+        let resolved = resolve(&project("_tensors_ = \"1.2.0\"\n"), &registry, &Lockfile::default()).ok().unwrap();
+        assert_eq!(picked(&resolved), vec!["blas 0.3.2", "tensors 1.4.1"]);
+        fs::remove_dir_all(&registry).ok();
+    }
+
+    #[test]
+    fn a_locked_version_is_kept_while_it_still_matches() {
+        let registry = registry("locked", &[("tensors", "1.2.0", ""), ("tensors", "1.4.1", "")]);
+        let mut lock = Lockfile::default();
+        lock.packages.insert("tensors".to_string(), Version::parse("1.2.0").unwrap());
+        let kept = resolve(&project("_tensors_ = \"1.0.0\"\n"), &registry, &lock).ok().unwrap();
+        assert_eq!(picked(&kept), vec!["tensors 1.2.0"]);
+        let bumped = resolve(&project("_tensors_ = \"1.3.0\"\n"), &registry, &lock).ok().unwrap();
+        assert_eq!(picked(&bumped), vec!["tensors 1.4.1"]);
+        fs::remove_dir_all(&registry).ok();
+    }
+
+    #[test]
+    fn two_requirements_one_version_cannot_satisfy_conflict() {
+        let registry = registry(
+            "conflict",
+            &[
+                ("models", "1.0.0", "_tensors_ = \"=1.0.0\"\n"),
+                ("plots", "1.0.0", "_tensors_ = \"2.0.0\"\n"),
+                ("tensors", "1.0.0", ""),
+                ("tensors", "2.0.0", ""),
+            ],
+        );
+        let manifest = project("_models_ = \"1.0.0\"\n_plots_ = \"1.0.0\"\n");
+        let errors = resolve(&manifest, &registry, &Lockfile::default()).err().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code.as_deref(), Some("ERR-PKG-003"));
+        assert!(errors[0].message.contains("plots v1.0.0 needs tensors ^2.0.0"), "{}", errors[0].message);
+        assert!(errors[0].message.contains("picked for models v1.0.0"), "{}", errors[0].message);
+        fs::remove_dir_all(&registry).ok();
+    }
+
+    #[test]
+    fn unavailable_packages_are_reported() {
+        let registry = registry("unavailable", &[("tensors", "1.2.0", "_blas_ = \"1.0.0\"\n")]);
+        let resolve = |deps: &str| resolve(&project(deps), &registry, &Lockfile::default());
+        assert_eq!(error_codes(resolve("_plots_ = \"1.0.0\"\n")), vec!["ERR-PKG-001"]);
+        assert_eq!(error_codes(resolve("_tensors_ = \"2.0.0\"\n")), vec!["ERR-PKG-002"]);
+        // a dependency of a dependency is looked up like any other
+        assert_eq!(error_codes(resolve("_tensors_ = \"1.0.0\"\n")), vec!["ERR-PKG-001"]);
+        fs::remove_dir_all(&registry).ok();
+    }
+
+    #[test]
+    fn install_adds_or_replaces_the_dependency_line() {
+        let registry = registry("add", &[("tensors", "1.0.0", ""), ("tensors", "1.3.0", "")]);
+        let add = |src: &str, spec: &str| {
+            add_dependency(&parse_manifest(src).ok().unwrap(), &registry, spec).map_err(|e| e[0].code.clone().unwrap())
+        };
+        let bare = "__Project__\n_name_ = \"app\"\n";
+        assert_eq!(add(bare, "tensors").unwrap(), format!("{}\n__Dependencies__\n_tensors_ = \"1.3.0\"\n", bare));
+        let with_deps = format!("{}\n__Dependencies__\n_plots_ = \"1.0.0\"\n", bare);
+        assert_eq!(
+            add(&with_deps, "tensors@=1.0.0").unwrap(),
+            format!("{}\n__Dependencies__\n_tensors_ = \"=1.0.0\"\n_plots_ = \"1.0.0\"\n", bare)
+        );
+        assert_eq!(add(&with_deps, "plots@2.0.0").unwrap(), format!("{}\n__Dependencies__\n_plots_ = \"2.0.0\"\n", bare));
+        assert_eq!(add(bare, "tensors@latest").unwrap_err(), "ERR-PKG-010");
+        assert_eq!(add(bare, "../tensors").unwrap_err(), "ERR-PKG-006");
+        assert_eq!(add(bare, "plots").unwrap_err(), "ERR-PKG-001");
+        fs::remove_dir_all(&registry).ok();
+    }
+}
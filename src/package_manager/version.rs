@@ -0,0 +1,111 @@
+//package versions and the version requirements dependencies are declared with,
+//"1.2.0" and "^1.2.0" accept any compatible release (same major, or same minor while the major is 0),
+//"=1.2.0" only that exact release, "*" any release
+
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VersionReq {
+    Compatible(Version),
+    Exact(Version),
+    Any,
+}
+
+impl Version {
+    // major.minor.patch
+    pub fn parse(s: &str) -> Option<Version> {
+        let mut parts = s.trim().split('.').map(|p| {
+            if !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()) { p.parse().ok() } else { None }
+        });
+        let version = Version {
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next()??,
+        };
+        parts.next().is_none().then_some(version)
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl VersionReq {
+    pub fn parse(s: &str) -> Option<VersionReq> {
+        let s = s.trim();
+        if s == "*" {
+            Some(VersionReq::Any)
+        } else if let Some(exact) = s.strip_prefix('=') {
+            Version::parse(exact).map(VersionReq::Exact)
+        } else {
+            Version::parse(s.strip_prefix('^').unwrap_or(s)).map(VersionReq::Compatible)
+        }
+    }
+
+    pub fn matches(&self, v: &Version) -> bool {
+        match self {
+            VersionReq::Any => true,
+            VersionReq::Exact(req) => v == req,
+            VersionReq::Compatible(req) if req.major == 0 => v.major == 0 && v.minor == req.minor && v >= req,
+            VersionReq::Compatible(req) => v.major == req.major && v >= req,
+        }
+    }
+}
+
+impl Display for VersionReq {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionReq::Compatible(v) => write!(f, "^{}", v),
+            VersionReq::Exact(v) => write!(f, "={}", v),
+            VersionReq::Any => write!(f, "*"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    #[test]
+    fn versions_are_three_numbers() {
+        assert_eq!(v(" 1.12.3 "), Version { major: 1, minor: 12, patch: 3 });
+        for bad in ["1.2", "1.2.3.4", "1.x.0", "1..0", "-1.0.0", "v1.0.0", ""] {
+            assert_eq!(Version::parse(bad), None, "{}", bad);
+        }
+        assert!(v("1.10.0") > v("1.9.9"));
+    }
+
+    #[test]
+    fn requirements_parse_and_print_back() {
+        for (written, printed) in [("1.2.0", "^1.2.0"), ("^1.2.0", "^1.2.0"), ("=1.2.0", "=1.2.0"), ("*", "*")] {
+            assert_eq!(VersionReq::parse(written).unwrap().to_string(), printed);
+        }
+        assert_eq!(VersionReq::parse("^1.2"), None);
+        assert_eq!(VersionReq::parse(">=1.0.0"), None);
+    }
+
+    #[test]
+    fn requirements_match_compatible_releases() {
+        let matches = |req: &str, version: &str| VersionReq::parse(req).unwrap().matches(&v(version));
+        assert!(matches("1.2.0", "1.2.0") && matches("1.2.0", "1.2.7") && matches("1.2.0", "1.9.0"));
+        assert!(!matches("1.2.0", "1.1.9") && !matches("1.2.0", "2.0.0"));
+        // below 1.0.0 the minor version is the breaking one
+        assert!(matches("0.3.1", "0.3.4"));
+        assert!(!matches("0.3.1", "0.3.0") && !matches("0.3.1", "0.4.0") && !matches("0.3.1", "1.3.1"));
+        assert!(matches("=1.2.0", "1.2.0") && !matches("=1.2.0", "1.2.1"));
+        assert!(matches("*", "0.0.1") && matches("*", "12.0.0"));
+    }
+}
@@ -1,6 +1,9 @@
 //the same logic as in semantic_error.rs, 
 //we collect the error vector and output them in main.rs
 
+use crate::backend::CompilerError;
+use crate::error_codes::lookup;
+use crate::semantic::semantic_error::SemanticError;
 use std::fmt::{self, Display, Formatter};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -10,6 +13,8 @@ pub enum ErrorKind {
     Type,
     Codegen,
     Linker,
+    // the program ran and failed, see Diagnostics::exit_code
+    Runtime,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -21,12 +26,17 @@ pub enum Severity {
 #[derive(Debug, Clone)]
 pub struct ParseError {
     pub kind: ErrorKind,
+    // `ERR-SYN-001`, split off the message so tools do not have to parse it out
+    pub code: Option<String>,
+    // plain text, without the code or terminal colors
     pub message: String,
     pub start: usize,
     pub end: usize,
     pub src: Option<String>,
     pub suggestion: Option<String>,
     pub severity: Severity,
+    // path of the file `src` came from, filled in by the module loader's source map
+    pub file: Option<String>,
 }
 
 impl ParseError {
@@ -39,34 +49,75 @@ impl ParseError {
         suggestion: Option<String>,
         severity: Severity,
     ) -> Self {
+        let (code, message) = split_code(&message);
+        debug_assert!(code.as_deref().is_none_or(|c| lookup(c).is_some()), "unregistered error code {:?}", code);
         ParseError {
             kind,
+            code,
             message,
             start,
             end,
             src,
-            suggestion,
+            suggestion: suggestion.map(|s| strip_ansi(&s)),
             severity,
+            file: None,
         }
     }
 
     pub fn eof(message: String) -> Self {
+        let (code, message) = split_code(&message);
+        debug_assert!(code.as_deref().is_none_or(|c| lookup(c).is_some()), "unregistered error code {:?}", code);
         ParseError {
             kind: ErrorKind::Syntax,
+            code,
             message,
             start: 0,
             end: 0,
             src: None,
             suggestion: None,
             severity: Severity::Error,
+            file: None,
         }
     }
 }
 
+// messages are written with their code and colors inline, `\x1b[31m[ERR-SYN-001] Invalid ...\x1b[0m`,
+// the code goes into its own field and the escape sequences are dropped
+fn split_code(message: &str) -> (Option<String>, String) {
+    let plain = strip_ansi(message);
+    let plain = plain.trim();
+    match plain.strip_prefix('[').and_then(|m| m.split_once(']')) {
+        Some((code, rest)) if code.starts_with("ERR-") || code.starts_with("WARN-") => {
+            (Some(code.to_string()), rest.trim().to_string())
+        }
+        _ => (None, plain.to_string()),
+    }
+}
+
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // ESC [ params letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
 impl Display for ParseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        
-        writeln!(f, "{}", self.message)?;
+        match &self.code {
+            Some(code) => writeln!(f, "\x1b[31m[{}]\x1b[0m {}", code, self.message)?,
+            None => writeln!(f, "{}", self.message)?,
+        }
         if let Some(src) = &self.src {
             let start_char = src[..self.start].chars().count();
             let end_char = src[..self.end].chars().count();
@@ -118,3 +169,24 @@ impl<T> ParseResult<T> {
         ParseResult { result, errors }
     }
 }
+
+impl From<&SemanticError> for ParseError {
+    fn from(e: &SemanticError) -> Self {
+        ParseError::new(ErrorKind::Semantic, e.message.clone(), e.start, e.end, e.src.clone(), None, Severity::Error)
+    }
+}
+
+impl ParseError {
+    pub fn from_compiler_error(e: &CompilerError) -> Self {
+        let (start, end) = e.1.as_ref().map_or((0, 0), |span| (span.start, span.end));
+        ParseError::new(
+            ErrorKind::Codegen,
+            e.0.clone(),
+            start,
+            end,
+            None,
+            None,
+            Severity::Error,
+        )
+    }
+}
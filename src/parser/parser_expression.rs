@@ -1,7 +1,7 @@
 //parsing expressons(types, binary op... )
 
 
-use crate::ast::{Expr, Operator};
+use crate::ast::{Expr, Logic, Operator};
 use crate::lexer_tokenizer::Token;
 use crate::parser::{
     parser_error::{ErrorKind, ParseError, ParseResult, Severity},
@@ -14,9 +14,10 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_binary_op(&mut self, min_precedence: i32) -> ParseResult<Expr> {
+        let start = self.token_start();
         let left_res = self.parse_term();
         if let Some(left) = left_res.result {
-            let mut result = left;
+            let mut result = self.located(left, start);
             let mut errors = left_res.errors;
 
             while let Some(op) = self.current().and_then(|t| self.token_to_operator(t)) {
@@ -28,11 +29,12 @@ impl<'a> Parser<'a> {
                 let next_min_prec = if assoc == Assoc::Left { prec + 1 } else { prec };
                 let right_res = self.parse_binary_op(next_min_prec);
                 if let Some(right) = right_res.result {
-                    result = Expr::BinaryOp {
+                    let binary = Expr::BinaryOp {
                         left: Box::new(result),
                         op,
                         right: Box::new(right),
                     };
+                    result = self.located(binary, start);
                     errors.extend(right_res.errors);
                 } else {
                     errors.extend(right_res.errors);
@@ -54,6 +56,24 @@ impl<'a> Parser<'a> {
         }
     }
 
+    // literals stay bare so later passes can still match on them directly
+    fn located(&self, expr: Expr, start: usize) -> Expr {
+        match expr {
+            Expr::Int32(_)
+            | Expr::Int64(_)
+            | Expr::Float32(_)
+            | Expr::Float64(_)
+            | Expr::String(_)
+            | Expr::Bool(_)
+            | Expr::Vector(_)
+            | Expr::Located { .. } => expr,
+            expr => Expr::Located {
+                span: self.span_from(start),
+                expr: Box::new(expr),
+            },
+        }
+    }
+
     pub fn parse_term(&mut self) -> ParseResult<Expr> {
         let current = self.current().cloned();
         let span = self.tokens.get(self.pos).map(|t| t.span.clone());
@@ -78,9 +98,41 @@ impl<'a> Parser<'a> {
                 self.advance();
                 ParseResult::ok(Expr::Bool(false))
             }
-            Some(Token::Identifier(id)) => {
+            Some(Token::Identifier(mut id)) => {
+                self.advance();
+                // `calc.pi` / `util.calc` name something in another module, `v.len()` stays a method call
+                while self.current() == Some(&Token::Dot) {
+                    let next = self.tokens.get(self.pos + 1).map(|t| &t.token);
+                    let after = self.tokens.get(self.pos + 2).map(|t| &t.token);
+                    match next {
+                        Some(Token::Identifier(part)) if part != "Err" && after != Some(&Token::LParen) => {
+                            id = format!("{}.{}", id, part);
+                            self.pos += 2;
+                        }
+                        _ => break,
+                    }
+                }
+                if self.current() == Some(&Token::LParen) {
+                    let args_res = self.parse_call_args();
+                    let Some(args) = args_res.result else {
+                        return ParseResult { result: None, errors: args_res.errors };
+                    };
+                    let mut postfix_res = self.parse_postfix(Expr::Call { name: id, args });
+                    let mut errors = args_res.errors;
+                    errors.append(&mut postfix_res.errors);
+                    ParseResult { result: postfix_res.result, errors }
+                } else {
+                    self.parse_postfix(Expr::Identifier(id))
+                }
+            }
+            Some(Token::Not) => {
+                let start = self.token_start();
                 self.advance();
-                ParseResult::ok(Expr::Identifier(id))
+                let operand = self.parse_binary_op(COMPARE_PRECEDENCE);
+                ParseResult {
+                    result: operand.result.map(|expr| self.located(Expr::Not { expr: Box::new(expr) }, start)),
+                    errors: operand.errors,
+                }
             }
             Some(Token::LBracket) => self.parse_vector(),
             Some(Token::LParen) => {
@@ -92,9 +144,13 @@ impl<'a> Parser<'a> {
                     errors.push(err);
                     ParseResult { result: None, errors }
                 } else {
-                    ParseResult {
-                        result: expr_res.result,
-                        errors,
+                    match expr_res.result {
+                        Some(expr) => {
+                            let mut postfix_res = self.parse_postfix(expr);
+                            errors.append(&mut postfix_res.errors);
+                            ParseResult { result: postfix_res.result, errors }
+                        }
+                        None => ParseResult { result: None, errors },
                     }
                 }
             }
@@ -104,10 +160,10 @@ impl<'a> Parser<'a> {
                     "\x1b[31m[ERR-SYN-004] Unexpected token '{:?}' in expression at position {}.\x1b[0m",
                     token, self.pos
                 ),
-                span.as_ref().map_or(self.pos, |s| s.start),
-                span.as_ref().map_or(self.pos, |s| s.end),
+                span.as_ref().map_or(self.token_start(), |s| s.start),
+                span.as_ref().map_or(self.token_start(), |s| s.end),
                 self.src.clone(),
-                Some("Expected a number, string, boolean, identifier, vector, or parenthesized expression.".to_string()),
+                Some("Expected a number, string, boolean, identifier, function call, vector, or parenthesized expression.".to_string()),
                 Severity::Error,
             )),
             None => ParseResult::err(ParseError::new(
@@ -116,8 +172,8 @@ impl<'a> Parser<'a> {
                     "\x1b[31m[ERR-SYN-005] Unexpected end of input at position {}. Expected an expression.\x1b[0m",
                     self.pos
                 ),
-                self.pos,
-                self.pos,
+                self.token_start(),
+                self.token_end(),
                 self.src.clone(),
                 None,
                 Severity::Error,
@@ -125,6 +181,108 @@ impl<'a> Parser<'a> {
         }
     }
 
+    // v[i], v.len(), v.push(x), v.pop() and chains of them
+    pub fn parse_postfix(&mut self, base: Expr) -> ParseResult<Expr> {
+        let mut result = base;
+        let mut errors = Vec::new();
+        loop {
+            match self.current() {
+                Some(Token::LBracket) => {
+                    self.advance();
+                    let index_res = self.parse_expr();
+                    errors.extend(index_res.errors);
+                    let Some(index) = index_res.result else {
+                        return ParseResult { result: None, errors };
+                    };
+                    if let Err(err) = self.expect(&Token::RBracket) {
+                        errors.push(err);
+                        return ParseResult { result: None, errors };
+                    }
+                    result = Expr::Index {
+                        target: Box::new(result),
+                        index: Box::new(index),
+                    };
+                }
+                Some(Token::Dot) => {
+                    // `.Err(...)` belongs to the enclosing statement, not to the expression
+                    if let Some(Token::Identifier(id)) = self.tokens.get(self.pos + 1).map(|t| &t.token)
+                        && id == "Err"
+                    {
+                        break;
+                    }
+                    self.advance();
+                    let span = self.tokens.get(self.pos).map(|t| t.span.clone());
+                    let method = match self.current() {
+                        Some(Token::Identifier(id)) => {
+                            let method = id.clone();
+                            self.advance();
+                            method
+                        }
+                        _ => {
+                            errors.push(ParseError::new(
+                                ErrorKind::Syntax,
+                                format!(
+                                    "\x1b[31m[ERR-SYN-006] Expected method name after '.' at position {}.\x1b[0m",
+                                    self.pos
+                                ),
+                                span.as_ref().map_or(self.token_start(), |s| s.start),
+                                span.as_ref().map_or(self.token_start(), |s| s.end),
+                                self.src.clone(),
+                                Some("Vectors support len(), push(value) and pop().".to_string()),
+                                Severity::Error,
+                            ));
+                            return ParseResult { result: None, errors };
+                        }
+                    };
+                    let args_res = self.parse_call_args();
+                    errors.extend(args_res.errors);
+                    let Some(args) = args_res.result else {
+                        return ParseResult { result: None, errors };
+                    };
+                    result = Expr::MethodCall {
+                        target: Box::new(result),
+                        method,
+                        args,
+                    };
+                }
+                _ => break,
+            }
+        }
+        ParseResult {
+            result: Some(result),
+            errors,
+        }
+    }
+
+    // `(arg, arg, ...)` of a function or method call
+    fn parse_call_args(&mut self) -> ParseResult<Vec<Expr>> {
+        let mut errors = Vec::new();
+        if let Err(err) = self.expect(&Token::LParen) {
+            return ParseResult::err(err);
+        }
+        let mut args = Vec::new();
+        if self.current() != Some(&Token::RParen) {
+            loop {
+                let arg_res = self.parse_expr();
+                if let Some(arg) = arg_res.result {
+                    args.push(arg);
+                }
+                errors.extend(arg_res.errors);
+                if !self.match_token(&Token::Comma) {
+                    break;
+                }
+            }
+        }
+        if let Err(err) = self.expect(&Token::RParen) {
+            errors.push(err);
+            return ParseResult { result: None, errors };
+        }
+        ParseResult {
+            result: Some(args),
+            errors,
+        }
+    }
+
     fn parse_vector(&mut self) -> ParseResult<Expr> {
         let mut errors = Vec::new();
         if let Err(err) = self.expect(&Token::LBracket) {
@@ -154,8 +312,8 @@ impl<'a> Parser<'a> {
             errors.push(ParseError::new(
                 ErrorKind::Semantic,
                 "\x1b[33m[WARN-SEM-006] Empty vector literal detected.\x1b[0m".to_string(),
-                self.pos,
-                self.pos,
+                self.token_start(),
+                self.token_end(),
                 self.src.clone(),
                 Some("Consider adding elements to the vector.".to_string()),
                 Severity::Warning,
@@ -173,18 +331,33 @@ impl<'a> Parser<'a> {
             Token::Minus => Some(Operator::Minus),
             Token::Star => Some(Operator::Multiply),
             Token::Slash => Some(Operator::Divide),
+            Token::Mod => Some(Operator::Modulo),
+            Token::Equal => Some(Operator::Compare(Logic::Equal)),
+            Token::NotEqual => Some(Operator::Compare(Logic::NotEqual)),
+            Token::Greater => Some(Operator::Compare(Logic::Greater)),
+            Token::Less => Some(Operator::Compare(Logic::Less)),
+            Token::GreaterEqual => Some(Operator::Compare(Logic::GreaterEqual)),
+            Token::LessEqual => Some(Operator::Compare(Logic::LessEqual)),
+            Token::And => Some(Operator::And),
+            Token::Or => Some(Operator::Or),
             _ => None,
         }
     }
 
     fn operator_precedence(&self, op: Operator) -> (i32, Assoc) {
         match op {
-            Operator::Plus | Operator::Minus => (1, Assoc::Left),
-            Operator::Multiply | Operator::Divide => (2, Assoc::Left),
+            Operator::Or => (1, Assoc::Left),
+            Operator::And => (2, Assoc::Left),
+            Operator::Compare(_) => (COMPARE_PRECEDENCE, Assoc::Left),
+            Operator::Plus | Operator::Minus => (4, Assoc::Left),
+            Operator::Multiply | Operator::Divide | Operator::Modulo => (5, Assoc::Left),
         }
     }
 }
 
+// `not` binds looser than comparisons, `not a == b` is `not (a == b)`
+const COMPARE_PRECEDENCE: i32 = 3;
+
 #[derive(PartialEq)]
 enum Assoc {
     Left,
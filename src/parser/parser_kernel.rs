@@ -8,6 +8,8 @@ pub struct Parser<'a> {
     pub tokens: &'a [PositionedToken],
     pub pos: usize,
     pub src: Option<String>,
+    // where `src` starts in the project-wide offsets the module loader hands out
+    pub base: usize,
 }
 
 impl<'a> Parser<'a> {
@@ -16,6 +18,7 @@ impl<'a> Parser<'a> {
             tokens,
             pos: 0,
             src,
+            base: 0,
         }
     }
 
@@ -23,6 +26,31 @@ impl<'a> Parser<'a> {
         self.tokens.get(self.pos).map(|t| &t.token)
     }
 
+    // byte offset where the current token starts (end of the source once tokens run out)
+    pub fn token_start(&self) -> usize {
+        match self.tokens.get(self.pos) {
+            Some(t) => t.span.start,
+            None => self.tokens.last().map_or(0, |t| t.span.end),
+        }
+    }
+
+    pub fn token_end(&self) -> usize {
+        match self.tokens.get(self.pos) {
+            Some(t) => t.span.end,
+            None => self.tokens.last().map_or(0, |t| t.span.end),
+        }
+    }
+
+    // span from `start` up to the end of the last consumed token
+    pub fn span_from(&self, start: usize) -> Span {
+        let end = self
+            .pos
+            .checked_sub(1)
+            .and_then(|i| self.tokens.get(i))
+            .map_or(start, |t| t.span.end);
+        start..end.max(start)
+    }
+
     pub fn advance(&mut self) {
         self.pos += 1;
     }
@@ -45,7 +73,7 @@ impl<'a> Parser<'a> {
             } else {
                 Err(ParseError::new(
                     ErrorKind::Syntax,
-                    format!("expected token {:?}, found {:?}", expected, token.token),
+                    format!("\x1b[31m[ERR-SYN-007] Expected {:?}, found {:?}.\x1b[0m", expected, token.token),
                     token.span.start,
                     token.span.end,
                     self.src.clone(),
@@ -54,7 +82,7 @@ impl<'a> Parser<'a> {
                 ))
             }
         } else {
-            Err(ParseError::eof(format!("expected token {:?}", expected)))
+            Err(ParseError::eof(format!("\x1b[31m[ERR-SYN-008] Unexpected end of input, expected {:?}.\x1b[0m", expected)))
         }
     }
 
@@ -11,6 +11,18 @@ use crate::parser::{
 
 impl<'a> Parser<'a> {
     pub fn parse_statement(&mut self) -> ParseResult<Statement> {
+        let start = self.token_start();
+        let res = self.parse_statement_kind();
+        ParseResult {
+            result: res.result.map(|stmt| Statement::Located {
+                span: self.span_from(start),
+                stmt: Box::new(stmt),
+            }),
+            errors: res.errors,
+        }
+    }
+
+    fn parse_statement_kind(&mut self) -> ParseResult<Statement> {
         if self.match_token(&Token::Do) {
             let stmt_res = self.parse_statement();
             return match stmt_res.result {
@@ -31,6 +43,14 @@ impl<'a> Parser<'a> {
             Some(Token::Set) => self.parse_variable(),
             Some(Token::Math) => self.parse_math(),
             Some(Token::If) => self.parse_if(),
+            Some(Token::Identifier(_))
+                if matches!(
+                    self.tokens.get(self.pos + 1).map(|t| &t.token),
+                    Some(Token::LBracket) | Some(Token::Dot) | Some(Token::LParen)
+                ) =>
+            {
+                self.parse_identifier_statement()
+            }
             Some(Token::Identifier(id)) => {
                 let span = self.tokens.get(self.pos).map(|t| t.span.clone());
                 ParseResult::err(ParseError::new(
@@ -46,7 +66,10 @@ impl<'a> Parser<'a> {
             Some(Token::Loop) => self.parse_loop(),
             Some(Token::While) => self.parse_while(),
             Some(Token::Break) => self.parse_break(),
+            Some(Token::Return) => self.parse_return(),
             Some(Token::Input) => self.parse_input(),
+            Some(Token::Assert) | Some(Token::AssertEq) => self.parse_assert(),
+            Some(Token::Use) => self.parse_use(),
             Some(token) => {
                 let span = self.tokens.get(self.pos).map(|t| t.span.clone());
                 ParseResult::err(ParseError::new(
@@ -68,14 +91,184 @@ impl<'a> Parser<'a> {
                     "\x1b[31m[ERR-SYN-003] Unexpected end of input at position {}. Expected a statement.\x1b[0m",
                     self.pos
                 ),
-                self.pos,
-                self.pos,
+                self.token_start(),
+                self.token_end(),
                 self.src.clone(),
                 None,
                 Severity::Error,
             )),
         }
     }
+    // statements that start with a name: `v[i] = x;`, `v.push(x);`, `foo(x);`
+    fn parse_identifier_statement(&mut self) -> ParseResult<Statement> {
+        let mut errors = Vec::new();
+        let span = self.tokens.get(self.pos).map(|t| t.span.clone());
+        let target_res = self.parse_term();
+        errors.extend(target_res.errors);
+        let Some(target) = target_res.result else {
+            return ParseResult { result: None, errors };
+        };
+        let result = match target {
+            Expr::Index { target, index } if self.current() == Some(&Token::Assign) => {
+                self.advance();
+                let Expr::Identifier(name) = *target else {
+                    errors.push(ParseError::new(
+                        ErrorKind::Syntax,
+                        "\x1b[31m[ERR-SYN-110] Only a vector variable can be indexed on the left side of '='.\x1b[0m".to_string(),
+                        span.as_ref().map_or(0, |s| s.start),
+                        span.as_ref().map_or(0, |s| s.end),
+                        self.src.clone(),
+                        Some("Use the form: name[index] = value;".to_string()),
+                        Severity::Error,
+                    ));
+                    return ParseResult { result: None, errors };
+                };
+                let value_res = self.parse_expr();
+                errors.extend(value_res.errors);
+                let Some(value) = value_res.result else {
+                    return ParseResult { result: None, errors };
+                };
+                Statement::IndexAssign {
+                    name,
+                    index: *index,
+                    value,
+                }
+            }
+            expr @ (Expr::MethodCall { .. } | Expr::Call { .. }) => Statement::ExprStatement { expr },
+            _ => {
+                errors.push(ParseError::new(
+                    ErrorKind::Syntax,
+                    "\x1b[31m[ERR-SYN-111] Expression cannot be used as a statement.\x1b[0m".to_string(),
+                    span.as_ref().map_or(0, |s| s.start),
+                    span.as_ref().map_or(0, |s| s.end),
+                    self.src.clone(),
+                    Some("Assign it to an element (v[i] = value;), call a method (v.push(value);) or call a function (foo(value);).".to_string()),
+                    Severity::Error,
+                ));
+                return ParseResult { result: None, errors };
+            }
+        };
+        if let Err(err) = self.expect(&Token::EndStr) {
+            errors.push(err);
+            return ParseResult { result: None, errors };
+        }
+        ParseResult {
+            result: Some(result),
+            errors,
+        }
+    }
+    fn parse_return(&mut self) -> ParseResult<Statement> {
+        let mut errors = Vec::new();
+        if let Err(err) = self.expect(&Token::Return) {
+            return ParseResult::err(err);
+        }
+        let value = if self.current() == Some(&Token::EndStr) {
+            None
+        } else {
+            let expr_res = self.parse_expr();
+            errors.extend(expr_res.errors);
+            match expr_res.result {
+                Some(expr) => Some(expr),
+                None => return ParseResult { result: None, errors },
+            }
+        };
+        if let Err(err) = self.expect(&Token::EndStr) {
+            errors.push(err);
+            return ParseResult { result: None, errors };
+        }
+        ParseResult {
+            result: Some(Statement::Return { value }),
+            errors,
+        }
+    }
+    // `assert(cond);` takes a comparison or a single bool, `assert_eq(left, right);` two values
+    fn parse_assert(&mut self) -> ParseResult<Statement> {
+        let mut errors = Vec::new();
+        let start = self.tokens.get(self.pos).map_or(0, |t| t.span.start);
+        let is_eq = self.current() == Some(&Token::AssertEq);
+        self.advance();
+        if let Err(err) = self.expect(&Token::LParen) {
+            return ParseResult::err(err);
+        }
+        let left_res = self.parse_expr();
+        errors.extend(left_res.errors);
+        let Some(left) = left_res.result else {
+            return ParseResult { result: None, errors };
+        };
+        let (logic, left, right) = if is_eq {
+            if let Err(err) = self.expect(&Token::Comma) {
+                errors.push(err);
+                return ParseResult { result: None, errors };
+            }
+            let right = self.parse_expr();
+            errors.extend(right.errors);
+            let Some(right) = right.result else {
+                return ParseResult { result: None, errors };
+            };
+            (Logic::Equal, left, right)
+        } else {
+            // a top-level comparison is split so the failure message can show both sides
+            match left.unlocated() {
+                Expr::BinaryOp { left, op: Operator::Compare(logic), right } => {
+                    (*logic, (**left).clone(), (**right).clone())
+                }
+                _ => (Logic::Equal, left, Expr::Bool(true)),
+            }
+        };
+        let end = self.tokens.get(self.pos).map_or(start, |t| t.span.end);
+        if let Err(err) = self.expect(&Token::RParen) {
+            errors.push(err);
+            return ParseResult { result: None, errors };
+        }
+        if let Err(err) = self.expect(&Token::EndStr) {
+            errors.push(err);
+            return ParseResult { result: None, errors };
+        }
+        // token spans are project-wide, `src` only holds this file
+        let (start, end) = (start - self.base, end - self.base);
+        let src = self.src.as_deref().unwrap_or("");
+        ParseResult {
+            result: Some(Statement::Assert {
+                logic,
+                args: vec![left, right],
+                source: src.get(start..end).unwrap_or("assert").to_string(),
+            }),
+            errors,
+        }
+    }
+    // `use util.calc;`
+    fn parse_use(&mut self) -> ParseResult<Statement> {
+        if let Err(err) = self.expect(&Token::Use) {
+            return ParseResult::err(err);
+        }
+        let mut path = Vec::new();
+        loop {
+            match self.current() {
+                Some(Token::Identifier(id)) => {
+                    path.push(id.clone());
+                    self.advance();
+                }
+                _ => {
+                    return ParseResult::err(ParseError::new(
+                        ErrorKind::Syntax,
+                        "\x1b[31m[ERR-SYN-120] Expected a module path after 'use'.\x1b[0m".to_string(),
+                        self.token_start(),
+                        self.token_end(),
+                        self.src.clone(),
+                        Some("Modules are named after their file in src/, e.g. use util.calc; for src/util/calc.ax".to_string()),
+                        Severity::Error,
+                    ));
+                }
+            }
+            if !self.match_token(&Token::Dot) {
+                break;
+            }
+        }
+        if let Err(err) = self.expect(&Token::EndStr) {
+            return ParseResult::err(err);
+        }
+        ParseResult::ok(Statement::Use { path })
+    }
     fn parse_break(&mut self) -> ParseResult<Statement> {
         let errors = Vec::new();
         if let Err(err) = self.expect(&Token::Break) {
@@ -107,12 +300,12 @@ impl<'a> Parser<'a> {
                 return ParseResult::err(ParseError::new(
                     ErrorKind::Syntax,
                     format!(
-                        "\x1b[ERR - SYN - 109] Expected Token inside 'in(...)' at position { }.Found: {:?}.\x1b[0m",
+                        "\x1b[31m[ERR-SYN-109] Expected a variable inside 'in(...)' at position {}, found {}.\x1b[0m",
                         self.pos,
-                        self.current()
+                        self.current().map_or("the end of input".to_string(), |t| format!("{:?}", t))
                     ),
-                    self.pos,
-                    self.pos,
+                    self.token_start(),
+                    self.token_end(),
                     self.src.clone(),
                     None,
                     Severity::Error,
@@ -143,8 +336,8 @@ impl<'a> Parser<'a> {
                                     "\x1b[31m[ERR-SEM-906] Expected string literal after Err at position {}.\x1b[0m",
                                     self.pos
                                 ),
-                                self.pos,
-                                self.pos,
+                                self.token_start(),
+                                self.token_end(),
                                 self.src.clone(),
                                 None,
                                 Severity::Error,
@@ -163,8 +356,8 @@ impl<'a> Parser<'a> {
                             "\x1b[31m[ERR-SEM-907] Expected 'Err' after '.' at position {}.\x1b[0m",
                             self.pos
                         ),
-                        self.pos,
-                        self.pos,
+                        self.token_start(),
+                        self.token_end(),
                         self.src.clone(),
                         None,
                         Severity::Error,
@@ -191,107 +384,10 @@ impl<'a> Parser<'a> {
         if let Err(err) = self.expect(&Token::LParen) {
             return ParseResult::err(err);
         }
-        let token = self.current().cloned();
-        let left = match token {
-            Some(Token::Identifier(id)) => {
-                self.advance();
-                Expr::Identifier(id.clone())
-            }
-            Some(Token::Number(val)) => {
-                self.advance();
-                Expr::Int64(val)
-            }
-            Some(Token::Float(val)) => {
-                self.advance();
-                Expr::Float64(val as f64)
-            }
-            _ => {
-                return ParseResult::err(ParseError::new(
-                    ErrorKind::Syntax,
-                    format!(
-                        "\x1b[31m[ERR-SYN-105] Expected identifier or number in while condition at position {}. Found: {:?}.\x1b[0m",
-                        self.pos,
-                        self.current()
-                    ),
-                    self.pos,
-                    self.pos,
-                    self.src.clone(),
-                    None,
-                    Severity::Error,
-                ));
-            }
-        };
-
-        let logic = match self.current() {
-            Some(Token::Equal) => {
-                self.advance();
-                Logic::Equal
-            }
-            Some(Token::NotEqual) => {
-                self.advance();
-                Logic::NotEqual
-            }
-            Some(Token::Greater) => {
-                self.advance();
-                Logic::Greater
-            }
-            Some(Token::Less) => {
-                self.advance();
-                Logic::Less
-            }
-            Some(Token::GreaterEqual) => {
-                self.advance();
-                Logic::GreaterEqual
-            }
-            Some(Token::LessEqual) => {
-                self.advance();
-                Logic::LessEqual
-            }
-            _ => {
-                return ParseResult::err(ParseError::new(
-                    ErrorKind::Semantic,
-                    format!(
-                        "\x1b[31m[ERR-SEM-111] Unknown comparison operator at position {}. Found: {:?}.\x1b[0m",
-                        self.pos,
-                        self.current()
-                    ),
-                    self.pos,
-                    self.pos,
-                    self.src.clone(),
-                    Some("Expected one of: <, >, ==, !=, <=, >=.".to_string()),
-                    Severity::Error,
-                ));
-            }
-        };
-        let token = self.current().cloned();
-        let right = match token {
-            Some(Token::Identifier(id)) => {
-                self.advance();
-                Expr::Identifier(id.clone())
-            }
-            Some(Token::Number(val)) => {
-                self.advance();
-                Expr::Int64(val)
-            }
-            Some(Token::Float(val)) => {
-                self.advance();
-                Expr::Float64(val as f64)
-            }
-            _ => {
-                return ParseResult::err(ParseError::new(
-                    ErrorKind::Syntax,
-                    format!(
-                        "\x1b[31m[ERR-SYN-106] Expected identifier or number in while condition at position {}. Found: {:?}.\x1b[0m",
-                        self.pos,
-                        self.current()
-                    ),
-                    self.pos,
-                    self.pos,
-                    self.src.clone(),
-                    None,
-                    Severity::Error,
-                ));
-            }
+        let condition_res = self.parse_expr();
+        errors.extend(condition_res.errors);
+        let Some(condition) = condition_res.result else {
+            return ParseResult { result: None, errors };
         };
         if let Err(err) = self.expect(&Token::RParen) {
             return ParseResult::err(err);
@@ -322,8 +418,7 @@ impl<'a> Parser<'a> {
         }
         return ParseResult {
             result: Some(Statement::While {
-                logic,
-                args: vec![left, right],
+                condition,
                 body: statements,
             }),
             errors,
@@ -371,107 +466,10 @@ impl<'a> Parser<'a> {
         if let Err(err) = self.expect(&Token::LParen) {
             return ParseResult::err(err);
         }
-        let token = self.current().cloned();
-        let left = match token {
-            Some(Token::Identifier(id)) => {
-                self.advance();
-                Expr::Identifier(id.clone())
-            }
-            Some(Token::Number(val)) => {
-                self.advance();
-                Expr::Int64(val)
-            }
-            Some(Token::Float(val)) => {
-                self.advance();
-                Expr::Float64(val as f64)
-            }
-            _ => {
-                return ParseResult::err(ParseError::new(
-                    ErrorKind::Syntax,
-                    format!(
-                        "\x1b[31m[ERR-SYN-107] Expected identifier or number in if condition at position {}. Found: {:?}.\x1b[0m",
-                        self.pos,
-                        self.current()
-                    ),
-                    self.pos,
-                    self.pos,
-                    self.src.clone(),
-                    None,
-                    Severity::Error,
-                ));
-            }
-        };
-
-        let logic = match self.current() {
-            Some(Token::Equal) => {
-                self.advance();
-                Logic::Equal
-            }
-            Some(Token::NotEqual) => {
-                self.advance();
-                Logic::NotEqual
-            }
-            Some(Token::Greater) => {
-                self.advance();
-                Logic::Greater
-            }
-            Some(Token::Less) => {
-                self.advance();
-                Logic::Less
-            }
-            Some(Token::GreaterEqual) => {
-                self.advance();
-                Logic::GreaterEqual
-            }
-            Some(Token::LessEqual) => {
-                self.advance();
-                Logic::LessEqual
-            }
-            _ => {
-                return ParseResult::err(ParseError::new(
-                    ErrorKind::Semantic,
-                    format!(
-                        "\x1b[31m[ERR-SEM-111] Unknown comparison operator at position {}. Found: {:?}.\x1b[0m",
-                        self.pos,
-                        self.current()
-                    ),
-                    self.pos,
-                    self.pos,
-                    self.src.clone(),
-                    Some("Expected one of: <, >, ==, !=, <=, >=.".to_string()),
-                    Severity::Error,
-                ));
-            }
-        };
-        let token = self.current().cloned();
-        let right = match token {
-            Some(Token::Identifier(id)) => {
-                self.advance();
-                Expr::Identifier(id.clone())
-            }
-            Some(Token::Number(val)) => {
-                self.advance();
-                Expr::Int64(val)
-            }
-            Some(Token::Float(val)) => {
-                self.advance();
-                Expr::Float64(val as f64)
-            }
-            _ => {
-                return ParseResult::err(ParseError::new(
-                    ErrorKind::Syntax,
-                    format!(
-                        "\x1b[31m[ERR-SYN-108] Expected identifier or number in if condition at position {}. Found: {:?}.\x1b[0m",
-                        self.pos,
-                        self.current()
-                    ),
-                    self.pos,
-                    self.pos,
-                    self.src.clone(),
-                    None,
-                    Severity::Error,
-                ));
-            }
+        let condition_res = self.parse_expr();
+        errors.extend(condition_res.errors);
+        let Some(condition) = condition_res.result else {
+            return ParseResult { result: None, errors };
         };
         if let Err(err) = self.expect(&Token::RParen) {
             return ParseResult::err(err);
@@ -534,8 +532,7 @@ impl<'a> Parser<'a> {
         }
         return ParseResult {
             result: Some(Statement::If {
-                logic,
-                args: vec![left, right],
+                condition,
                 body: statements,
                 else_body,
             }),
@@ -561,8 +558,8 @@ impl<'a> Parser<'a> {
                         "\x1b[31m[ERR-SEM-101] Expected function name at position {}.\x1b[0m",
                         self.pos
                     ),
-                    self.pos,
-                    self.pos,
+                    self.token_start(),
+                    self.token_end(),
                     self.src.clone(),
                     None,
                     Severity::Error,
@@ -580,12 +577,13 @@ impl<'a> Parser<'a> {
         let mut params = Vec::new();
         if self.current() != Some(&Token::RParen) {
             loop {
-                let param_res = self.parse_parameter_name();
-                if let Some(param_name) = param_res.result {
-                    params.push(Expr::Identifier(param_name));
+                let param_res = self.parse_parameter();
+                let failed = param_res.result.is_none();
+                if let Some(param) = param_res.result {
+                    params.push(param);
                 }
                 errors.extend(param_res.errors);
-                if !self.match_token(&Token::Comma) {
+                if failed || !self.match_token(&Token::Comma) {
                     break;
                 }
             }
@@ -597,12 +595,26 @@ impl<'a> Parser<'a> {
                 errors,
             };
         }
+        let return_type = if self.match_token(&Token::Arrow) {
+            let type_res = self.parse_type();
+            errors.extend(type_res.errors);
+            if type_res.result.is_none() {
+                return ParseResult {
+                    result: None,
+                    errors,
+                };
+            }
+            type_res.result
+        } else {
+            None
+        };
         let body_res = self.parse_block();
         errors.extend(body_res.errors);
         let result = body_res.result.map(|body| Statement::FunctionCall {
             name,
             start,
             params,
+            return_type,
             body,
         });
         ParseResult { result, errors }
@@ -636,8 +648,8 @@ impl<'a> Parser<'a> {
             errors.push(ParseError::new(
                 ErrorKind::Semantic,
                 "\x1b[33m[WARN-SEM-002] Empty print statement.\x1b[0m".to_string(),
-                self.pos,
-                self.pos,
+                self.token_start(),
+                self.token_end(),
                 self.src.clone(),
                 Some("Consider adding expressions to print.".to_string()),
                 Severity::Warning,
@@ -706,8 +718,8 @@ impl<'a> Parser<'a> {
                             "\x1b[31m[ERR-SEM-102] Expected identifier for math result after comma at position {}.\x1b[0m",
                             self.pos
                         ),
-                        self.pos,
-                        self.pos,
+                        self.token_start(),
+                        self.token_end(),
                         self.src.clone(),
                         None,
                         Severity::Error,
@@ -720,8 +732,8 @@ impl<'a> Parser<'a> {
                 ErrorKind::Semantic,
                 "\x1b[33m[WARN-SEM-003] No destination specified for math expression.\x1b[0m"
                     .to_string(),
-                self.pos,
-                self.pos,
+                self.token_start(),
+                self.token_end(),
                 self.src.clone(),
                 Some("Using default 'Result'.".to_string()),
                 Severity::Warning,
@@ -760,8 +772,8 @@ impl<'a> Parser<'a> {
                                         "\x1b[31m[ERR-SEM-905] Expected string literal after Err at position {}.\x1b[0m",
                                         self.pos
                                     ),
-                                    self.pos,
-                                    self.pos,
+                                    self.token_start(),
+                                    self.token_end(),
                                     self.src.clone(),
                                     None,
                                     Severity::Error,
@@ -778,8 +790,8 @@ impl<'a> Parser<'a> {
                             "\x1b[31m[ERR-SEM-904] Expected 'Err' after '.' at position {}.\x1b[0m",
                             self.pos
                         ),
-                        self.pos,
-                        self.pos,
+                        self.token_start(),
+                        self.token_end(),
                         self.src.clone(),
                         None,
                         Severity::Error,
@@ -832,8 +844,8 @@ impl<'a> Parser<'a> {
                         "\x1b[31m[ERR-SEM-103] Expected variable name at position {}.\x1b[0m",
                         self.pos
                     ),
-                    self.pos,
-                    self.pos,
+                    self.token_start(),
+                    self.token_end(),
                     self.src.clone(),
                     None,
                     Severity::Error,
@@ -851,53 +863,9 @@ impl<'a> Parser<'a> {
                 errors,
             };
         }
-        let type_var = match self.current() {
-            Some(Token::I32) => {
-                self.advance();
-                Some(Type::I32)
-            }
-            Some(Token::I64) => {
-                self.advance();
-                Some(Type::I64)
-            }
-            Some(Token::F32) => {
-                self.advance();
-                Some(Type::F32)
-            }
-            Some(Token::F64) => {
-                self.advance();
-                Some(Type::F64)
-            }
-            Some(Token::TypeString) => {
-                self.advance();
-                Some(Type::String)
-            }
-            Some(Token::Vector) => {
-                self.advance();
-                let inner_res = self.parse_vector_type();
-                errors.extend(inner_res.errors);
-                inner_res.result.map(|inner| Type::Vector(Box::new(inner)))
-            }
-            Some(Token::Bool) => {
-                self.advance();
-                Some(Type::Bool)
-            }
-            _ => {
-                errors.push(ParseError::new(
-                    ErrorKind::Type,
-                    format!(
-                        "\x1b[31m[ERR-TYP-001] Expected type (i32, i64, f32, f64, string, vector, bool) at position {}.\x1b[0m",
-                        self.pos
-                    ),
-                    self.pos,
-                    self.pos,
-                    self.src.clone(),
-                    None,
-                    Severity::Error,
-                ));
-                None
-            }
-        };
+        let type_res = self.parse_type();
+        errors.extend(type_res.errors);
+        let type_var = type_res.result;
         if let Err(err) = self.expect(&Token::RParen) {
             errors.push(err);
             return ParseResult {
@@ -916,8 +884,8 @@ impl<'a> Parser<'a> {
                     "\x1b[31m[ERR-SEM-105] Variable '{}' requires an initialization value at position {}.\x1b[0m",
                     name, self.pos
                 ),
-                self.pos,
-                self.pos,
+                self.token_start(),
+                self.token_end(),
                 self.src.clone(),
                 Some("Add an expression after '=' to initialize the variable.".to_string()),
                 Severity::Error,
@@ -945,27 +913,97 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_parameter_name(&mut self) -> ParseResult<String> {
+    pub fn parse_type(&mut self) -> ParseResult<Type> {
         match self.current() {
-            Some(Token::Identifier(id)) => {
-                let name = id.clone();
+            Some(Token::I32) => {
                 self.advance();
-                ParseResult::ok(name)
+                ParseResult::ok(Type::I32)
+            }
+            Some(Token::I64) => {
+                self.advance();
+                ParseResult::ok(Type::I64)
+            }
+            Some(Token::F32) => {
+                self.advance();
+                ParseResult::ok(Type::F32)
+            }
+            Some(Token::F64) => {
+                self.advance();
+                ParseResult::ok(Type::F64)
+            }
+            Some(Token::TypeString) => {
+                self.advance();
+                ParseResult::ok(Type::String)
+            }
+            Some(Token::Vector) => {
+                self.advance();
+                self.parse_vector_type()
+            }
+            Some(Token::Tensor) => {
+                self.advance();
+                self.parse_tensor_type()
+            }
+            Some(Token::Bool) => {
+                self.advance();
+                ParseResult::ok(Type::Bool)
             }
             _ => ParseResult::err(ParseError::new(
-                ErrorKind::Semantic,
+                ErrorKind::Type,
                 format!(
-                    "\x1b[31m[ERR-SEM-104] Expected parameter name at position {}.\x1b[0m",
+                    "\x1b[31m[ERR-TYP-001] Expected type (i32, i64, f32, f64, string, vector, tensor, bool) at position {}.\x1b[0m",
                     self.pos
                 ),
-                self.pos,
-                self.pos,
+                self.token_start(),
+                self.token_end(),
                 self.src.clone(),
                 None,
                 Severity::Error,
             )),
         }
     }
+
+    fn parse_parameter(&mut self) -> ParseResult<(String, Type)> {
+        let name = match self.current() {
+            Some(Token::Identifier(id)) => {
+                let name = id.clone();
+                self.advance();
+                name
+            }
+            _ => {
+                return ParseResult::err(ParseError::new(
+                    ErrorKind::Semantic,
+                    format!(
+                        "\x1b[31m[ERR-SEM-104] Expected parameter name at position {}.\x1b[0m",
+                        self.pos
+                    ),
+                    self.token_start(),
+                    self.token_end(),
+                    self.src.clone(),
+                    None,
+                    Severity::Error,
+                ));
+            }
+        };
+        if !self.match_token(&Token::Colon) {
+            return ParseResult::err(ParseError::new(
+                ErrorKind::Type,
+                format!(
+                    "\x1b[31m[ERR-TYP-003] Parameter '{}' needs a type at position {}.\x1b[0m",
+                    name, self.pos
+                ),
+                self.token_start(),
+                self.token_end(),
+                self.src.clone(),
+                Some(format!("Write it as '{}: i32'.", name)),
+                Severity::Error,
+            ));
+        }
+        let type_res = self.parse_type();
+        ParseResult {
+            result: type_res.result.map(|ty| (name, ty)),
+            errors: type_res.errors,
+        }
+    }
     fn parse_block(&mut self) -> ParseResult<Vec<Statement>> {
         let mut statements = Vec::new();
         let mut errors = Vec::new();
@@ -973,9 +1011,9 @@ impl<'a> Parser<'a> {
         if !self.match_token(&Token::DoubleGt) {
             return ParseResult::err(ParseError::new(
                 ErrorKind::Syntax,
-                "Expected '>>' to start block".to_string(),
-                self.pos,
-                self.pos,
+                "\x1b[31m[ERR-SYN-009] Expected '>>' to start a block.\x1b[0m".to_string(),
+                self.token_start(),
+                self.token_end(),
                 self.src.clone(),
                 None,
                 Severity::Error,
@@ -1039,6 +1077,13 @@ impl<'a> Parser<'a> {
                 self.advance();
                 Type::Bool
             }
+            Some(Token::Vector) => {
+                self.advance();
+                match self.parse_vector_type() {
+                    ParseResult { result: Some(inner), .. } => inner,
+                    res => return res,
+                }
+            }
             _ => {
                 return ParseResult::err(ParseError::new(
                     ErrorKind::Type,
@@ -1046,8 +1091,8 @@ impl<'a> Parser<'a> {
                         "\x1b[31m[ERR-TYP-002] Expected inner type for vector at position {}.\x1b[0m",
                         self.pos
                     ),
-                    self.pos,
-                    self.pos,
+                    self.token_start(),
+                    self.token_end(),
                     self.src.clone(),
                     None,
                     Severity::Error,
@@ -1059,4 +1104,78 @@ impl<'a> Parser<'a> {
         }
         ParseResult::ok(Type::Vector(Box::new(inner_type)))
     }
+
+    // Tensor(f32, [2, 3])
+    fn parse_tensor_type(&mut self) -> ParseResult<Type> {
+        let mut errors = Vec::new();
+        if let Err(err) = self.expect(&Token::LParen) {
+            return ParseResult::err(err);
+        }
+        let elem_pos = self.tokens.get(self.pos).map(|t| t.span.clone());
+        let elem_res = self.parse_type();
+        errors.extend(elem_res.errors);
+        let Some(elem) = elem_res.result else {
+            return ParseResult { result: None, errors };
+        };
+        if elem != Type::F32 {
+            errors.push(ParseError::new(
+                ErrorKind::Type,
+                format!(
+                    "\x1b[31m[ERR-TYP-004] Tensor element type must be f32, found {:?}.\x1b[0m",
+                    elem
+                ),
+                elem_pos.as_ref().map_or(self.token_start(), |s| s.start),
+                elem_pos.as_ref().map_or(self.token_start(), |s| s.end),
+                self.src.clone(),
+                Some("Declare it as Tensor(f32, [rows, cols]).".to_string()),
+                Severity::Error,
+            ));
+        }
+        for expected in [Token::Comma, Token::LBracket] {
+            if let Err(err) = self.expect(&expected) {
+                errors.push(err);
+                return ParseResult { result: None, errors };
+            }
+        }
+        let mut shape = Vec::new();
+        loop {
+            match self.current() {
+                // `[]` is a rank-0 tensor, the kind a whole-tensor reduction returns
+                Some(Token::RBracket) if shape.is_empty() => break,
+                Some(Token::Number(n)) if *n > 0 => {
+                    shape.push(*n as usize);
+                    self.advance();
+                }
+                _ => {
+                    errors.push(ParseError::new(
+                        ErrorKind::Type,
+                        format!(
+                            "\x1b[31m[ERR-TYP-005] Expected a positive tensor dimension at position {}. Found: {:?}.\x1b[0m",
+                            self.pos,
+                            self.current()
+                        ),
+                        self.token_start(),
+                        self.token_end(),
+                        self.src.clone(),
+                        Some("Tensor shapes are written as a list of sizes, e.g. [2, 3].".to_string()),
+                        Severity::Error,
+                    ));
+                    return ParseResult { result: None, errors };
+                }
+            }
+            if !self.match_token(&Token::Comma) {
+                break;
+            }
+        }
+        for expected in [Token::RBracket, Token::RParen] {
+            if let Err(err) = self.expect(&expected) {
+                errors.push(err);
+                return ParseResult { result: None, errors };
+            }
+        }
+        ParseResult {
+            result: Some(Type::Tensor(Box::new(elem), shape)),
+            errors,
+        }
+    }
 }
@@ -0,0 +1,182 @@
+// `axon repl`,
+// entries are read line by line until their `>>` blocks close, then lexed and parsed here,
+// running them needs the JIT (repl_session), so without LLVM only this part is built, for its tests
+
+use crate::ast::Statement;
+use crate::high_level_ir::HIRType;
+use crate::lexer_tokenizer::{lex_with_span, PositionedToken, Token};
+use crate::parser::parser_error::{ParseError, Severity};
+use crate::parser::parser_kernel::Parser as AxonParser;
+use crate::semantic::{repl_expr_type, ReplScope};
+use console::style;
+use std::io::{self, Write};
+
+#[cfg(feature = "llvm")]
+mod repl_session;
+
+#[cfg(feature = "llvm")]
+pub use repl_session::run_repl;
+
+// what an entry asks for
+#[derive(Debug, PartialEq)]
+enum Command<'a> {
+    Nothing,
+    Quit,
+    Help,
+    Reset,
+    Ir,
+    // `:type a * 2`
+    Type(&'a str),
+    Unknown(&'a str),
+    // statements, or an expression to print
+    Code(&'a str),
+}
+
+fn command(entry: &str) -> Command<'_> {
+    let entry = entry.trim();
+    let (command, rest) = entry.split_once(char::is_whitespace).unwrap_or((entry, ""));
+    match command {
+        "" => Command::Nothing,
+        ":quit" | ":q" => Command::Quit,
+        ":help" => Command::Help,
+        ":reset" => Command::Reset,
+        ":ir" => Command::Ir,
+        ":type" => Command::Type(rest.trim()),
+        command if command.starts_with(':') => Command::Unknown(command),
+        _ => Command::Code(entry),
+    }
+}
+
+// one entry, more lines are read while a `>>` block is still open
+fn read_entry(lines: &mut impl Iterator<Item = io::Result<String>>) -> Option<String> {
+    let mut entry = String::new();
+    loop {
+        let prompt = if entry.is_empty() { "axon> " } else { " ...  " };
+        print!("{}", style(prompt).cyan());
+        io::stdout().flush().ok();
+        let line = lines.next()?.ok()?;
+        entry.push_str(&line);
+        entry.push('\n');
+        let depth: i32 = lex_with_span(&entry)
+            .iter()
+            .map(|t| match t.token {
+                Token::DoubleGt => 1,
+                Token::DoubleLt => -1,
+                _ => 0,
+            })
+            .sum();
+        if depth <= 0 {
+            return Some(entry);
+        }
+    }
+}
+
+fn parse_entry(code: &str) -> Result<Vec<Statement>, Vec<ParseError>> {
+    let tokens = lex_with_span(code);
+    let mut parser = AxonParser::new(&tokens, Some(code.to_string()));
+    let mut statements = Vec::new();
+    while parser.current().is_some() {
+        if parser.match_token(&Token::EndStr) {
+            continue;
+        }
+        let res = parser.parse_statement();
+        let (warnings, errors): (Vec<_>, Vec<_>) =
+            res.errors.into_iter().partition(|e| matches!(e.severity, Severity::Warning));
+        if !errors.is_empty() {
+            return parse_bare_expr(code, &tokens).ok_or(errors);
+        }
+        for warning in warnings {
+            print!("{}", warning);
+        }
+        statements.extend(res.result);
+    }
+    Ok(statements)
+}
+
+// `a * 2` on its own is shown like `out(a * 2);`
+fn parse_bare_expr(code: &str, tokens: &[PositionedToken]) -> Option<Vec<Statement>> {
+    let mut parser = AxonParser::new(tokens, Some(code.to_string()));
+    let expr = parser.parse_expr().result?;
+    parser.match_token(&Token::EndStr);
+    if parser.current().is_some() {
+        return None;
+    }
+    Some(vec![Statement::Located {
+        span: 0..code.len(),
+        stmt: Box::new(Statement::Print { params: vec![expr] }),
+    }])
+}
+
+// the type `:type` shows, checked against the variables and functions of earlier entries
+fn type_of(code: &str, scope: &ReplScope) -> Result<HIRType, Vec<ParseError>> {
+    let tokens = lex_with_span(code);
+    let mut parser = AxonParser::new(&tokens, Some(code.to_string()));
+    let parsed = parser.parse_expr();
+    let Some(expr) = parsed.result else {
+        return Err(parsed.errors);
+    };
+    repl_expr_type(expr, Some(code.to_string()), scope).map_err(|errors| errors.iter().map(ParseError::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the entries `read_entry` makes of what is typed, line by line
+    fn entries(typed: &[&str]) -> Vec<String> {
+        let mut lines = typed.iter().map(|line| Ok(line.to_string()));
+        std::iter::from_fn(|| read_entry(&mut lines)).collect()
+    }
+
+    #[test]
+    fn an_entry_goes_on_until_its_blocks_close() {
+        let typed = [
+            "cast twice(n: i32) -> i32 >>",
+            "    if (n > 10) >>",
+            "        return n;",
+            "    <<",
+            "    return n * 2;",
+            "<<",
+            "out(twice(4));",
+        ];
+        assert_eq!(
+            entries(&typed),
+            vec![
+                "cast twice(n: i32) -> i32 >>\n    if (n > 10) >>\n        return n;\n    <<\n    return n * 2;\n<<\n",
+                "out(twice(4));\n",
+            ]
+        );
+        // end of input inside a block drops the unfinished entry
+        assert_eq!(entries(&["loop >>", "    break;"]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn commands_are_told_apart_from_code() {
+        assert_eq!(command("  \n"), Command::Nothing);
+        assert_eq!(command(":q\n"), Command::Quit);
+        assert_eq!(command(":reset\n"), Command::Reset);
+        assert_eq!(command(":type  a * 2 \n"), Command::Type("a * 2"));
+        assert_eq!(command(":tpye a\n"), Command::Unknown(":tpye"));
+        assert_eq!(command("set a(i32) = 1;\n"), Command::Code("set a(i32) = 1;"));
+    }
+
+    #[test]
+    fn a_bare_expression_is_printed() {
+        let statements = parse_entry("set: a(i32) = 1;\nmath([a + 1], a);").ok().unwrap();
+        assert_eq!(statements.len(), 2);
+        let statements = parse_entry("a * 2").ok().unwrap();
+        assert!(matches!(&statements[..], [stmt] if matches!(stmt.unlocated(), Statement::Print { params } if params.len() == 1)));
+        assert!(parse_entry("set a(i32) = ;").is_err());
+    }
+
+    #[test]
+    fn types_come_from_the_earlier_entries() {
+        let mut scope = ReplScope::default();
+        scope.variables.insert("a".to_string(), HIRType::I64);
+        scope.functions.insert("half".to_string(), (vec![HIRType::F64], HIRType::F64));
+        assert_eq!(type_of("a * 2", &scope).ok(), Some(HIRType::I64));
+        assert_eq!(type_of("half(1.5)", &scope).ok(), Some(HIRType::F64));
+        let codes = |code| type_of(code, &ReplScope::default()).err().unwrap().into_iter().filter_map(|e| e.code);
+        assert_eq!(codes("a * 2").collect::<Vec<_>>(), vec!["ERR-SEM-999"]);
+    }
+}
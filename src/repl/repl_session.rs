@@ -0,0 +1,247 @@
+// the JIT side of `axon repl`,
+// an entry that checked against what the earlier entries declared is compiled into a module of its own
+// and added to one long-lived JIT.
+// the statements of an entry run inside a fresh function `repl.entry.N` and the variables they
+// declare become globals, later entries declare those and the earlier functions as extern
+
+use super::{command, parse_entry, read_entry, type_of, Command};
+use crate::ast::Statement;
+use crate::compiler_neuron::compiler_context::Compiler;
+use crate::compiler_neuron::compiler_runtime::{axon_jit_call, register_runtime_symbols};
+use crate::compiler_neuron::{codegen_statement, declare_functions};
+use crate::high_level_ir::HIRStatement;
+use crate::parser::parser_error::ParseError;
+use crate::semantic::{repl_entry_to_hir, ReplScope};
+use console::style;
+use llvm_sys::analysis::{LLVMVerifierFailureAction, LLVMVerifyModule};
+use llvm_sys::core::*;
+use llvm_sys::execution_engine::*;
+use llvm_sys::prelude::*;
+use llvm_sys::target::{
+    LLVM_InitializeNativeAsmParser, LLVM_InitializeNativeAsmPrinter, LLVM_InitializeNativeTarget,
+};
+use std::collections::HashSet;
+use std::ffi::{CStr, CString};
+use std::io::{self, BufRead};
+
+const HELP: &str = "Enter statements as in a function body, `cast` declarations stay available for later entries.\n\
+A bare expression is printed: `a * 2` works like `out(a * 2);`\n\n\
+Commands:\n  :type <expr>   Show the type of an expression\n  :ir            Show the LLVM IR of the last entry\n  :reset         Forget all variables and functions\n  :help          Show this help\n  :quit, :q      Leave the REPL";
+
+pub fn run_repl() -> i32 {
+    unsafe {
+        LLVM_InitializeNativeTarget();
+        LLVM_InitializeNativeAsmPrinter();
+        LLVM_InitializeNativeAsmParser();
+    }
+    println!(
+        "{} {}",
+        style("AxonScript REPL").cyan().bold(),
+        style("(:help for commands, :quit to leave)").dim()
+    );
+
+    let mut session = Session::default();
+    let mut lines = io::stdin().lock().lines();
+    while let Some(entry) = read_entry(&mut lines) {
+        match command(&entry) {
+            Command::Nothing => {}
+            Command::Quit => break,
+            Command::Help => println!("{}", HELP),
+            Command::Reset => {
+                session = Session::default();
+                println!("{} Session cleared", style("✔").green().bold());
+            }
+            Command::Ir => match &session.last_ir {
+                Some(ir) => print!("{}", ir),
+                None => println!("Nothing has been compiled yet."),
+            },
+            Command::Type(code) => session.show_type(code),
+            Command::Unknown(command) => {
+                println!("{}", style(format!("Unknown command '{}', try :help", command)).red())
+            }
+            Command::Code(code) => session.run(code),
+        }
+    }
+    0
+}
+
+#[derive(Default)]
+struct Session {
+    scope: ReplScope,
+    // created with the first entry, every later module is added to it
+    engine: Option<LLVMExecutionEngineRef>,
+    // the engine owns the modules, their contexts have to outlive it
+    contexts: Vec<LLVMContextRef>,
+    entries: usize,
+    last_ir: Option<String>,
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(engine) = self.engine.take() {
+                LLVMDisposeExecutionEngine(engine);
+            }
+            for context in self.contexts.drain(..) {
+                LLVMContextDispose(context);
+            }
+        }
+    }
+}
+
+impl Session {
+    fn show_type(&self, code: &str) {
+        match type_of(code, &self.scope) {
+            Ok(ty) => println!("{}", style(ty).yellow()),
+            Err(errors) => errors.iter().for_each(|e| print!("{}", e)),
+        }
+    }
+
+    fn run(&mut self, code: &str) {
+        let ast = match parse_entry(code) {
+            Ok(ast) => ast,
+            Err(errors) => return errors.iter().for_each(|e| print!("{}", e)),
+        };
+        if ast.iter().any(|stmt| matches!(stmt.unlocated(), Statement::Use { .. })) {
+            println!(
+                "\x1b[31m[ERR-REPL-001] `use` is not available in the REPL, it has no project to load modules from.\x1b[0m"
+            );
+            return;
+        }
+
+        let entry = format!("repl.entry.{}", self.entries);
+        self.entries += 1;
+        let mut scope = self.scope.clone();
+        let sem = repl_entry_to_hir(ast, Some(code.to_string()), &entry, &mut scope);
+        if !sem.errors.is_empty() {
+            return sem.errors.iter().for_each(|e| print!("{}", ParseError::from(e)));
+        }
+        let compiler = match self.compile(sem.result, sem.mutable_vars, &entry) {
+            Ok(compiler) => compiler,
+            Err(errors) => {
+                return errors.iter().for_each(|e| println!("{} {}", style("✘").red().bold(), e));
+            }
+        };
+        // the entry compiled, from here on its names exist for the next one
+        self.scope = scope;
+
+        unsafe {
+            let module = compiler.module;
+            let ir = LLVMPrintModuleToString(module);
+            self.last_ir = Some(CStr::from_ptr(ir).to_string_lossy().into_owned());
+            LLVMDisposeMessage(ir);
+
+            let engine = match self.engine {
+                Some(engine) => {
+                    LLVMAddModule(engine, module);
+                    engine
+                }
+                None => {
+                    let mut engine = std::ptr::null_mut();
+                    let mut error_msg = std::ptr::null_mut();
+                    if LLVMCreateExecutionEngineForModule(&mut engine, module, &mut error_msg) != 0 {
+                        let msg = CStr::from_ptr(error_msg).to_string_lossy().into_owned();
+                        LLVMDisposeMessage(error_msg);
+                        println!("{} Failed to create JIT execution engine: {}", style("✘").red().bold(), msg);
+                        compiler.dispose();
+                        return;
+                    }
+                    self.engine = Some(engine);
+                    engine
+                }
+            };
+            // a runtime error ends the entry, not the session
+            register_runtime_symbols(engine, module);
+            LLVMDisposeBuilder(compiler.builder);
+            self.contexts.push(compiler.context);
+
+            let entry_c = CString::new(entry).unwrap();
+            let address = LLVMGetFunctionAddress(engine, entry_c.as_ptr());
+            if address == 0 {
+                println!("{} The entry function was not found in the JIT", style("✘").red().bold());
+                return;
+            }
+            let entry_fn = std::mem::transmute::<u64, extern "C" fn()>(address);
+            let code = axon_jit_call(entry_fn);
+            if code != 0 {
+                println!("{} Entry stopped with exit code {}", style("✘").red().bold(), code);
+            }
+        }
+    }
+
+    fn compile(&self, hir: Vec<HIRStatement>, mutable_vars: HashSet<String>, entry: &str) -> Result<Compiler, Vec<String>> {
+        let mut compiler = Compiler::new(entry);
+        compiler.mutable_vars = mutable_vars;
+        compiler.checked = true;
+        declare_functions(&mut compiler, &hir);
+        compiler.repl_entry = compiler.functions.get(entry).map(|(func, _, _)| *func);
+
+        // earlier entries own these, here they are only declared
+        for (name, (params, return_type)) in &self.scope.functions {
+            compiler.declare_function(name, name, params, return_type.clone());
+        }
+        for (name, ty) in &self.scope.variables {
+            let symbol = CString::new(compiler.global_name(name)).unwrap();
+            let global = unsafe { LLVMAddGlobal(compiler.module, compiler.hir_type_to_llvm_type(ty), symbol.as_ptr()) };
+            compiler.variables.insert(name.clone(), (global, ty.clone()));
+        }
+
+        let mut errors: Vec<String> = hir
+            .iter()
+            .filter_map(|stmt| codegen_statement(&mut compiler, stmt).err())
+            .collect();
+        if errors.is_empty() {
+            unsafe {
+                let mut error_msg = std::ptr::null_mut();
+                if LLVMVerifyModule(compiler.module, LLVMVerifierFailureAction::LLVMReturnStatusAction, &mut error_msg) == 1 {
+                    errors.push(format!(
+                        "LLVM module verification failed: {}",
+                        CStr::from_ptr(error_msg).to_string_lossy()
+                    ));
+                }
+                LLVMDisposeMessage(error_msg);
+            }
+        }
+        if errors.is_empty() {
+            Ok(compiler)
+        } else {
+            compiler.dispose();
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::high_level_ir::HIRType;
+
+    #[test]
+    fn entries_build_on_each_other_until_a_reset() {
+        unsafe {
+            LLVM_InitializeNativeTarget();
+            LLVM_InitializeNativeAsmPrinter();
+            LLVM_InitializeNativeAsmParser();
+        }
+        let mut session = Session::default();
+        session.run("set: total(i32) = 2;");
+        session.run("cast twice(n: i32) -> i32 >>\n    return n * 2;\n<<\n");
+        session.run("math([twice(total) + 1], total);");
+        assert_eq!(session.entries, 3);
+        assert!(session.last_ir.as_deref().is_some_and(|ir| ir.contains("repl.entry.2")));
+        assert_eq!(type_of("twice(total)", &session.scope).ok(), Some(HIRType::I32));
+
+        // the variable is a global of the JIT, it holds what the last entry left in it
+        let address = unsafe { LLVMGetGlobalValueAddress(session.engine.unwrap(), c"repl.total".as_ptr()) };
+        assert_ne!(address, 0);
+        assert_eq!(unsafe { *(address as *const i32) }, 5);
+
+        // an entry that does not check leaves the session as it was
+        session.run("math([total + \"one\"], total);");
+        assert_eq!(unsafe { *(address as *const i32) }, 5);
+
+        session = Session::default();
+        assert!(type_of("total", &session.scope).is_err());
+        assert_eq!(session.entries, 0);
+    }
+}
@@ -0,0 +1,167 @@
+//the Rust side of the runtime library (runtime/axon_runtime.c),
+//build.rs compiles it into the compiler, the interpreter calls these functions directly
+//and compiler_runtime maps the JIT's calls to them, `axon build` links RUNTIME_SOURCE into the binary instead
+
+use std::ffi::{c_char, c_void};
+
+// an `AxonTensor *`, the struct never leaves the C side
+pub type Tensor = *mut c_void;
+
+pub const RUNTIME_SOURCE: &str = include_str!("../runtime/axon_runtime.c");
+
+// must match axon_runtime.c
+unsafe extern "C" {
+    pub fn axon_tensor_fill(rank: i64, shape: *const i64, value: f32) -> Tensor;
+    pub fn axon_tensor_from(rank: i64, shape: *const i64, data: *const f32) -> Tensor;
+    pub fn axon_tensor_rand(rank: i64, shape: *const i64) -> Tensor;
+    pub fn axon_tensor_binary(op: i32, a: Tensor, b: Tensor) -> Tensor;
+    pub fn axon_tensor_matmul(a: Tensor, b: Tensor) -> Tensor;
+    pub fn axon_tensor_reshape(t: Tensor, rank: i64, shape: *const i64) -> Tensor;
+    pub fn axon_tensor_transpose(t: Tensor) -> Tensor;
+    pub fn axon_tensor_reduce(op: i32, t: Tensor) -> Tensor;
+    pub fn axon_tensor_reduce_axis(op: i32, t: Tensor, axis: i64) -> Tensor;
+    pub fn axon_tensor_unary(op: i32, t: Tensor) -> Tensor;
+    pub fn axon_tensor_softmax(t: Tensor) -> Tensor;
+    pub fn axon_tensor_get(t: Tensor, index: *const i64) -> f32;
+    pub fn axon_tensor_item(t: Tensor) -> f32;
+    pub fn axon_tensor_print(t: Tensor, newline: i32);
+    pub fn axon_tensor_backward(loss: Tensor);
+    pub fn axon_tensor_grad(t: Tensor) -> Tensor;
+    pub fn axon_tensor_grad_of(loss: Tensor, t: Tensor) -> Tensor;
+    pub fn axon_tensor_detach(t: Tensor) -> Tensor;
+    // only compiled code takes a second reference, interpreter values share theirs through an Rc
+    #[cfg(feature = "llvm")]
+    pub fn axon_tensor_retain(t: Tensor);
+    pub fn axon_tensor_release(t: Tensor);
+    pub fn axon_tensor_at(location: *const c_char);
+}
+
+// must match the enums in axon_runtime.c
+pub const OP_ADD: u64 = 0;
+pub const OP_SUB: u64 = 1;
+pub const OP_MUL: u64 = 2;
+pub const OP_DIV: u64 = 3;
+pub const REDUCE_SUM: u64 = 0;
+pub const REDUCE_MEAN: u64 = 1;
+pub const REDUCE_MAX: u64 = 2;
+pub const UNARY_RELU: u64 = 0;
+pub const UNARY_SIGMOID: u64 = 1;
+pub const UNARY_TANH: u64 = 2;
+pub const UNARY_EXP: u64 = 3;
+
+#[cfg(test)]
+mod tests {
+    // finite-difference gradient checks: for every taped operation, the gradient backward() leaves on an input
+    // has to match (L(x + ε) - L(x - ε)) / 2ε, one element at a time, with L = (out * out).sum()
+    use super::*;
+    use std::sync::Mutex;
+
+    const EPSILON: f32 = 1e-2;
+    const TOLERANCE: f32 = 1e-2;
+
+    // the tape epoch is global in the runtime, a backward() on another thread would clear our gradients
+    static TAPE: Mutex<()> = Mutex::new(());
+
+    fn tensor(shape: &[usize], data: &[f32]) -> Tensor {
+        let dims: Vec<i64> = shape.iter().map(|d| *d as i64).collect();
+        unsafe { axon_tensor_from(shape.len() as i64, dims.as_ptr(), data.as_ptr()) }
+    }
+
+    // the elements of `t` in row-major order
+    fn values(t: Tensor, shape: &[usize]) -> Vec<f32> {
+        let size: usize = shape.iter().product();
+        (0..size)
+            .map(|flat| {
+                let mut index = vec![0i64; shape.len()];
+                let mut rest = flat;
+                for axis in (0..shape.len()).rev() {
+                    index[axis] = (rest % shape[axis]) as i64;
+                    rest /= shape[axis];
+                }
+                unsafe { axon_tensor_get(t, index.as_ptr()) }
+            })
+            .collect()
+    }
+
+    // `op` builds the output from tensors holding `inputs`, every input element is checked
+    fn gradcheck(name: &str, inputs: &[(&[usize], &[f32])], op: impl Fn(&[Tensor]) -> Tensor) {
+        let _tape = TAPE.lock().unwrap_or_else(|e| e.into_inner());
+        let loss = |data: &[Vec<f32>]| unsafe {
+            let tensors: Vec<Tensor> = inputs.iter().zip(data).map(|((shape, _), d)| tensor(shape, d)).collect();
+            let out = op(&tensors);
+            let loss = axon_tensor_reduce(REDUCE_SUM as i32, axon_tensor_binary(OP_MUL as i32, out, out));
+            (loss, tensors)
+        };
+        let data: Vec<Vec<f32>> = inputs.iter().map(|(_, d)| d.to_vec()).collect();
+        let (l, tensors) = loss(&data);
+        unsafe { axon_tensor_backward(l) };
+        for (n, (shape, _)) in inputs.iter().enumerate() {
+            let analytic = values(unsafe { axon_tensor_grad(tensors[n]) }, shape);
+            for i in 0..data[n].len() {
+                let at = |delta: f32| {
+                    let mut moved = data.clone();
+                    moved[n][i] += delta;
+                    unsafe { axon_tensor_item(loss(&moved).0) }
+                };
+                let numeric = (at(EPSILON) - at(-EPSILON)) / (2.0 * EPSILON);
+                assert!(
+                    (analytic[i] - numeric).abs() <= TOLERANCE * (1.0 + numeric.abs()),
+                    "{}: input {} element {}: backward gives {}, finite differences {}",
+                    name,
+                    n,
+                    i,
+                    analytic[i],
+                    numeric
+                );
+            }
+        }
+    }
+
+    const A: (&[usize], &[f32]) = (&[2, 3], &[0.5, -1.2, 2.0, 1.5, 0.3, -0.7]);
+    const B: (&[usize], &[f32]) = (&[2, 3], &[1.1, 0.4, -0.9, 2.2, -1.6, 0.8]);
+    const ROW: (&[usize], &[f32]) = (&[3], &[0.6, -1.3, 1.9]);
+
+    #[test]
+    fn binary_ops() {
+        for (name, op) in [("add", OP_ADD), ("sub", OP_SUB), ("mul", OP_MUL), ("div", OP_DIV)] {
+            gradcheck(name, &[A, B], |t| unsafe { axon_tensor_binary(op as i32, t[0], t[1]) });
+            // the row is broadcast over both rows of A, its gradient sums them
+            gradcheck(name, &[A, ROW], |t| unsafe { axon_tensor_binary(op as i32, t[0], t[1]) });
+        }
+    }
+
+    #[test]
+    fn matmul() {
+        let right: (&[usize], &[f32]) = (&[3, 2], &[0.2, -0.5, 1.3, 0.9, -1.1, 0.4]);
+        gradcheck("matmul", &[A, right], |t| unsafe { axon_tensor_matmul(t[0], t[1]) });
+    }
+
+    #[test]
+    fn reshape_and_transpose() {
+        gradcheck("reshape", &[A], |t| unsafe { axon_tensor_reshape(t[0], 2, [3i64, 2].as_ptr()) });
+        gradcheck("transpose", &[A], |t| unsafe { axon_tensor_transpose(t[0]) });
+    }
+
+    #[test]
+    fn reductions() {
+        for (name, op) in [("sum", REDUCE_SUM), ("mean", REDUCE_MEAN), ("max", REDUCE_MAX)] {
+            gradcheck(name, &[A], |t| unsafe { axon_tensor_reduce(op as i32, t[0]) });
+            for axis in 0..2 {
+                gradcheck(name, &[A], |t| unsafe { axon_tensor_reduce_axis(op as i32, t[0], axis) });
+            }
+        }
+    }
+
+    // A keeps every element further than ε from relu's kink at 0
+    #[test]
+    fn unary_ops() {
+        for (name, op) in [("relu", UNARY_RELU), ("sigmoid", UNARY_SIGMOID), ("tanh", UNARY_TANH), ("exp", UNARY_EXP)] {
+            gradcheck(name, &[A], |t| unsafe { axon_tensor_unary(op as i32, t[0]) });
+        }
+    }
+
+    #[test]
+    fn softmax() {
+        gradcheck("softmax", &[A], |t| unsafe { axon_tensor_softmax(t[0]) });
+    }
+}
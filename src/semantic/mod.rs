@@ -1,5 +1,6 @@
 
 pub mod semantic_analysis;
 pub mod semantic_error;
+pub mod semantic_tensor;
 
 pub use semantic_analysis::*;
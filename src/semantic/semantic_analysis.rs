@@ -7,6 +7,8 @@
 use crate::ast::*;
 use crate::high_level_ir::*;
 use crate::semantic::semantic_error::SemanticError;
+use crate::semantic::semantic_tensor::*;
+use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
 
 pub struct SemanticResult<T> {
@@ -16,24 +18,118 @@ pub struct SemanticResult<T> {
 }
 
 struct SemanticContext {
-    functions: HashSet<String>,
+    functions: HashMap<String, (Vec<HIRType>, HIRType)>,
     variables: HashMap<String, HIRType>,
     const_values: HashMap<String, i64>,
+    // the `set:` variables in scope, what the checks look at
     mutable_vars: HashSet<String>,
+    // every name declared with `set:` anywhere, what the backends get
+    declared_mutable: HashSet<String>,
     start_count: usize,
     loop_depth: usize,
+    current_return: Option<HIRType>,
+    // span of the innermost statement or expression being checked, errors point here
+    span: Cell<(usize, usize)>,
+    // span of the function being checked, the scope of the names declared inside it
+    scope: Option<(usize, usize)>,
+    symbols: Vec<Symbol>,
+}
+
+// a name the program declares, kept for editor tooling (`axon lsp`)
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    // the value type, the return type for functions
+    pub ty: HIRType,
+    // the statement that declares it
+    pub span: Span,
+    // the function it was declared in, None at the top level
+    pub scope: Option<Span>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SymbolKind {
+    Function { params: Vec<(String, HIRType)> },
+    Parameter,
+    Variable,
+}
+
+impl SemanticContext {
+    fn error(&self, message: impl Into<String>, src: &Option<String>) -> SemanticError {
+        let (start, end) = self.span.get();
+        SemanticError::new(message, start, end, src.clone())
+    }
+
+    // returns the previous span so the caller can restore it
+    fn enter(&self, span: &Span) -> (usize, usize) {
+        self.span.replace((span.start, span.end))
+    }
+
+    fn declare(&mut self, name: &str, kind: SymbolKind, ty: HIRType) {
+        let (start, end) = self.span.get();
+        self.symbols.push(Symbol {
+            name: name.to_string(),
+            kind,
+            ty,
+            span: start..end,
+            scope: self.scope.map(|(start, end)| start..end),
+        });
+    }
 }
 
 pub fn ast_to_hir(ast: Vec<Statement>, src: Option<String>) -> SemanticResult<Vec<HIRStatement>> {
+    ast_to_hir_with_symbols(ast, src).0
+}
+
+// the same check, also returns every function, parameter and variable it saw declared
+pub fn ast_to_hir_with_symbols(
+    ast: Vec<Statement>,
+    src: Option<String>,
+) -> (SemanticResult<Vec<HIRStatement>>, Vec<Symbol>) {
     let mut ctx = SemanticContext {
-        functions: HashSet::new(),
+        functions: HashMap::new(),
         variables: HashMap::new(),
         const_values: HashMap::new(),
         mutable_vars: HashSet::new(),
+        declared_mutable: HashSet::new(),
         start_count: 0,
         loop_depth: 0,
+        current_return: None,
+        span: Cell::new((0, 0)),
+        scope: None,
+        symbols: Vec::new(),
     };
+    // signatures are collected first so functions can call each other (and themselves)
+    // regardless of the order they are declared in
+    let mut signature_errors = Vec::new();
+    for stmt in &ast {
+        if let Statement::Located { span, .. } = stmt {
+            ctx.enter(span);
+        }
+        if let Statement::FunctionCall { name, params, return_type, start, .. } = stmt.unlocated() {
+            if *start && (!params.is_empty() || return_type.is_some()) {
+                signature_errors.push(ctx.error(
+                    "\x1b[1;31m[ERR-SEM-304]\x1b[0m \x1b[1;36mcast Start()\x1b[0m cannot take parameters or declare a return type",
+                    &src,
+                ));
+            }
+            let param_types = params.iter().map(|(_, t)| type_to_hir(t.clone())).collect();
+            let ret = return_type.clone().map(type_to_hir).unwrap_or(HIRType::Void);
+            if ctx.functions.insert(name.clone(), (param_types, ret)).is_some() {
+                signature_errors.push(ctx.error(
+                    format!(
+                        "\x1b[1;31m[ERR-SEM-303]\x1b[0m Function '{}' is declared more than once",
+                        name
+                    ),
+                    &src,
+                ));
+            }
+        }
+    }
+    ctx.span.set((0, 0));
     let mut intermediate = ast_to_hir_with_ctx(ast, &src, &mut ctx);
+    intermediate.errors.splice(0..0, signature_errors);
     intermediate.result.insert(
         0,
         HIRStatement::Assignment {
@@ -42,21 +138,121 @@ pub fn ast_to_hir(ast: Vec<Statement>, src: Option<String>) -> SemanticResult<Ve
         },
     );
     if ctx.start_count != 1 {
-        intermediate.errors.push(SemanticError::new(
+        intermediate.errors.push(ctx.error(
             format!(
                 "\x1b[1;31m[ERR-SEM-301]\x1b[0m Program must have \x1b[1;33mexactly one\x1b[0m \x1b[1;36mcast Start() << >>\x1b[0m function.\n\
 Hint: Declare a start function like so:\n\
 \x1b[1;32mcast Start() >>\n   ?? ...your code...\n<<\x1b[0m"
             ),
-            0,
-            0,
-            src.clone(),
+            &src,
         ));
     }
-    SemanticResult {
+    let result = SemanticResult {
         result: intermediate.result,
         errors: intermediate.errors,
-        mutable_vars: ctx.mutable_vars.clone(),
+        mutable_vars: ctx.declared_mutable.clone(),
+    };
+    (result, ctx.symbols)
+}
+
+// what earlier `axon repl` entries left behind, the functions they declared and their variables
+#[derive(Default, Clone)]
+pub struct ReplScope {
+    pub functions: HashMap<String, (Vec<HIRType>, HIRType)>,
+    pub variables: HashMap<String, HIRType>,
+    pub mutable_vars: HashSet<String>,
+}
+
+impl ReplScope {
+    // values from earlier entries are only known at run time, so no const_values
+    fn context(&self) -> SemanticContext {
+        SemanticContext {
+            functions: self.functions.clone(),
+            variables: self.variables.clone(),
+            const_values: HashMap::new(),
+            mutable_vars: self.mutable_vars.clone(),
+            declared_mutable: self.mutable_vars.clone(),
+            start_count: 0,
+            loop_depth: 0,
+            current_return: None,
+            span: Cell::new((0, 0)),
+            scope: None,
+            symbols: Vec::new(),
+        }
+    }
+}
+
+// one REPL entry, its `cast` declarations stay functions and every other statement goes into
+// the body of the function `entry`, the scope only takes the new names when the entry checks cleanly
+pub fn repl_entry_to_hir(
+    ast: Vec<Statement>,
+    src: Option<String>,
+    entry: &str,
+    scope: &mut ReplScope,
+) -> SemanticResult<Vec<HIRStatement>> {
+    let mut ctx = scope.context();
+    let mut errors = Vec::new();
+    let (functions, statements): (Vec<_>, Vec<_>) = ast
+        .into_iter()
+        .partition(|stmt| matches!(stmt.unlocated(), Statement::FunctionCall { .. }));
+    for stmt in &functions {
+        if let Statement::Located { span, .. } = stmt {
+            ctx.enter(span);
+        }
+        if let Statement::FunctionCall { name, params, return_type, start, .. } = stmt.unlocated() {
+            if *start {
+                errors.push(ctx.error(
+                    "\x1b[1;31m[ERR-SEM-305]\x1b[0m \x1b[1;36mcast Start()\x1b[0m is not needed in the REPL, statements run as soon as they are entered",
+                    &src,
+                ));
+            }
+            let param_types = params.iter().map(|(_, t)| type_to_hir(t.clone())).collect();
+            let ret = return_type.clone().map(type_to_hir).unwrap_or(HIRType::Void);
+            if ctx.functions.insert(name.clone(), (param_types, ret)).is_some() {
+                errors.push(ctx.error(
+                    format!(
+                        "\x1b[1;31m[ERR-SEM-303]\x1b[0m Function '{}' is declared more than once",
+                        name
+                    ),
+                    &src,
+                ));
+            }
+        }
+    }
+    ctx.span.set((0, 0));
+    let mut hir = ast_to_hir_with_ctx(functions, &src, &mut ctx);
+    ctx.current_return = Some(HIRType::Void);
+    let body = ast_to_hir_with_ctx(statements, &src, &mut ctx);
+    errors.append(&mut hir.errors);
+    errors.extend(body.errors);
+    hir.result.push(HIRStatement::Function {
+        name: entry.to_string(),
+        params: Vec::new(),
+        return_type: HIRType::Void,
+        start: false,
+        body: body.result,
+    });
+    if errors.is_empty() {
+        scope.functions = ctx.functions;
+        scope.variables = ctx.variables;
+        scope.mutable_vars = ctx.mutable_vars;
+    }
+    SemanticResult {
+        result: hir.result,
+        errors,
+        mutable_vars: ctx.declared_mutable,
+    }
+}
+
+// `:type expr` in the REPL
+pub fn repl_expr_type(expr: Expr, src: Option<String>, scope: &ReplScope) -> Result<HIRType, Vec<SemanticError>> {
+    let mut ctx = scope.context();
+    ctx.current_return = Some(HIRType::Void);
+    let res = expr_to_hir(expr, &src, &ctx);
+    if res.errors.is_empty() {
+        Ok(infer_expr_type(&res.result, &ctx))
+    } else {
+        Err(res.errors)
     }
 }
 
@@ -93,6 +289,16 @@ fn statement_to_hir(
             errors.extend(res.errors);
             out.extend(res.result);
         }
+        Statement::Located { span, stmt } => {
+            let outer = ctx.enter(&span);
+            let res = statement_to_hir(*stmt, src, ctx);
+            ctx.span.set(outer);
+            errors.extend(res.errors);
+            out.extend(res.result.into_iter().map(|stmt| HIRStatement::Located {
+                span: span.clone(),
+                stmt: Box::new(stmt),
+            }));
+        }
         Statement::Assignment {
             name,
             mutable,
@@ -100,18 +306,54 @@ fn statement_to_hir(
             value,
         } => {
             if !mutable && ctx.variables.contains_key(&name) && ctx.mutable_vars.contains(&name) {
-                errors.push(SemanticError::new(
+                errors.push(ctx.error(
                     format!(
                         "\x1b[1;31m[ERR-SEM-560]\x1b[0m Cannot reassign to immutable variable '{}'",
                         name
                     ),
-                    0,
-                    0,
-                    src.clone(),
+                    src,
                 ));
             }
-            let value_res = expr_to_hir(value, src, ctx);
-            errors.extend(value_res.errors);
+            let declared = type_var.map(type_to_hir);
+            let mut value_res = match (value, &declared) {
+                (Expr::Vector(elements), Some(HIRType::Vector(elem))) => {
+                    vector_literal_to_hir(elements, Some((**elem).clone()), src, ctx)
+                }
+                (Expr::Vector(elements), Some(HIRType::Tensor(shape))) => {
+                    tensor_literal_to_hir(elements, shape.clone(), src, ctx)
+                }
+                (value, _) => expr_to_hir(value, src, ctx),
+            };
+            errors.extend(std::mem::take(&mut value_res.errors));
+            // the value takes the declared type, `set: x(f64) = 1.5;` stores an f64, not the f32 literal
+            if let Some(declared_ty) = &declared {
+                let value_ty = infer_expr_type(&value_res.result, ctx);
+                let value = value_res.result.clone();
+                if let Some(coerced) = coerce_to_type(value, &value_ty, declared_ty)
+                    .or_else(|| narrow_literal(&value_res.result, declared_ty))
+                {
+                    value_res.result = coerced;
+                }
+            }
+            if let Some(declared_ty) = &declared {
+                let value_ty = infer_expr_type(&value_res.result, ctx);
+                // vectors and tensors have to match exactly, a number can be widened but not narrowed
+                // (and is never a tensor, `t.sum()` needs `.item()` to become an f32)
+                let mismatch = match (declared_ty, &value_ty) {
+                    (HIRType::Vector(_) | HIRType::Tensor(_), _) => value_ty != *declared_ty,
+                    (_, HIRType::Vector(_) | HIRType::Tensor(_)) => true,
+                    _ => narrows(&value_ty, declared_ty),
+                };
+                if mismatch {
+                    errors.push(ctx.error(
+                        format!(
+                            "\x1b[1;31m[ERR-SEM-236]\x1b[0m Variable '{}' is declared as {:?} but initialized with {:?}",
+                            name, declared_ty, value_ty
+                        ),
+                        src,
+                    ));
+                }
+            }
             match &value_res.result {
                 HIRExpr::Int32(v) => {
                     ctx.const_values.insert(name.clone(), *v as i64);
@@ -123,11 +365,17 @@ fn statement_to_hir(
                     ctx.const_values.remove(&name);
                 }
             }
-            if let Some(t) = type_var {
-                ctx.variables.insert(name.clone(), type_to_hir(t));
+            errors.extend(void_call_error(&value_res.result, src, ctx));
+            if declared.is_some() || !ctx.variables.contains_key(&name) {
+                let ty = declared.clone().unwrap_or_else(|| infer_expr_type(&value_res.result, ctx));
+                ctx.declare(&name, SymbolKind::Variable, ty);
+            }
+            if let Some(t) = declared {
+                ctx.variables.insert(name.clone(), t);
             }
             if mutable {
                 ctx.mutable_vars.insert(name.clone());
+                ctx.declared_mutable.insert(name.clone());
             } else {
                 ctx.mutable_vars.remove(&name);
             }
@@ -139,85 +387,196 @@ fn statement_to_hir(
         Statement::FunctionCall {
             name,
             params,
+            return_type,
             start,
             body,
         } => {
+            if ctx.current_return.is_some() {
+                errors.push(ctx.error(
+                    format!(
+                        "\x1b[1;31m[ERR-SEM-302]\x1b[0m Function '{}' must be declared at the top level, not inside another function",
+                        name
+                    ),
+                    src,
+                ));
+            }
             if start {
                 ctx.start_count += 1;
             }
-            let mut hir_params = Vec::new();
-            for param in params {
-                if let Expr::Identifier(id) = param {
-                    ctx.variables.insert(id.clone(), HIRType::Void);
-                    hir_params.push((id, HIRType::Void));
-                }
+            let outer_vars = ctx.variables.clone();
+            let outer_consts = ctx.const_values.clone();
+            let outer_mutable = ctx.mutable_vars.clone();
+            let return_type = match return_type {
+                Some(t) if !start => type_to_hir(t),
+                _ => HIRType::Void,
+            };
+            let hir_params: Vec<_> = params.into_iter().map(|(id, ty)| (id, type_to_hir(ty))).collect();
+            ctx.declare(&name, SymbolKind::Function { params: hir_params.clone() }, return_type.clone());
+            let outer_scope = ctx.scope.replace(ctx.span.get());
+            for (id, ty) in &hir_params {
+                ctx.declare(id, SymbolKind::Parameter, ty.clone());
+                ctx.variables.insert(id.clone(), ty.clone());
+                ctx.const_values.remove(id);
+                ctx.mutable_vars.remove(id);
             }
+            let outer_return = ctx.current_return.replace(return_type.clone());
             let body_res = ast_to_hir_with_ctx(body, src, ctx);
+            ctx.current_return = outer_return;
+            ctx.scope = outer_scope;
+            ctx.variables = outer_vars;
+            ctx.const_values = outer_consts;
+            ctx.mutable_vars = outer_mutable;
             errors.extend(body_res.errors);
             out.push(HIRStatement::Function {
                 name,
                 params: hir_params,
-                return_type: HIRType::Void,
+                return_type,
                 start,
                 body: body_res.result,
             });
         }
+        Statement::Return { value } => match (ctx.current_return.clone(), value) {
+            (None, _) => {
+                errors.push(ctx.error(
+                    "\x1b[1;31m[ERR-SEM-312]\x1b[0m 'return' used outside of function",
+                    src,
+                ));
+            }
+            (Some(HIRType::Void), None) => out.push(HIRStatement::Return { value: None }),
+            (Some(HIRType::Void), Some(_)) => {
+                errors.push(ctx.error(
+                    "\x1b[1;31m[ERR-SEM-313]\x1b[0m Cannot return a value from a function without a return type.\n\
+Hint: Declare it like so: \x1b[1;32mcast name(a: i32) -> i32 >> ... <<\x1b[0m",
+                    src,
+                ));
+            }
+            (Some(ret_ty), None) => {
+                errors.push(ctx.error(
+                    format!(
+                        "\x1b[1;31m[ERR-SEM-313]\x1b[0m Function must return a value of type {:?}",
+                        ret_ty
+                    ),
+                    src,
+                ));
+            }
+            (Some(ret_ty), Some(value)) => {
+                let res = expr_to_hir(value, src, ctx);
+                errors.extend(res.errors);
+                let value_ty = infer_expr_type(&res.result, ctx);
+                match coerce_to_type(res.result, &value_ty, &ret_ty) {
+                    Some(value) => out.push(HIRStatement::Return { value: Some(value) }),
+                    None => errors.push(ctx.error(
+                        format!(
+                            "\x1b[1;31m[ERR-SEM-314]\x1b[0m Cannot return {:?} from a function returning {:?}",
+                            value_ty, ret_ty
+                        ),
+                        src,
+                    )),
+                }
+            }
+        },
         Statement::Print { params } => {
             let mut hir_params = Vec::new();
             for expr in params {
                 let res = expr_to_hir(expr, src, ctx);
                 errors.extend(res.errors);
+                errors.extend(void_call_error(&res.result, src, ctx));
                 hir_params.push(res.result);
             }
             out.push(HIRStatement::Print { params: hir_params });
         }
 
-        Statement::Math { expression, destination, .. } => {
+        Statement::Math { expression, destination, err } => {
             let res = expr_to_hir(expression, src, ctx);
             errors.extend(res.errors);
             let ty = infer_expr_type(&res.result, ctx);
-            ctx.variables.insert(destination.clone(), ty);
-            out.push(HIRStatement::Assignment {
-                name: destination,
+            let is_new = ctx.variables.insert(destination.clone(), ty.clone()).is_none();
+            if is_new {
+                ctx.declare(&destination, SymbolKind::Variable, ty.clone());
+            }
+            let assignment = HIRStatement::Assignment {
+                name: destination.clone(),
                 value: res.result,
-            });
+            };
+            match err {
+                Some(message) => {
+                    // when the handler fires the destination keeps its old value, a new one starts at zero
+                    if let (true, Some(zero)) = (is_new, zero_value(&ty)) {
+                        out.push(HIRStatement::Assignment {
+                            name: destination,
+                            value: zero,
+                        });
+                    }
+                    out.push(HIRStatement::OnError {
+                        stmt: Box::new(assignment),
+                        message,
+                    });
+                }
+                None => out.push(assignment),
+            }
         }
 
-        Statement::Input { target, .. } => {
+        Statement::Input { target, err } => {
 
             let res = expr_to_hir(target, src, ctx);
             errors.extend(res.errors);
-            out.push(HIRStatement::Input { target: res.result });
-        }
-        Statement::If { logic, args, body, else_body } => {
-            if args.len() == 2 {
-                let left_res = expr_to_hir(args[0].clone(), src, ctx);
-                let right_res = expr_to_hir(args[1].clone(), src, ctx);
-                errors.extend(left_res.errors);
-                errors.extend(right_res.errors);
-                let op = logic_to_hir(logic);
-                let cond = HIRExpr::BinaryOp {
-                    left: Box::new(left_res.result),
-                    op,
-                    right: Box::new(right_res.result),
-                };
-                let body_res = ast_to_hir_with_ctx(body, src, ctx);
-                errors.extend(body_res.errors);
-                let else_hir = match else_body {
-                    Some(b) => {
-                        let else_res = ast_to_hir_with_ctx(b, src, ctx);
-                        errors.extend(else_res.errors);
-                        Some(else_res.result)
-                    }
-                    None => None,
-                };
-                out.push(HIRStatement::If {
-                    condition: cond,
-                    body: body_res.result,
-                    else_body: else_hir,
-                });
+            let input = HIRStatement::Input { target: res.result };
+            out.push(match err {
+                Some(message) => HIRStatement::OnError {
+                    stmt: Box::new(input),
+                    message,
+                },
+                None => input,
+            });
+        }
+        Statement::Assert { logic, args, source } => {
+            let mut args = args.into_iter();
+            let left_res = expr_to_hir(args.next().unwrap(), src, ctx);
+            let right_res = expr_to_hir(args.next().unwrap(), src, ctx);
+            errors.extend(left_res.errors);
+            errors.extend(right_res.errors);
+            let left_ty = infer_expr_type(&left_res.result, ctx);
+            let right_ty = infer_expr_type(&right_res.result, ctx);
+            match coerce_types(left_res.result, left_ty.clone(), right_res.result, right_ty.clone()) {
+                Ok((left, right, ty))
+                    if matches!(ty, HIRType::I32 | HIRType::I64 | HIRType::F32 | HIRType::F64 | HIRType::Bool) =>
+                {
+                    out.push(HIRStatement::Assert {
+                        left,
+                        op: logic_to_hir(logic),
+                        right,
+                        ty,
+                        source,
+                    });
+                }
+                _ => errors.push(ctx.error(
+                    format!(
+                        "\x1b[1;31m[ERR-SEM-330]\x1b[0m Cannot compare {:?} with {:?} in '{}', assertions work on numbers and bools of compatible types",
+                        left_ty, right_ty, source
+                    ),
+                    src,
+                )),
             }
         }
+        Statement::If { condition, body, else_body } => {
+            let cond_res = condition_to_hir(condition, src, ctx);
+            errors.extend(cond_res.errors);
+            let body_res = ast_to_hir_with_ctx(body, src, ctx);
+            errors.extend(body_res.errors);
+            let else_hir = match else_body {
+                Some(b) => {
+                    let else_res = ast_to_hir_with_ctx(b, src, ctx);
+                    errors.extend(else_res.errors);
+                    Some(else_res.result)
+                }
+                None => None,
+            };
+            out.push(HIRStatement::If {
+                condition: cond_res.result,
+                body: body_res.result,
+                else_body: else_hir,
+            });
+        }
         Statement::Loop { body } => {
             ctx.loop_depth += 1;
             let body_res = ast_to_hir_with_ctx(body, src, ctx);
@@ -225,40 +584,90 @@ fn statement_to_hir(
             errors.extend(body_res.errors);
             out.push(HIRStatement::Loop { body: body_res.result });
         }
-        Statement::While { logic, args, body } => {
-            if args.len() == 2 {
-                let left_res = expr_to_hir(args[0].clone(), src, ctx);
-                let right_res = expr_to_hir(args[1].clone(), src, ctx);
-                errors.extend(left_res.errors);
-                errors.extend(right_res.errors);
-                let op = logic_to_hir(logic);
-                let cond = HIRExpr::BinaryOp {
-                    left: Box::new(left_res.result),
-                    op,
-                    right: Box::new(right_res.result),
-                };
-                ctx.loop_depth += 1;
-                let body_res = ast_to_hir_with_ctx(body, src, ctx);
-                ctx.loop_depth -= 1;
-                errors.extend(body_res.errors);
-                out.push(HIRStatement::While {
-                    condition: cond,
-                    body: body_res.result,
-                });
+        Statement::While { condition, body } => {
+            let cond_res = condition_to_hir(condition, src, ctx);
+            errors.extend(cond_res.errors);
+            ctx.loop_depth += 1;
+            let body_res = ast_to_hir_with_ctx(body, src, ctx);
+            ctx.loop_depth -= 1;
+            errors.extend(body_res.errors);
+            out.push(HIRStatement::While {
+                condition: cond_res.result,
+                body: body_res.result,
+            });
+        }
+        Statement::IndexAssign { name, index, value } => {
+            let elem_ty = match ctx.variables.get(&name) {
+                Some(HIRType::Vector(elem)) => Some((**elem).clone()),
+                Some(other) => {
+                    errors.push(ctx.error(
+                        format!(
+                            "\x1b[1;31m[ERR-SEM-233]\x1b[0m Cannot index into '{}' of type {:?}",
+                            name, other
+                        ),
+                        src,
+                    ));
+                    None
+                }
+                None => {
+                    errors.push(ctx.error(
+                        format!(
+                            "\x1b[1;31m[ERR-SEM-999]\x1b[0m Variable '{}' used before declaration",
+                            name
+                        ),
+                        src,
+                    ));
+                    None
+                }
+            };
+            if !ctx.mutable_vars.contains(&name) {
+                errors.push(ctx.error(
+                    format!(
+                        "\x1b[1;31m[ERR-SEM-561]\x1b[0m Cannot modify immutable vector '{}'",
+                        name
+                    ),
+                    src,
+                ));
+            }
+            let index_res = index_to_hir(index, src, ctx);
+            let value_res = expr_to_hir(value, src, ctx);
+            errors.extend(index_res.errors);
+            errors.extend(value_res.errors);
+            if let Some(elem_ty) = elem_ty {
+                let value_ty = infer_expr_type(&value_res.result, ctx);
+                match coerce_to_type(value_res.result, &value_ty, &elem_ty) {
+                    Some(value) => out.push(HIRStatement::IndexAssign {
+                        name,
+                        index: index_res.result,
+                        value,
+                    }),
+                    None => errors.push(ctx.error(
+                        format!(
+                            "\x1b[1;31m[ERR-SEM-232]\x1b[0m Cannot store {:?} into vector '{}' of {:?}",
+                            value_ty, name, elem_ty
+                        ),
+                        src,
+                    )),
+                }
             }
         }
+        Statement::ExprStatement { expr } => {
+            let res = expr_to_hir(expr, src, ctx);
+            errors.extend(res.errors);
+            out.push(HIRStatement::ExprStatement { expr: res.result });
+        }
         Statement::Break => {
             if ctx.loop_depth == 0 {
-                errors.push(SemanticError::new(
+                errors.push(ctx.error(
                     "\x1b[1;31m[ERR-SEM-310]\x1b[0m 'break' used outside of loop",
-                    0,
-                    0,
-                    src.clone(),
+                    src,
                 ));
             } else {
                 out.push(HIRStatement::Break);
             }
         }
+        // imports are resolved by the module loader before this pass
+        Statement::Use { .. } => {}
     }
     SemanticResult {
         result: out,
@@ -276,37 +685,33 @@ fn expr_to_hir(expr: Expr, src: &Option<String>, ctx: &SemanticContext) -> Seman
         Expr::Float64(f) => HIRExpr::Float64(f),
         Expr::String(s) => HIRExpr::String(s),
         Expr::Bool(b) => HIRExpr::Bool(b),
+        Expr::Located { span, expr } => {
+            let outer = ctx.enter(&span);
+            let res = expr_to_hir(*expr, src, ctx);
+            ctx.span.set(outer);
+            return res;
+        }
         Expr::Identifier(name) => {
             if !ctx.variables.contains_key(&name) {
-                errors.push(SemanticError::new(
+                errors.push(ctx.error(
                     format!(
                         "\x1b[1;31m[ERR-SEM-999]\x1b[0m Variable '{}' used before declaration",
                         name
                     ),
-                    0,
-                    0,
-                    src.clone(),
+                    src,
                 ));
             }
             HIRExpr::Identifier(name)
         }
         Expr::BinaryOp { left, op, right } => {
-            if let Operator::Divide = op {
-                if let Expr::Identifier(name) = &*right {
-                    if let Some(val) = ctx.const_values.get(name) {
-                        if *val == 0 {
-                            errors.push(SemanticError::new(
-                                format!(
-                                    "\x1b[1;31m[ERR-SEM-550]\x1b[0m Division by variable '{}' with known value 0",
-                                    name
-                                ),
-                                0,
-                                0,
-                                src.clone(),
-                            ));
-                        }
-                    }
-                }
+            if let Operator::Divide | Operator::Modulo = op
+                && let Some(divisor) = zero_divisor(right.unlocated(), ctx)
+            {
+                let message = match op {
+                    Operator::Divide => format!("\x1b[1;31m[ERR-SEM-550]\x1b[0m Division by {}", divisor),
+                    _ => format!("\x1b[1;31m[ERR-SEM-551]\x1b[0m Remainder of division by {}", divisor),
+                };
+                errors.push(ctx.error(message, src));
             }
             let left_res = expr_to_hir(*left, src, ctx);
             let right_res = expr_to_hir(*right, src, ctx);
@@ -314,28 +719,550 @@ fn expr_to_hir(expr: Expr, src: &Option<String>, ctx: &SemanticContext) -> Seman
             errors.extend(right_res.errors);
             let left_ty = infer_expr_type(&left_res.result, ctx);
             let right_ty = infer_expr_type(&right_res.result, ctx);
-            match coerce_types(left_res.result, left_ty, right_res.result, right_ty) {
-                Ok((new_left, new_right, _)) => HIRExpr::BinaryOp {
-                    left: Box::new(new_left),
-                    op: operator_to_hir(op),
-                    right: Box::new(new_right),
+            let combined = if let Operator::And | Operator::Or = op {
+                logical_to_hir(left_res.result, left_ty, op, right_res.result, right_ty)
+            } else if let Operator::Compare(logic) = op {
+                comparison_to_hir(left_res.result, left_ty, logic, right_res.result, right_ty)
+            } else if matches!(left_ty, HIRType::Tensor(_)) || matches!(right_ty, HIRType::Tensor(_)) {
+                tensor_binary_to_hir(left_res.result, left_ty, op, right_res.result, right_ty)
+            } else {
+                coerce_types(left_res.result, left_ty, right_res.result, right_ty).map(
+                    |(new_left, new_right, _)| HIRExpr::BinaryOp {
+                        left: Box::new(new_left),
+                        op: operator_to_hir(op),
+                        right: Box::new(new_right),
+                    },
+                )
+            };
+            match combined {
+                Ok(expr) => expr,
+                Err(e) => {
+                    errors.push(ctx.error(e, src));
+                    HIRExpr::Int32(0)
+                }
+            }
+        }
+        Expr::Not { expr } => {
+            let res = expr_to_hir(*expr, src, ctx);
+            errors.extend(res.errors);
+            let ty = infer_expr_type(&res.result, ctx);
+            if ty != HIRType::Bool {
+                errors.push(ctx.error(
+                    format!("\x1b[1;31m[ERR-SEM-342]\x1b[0m 'not' expects a bool, found {:?}", ty),
+                    src,
+                ));
+            }
+            HIRExpr::Not {
+                expr: Box::new(res.result),
+            }
+        }
+        Expr::Vector(elements) => {
+            let res = vector_literal_to_hir(elements, None, src, ctx);
+            errors.extend(res.errors);
+            res.result
+        }
+        Expr::Index { target, index } => {
+            let target_res = expr_to_hir(*target, src, ctx);
+            let index_res = index_to_hir(*index, src, ctx);
+            errors.extend(target_res.errors);
+            errors.extend(index_res.errors);
+            let target_ty = infer_expr_type(&target_res.result, ctx);
+            if !matches!(target_ty, HIRType::Vector(_)) {
+                errors.push(ctx.error(
+                    format!(
+                        "\x1b[1;31m[ERR-SEM-233]\x1b[0m Cannot index into a value of type {:?}",
+                        target_ty
+                    ),
+                    src,
+                ));
+            }
+            HIRExpr::Index {
+                target: Box::new(target_res.result),
+                index: Box::new(index_res.result),
+            }
+        }
+        Expr::Call { name, args } if !ctx.functions.contains_key(&name) && name == "grad" => {
+            let mut hir_args = Vec::new();
+            for arg in args {
+                let res = expr_to_hir(arg, src, ctx);
+                errors.extend(res.errors);
+                hir_args.push(res.result);
+            }
+            let types: Vec<HIRType> = hir_args.iter().map(|arg| infer_expr_type(arg, ctx)).collect();
+            match types.as_slice() {
+                [HIRType::Tensor(_), HIRType::Tensor(shape)] => HIRExpr::TensorCall {
+                    func: TensorFunc::GradOf,
+                    ty: HIRType::Tensor(shape.clone()),
+                    args: hir_args,
                 },
+                _ => {
+                    errors.push(ctx.error(
+                        format!(
+                            "\x1b[1;31m[ERR-SEM-250]\x1b[0m grad() expects a loss tensor and a parameter tensor, found {:?}",
+                            types
+                        ),
+                        src,
+                    ));
+                    HIRExpr::Int32(0)
+                }
+            }
+        }
+        Expr::Call { name, args }
+            if !ctx.functions.contains_key(&name) && matches!(name.as_str(), "zeros" | "ones" | "rand") =>
+        {
+            match tensor_builtin_to_hir(&name, &args) {
+                Ok(expr) => expr,
                 Err(e) => {
-                    errors.push(SemanticError::new(e, 0, 0, src.clone()));
+                    errors.push(ctx.error(e, src));
                     HIRExpr::Int32(0)
                 }
             }
         }
-        Expr::Vector(_) => {
-            errors.push(SemanticError::new(
-                "\x1b[1;31m[ERR-SEM-230]\x1b[0m Vector literal expressions are not directly supported in HIRExpr.",
-                0,
-                0,
-                src.clone(),
+        Expr::Call { name, args } => {
+            let Some((param_types, _)) = ctx.functions.get(&name).cloned() else {
+                errors.push(ctx.error(
+                    format!(
+                        "\x1b[1;31m[ERR-SEM-320]\x1b[0m Call to undeclared function '{}'",
+                        name
+                    ),
+                    src,
+                ));
+                return SemanticResult {
+                    result: HIRExpr::Int32(0),
+                    errors,
+                    mutable_vars: HashSet::new(),
+                };
+            };
+            if args.len() != param_types.len() {
+                errors.push(ctx.error(
+                    format!(
+                        "\x1b[1;31m[ERR-SEM-321]\x1b[0m Function '{}' expects {} argument(s), but {} were given",
+                        name,
+                        param_types.len(),
+                        args.len()
+                    ),
+                    src,
+                ));
+            }
+            let mut hir_args = Vec::new();
+            for (i, (arg, param_ty)) in args.into_iter().zip(param_types.iter()).enumerate() {
+                let res = expr_to_hir(arg, src, ctx);
+                errors.extend(res.errors);
+                let arg_ty = infer_expr_type(&res.result, ctx);
+                match coerce_to_type(res.result, &arg_ty, param_ty) {
+                    Some(value) => hir_args.push(value),
+                    None => errors.push(ctx.error(
+                        format!(
+                            "\x1b[1;31m[ERR-SEM-322]\x1b[0m Argument {} of '{}' has type {:?}, expected {:?}",
+                            i + 1,
+                            name,
+                            arg_ty,
+                            param_ty
+                        ),
+                        src,
+                    )),
+                }
+            }
+            HIRExpr::FunctionCall { name, args: hir_args }
+        }
+        Expr::MethodCall { target, method, args } => {
+            if let Expr::Identifier(name) = target.unlocated()
+                && (method == "push" || method == "pop")
+                && !ctx.mutable_vars.contains(name)
+            {
+                errors.push(ctx.error(
+                    format!(
+                        "\x1b[1;31m[ERR-SEM-561]\x1b[0m Cannot modify immutable vector '{}'",
+                        name
+                    ),
+                    src,
+                ));
+            }
+            let target_res = expr_to_hir(*target, src, ctx);
+            errors.extend(target_res.errors);
+            let target_ty = infer_expr_type(&target_res.result, ctx);
+            if let HIRType::Tensor(shape) = target_ty {
+                let res = tensor_method_to_hir(target_res.result, shape, &method, args, src, ctx);
+                errors.extend(res.errors);
+                return SemanticResult {
+                    result: res.result,
+                    errors,
+                    mutable_vars: HashSet::new(),
+                };
+            }
+            let HIRType::Vector(elem_ty) = target_ty else {
+                errors.push(ctx.error(
+                    format!(
+                        "\x1b[1;31m[ERR-SEM-235]\x1b[0m Type {:?} has no method '{}'",
+                        target_ty, method
+                    ),
+                    src,
+                ));
+                return SemanticResult {
+                    result: HIRExpr::Int32(0),
+                    errors,
+                    mutable_vars: HashSet::new(),
+                };
+            };
+            let target = Box::new(target_res.result);
+            match (method.as_str(), args.len()) {
+                ("len", 0) => HIRExpr::VectorLen { target },
+                ("pop", 0) => HIRExpr::VectorPop { target },
+                ("push", 1) => {
+                    let value_res = expr_to_hir(args.into_iter().next().unwrap(), src, ctx);
+                    errors.extend(value_res.errors);
+                    let value_ty = infer_expr_type(&value_res.result, ctx);
+                    match coerce_to_type(value_res.result, &value_ty, &elem_ty) {
+                        Some(value) => HIRExpr::VectorPush {
+                            target,
+                            value: Box::new(value),
+                        },
+                        None => {
+                            errors.push(ctx.error(
+                                format!(
+                                    "\x1b[1;31m[ERR-SEM-232]\x1b[0m Cannot push {:?} into a vector of {:?}",
+                                    value_ty, elem_ty
+                                ),
+                                src,
+                            ));
+                            HIRExpr::Int32(0)
+                        }
+                    }
+                }
+                ("len", _) | ("pop", _) | ("push", _) => {
+                    errors.push(ctx.error(
+                        format!(
+                            "\x1b[1;31m[ERR-SEM-254]\x1b[0m Method '{}' called with {} argument(s)",
+                            method,
+                            args.len()
+                        ),
+                        src,
+                    ));
+                    HIRExpr::Int32(0)
+                }
+                _ => {
+                    errors.push(ctx.error(
+                        format!(
+                            "\x1b[1;31m[ERR-SEM-235]\x1b[0m Unknown vector method '{}'. Available: len(), push(value), pop()",
+                            method
+                        ),
+                        src,
+                    ));
+                    HIRExpr::Int32(0)
+                }
+            }
+        }
+    };
+    SemanticResult {
+        result,
+        errors,
+        mutable_vars: HashSet::new(),
+    }
+}
+
+fn vector_literal_to_hir(
+    elements: Vec<Expr>,
+    expected: Option<HIRType>,
+    src: &Option<String>,
+    ctx: &SemanticContext,
+) -> SemanticResult<HIRExpr> {
+    let mut errors = Vec::new();
+    let mut hir_elements = Vec::new();
+    let mut elem_type = expected;
+    for element in elements {
+        let res = expr_to_hir(element, src, ctx);
+        errors.extend(res.errors);
+        let ty = infer_expr_type(&res.result, ctx);
+        let target_ty = elem_type.get_or_insert_with(|| ty.clone()).clone();
+        match coerce_to_type(res.result, &ty, &target_ty) {
+            Some(value) => hir_elements.push(value),
+            None => errors.push(ctx.error(
+                format!(
+                    "\x1b[1;31m[ERR-SEM-232]\x1b[0m Vector element {} has type {:?}, expected {:?}",
+                    hir_elements.len(),
+                    ty,
+                    target_ty
+                ),
+                src,
+            )),
+        }
+    }
+    let elem_type = elem_type.unwrap_or_else(|| {
+        errors.push(ctx.error(
+            "\x1b[1;31m[ERR-SEM-231]\x1b[0m Cannot infer the element type of an empty vector literal.\n\
+Hint: Declare the type explicitly: \x1b[1;32mset: v(Vec(i32)) = [];\x1b[0m",
+            src,
+        ));
+        HIRType::Void
+    });
+    SemanticResult {
+        result: HIRExpr::Vector {
+            elements: hir_elements,
+            elem_type,
+        },
+        errors,
+        mutable_vars: HashSet::new(),
+    }
+}
+
+fn tensor_literal_to_hir(
+    elements: Vec<Expr>,
+    shape: Vec<usize>,
+    src: &Option<String>,
+    ctx: &SemanticContext,
+) -> SemanticResult<HIRExpr> {
+    let mut flat = Vec::new();
+    let mut errors = Vec::new();
+    flatten_tensor_literal(Expr::Vector(elements), &shape, 0, &mut flat, &mut errors, src, ctx);
+    SemanticResult {
+        result: HIRExpr::Tensor {
+            elements: flat,
+            shape,
+        },
+        errors,
+        mutable_vars: HashSet::new(),
+    }
+}
+
+fn flatten_tensor_literal(
+    expr: Expr,
+    shape: &[usize],
+    depth: usize,
+    flat: &mut Vec<HIRExpr>,
+    errors: &mut Vec<SemanticError>,
+    src: &Option<String>,
+    ctx: &SemanticContext,
+) {
+    match expr {
+        Expr::Vector(items) if depth < shape.len() && items.len() == shape[depth] => {
+            for item in items {
+                flatten_tensor_literal(item, shape, depth + 1, flat, errors, src, ctx);
+            }
+        }
+        Expr::Vector(items) => {
+            errors.push(ctx.error(
+                literal_shape_error(shape, depth, Some(items.len())),
+                src,
+            ));
+        }
+        scalar if depth == shape.len() => {
+            let res = expr_to_hir(scalar, src, ctx);
+            errors.extend(res.errors);
+            let ty = infer_expr_type(&res.result, ctx);
+            match to_f32(res.result, &ty) {
+                Some(value) => flat.push(value),
+                None => errors.push(ctx.error(
+                    format!(
+                        "\x1b[1;31m[ERR-SEM-242]\x1b[0m Tensor elements must be numbers, found {:?}",
+                        ty
+                    ),
+                    src,
+                )),
+            }
+        }
+        _ => {
+            errors.push(ctx.error(
+                literal_shape_error(shape, depth, None),
+                src,
+            ));
+        }
+    }
+}
+
+// `zeros([2, 3])`, `ones([2, 3])`, `rand([2, 3])`
+fn tensor_builtin_to_hir(name: &str, args: &[Expr]) -> Result<HIRExpr, String> {
+    let shape = match args {
+        [arg] => literal_dims(arg),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        format!(
+            "\x1b[1;31m[ERR-SEM-248]\x1b[0m {}() expects a shape literal like {}([2, 3])",
+            name, name
+        )
+    })?;
+    let (func, args) = match name {
+        "zeros" => (TensorFunc::Fill, vec![HIRExpr::Float32(0.0)]),
+        "ones" => (TensorFunc::Fill, vec![HIRExpr::Float32(1.0)]),
+        _ => (TensorFunc::Rand, Vec::new()),
+    };
+    Ok(HIRExpr::TensorCall {
+        func,
+        args,
+        ty: HIRType::Tensor(shape),
+    })
+}
+
+fn literal_dims(expr: &Expr) -> Option<Vec<usize>> {
+    let Expr::Vector(items) = expr else {
+        return None;
+    };
+    items
+        .iter()
+        .map(|item| match item {
+            Expr::Int32(n) if *n > 0 => Some(*n as usize),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|dims| !dims.is_empty())
+}
+
+fn tensor_binary_to_hir(
+    left: HIRExpr,
+    left_ty: HIRType,
+    op: Operator,
+    right: HIRExpr,
+    right_ty: HIRType,
+) -> Result<HIRExpr, String> {
+    let func = match op {
+        Operator::Plus => TensorFunc::Add,
+        Operator::Minus => TensorFunc::Sub,
+        Operator::Multiply => TensorFunc::Mul,
+        Operator::Divide => TensorFunc::Div,
+        Operator::Modulo | Operator::Compare(_) | Operator::And | Operator::Or => {
+            return Err(format!(
+                "\x1b[1;31m[ERR-SEM-251]\x1b[0m '{}' is not supported on tensors ({:?} {} {:?})",
+                operator_symbol(op),
+                left_ty,
+                operator_symbol(op),
+                right_ty
             ));
-            HIRExpr::Int32(0)
         }
     };
+    let (left, left_shape) = as_tensor(left, left_ty)?;
+    let (right, right_shape) = as_tensor(right, right_ty)?;
+    let shape = broadcast_shapes(&left_shape, &right_shape)?;
+    Ok(HIRExpr::TensorCall {
+        func,
+        args: vec![left, right],
+        ty: HIRType::Tensor(shape),
+    })
+}
+
+// scalars take part in tensor math as a rank-0 tensor that broadcasts to any shape
+fn as_tensor(expr: HIRExpr, ty: HIRType) -> Result<(HIRExpr, Vec<usize>), String> {
+    match ty {
+        HIRType::Tensor(shape) => Ok((expr, shape)),
+        other => match to_f32(expr, &other) {
+            Some(value) => Ok((
+                HIRExpr::TensorCall {
+                    func: TensorFunc::Fill,
+                    args: vec![value],
+                    ty: HIRType::Tensor(Vec::new()),
+                },
+                Vec::new(),
+            )),
+            None => Err(format!(
+                "\x1b[1;31m[ERR-SEM-243]\x1b[0m Cannot combine a tensor with a value of type {:?}",
+                other
+            )),
+        },
+    }
+}
+
+fn to_f32(expr: HIRExpr, ty: &HIRType) -> Option<HIRExpr> {
+    match (expr, ty) {
+        (expr, HIRType::F32) => Some(expr),
+        (HIRExpr::Int32(v), _) => Some(HIRExpr::Float32(v as f32)),
+        (HIRExpr::Int64(v), _) => Some(HIRExpr::Float32(v as f32)),
+        (HIRExpr::Float64(v), _) => Some(HIRExpr::Float32(v as f32)),
+        (expr, HIRType::I32 | HIRType::I64 | HIRType::F64) => Some(HIRExpr::Coerce {
+            expr: Box::new(expr),
+            target: HIRType::F32,
+        }),
+        _ => None,
+    }
+}
+
+fn tensor_method_to_hir(
+    target: HIRExpr,
+    shape: Vec<usize>,
+    method: &str,
+    args: Vec<Expr>,
+    src: &Option<String>,
+    ctx: &SemanticContext,
+) -> SemanticResult<HIRExpr> {
+    let mut errors = Vec::new();
+    let call = |func, args: Vec<HIRExpr>, ty| HIRExpr::TensorCall { func, args, ty };
+    let result = match (method, args.len()) {
+        ("matmul", 1) => {
+            let other_res = expr_to_hir(args.into_iter().next().unwrap(), src, ctx);
+            errors.extend(other_res.errors);
+            match infer_expr_type(&other_res.result, ctx) {
+                HIRType::Tensor(other_shape) => matmul_shape(&shape, &other_shape).map(|out| {
+                    call(TensorFunc::MatMul, vec![target, other_res.result], HIRType::Tensor(out))
+                }),
+                other => Err(format!(
+                    "\x1b[1;31m[ERR-SEM-244]\x1b[0m matmul() expects a tensor argument, found {:?}",
+                    other
+                )),
+            }
+        }
+        ("reshape", 1) => literal_dims(&args[0])
+            .ok_or_else(|| {
+                "\x1b[1;31m[ERR-SEM-245]\x1b[0m reshape() expects a shape literal like reshape([3, 2])".to_string()
+            })
+            .and_then(|to| reshape_shape(&shape, &to))
+            .map(|out| call(TensorFunc::Reshape, vec![target], HIRType::Tensor(out))),
+        ("transpose", 0) => transpose_shape(&shape)
+            .map(|out| call(TensorFunc::Transpose, vec![target], HIRType::Tensor(out))),
+        ("sum" | "mean" | "max", 0) => {
+            Ok(call(reduce_func(method, None), vec![target], HIRType::Tensor(Vec::new())))
+        }
+        ("sum" | "mean" | "max", 1) => match &args[0] {
+            Expr::Int32(axis) if *axis >= 0 => reduce_axis_shape(&shape, *axis as usize).map(|out| {
+                call(reduce_func(method, Some(*axis as usize)), vec![target], HIRType::Tensor(out))
+            }),
+            _ => Err(format!(
+                "\x1b[1;31m[ERR-SEM-246]\x1b[0m {}() expects the axis as an integer literal",
+                method
+            )),
+        },
+        ("relu" | "sigmoid" | "tanh" | "exp" | "softmax", 0) => {
+            let func = match method {
+                "relu" => TensorFunc::Relu,
+                "sigmoid" => TensorFunc::Sigmoid,
+                "tanh" => TensorFunc::Tanh,
+                "exp" => TensorFunc::Exp,
+                _ => TensorFunc::Softmax,
+            };
+            Ok(call(func, vec![target], HIRType::Tensor(shape)))
+        }
+        ("backward", 0) => Ok(call(TensorFunc::Backward, vec![target], HIRType::Void)),
+        ("grad", 0) => Ok(call(TensorFunc::Grad, vec![target], HIRType::Tensor(shape))),
+        ("detach", 0) => Ok(call(TensorFunc::Detach, vec![target], HIRType::Tensor(shape))),
+        ("item", 0) => item_shape(&shape).map(|()| call(TensorFunc::Item, vec![target], HIRType::F32)),
+        ("get", n) if n == shape.len() => {
+            let mut hir_args = vec![target];
+            let mut out_of_range = None;
+            for (axis, index) in args.into_iter().enumerate() {
+                if let Expr::Int32(i) = index
+                    && (i < 0 || i as usize >= shape[axis])
+                {
+                    out_of_range = Some((i, axis));
+                }
+                let res = index_to_hir(index, src, ctx);
+                errors.extend(res.errors);
+                hir_args.push(res.result);
+            }
+            match out_of_range {
+                Some((i, axis)) => Err(format!(
+                    "\x1b[1;31m[ERR-SEM-249]\x1b[0m Index {} is out of bounds for axis {} of a tensor with shape {:?}",
+                    i, axis, shape
+                )),
+                None => Ok(call(TensorFunc::Get, hir_args, HIRType::F32)),
+            }
+        }
+        _ => Err(format!(
+            "\x1b[1;31m[ERR-SEM-247]\x1b[0m Unknown tensor method '{}' with {} argument(s). Available: matmul(t), reshape([..]), transpose(), sum/mean/max([axis]), relu(), sigmoid(), tanh(), exp(), softmax(), get(i, ..), item(), backward(), grad(), detach()",
+            method,
+            args.len()
+        )),
+    };
+    let result = result.unwrap_or_else(|e| {
+        errors.push(ctx.error(e, src));
+        HIRExpr::Int32(0)
+    });
     SemanticResult {
         result,
         errors,
@@ -343,6 +1270,53 @@ fn expr_to_hir(expr: Expr, src: &Option<String>, ctx: &SemanticContext) -> Seman
     }
 }
 
+fn reduce_func(method: &str, axis: Option<usize>) -> TensorFunc {
+    match method {
+        "sum" => TensorFunc::Sum(axis),
+        "mean" => TensorFunc::Mean(axis),
+        _ => TensorFunc::Max(axis),
+    }
+}
+
+// a call used as a value, assigned or printed, has to return one
+fn void_call_error(expr: &HIRExpr, src: &Option<String>, ctx: &SemanticContext) -> Option<SemanticError> {
+    match expr {
+        HIRExpr::FunctionCall { name, .. } if infer_expr_type(expr, ctx) == HIRType::Void => Some(ctx.error(
+            format!("\x1b[1;31m[ERR-SEM-323]\x1b[0m Function '{}' does not return a value", name),
+            src,
+        )),
+        _ => None,
+    }
+}
+
+// the divisor of `/` or `%` when it is known to be 0, described for the error
+fn zero_divisor(divisor: &Expr, ctx: &SemanticContext) -> Option<String> {
+    match divisor {
+        Expr::Identifier(name) if ctx.const_values.get(name) == Some(&0) => {
+            Some(format!("variable '{}' with known value 0", name))
+        }
+        Expr::Int32(0) | Expr::Int64(0) => Some("zero".to_string()),
+        Expr::Float32(f) if *f == 0.0 => Some("zero".to_string()),
+        Expr::Float64(f) if *f == 0.0 => Some("zero".to_string()),
+        _ => None,
+    }
+}
+
+fn index_to_hir(index: Expr, src: &Option<String>, ctx: &SemanticContext) -> SemanticResult<HIRExpr> {
+    let mut res = expr_to_hir(index, src, ctx);
+    let ty = infer_expr_type(&res.result, ctx);
+    if !matches!(ty, HIRType::I32 | HIRType::I64) {
+        res.errors.push(ctx.error(
+            format!(
+                "\x1b[1;31m[ERR-SEM-234]\x1b[0m Vector index must be an integer, found {:?}",
+                ty
+            ),
+            src,
+        ));
+    }
+    res
+}
+
 fn infer_expr_type(expr: &HIRExpr, ctx: &SemanticContext) -> HIRType {
     match expr {
         HIRExpr::Int32(_) => HIRType::I32,
@@ -363,12 +1337,30 @@ fn infer_expr_type(expr: &HIRExpr, ctx: &SemanticContext) -> HIRType {
                 | HIROperator::GreaterThan
                 | HIROperator::LessThan
                 | HIROperator::GreaterEqual
-                | HIROperator::LessEqual => HIRType::Bool,
+                | HIROperator::LessEqual
+                | HIROperator::And
+                | HIROperator::Or => HIRType::Bool,
                 _ => common_ty,
             }
         }
-        HIRExpr::FunctionCall { .. } => HIRType::Void,
+        HIRExpr::FunctionCall { name, .. } => ctx
+            .functions
+            .get(name)
+            .map(|(_, ret)| ret.clone())
+            .unwrap_or(HIRType::Void),
+        HIRExpr::Not { .. } => HIRType::Bool,
         HIRExpr::Coerce { target, .. } => target.clone(),
+        HIRExpr::Vector { elem_type, .. } => HIRType::Vector(Box::new(elem_type.clone())),
+        HIRExpr::Index { target, .. } | HIRExpr::VectorPop { target } => {
+            match infer_expr_type(target, ctx) {
+                HIRType::Vector(elem) => *elem,
+                _ => HIRType::Void,
+            }
+        }
+        HIRExpr::Tensor { shape, .. } => HIRType::Tensor(shape.clone()),
+        HIRExpr::TensorCall { ty, .. } => ty.clone(),
+        HIRExpr::VectorLen { .. } => HIRType::I32,
+        HIRExpr::VectorPush { .. } => HIRType::Void,
     }
 }
 
@@ -380,7 +1372,8 @@ fn type_to_hir(typ: Type) -> HIRType {
         Type::F64 => HIRType::F64,
         Type::String => HIRType::String,
         Type::Bool => HIRType::Bool,
-        Type::Vector(_) => panic!("Vector types are not supported in HIRType."),
+        Type::Vector(inner) => HIRType::Vector(Box::new(type_to_hir(*inner))),
+        Type::Tensor(_, shape) => HIRType::Tensor(shape),
     }
 }
 
@@ -390,6 +1383,114 @@ fn operator_to_hir(op: Operator) -> HIROperator {
         Operator::Minus => HIROperator::Minus,
         Operator::Multiply => HIROperator::Multiply,
         Operator::Divide => HIROperator::Divide,
+        Operator::Modulo => HIROperator::Modulo,
+        Operator::Compare(logic) => logic_to_hir(logic),
+        Operator::And => HIROperator::And,
+        Operator::Or => HIROperator::Or,
+    }
+}
+
+fn zero_value(ty: &HIRType) -> Option<HIRExpr> {
+    match ty {
+        HIRType::I32 => Some(HIRExpr::Int32(0)),
+        HIRType::I64 => Some(HIRExpr::Int64(0)),
+        HIRType::F32 => Some(HIRExpr::Float32(0.0)),
+        HIRType::F64 => Some(HIRExpr::Float64(0.0)),
+        HIRType::Bool => Some(HIRExpr::Bool(false)),
+        _ => None,
+    }
+}
+
+fn operator_symbol(op: Operator) -> &'static str {
+    match op {
+        Operator::Plus => "+",
+        Operator::Minus => "-",
+        Operator::Multiply => "*",
+        Operator::Divide => "/",
+        Operator::Modulo => "%",
+        Operator::Compare(Logic::Equal) => "==",
+        Operator::Compare(Logic::NotEqual) => "!=",
+        Operator::Compare(Logic::Greater) => ">",
+        Operator::Compare(Logic::Less) => "<",
+        Operator::Compare(Logic::GreaterEqual) => ">=",
+        Operator::Compare(Logic::LessEqual) => "<=",
+        Operator::And => "and",
+        Operator::Or => "or",
+    }
+}
+
+// `if` and `while` only branch on bools, `if (n)` has to be written `if (n != 0)`
+fn condition_to_hir(condition: Expr, src: &Option<String>, ctx: &SemanticContext) -> SemanticResult<HIRExpr> {
+    let outer = match &condition {
+        Expr::Located { span, .. } => ctx.enter(span),
+        _ => ctx.span.get(),
+    };
+    let mut res = expr_to_hir(condition, src, ctx);
+    let ty = infer_expr_type(&res.result, ctx);
+    if ty != HIRType::Bool {
+        res.errors.push(ctx.error(
+            format!("\x1b[1;31m[ERR-SEM-340]\x1b[0m Condition must be a bool, found {:?}", ty),
+            src,
+        ));
+    }
+    ctx.span.set(outer);
+    res
+}
+
+fn logical_to_hir(
+    left: HIRExpr,
+    left_ty: HIRType,
+    op: Operator,
+    right: HIRExpr,
+    right_ty: HIRType,
+) -> Result<HIRExpr, String> {
+    if left_ty != HIRType::Bool || right_ty != HIRType::Bool {
+        return Err(format!(
+            "\x1b[1;31m[ERR-SEM-341]\x1b[0m '{}' expects bool operands, found {:?} and {:?}",
+            operator_symbol(op),
+            left_ty,
+            right_ty
+        ));
+    }
+    Ok(HIRExpr::BinaryOp {
+        left: Box::new(left),
+        op: operator_to_hir(op),
+        right: Box::new(right),
+    })
+}
+
+// numbers are widened like in arithmetic, strings compare by content and only for (in)equality
+fn comparison_to_hir(
+    left: HIRExpr,
+    left_ty: HIRType,
+    logic: Logic,
+    right: HIRExpr,
+    right_ty: HIRType,
+) -> Result<HIRExpr, String> {
+    let op = Operator::Compare(logic);
+    let comparable = match (&left_ty, &right_ty) {
+        (HIRType::String, HIRType::String) => matches!(logic, Logic::Equal | Logic::NotEqual),
+        (HIRType::Vector(_) | HIRType::Tensor(_) | HIRType::Void, _)
+        | (_, HIRType::Vector(_) | HIRType::Tensor(_) | HIRType::Void) => false,
+        _ => true,
+    };
+    let coerced = if comparable {
+        coerce_types(left, left_ty.clone(), right, right_ty.clone()).ok()
+    } else {
+        None
+    };
+    match coerced {
+        Some((left, right, _)) => Ok(HIRExpr::BinaryOp {
+            left: Box::new(left),
+            op: operator_to_hir(op),
+            right: Box::new(right),
+        }),
+        None => Err(format!(
+            "\x1b[1;31m[ERR-SEM-343]\x1b[0m Cannot compare {:?} {} {:?}",
+            left_ty,
+            operator_symbol(op),
+            right_ty
+        )),
     }
 }
 
@@ -404,6 +1505,32 @@ fn logic_to_hir(logic: Logic) -> HIROperator {
     }
 }
 
+// widens a single value to the target type, like coerce_types does for binary operands
+fn coerce_to_type(expr: HIRExpr, from: &HIRType, to: &HIRType) -> Option<HIRExpr> {
+    use HIRType::*;
+    match (from, to) {
+        (a, b) if a == b => Some(expr),
+        (I32, I64) | (I32, F64) | (I64, F64) | (F32, F64) => Some(HIRExpr::Coerce {
+            expr: Box::new(expr),
+            target: to.clone(),
+        }),
+        _ => None,
+    }
+}
+
+// the other direction would silently lose part of the value, it is only done for a literal that fits
+fn narrow_literal(expr: &HIRExpr, to: &HIRType) -> Option<HIRExpr> {
+    match (expr, to) {
+        (HIRExpr::Int64(v), HIRType::I32) => i32::try_from(*v).ok().map(HIRExpr::Int32),
+        (HIRExpr::Float64(v), HIRType::F32) if v.abs() <= f32::MAX as f64 => Some(HIRExpr::Float32(*v as f32)),
+        _ => None,
+    }
+}
+
+fn narrows(from: &HIRType, to: &HIRType) -> bool {
+    matches!((from, to), (HIRType::I64, HIRType::I32) | (HIRType::F64, HIRType::F32))
+}
+
 fn coerce_types(
     left: HIRExpr,
     left_ty: HIRType,
@@ -476,3 +1603,101 @@ fn coerce_types(
         )),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::high_level_ir::dump;
+    use crate::{analyze, parse, Source};
+
+    // the codes of the errors the semantic pass reports for `code`
+    fn error_codes(code: &str) -> Vec<String> {
+        match analyze(parse(&Source::code(code)).ok().unwrap()) {
+            Ok(_) => Vec::new(),
+            Err(diagnostics) => diagnostics.errors().filter_map(|d| d.error.code.clone()).collect(),
+        }
+    }
+
+    #[test]
+    fn division_by_a_literal_zero_is_rejected_before_codegen() {
+        let code = "cast Start() >>\n    set: x(i32) = 7;\n    out(x / 0);\n    out(2.5 / 0.0);\n    return;\n<<\n";
+        assert_eq!(error_codes(code), vec!["ERR-SEM-550", "ERR-SEM-550"]);
+    }
+
+    #[test]
+    fn remainder_by_zero_is_rejected_before_codegen() {
+        let code = "cast Start() >>\n    set: x(i32) = 7;\n    out(x % 0);\n    out(2.5 % 0.0);\n    return;\n<<\n";
+        assert_eq!(error_codes(code), vec!["ERR-SEM-551", "ERR-SEM-551"]);
+        let code = "set n(i32) = 0;\ncast Start() >>\n    out(7 % n);\n    return;\n<<\n";
+        assert_eq!(error_codes(code), vec!["ERR-SEM-551"]);
+    }
+
+    #[test]
+    fn a_parameter_does_not_make_a_mutable_global_immutable() {
+        let code = "set: count(i32) = 0;\n\
+                    cast show(count: i32) >>\n    out(count);\n    return;\n<<\n\
+                    cast Start() >>\n    set count(i32) = 5;\n    return;\n<<\n";
+        assert_eq!(error_codes(code), vec!["ERR-SEM-560"]);
+    }
+
+    #[test]
+    fn a_mutable_local_does_not_make_a_global_mutable() {
+        let code = "set limit(i32) = 3;\n\
+                    cast reset() >>\n    set: limit(i32) = 1;\n    out(limit);\n    return;\n<<\n\
+                    cast Start() >>\n    set limit(i32) = 4;\n    out(limit);\n    return;\n<<\n";
+        assert_eq!(error_codes(code), Vec::<String>::new());
+    }
+
+    #[test]
+    fn declarations_widen_to_the_declared_type() {
+        let code = "cast Start() >>\n    set: x(f64) = 1.5;\n    set n(i64) = 3;\n    out(x, n);\n    return;\n<<\n";
+        let parsed = parse(&Source::code(code)).ok().unwrap();
+        let text = dump(&super::ast_to_hir(parsed.ast, None).result);
+        assert!(text.contains("x = (1.5 as f64);"), "{}", text);
+        assert!(text.contains("n = (3 as i64);"), "{}", text);
+    }
+
+    #[test]
+    fn declarations_do_not_narrow() {
+        let ints = "cast Start() >>\n    set big(i64) = 5;\n    set small(i32) = big;\n    return;\n<<\n";
+        assert_eq!(error_codes(ints), vec!["ERR-SEM-236"]);
+        let floats = "cast Start() >>\n    set wide(f64) = 1.5;\n    set x(f32) = wide * 2.0;\n    return;\n<<\n";
+        assert_eq!(error_codes(floats), vec!["ERR-SEM-236"]);
+    }
+
+    #[test]
+    fn a_literal_that_fits_is_narrowed() {
+        use super::{narrow_literal, HIRExpr, HIRType};
+        assert_eq!(narrow_literal(&HIRExpr::Int64(7), &HIRType::I32), Some(HIRExpr::Int32(7)));
+        assert_eq!(narrow_literal(&HIRExpr::Int64(1 << 40), &HIRType::I32), None);
+        assert_eq!(narrow_literal(&HIRExpr::Float64(0.5), &HIRType::F32), Some(HIRExpr::Float32(0.5)));
+        assert_eq!(narrow_literal(&HIRExpr::Float64(1e300), &HIRType::F32), None);
+    }
+
+    #[test]
+    fn whole_tensor_reductions_are_rank_0_tensors() {
+        let start = |body: &str| format!("cast Start() >>\n    set t(Tensor(f32, [2])) = [1, 2];\n{}\n    return;\n<<\n", body);
+        let loss = start("    set loss(Tensor(f32, [])) = (t - 1.0).mean();\n    loss.backward();\n    set v(f32) = loss.item();");
+        assert_eq!(error_codes(&loss), Vec::<String>::new());
+        assert_eq!(error_codes(&start("    set v(f32) = t.sum();")), vec!["ERR-SEM-236"]);
+        assert_eq!(error_codes(&start("    out(t.item());")), vec!["ERR-SEM-255"]);
+    }
+
+    #[test]
+    fn calls_are_checked_against_the_signature() {
+        let call = |expr: &str| {
+            format!(
+                "cast add(a: i64, b: i64) -> i64 >>\n    return a + b;\n<<\n\
+                 cast hello() >>\n    out(\"hello\");\n    return;\n<<\n\
+                 cast Start() >>\n    out({});\n    return;\n<<\n",
+                expr
+            )
+        };
+        assert_eq!(error_codes(&call("add(1, 2)")), Vec::<String>::new());
+        assert_eq!(error_codes(&call("add(1)")), vec!["ERR-SEM-321"]);
+        assert_eq!(error_codes(&call("add(1, 2, 3)")), vec!["ERR-SEM-321"]);
+        assert_eq!(error_codes(&call("add(1, \"two\")")), vec!["ERR-SEM-322"]);
+        assert_eq!(error_codes(&call("add(1, 2.5)")), vec!["ERR-SEM-322"]);
+        assert_eq!(error_codes(&call("hello()")), vec!["ERR-SEM-323"]);
+        assert_eq!(error_codes(&call("sub(1, 2)")), vec!["ERR-SEM-320"]);
+    }
+}
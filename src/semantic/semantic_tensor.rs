@@ -0,0 +1,93 @@
+//shape rules for tensors,
+//every tensor type carries its shape, so the result shape of each operation
+//is computed here and mismatches are reported before codegen
+
+pub fn broadcast_shapes(left: &[usize], right: &[usize]) -> Result<Vec<usize>, String> {
+    let rank = left.len().max(right.len());
+    let mut shape = vec![0; rank];
+    for i in 0..rank {
+        let l = if i < rank - left.len() { 1 } else { left[i - (rank - left.len())] };
+        let r = if i < rank - right.len() { 1 } else { right[i - (rank - right.len())] };
+        if l != r && l != 1 && r != 1 {
+            return Err(format!(
+                "\x1b[1;31m[ERR-SEM-240]\x1b[0m Tensor shapes {:?} and {:?} cannot be broadcast together (dimension {} is {} vs {})",
+                left, right, i, l, r
+            ));
+        }
+        shape[i] = l.max(r);
+    }
+    Ok(shape)
+}
+
+pub fn matmul_shape(left: &[usize], right: &[usize]) -> Result<Vec<usize>, String> {
+    match (left, right) {
+        ([m, k1], [k2, n]) if k1 == k2 => Ok(vec![*m, *n]),
+        _ => Err(format!(
+            "\x1b[1;31m[ERR-SEM-244]\x1b[0m matmul needs [m, k] x [k, n] tensors, found {:?} x {:?}",
+            left, right
+        )),
+    }
+}
+
+pub fn reshape_shape(from: &[usize], to: &[usize]) -> Result<Vec<usize>, String> {
+    let from_size: usize = from.iter().product();
+    let to_size: usize = to.iter().product();
+    if to.is_empty() || to.contains(&0) || from_size != to_size {
+        return Err(format!(
+            "\x1b[1;31m[ERR-SEM-245]\x1b[0m Cannot reshape {:?} ({} elements) into {:?} ({} elements)",
+            from, from_size, to, to_size
+        ));
+    }
+    Ok(to.to_vec())
+}
+
+pub fn transpose_shape(shape: &[usize]) -> Result<Vec<usize>, String> {
+    match shape {
+        [rows, cols] => Ok(vec![*cols, *rows]),
+        _ => Err(format!(
+            "\x1b[1;31m[ERR-SEM-252]\x1b[0m transpose() needs a 2-D tensor, found shape {:?}",
+            shape
+        )),
+    }
+}
+
+// shape left after reducing over `axis`, a rank-1 tensor reduces to a rank-0 one
+pub fn reduce_axis_shape(shape: &[usize], axis: usize) -> Result<Vec<usize>, String> {
+    if axis >= shape.len() {
+        return Err(format!(
+            "\x1b[1;31m[ERR-SEM-253]\x1b[0m Axis {} is out of range for a tensor of shape {:?}",
+            axis, shape
+        ));
+    }
+    let mut out = shape.to_vec();
+    out.remove(axis);
+    Ok(out)
+}
+
+// `t.item()` reads the single element of a tensor, like the loss a whole-tensor reduction leaves
+pub fn item_shape(shape: &[usize]) -> Result<(), String> {
+    if shape.iter().product::<usize>() != 1 {
+        return Err(format!(
+            "\x1b[1;31m[ERR-SEM-255]\x1b[0m item() needs a tensor with a single element, found shape {:?}",
+            shape
+        ));
+    }
+    Ok(())
+}
+
+// a nested literal like [[1, 2], [3, 4]] must have exactly the declared shape,
+// `found` is None when a plain value sits where a nested list was expected (or the other way around)
+pub fn literal_shape_error(expected: &[usize], depth: usize, found: Option<usize>) -> String {
+    let found = match found {
+        Some(n) => format!("a list of {}", n),
+        None => "a single value".to_string(),
+    };
+    let wanted = match expected.get(depth) {
+        Some(n) => format!("a list of {}", n),
+        None => "a single value".to_string(),
+    };
+    format!(
+        "\x1b[1;31m[ERR-SEM-241]\x1b[0m Tensor literal does not match shape {:?}: expected {} at depth {}, found {}",
+        expected, wanted, depth, found
+    )
+}
@@ -0,0 +1,307 @@
+// terminal output shared by the axon subcommands,
+// the progress bar and the colored error and warning blocks, or JSON lines with `--message-format=json`
+
+use crate::diagnostics::Diagnostics;
+use crate::parser::parser_error::{ParseError, Severity};
+use console::style;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde_json::json;
+use std::io::{stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::sleep;
+use std::time::Duration;
+
+pub const VERSION: &str = "AxonScript Build #0001 Pre-Alpha Demo";
+pub const WEBSITE: &str = "https://axonscript.org";
+
+// `--message-format=json`, diagnostics go out as JSON lines and the decoration is left out
+static JSON_MESSAGES: AtomicBool = AtomicBool::new(false);
+
+pub fn json_messages() -> bool {
+    JSON_MESSAGES.load(Ordering::Relaxed)
+}
+
+pub fn set_json_messages(on: bool) {
+    JSON_MESSAGES.store(on, Ordering::Relaxed);
+}
+
+// `--quiet`, `--ci` or output that is not a terminal, no screen clearing, header, progress bar or pauses
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+pub fn set_quiet(on: bool) {
+    QUIET.store(on, Ordering::Relaxed);
+}
+
+// someone is watching a terminal and gets the animated output
+pub fn interactive() -> bool {
+    !quiet() && !json_messages()
+}
+
+// everything a stage reported, warnings before errors, one block per stage
+pub fn report(diagnostics: &Diagnostics) {
+    for stage in diagnostics.stages() {
+        let (warnings, errors): (Vec<ParseError>, Vec<ParseError>) = diagnostics
+            .iter()
+            .filter(|d| d.stage == stage)
+            .map(|d| d.error.clone())
+            .partition(|e| matches!(e.severity, Severity::Warning));
+        if !warnings.is_empty() {
+            print_warning(stage.name(), &warnings);
+        }
+        if !errors.is_empty() {
+            print_error(stage.name(), &errors);
+        }
+    }
+}
+
+pub fn print_header() {
+    if !interactive() {
+        return;
+    }
+    let title = format!(
+        "{} {} {}",
+        style("Neuron — AxonScript Compiler").cyan().bold(),
+        style("•").white().bold(),
+        style("Build #0001 Pre-Alpha Demo").yellow().bold()
+    );
+    println!(
+        "{line}\n{title}\n    💻 Community: {website}/community\n{line}",
+        line = style("━").dim().to_string().repeat(60),
+        title = title,
+        website = WEBSITE
+    );
+}
+
+// the PIPELINE bar, stage by stage, hidden when nobody is watching
+pub struct Progress {
+    bar: ProgressBar,
+    pipeline: Vec<&'static str>,
+}
+
+impl Progress {
+    pub fn new(pipeline: &[&'static str]) -> Progress {
+        let bar = if interactive() {
+            ProgressBar::with_draw_target(Some(pipeline.len() as u64), ProgressDrawTarget::stdout())
+        } else {
+            ProgressBar::hidden()
+        };
+        bar.set_style(
+            ProgressStyle::with_template("PIPELINE [{bar:40.green}] {msg:.bold}")
+                .unwrap()
+                .progress_chars("━━-"),
+        );
+        bar.set_message("Starting...");
+        Progress { bar, pipeline: pipeline.to_vec() }
+    }
+
+    // one more stage done, the bar names the stage that just finished
+    pub fn advance(&self) {
+        let done = self.bar.position() as usize;
+        self.bar.set_message(self.pipeline.get(done).copied().unwrap_or("Done"));
+        self.bar.inc(1);
+    }
+
+    // every stage takes a blink, the pause lets a person see it go by
+    pub fn pause(&self) {
+        if interactive() {
+            sleep(Duration::from_millis(100));
+        }
+    }
+
+    // prints below the bar and draws it again afterwards
+    pub fn suspend<R>(&self, print: impl FnOnce() -> R) -> R {
+        self.bar.suspend(print)
+    }
+
+    // leaves the bar where it stopped, for a failed stage or the end of the pipeline
+    pub fn stop(&self) {
+        self.bar.abandon();
+    }
+}
+
+pub fn print_error(phase: &str, errors: &[ParseError]) {
+    if json_messages() {
+        return errors.iter().for_each(|error| print_json(phase, error));
+    }
+    println!();
+    println!(
+        "\n{} Stage: {}",
+        style("✘").red().bold(),
+        style(phase).red().bold()
+    );
+
+    for error in errors {
+        let location = location(error).map_or(String::new(), |location| format!("{} ", location));
+        let code = error.code.as_ref().map_or(String::new(), |code| format!("[{}] ", code));
+        println!(
+            "{} {}{}{}",
+            style("").red().bold(),
+            style(location).cyan(),
+            style(code).red().bold(),
+            error.message
+        );
+
+        if let Some(src) = &error.src
+            && error.start < src.len()
+            && error.end <= src.len()
+        {
+            let line_start = src[..error.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+            let line_end = src[error.start..]
+                .find('\n')
+                .map(|i| error.start + i)
+                .unwrap_or(src.len());
+            if line_start < line_end {
+                let line_text = &src[line_start..line_end];
+                println!("{}", style(line_text).dim());
+                println!(
+                    "{}",
+                    " ".repeat(error.start - line_start)
+                        + &style("^".repeat(error.end.min(line_end).saturating_sub(error.start).max(1))).red().bold().to_string()
+                );
+            }
+        }
+
+        if let Some(suggestion) = &error.suggestion {
+            println!(
+                "{} {}",
+                style("Hint:").cyan().bold(),
+                style(suggestion).cyan()
+            );
+        }
+    }
+
+    if let Some(code) = errors.iter().find_map(|e| e.code.as_ref()) {
+        println!(
+            "\n{} run `axon explain {}` for more about an error code",
+            style("Note:").cyan().bold(),
+            code
+        );
+    }
+    println!(
+        "\n📚 Docs: {}/docs\n🐞 Bugs: {}/bugs\n💻 Community: {}/community\n",
+        WEBSITE, WEBSITE, WEBSITE
+    );
+}
+
+pub fn print_warning(phase: &str, warnings: &[ParseError]) {
+    if json_messages() {
+        return warnings.iter().for_each(|warning| print_json(phase, warning));
+    }
+    println!();
+    println!(
+        "\n{} Stage: {}",
+        style("!").yellow().bold(),
+        style(phase).yellow().bold()
+    );
+
+    for warning in warnings {
+        let location = location(warning).map_or(String::new(), |location| format!("{} ", location));
+        let code = warning.code.as_ref().map_or(format!("{:?}", warning.kind), |code| code.clone());
+        println!(
+            "{} {}{} {}",
+            style("").yellow().bold(),
+            style(location).cyan(),
+            style(format!("[{}]", code)).yellow(),
+            warning.message
+        );
+
+        if let Some(src) = &warning.src
+            && warning.start < src.len()
+            && warning.end <= src.len()
+        {
+            let line_start = src[..warning.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+            let line_end = src[warning.start..]
+                .find('\n')
+                .map(|i| warning.start + i)
+                .unwrap_or(src.len());
+            if line_start < line_end {
+                let line_text = &src[line_start..line_end];
+                println!("{}", style(line_text).dim());
+                println!(
+                    "{}",
+                    " ".repeat(warning.start - line_start)
+                        + &style("^".repeat(warning.end.min(line_end).saturating_sub(warning.start).max(1))).yellow().bold().to_string()
+                );
+            }
+        }
+
+        if let Some(suggestion) = &warning.suggestion {
+            println!(
+                "{} {}",
+                style("Hint:").cyan().bold(),
+                style(suggestion).cyan()
+            );
+        }
+    }
+}
+
+// one diagnostic as a line of JSON, for editors and CI annotators
+fn print_json(phase: &str, error: &ParseError) {
+    let severity = match error.severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    };
+    let (span, start, end) = match (line_col(error), &error.src) {
+        (Some((line, column)), Some(src)) => {
+            let (end_line, end_column) = line_col_at(src, error.end.clamp(error.start, src.len()));
+            (
+                json!({ "start": error.start, "end": error.end }),
+                json!({ "line": line, "column": column }),
+                json!({ "line": end_line, "column": end_column }),
+            )
+        }
+        _ => (json!(null), json!(null), json!(null)),
+    };
+    let diagnostic = json!({
+        "type": "diagnostic",
+        "stage": phase,
+        "code": error.code,
+        "severity": severity,
+        "kind": format!("{:?}", error.kind).to_lowercase(),
+        "file": error.file.as_deref().map(|file| file.trim_start_matches("./")),
+        "message": error.message,
+        "span": span,
+        "start": start,
+        "end": end,
+        "suggestion": error.suggestion,
+    });
+    println!("{}", diagnostic);
+}
+
+// 1-based line and column of where an error starts, None when it has no real location
+fn line_col(error: &ParseError) -> Option<(usize, usize)> {
+    let src = error.src.as_ref()?;
+    if error.start == 0 && error.end == 0 || error.start > src.len() {
+        return None;
+    }
+    Some(line_col_at(src, error.start))
+}
+
+// columns count bytes, as the spans do
+fn line_col_at(src: &str, offset: usize) -> (usize, usize) {
+    let before = &src[..offset];
+    let line = before.matches('\n').count() + 1;
+    let col = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, col)
+}
+
+// `src/util/math.ax:3:5`, or just `3:5` when the error does not know its file
+fn location(error: &ParseError) -> Option<String> {
+    let (line, col) = line_col(error)?;
+    Some(match &error.file {
+        Some(file) => format!("{}:{}:{}", file.trim_start_matches("./"), line, col),
+        None => format!("{}:{}", line, col),
+    })
+}
+
+pub fn clear_screen() {
+    if !interactive() {
+        return;
+    }
+    print!("\x1b[2J\x1b[1;1H");
+    stdout().flush().unwrap();
+}
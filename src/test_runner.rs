@@ -0,0 +1,209 @@
+// `axon test`,
+// every `cast test_xxx()` function in src/ is a test, each one runs as its own program
+// (the test function becomes the entry point instead of Start) in a separate process,
+// so a failed assertion, which exits the program, only takes down that one test
+
+use crate::ast::Statement;
+use crate::diagnostics::Diagnostics;
+use crate::manifest::Manifest;
+use crate::terminal::{print_error, report};
+use crate::{analyze, parse, run, CompileOptions, OptLevel, Source};
+use console::style;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const TEST_PREFIX: &str = "test_";
+// hidden flag the runner passes to its own child processes
+const EXEC_FLAG: &str = "--exec";
+
+struct TestCase {
+    file: PathBuf,
+    name: String,
+}
+
+pub fn run_tests(args: &[String]) -> i32 {
+    if let Some(pos) = args.iter().position(|a| a == EXEC_FLAG) {
+        return match (args.get(pos + 1), args.get(pos + 2)) {
+            (Some(file), Some(name)) => run_single_test(Path::new(file), name),
+            _ => 2,
+        };
+    }
+    let filter = args.iter().find(|a| !a.starts_with("--")).cloned();
+
+    let src_dir = Path::new("src");
+    let manifest = match Manifest::load(Path::new(".")) {
+        Ok(manifest) => manifest,
+        Err(errors) => {
+            print_error("Setup", &errors);
+            return 1;
+        }
+    };
+
+    let mut files = Vec::new();
+    collect_sources(src_dir, &mut files);
+    files.sort();
+
+    let mut tests = Vec::new();
+    let mut broken_files = 0;
+    for file in &files {
+        match discover_tests(&test_source(&manifest, file)) {
+            Ok(names) => tests.extend(
+                names
+                    .into_iter()
+                    .filter(|name| filter.as_ref().is_none_or(|f| name.contains(f.as_str())))
+                    .map(|name| TestCase { file: file.clone(), name }),
+            ),
+            Err(diagnostics) => {
+                report(&diagnostics);
+                broken_files += 1;
+            }
+        }
+    }
+
+    println!(
+        "\n{} Running {} test(s) from {} file(s)\n",
+        style("▶").cyan().bold(),
+        tests.len(),
+        files.len()
+    );
+
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            println!("{} Cannot locate the axon executable: {}", style("✘").red().bold(), e);
+            return 1;
+        }
+    };
+
+    let mut failures = Vec::new();
+    for test in &tests {
+        let label = format!("{}::{}", test.file.display(), test.name);
+        let output = Command::new(&exe)
+            .args(["test", EXEC_FLAG, test.file.to_str().unwrap_or_default(), &test.name])
+            .output();
+        match output {
+            Ok(out) if out.status.success() => {
+                println!("test {} ... {}", label, style("ok").green().bold());
+            }
+            Ok(out) => {
+                println!("test {} ... {}", label, style("FAILED").red().bold());
+                let mut log = String::from_utf8_lossy(&out.stdout).into_owned();
+                log.push_str(&String::from_utf8_lossy(&out.stderr));
+                failures.push((label, log));
+            }
+            Err(e) => {
+                println!("test {} ... {}", label, style("FAILED").red().bold());
+                failures.push((label, format!("failed to start the test process: {}", e)));
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nfailures:");
+        for (label, log) in &failures {
+            println!("\n---- {} ----\n{}", style(label).red().bold(), log.trim_end());
+        }
+    }
+
+    let passed = tests.len() - failures.len();
+    let ok = failures.is_empty() && broken_files == 0;
+    println!(
+        "\ntest result: {}. {} passed; {} failed{}",
+        if ok { style("ok").green().bold() } else { style("FAILED").red().bold() },
+        passed,
+        failures.len(),
+        if broken_files > 0 { format!("; {} file(s) failed to parse", broken_files) } else { String::new() }
+    );
+    if ok { 0 } else { 1 }
+}
+
+pub fn collect_sources(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_sources(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "ax") {
+            files.push(path);
+        }
+    }
+}
+
+// the project with `file` as the entry, what it imports is loaded like for `axon run`
+fn test_source(manifest: &Manifest, file: &Path) -> Source {
+    Source {
+        entry: file.to_path_buf(),
+        src_dir: PathBuf::from("src"),
+        packages: manifest.package_roots(Path::new(".")),
+        open: HashMap::new(),
+    }
+}
+
+// the test functions of the file itself, imported functions carry their module's name
+fn discover_tests(source: &Source) -> Result<Vec<String>, Diagnostics> {
+    let parsed = parse(source)?;
+    Ok(parsed
+        .ast
+        .iter()
+        .filter_map(|stmt| match stmt.unlocated() {
+            Statement::FunctionCall { name, .. } if name.starts_with(TEST_PREFIX) => Some(name.clone()),
+            _ => None,
+        })
+        .collect())
+}
+
+// child side: compiles the file (and what it imports) with `name` as the entry point and runs it
+// in the JIT (the interpreter when the build has no LLVM)
+fn run_single_test(file: &Path, name: &str) -> i32 {
+    let source = match Manifest::load(Path::new(".")) {
+        Ok(manifest) => test_source(&manifest, file),
+        Err(errors) => {
+            print_error("Setup", &errors);
+            return 2;
+        }
+    };
+    let analyzed = parse(&source).and_then(|mut parsed| {
+        parsed.ast = parsed.ast.into_iter().filter_map(|stmt| as_test_entry(stmt, name)).collect();
+        report(&parsed.diagnostics);
+        analyze(parsed)
+    });
+    let analyzed = match analyzed {
+        Ok(analyzed) => {
+            report(&analyzed.diagnostics);
+            analyzed
+        }
+        Err(diagnostics) => {
+            report(&diagnostics);
+            return 2;
+        }
+    };
+    let options = CompileOptions { opt_level: OptLevel::O0, ..CompileOptions::default() };
+    match run(analyzed, &options) {
+        Ok(()) => 0,
+        // a failed assertion already printed its message
+        Err(diagnostics) if diagnostics.exit_code().is_some() => diagnostics.exit_code().unwrap_or(1),
+        Err(diagnostics) => {
+            report(&diagnostics);
+            1
+        }
+    }
+}
+
+// drops the real Start function and turns the test `name` into the entry point
+fn as_test_entry(stmt: Statement, name: &str) -> Option<Statement> {
+    match stmt {
+        Statement::Located { span, stmt } => {
+            as_test_entry(*stmt, name).map(|stmt| Statement::Located { span, stmt: Box::new(stmt) })
+        }
+        Statement::FunctionCall { start: true, .. } => None,
+        Statement::FunctionCall { name: fn_name, params, return_type, body, .. } => {
+            let start = fn_name == name;
+            Some(Statement::FunctionCall { name: fn_name, params, return_type, start, body })
+        }
+        other => Some(other),
+    }
+}
@@ -0,0 +1,201 @@
+// every program in tests/programs runs under `--backend=jit` and `--backend=interp`,
+// both have to print what `name.out` holds: the expected stdout followed by an `exit status: N` line
+// (a build without the `llvm` feature only has the interpreter)
+//
+// `name.ax` is the program, an optional `name.in` is fed to it on stdin,
+// `AXON_BLESS=1 cargo test --test backends` writes the .out files from what the programs print now
+
+use axonscript::{run_jit, Backend, CompileOptions, Source};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+
+#[cfg(feature = "llvm")]
+const BACKENDS: &[&str] = &["jit", "interp"];
+#[cfg(not(feature = "llvm"))]
+const BACKENDS: &[&str] = &["interp"];
+
+fn programs() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/programs");
+    let mut programs: Vec<PathBuf> = fs::read_dir(&dir)
+        .expect("tests/programs is missing")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ax"))
+        .collect();
+    programs.sort();
+    programs
+}
+
+// a project of its own per program and backend, so the tests can run in parallel
+fn project(name: &str, backend: &str, code: &str) -> PathBuf {
+    let project = std::env::temp_dir().join(format!("axon_backends_{}_{}_{}", std::process::id(), name, backend));
+    fs::remove_dir_all(&project).ok();
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(
+        project.join("project.asml"),
+        format!("__Project__\n_name_ = \"{}\"\n_version_ = \"0.1.0\"\n", name),
+    )
+    .unwrap();
+    fs::write(project.join("src/init.ax"), code).unwrap();
+    project
+}
+
+fn run(program: &Path, backend: &str) -> Output {
+    let name = program.file_stem().unwrap().to_string_lossy();
+    let project = project(&name, backend, &fs::read_to_string(program).unwrap());
+    let input = fs::read(program.with_extension("in")).unwrap_or_default();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_AxonScript"))
+        .args(["run", "--ci", &format!("--backend={}", backend)])
+        .current_dir(&project)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start axon run");
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    fs::remove_dir_all(&project).ok();
+    output
+}
+
+// what a run is compared with
+fn transcript(output: &Output) -> String {
+    let status = output.status.code().map_or("none".to_string(), |code| code.to_string());
+    format!("{}exit status: {}\n", String::from_utf8_lossy(&output.stdout), status)
+}
+
+#[test]
+fn backends_match_expected_output() {
+    let programs = programs();
+    assert!(!programs.is_empty(), "no test programs found");
+    let bless = std::env::var_os("AXON_BLESS").is_some();
+
+    let mut failures = Vec::new();
+    for program in &programs {
+        let name = program.file_name().unwrap().to_string_lossy();
+        let expected_path = program.with_extension("out");
+        for backend in BACKENDS {
+            let output = run(program, backend);
+            let actual = transcript(&output);
+            // a program that does not compile is never the expected output
+            if actual.contains("Stage:") {
+                failures.push(format!("{} failed to compile under {}:\n{}", name, backend, actual));
+                continue;
+            }
+            // the first backend writes the file, the others are still checked against it
+            if bless && backend == &BACKENDS[0] {
+                fs::write(&expected_path, &actual).unwrap();
+            }
+            match fs::read_to_string(&expected_path) {
+                Ok(expected) if expected == actual => {}
+                Ok(expected) => failures.push(format!(
+                    "{} under {} does not match {}\n--- expected\n{}--- actual\n{}",
+                    name,
+                    backend,
+                    expected_path.file_name().unwrap().to_string_lossy(),
+                    expected,
+                    actual
+                )),
+                Err(_) => failures.push(format!(
+                    "{} has no {}, run with AXON_BLESS=1 to write it",
+                    name,
+                    expected_path.file_name().unwrap().to_string_lossy()
+                )),
+            }
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
+
+// a program that fails at runtime hands its exit code back to the embedding process, which goes on running
+#[test]
+fn runtime_errors_return_to_the_caller() {
+    let failing = "cast Start() >>\n    set: v(Vec(i32)) = [];\n    out(v.pop());\n    return;\n<<\n";
+    for backend in BACKENDS.iter().filter_map(|b| Backend::from_flag(b)) {
+        let options = CompileOptions { backend, ..CompileOptions::default() };
+        for _ in 0..2 {
+            let errors = run_jit(&Source::code(failing), &options).expect_err("the program should fail");
+            assert_eq!(errors.exit_code(), Some(1), "under {}", backend.name());
+        }
+        let ok = "cast Start() >>\n    out(1);\n    return;\n<<\n";
+        assert!(run_jit(&Source::code(ok), &options).is_ok(), "under {}", backend.name());
+    }
+}
+
+// the JIT finds the missing `return` when it compiles the function, the interpreter when a call falls off its end
+#[test]
+fn falling_off_the_end_of_a_function_is_an_error() {
+    let code = "cast sign(n: i32) -> i32 >>\n    if (n < 0) >>\n        return 0 - 1;\n    <<\n<<\n\
+                cast Start() >>\n    out(sign(4));\n    return;\n<<\n";
+    for backend in BACKENDS.iter().filter_map(|b| Backend::from_flag(b)) {
+        let options = CompileOptions { backend, ..CompileOptions::default() };
+        let errors = run_jit(&Source::code(code), &options).expect_err("the program should fail");
+        let codes: Vec<_> = errors.errors().filter_map(|d| d.error.code.as_deref()).collect();
+        assert_eq!(codes, vec!["ERR-SEM-520"], "under {}", backend.name());
+    }
+}
+
+// recursion through `axon build`, the executable runs on its own
+#[cfg(feature = "llvm")]
+#[test]
+fn built_executables_run_recursive_calls() {
+    let code = "cast fib(n: i32) -> i32 >>\n    if (n < 2) >>\n        return n;\n    <<\n    return fib(n - 1) + fib(n - 2);\n<<\n\
+                cast Start() >>\n    out(fib(20));\n    return;\n<<\n";
+    let project = project("recursion", "build", code);
+    let build = Command::new(env!("CARGO_BIN_EXE_AxonScript"))
+        .args(["build", "--ci"])
+        .current_dir(&project)
+        .output()
+        .expect("failed to start axon build");
+    assert!(build.status.success(), "{}", String::from_utf8_lossy(&build.stdout));
+
+    let executable = project.join("release").join(format!("recursion{}", std::env::consts::EXE_SUFFIX));
+    let output = Command::new(&executable).output().expect("the build left no executable");
+    fs::remove_dir_all(&project).ok();
+    assert_eq!(transcript(&output), "6765\nexit status: 0\n");
+}
+
+// waits for `child` like Child::wait does, with the peak of its resident memory in kilobytes
+// as the kernel counted it for that process alone
+fn wait_with_peak_memory(child: Child) -> (i32, i64) {
+    let mut status = 0;
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    let pid = unsafe { libc::wait4(child.id() as i32, &mut status, 0, &mut usage) };
+    assert_eq!(pid, child.id() as i32, "wait4 failed");
+    let code = if libc::WIFEXITED(status) { libc::WEXITSTATUS(status) } else { -1 };
+    (code, usage.ru_maxrss)
+}
+
+// a detached training loop lets go of each step's graph, so running it 100 times longer
+// must not raise the peak memory of the process by more than noise
+#[test]
+fn training_loops_run_in_bounded_memory() {
+    let code = "cast Start() >>\n    set x(Tensor(f32, [32, 32])) = rand([32, 32]);\n    set y(Tensor(f32, [32, 32])) = rand([32, 32]);\n\
+                \x20   set: w(Tensor(f32, [32, 32])) = rand([32, 32]);\n    set: step(i32) = 0;\n    while (step < STEPS) >>\n\
+                \x20       set loss(Tensor(f32, [])) = ((x.matmul(w) - y) * (x.matmul(w) - y)).mean();\n\
+                \x20       set: w(Tensor(f32, [32, 32])) = (w - grad(loss, w) * 0.01).detach();\n\
+                \x20       math([step + 1], step);\n    <<\n    out(w.sum().item());\n    return;\n<<\n";
+    let peak = |backend: &str, steps: u32| -> i64 {
+        let name = format!("training_{}", steps);
+        let project = project(&name, backend, &code.replace("STEPS", &steps.to_string()));
+        let child = Command::new(env!("CARGO_BIN_EXE_AxonScript"))
+            .args(["run", "--ci", &format!("--backend={}", backend)])
+            .current_dir(&project)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start axon run");
+        let (code, peak) = wait_with_peak_memory(child);
+        fs::remove_dir_all(&project).ok();
+        assert_eq!(code, 0, "{} steps failed under {}", steps, backend);
+        peak
+    };
+    for backend in BACKENDS {
+        let short = peak(backend, 20);
+        let long = peak(backend, 2000);
+        // every step allocates well over 50 KB, all of it would still be there at the end
+        assert!(long - short < 16 * 1024, "under {}: {} KB after 20 steps, {} KB after 2000", backend, short, long);
+    }
+}
@@ -0,0 +1,306 @@
+// the axon binary as an editor or a CI job drives it, through pipes rather than a terminal
+
+use serde_json::{json, Value};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+// a project of its own per test, so the tests can run in parallel
+fn project(test: &str, code: &str) -> PathBuf {
+    let project = std::env::temp_dir().join(format!("axon_cli_{}_{}", std::process::id(), test));
+    fs::remove_dir_all(&project).ok();
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(project.join("project.asml"), "__Project__\n_name_ = \"demo\"\n_version_ = \"0.1.0\"\n").unwrap();
+    fs::write(project.join("src/init.ax"), code).unwrap();
+    project
+}
+
+fn axon(project: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_AxonScript"))
+        .args(args)
+        .current_dir(project)
+        .output()
+        .expect("failed to start axon")
+}
+
+// every line of stdout has to be a JSON object, nothing else is printed
+fn json_lines(output: &Output) -> Vec<Value> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|_| panic!("not a JSON line: {:?}", line)))
+        .collect()
+}
+
+#[test]
+fn diagnostics_are_json_lines_with_code_and_position() {
+    let code = "cast add(a: i32, b: i32) -> i32 >>\n    return a + b;\n<<\n\
+                cast Start() >>\n    out();\n    out(add(1, \"two\"));\n    out(missing);\n    return;\n<<\n";
+    let project = project("json", code);
+    for command in ["check", "run"] {
+        let output = axon(&project, &[command, "--message-format=json"]);
+        assert_eq!(output.status.code(), Some(1), "axon {}", command);
+        let lines = json_lines(&output);
+        let summary: Vec<_> = lines
+            .iter()
+            .map(|d| {
+                format!(
+                    "{} {} {} {}:{}:{}",
+                    d["severity"].as_str().unwrap(),
+                    d["code"].as_str().unwrap(),
+                    d["stage"].as_str().unwrap(),
+                    d["file"].as_str().unwrap(),
+                    d["start"]["line"],
+                    d["start"]["column"]
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                "warning WARN-SEM-002 Parsing src/init.ax:5:9",
+                "error ERR-SEM-322 Semantic src/init.ax:6:9",
+                "error ERR-SEM-999 Semantic src/init.ax:7:9",
+            ],
+            "axon {}",
+            command
+        );
+        let error = &lines[1];
+        assert_eq!(error["type"], "diagnostic");
+        assert_eq!(error["kind"], "semantic");
+        assert_eq!(error["message"], "Argument 2 of 'add' has type String, expected I32");
+        assert_eq!(error["end"], json!({ "line": 6, "column": 22 }));
+        let span = (error["span"]["start"].as_u64().unwrap() as usize)..(error["span"]["end"].as_u64().unwrap() as usize);
+        assert_eq!(&code[span], "add(1, \"two\")");
+        assert_eq!(lines[0]["suggestion"], "Consider adding expressions to print.");
+    }
+    fs::remove_dir_all(&project).ok();
+}
+
+#[test]
+fn syntax_errors_have_codes_too() {
+    let project = project("json_syntax", "cast Start() >>\n    out(1)\n    out(2);\n<<\n");
+    let output = axon(&project, &["check", "--message-format=json"]);
+    assert_eq!(output.status.code(), Some(1));
+    let lines = json_lines(&output);
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["code"], "ERR-SYN-007");
+    assert_eq!(lines[0]["kind"], "syntax");
+    assert_eq!(lines[0]["start"], json!({ "line": 3, "column": 5 }));
+    fs::remove_dir_all(&project).ok();
+}
+
+// a function with a local and a Start that calls it, for the build tests
+#[cfg(feature = "llvm")]
+const ADD: &str = "cast add(a: i32, b: i32) -> i32 >>\n    set s(i32) = a + b;\n    return s;\n<<\n\
+                   cast Start() >>\n    out(add(1, 2));\n    return;\n<<\n";
+
+// `axon build --ci` with `args`, what it wrote to release/`file`
+#[cfg(feature = "llvm")]
+fn build(project: &Path, args: &[&str], file: &str) -> Vec<u8> {
+    let output = axon(project, &[&["build", "--ci"], args].concat());
+    assert!(output.status.success(), "axon build {:?}:\n{}", args, String::from_utf8_lossy(&output.stdout));
+    fs::read(project.join("release").join(file)).unwrap_or_else(|_| panic!("axon build {:?} wrote no {}", args, file))
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn emit_stops_the_build_at_each_kind() {
+    let project = project("emit", ADD);
+    let ir = String::from_utf8(build(&project, &["--emit=llvm-ir"], "demo.ll")).unwrap();
+    assert!(ir.contains("define i32 @main("), "Start is not defined in\n{}", ir);
+    assert!(ir.contains("define i32 @add("), "{}", ir);
+    assert!(!build(&project, &["--emit=bc"], "demo.bc").is_empty());
+    assert!(!build(&project, &["--emit=asm"], "demo.s").is_empty());
+    fs::remove_dir_all(&project).ok();
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn debug_info_points_at_the_ax_source() {
+    let project = project("debug_info", ADD);
+    let ir = String::from_utf8(build(&project, &["--emit=llvm-ir", "-g", "-O0"], "demo.ll")).unwrap();
+    fs::remove_dir_all(&project).ok();
+    assert_eq!(ir.matches("!DICompileUnit(").count(), 1, "{}", ir);
+    assert!(ir.contains("!DIFile(filename: \"src/init.ax\""), "{}", ir);
+    let subprograms: Vec<&str> = ir.lines().filter(|line| line.contains("!DISubprogram(")).collect();
+    assert_eq!(subprograms.len(), 2, "{:?}", subprograms);
+    assert!(subprograms[0].contains("name: \"add\"") && subprograms[0].contains("line: 1,"), "{}", subprograms[0]);
+    assert!(subprograms[1].contains("name: \"Start\"") && subprograms[1].contains("line: 5,"), "{}", subprograms[1]);
+    // one location per statement, the parameters are declared on the line of their function
+    let mut lines: Vec<u32> = ir
+        .match_indices("!DILocation(line: ")
+        .map(|(i, prefix)| {
+            let rest = &ir[i + prefix.len()..];
+            rest[..rest.find(',').unwrap()].parse().unwrap()
+        })
+        .collect();
+    lines.sort();
+    lines.dedup();
+    assert_eq!(lines, vec![1, 2, 3, 6, 7]);
+}
+
+// -O0 keeps the locals in stack slots, -O2 promotes them to registers (mem2reg)
+#[cfg(feature = "llvm")]
+#[test]
+fn opt_levels_reach_the_pass_pipeline() {
+    let project = project("opt_levels", ADD);
+    let allocas = |args: &[&str]| {
+        let ir = String::from_utf8(build(&project, &[&["--emit=llvm-ir"], args].concat(), "demo.ll")).unwrap();
+        ir.matches(" alloca ").count()
+    };
+    assert!(allocas(&["-O0"]) > 0);
+    assert_eq!(allocas(&["-O2"]), 0);
+
+    let manifest = "__Project__\n_name_ = \"demo\"\n_version_ = \"0.1.0\"\n\n__Build__\n_opt_level_ = ";
+    fs::write(project.join("project.asml"), format!("{}0\n", manifest)).unwrap();
+    assert!(allocas(&[]) > 0);
+    assert_eq!(allocas(&["-O2"]), 0, "-O overrides _opt_level_");
+    fs::write(project.join("project.asml"), format!("{}2\n", manifest)).unwrap();
+    assert_eq!(allocas(&[]), 0);
+    assert!(allocas(&["-O0"]) > 0, "-O overrides _opt_level_");
+    fs::remove_dir_all(&project).ok();
+}
+
+#[test]
+fn test_runs_every_test_function_and_fails_when_one_does() {
+    let code = "cast add(a: i32, b: i32) -> i32 >>\n    return a + b;\n<<\n\
+                cast test_add() >>\n    assert_eq(add(1, 2), 3);\n    return;\n<<\n\
+                cast test_broken() >>\n    assert_eq(add(1, 2), 4);\n    return;\n<<\n\
+                cast Start() >>\n    return;\n<<\n";
+    let project = project("test", code);
+    fs::create_dir_all(project.join("src/util")).unwrap();
+    fs::write(project.join("src/util/calc.ax"), "cast test_compare() >>\n    assert(3 > 1);\n    return;\n<<\n").unwrap();
+
+    let output = axon(&project, &["test"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "{}", stdout);
+    for line in [
+        "Running 3 test(s) from 2 file(s)",
+        "test src/init.ax::test_add ... ok",
+        "test src/init.ax::test_broken ... FAILED",
+        "test src/util/calc.ax::test_compare ... ok",
+        "[ERR-RT-020] Assertion failed: assert_eq(add(1, 2), 4)\n  left:  3\n  right: 4\n  --> src/init.ax:9:5\n",
+        "test result: FAILED. 2 passed; 1 failed",
+    ] {
+        assert!(stdout.contains(line), "no {:?} in\n{}", line, stdout);
+    }
+
+    let output = axon(&project, &["test", "add"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout);
+    assert!(stdout.contains("Running 1 test(s) from 2 file(s)"), "{}", stdout);
+    assert!(stdout.contains("test result: ok. 1 passed; 0 failed"), "{}", stdout);
+    fs::remove_dir_all(&project).ok();
+}
+
+#[test]
+fn publish_keeps_a_bad_name_out_of_the_registry() {
+    let project = project("publish", "cast Start() >>\n    return;\n<<\n");
+    fs::write(project.join("project.asml"), "__Project__\n_name_ = \"../escaped\"\n_version_ = \"0.1.0\"\n").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_AxonScript"))
+        .arg("publish")
+        .env("AXON_REGISTRY", project.join("registry"))
+        .current_dir(&project)
+        .output()
+        .expect("failed to start axon publish");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("ERR-PKG-006"));
+    assert!(!project.join("registry").exists() && !project.join("escaped").exists());
+    fs::remove_dir_all(&project).ok();
+}
+
+// one framed JSON-RPC message, as the client sends it
+fn lsp_message(message: Value) -> String {
+    let body = message.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+// everything the server wrote, split back into its messages
+fn lsp_messages(mut stdout: &[u8]) -> Vec<Value> {
+    let mut messages = Vec::new();
+    while !stdout.is_empty() {
+        let text = String::from_utf8_lossy(stdout);
+        let header_end = text.find("\r\n\r\n").expect("a message without a header");
+        let length: usize = text[..header_end]
+            .lines()
+            .find_map(|line| line.strip_prefix("Content-Length:"))
+            .expect("a header without Content-Length")
+            .trim()
+            .parse()
+            .unwrap();
+        let body = &stdout[header_end + 4..header_end + 4 + length];
+        messages.push(serde_json::from_slice(body).unwrap());
+        stdout = &stdout[header_end + 4 + length..];
+    }
+    messages
+}
+
+#[test]
+fn language_server_publishes_diagnostics_and_answers_hover() {
+    let broken = "cast add(a: i32, b: i32) -> i32 >>\n    return a + b;\n<<\ncast Start() >>\n    out(add(1, \"two\"));\n    return;\n<<\n";
+    let fixed = broken.replace("\"two\"", "2");
+    let project = project("lsp", broken);
+    let uri = format!("file://{}", project.join("src/init.ax").display());
+    let document = json!({ "uri": uri });
+
+    let requests = [
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "capabilities": {} } }),
+        json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {
+            "textDocument": { "uri": uri, "languageId": "axon", "version": 1, "text": broken },
+        } }),
+        // on `add` in `out(add(1, "two"));`
+        json!({ "jsonrpc": "2.0", "id": 2, "method": "textDocument/hover", "params": {
+            "textDocument": document, "position": { "line": 4, "character": 9 },
+        } }),
+        json!({ "jsonrpc": "2.0", "method": "textDocument/didChange", "params": {
+            "textDocument": { "uri": uri, "version": 2 }, "contentChanges": [{ "text": fixed }],
+        } }),
+        json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }),
+        json!({ "jsonrpc": "2.0", "method": "exit" }),
+    ];
+    let mut child = Command::new(env!("CARGO_BIN_EXE_AxonScript"))
+        .arg("lsp")
+        .current_dir(&project)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start axon lsp");
+    let input: String = requests.into_iter().map(lsp_message).collect();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    fs::remove_dir_all(&project).ok();
+    assert_eq!(output.status.code(), Some(0), "exit after shutdown is a clean exit");
+
+    let messages = lsp_messages(&output.stdout);
+    let response = |id: u64| messages.iter().find(|m| m["id"] == id).unwrap_or_else(|| panic!("no response to {}", id));
+    let published: Vec<&Value> = messages
+        .iter()
+        .filter(|m| m["method"] == "textDocument/publishDiagnostics")
+        .map(|m| &m["params"])
+        .collect();
+
+    let capabilities = &response(1)["result"]["capabilities"];
+    assert_eq!(capabilities["textDocumentSync"], 1);
+    assert_eq!(capabilities["hoverProvider"], true);
+
+    assert_eq!(published.len(), 2, "one publish per open and change: {:?}", published);
+    assert!(published.iter().all(|p| p["uri"] == uri.as_str()));
+    let diagnostics = published[0]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["code"], "ERR-SEM-322");
+    assert_eq!(diagnostics[0]["severity"], 1);
+    assert_eq!(
+        diagnostics[0]["range"],
+        json!({ "start": { "line": 4, "character": 8 }, "end": { "line": 4, "character": 21 } })
+    );
+    assert_eq!(published[1]["diagnostics"], json!([]));
+
+    let hover = &response(2)["result"];
+    assert_eq!(hover["contents"]["kind"], "markdown");
+    assert!(hover["contents"]["value"].as_str().unwrap().contains("add(a: i32, b: i32) -> i32"), "{}", hover);
+    assert_eq!(hover["range"], json!({ "start": { "line": 4, "character": 8 }, "end": { "line": 4, "character": 11 } }));
+
+    assert_eq!(response(3)["result"], Value::Null);
+}
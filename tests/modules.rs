@@ -0,0 +1,72 @@
+// the projects in tests/modules split a program over several files with `use`,
+// the working ones run under every backend, the broken ones have to report each error in the file it is in
+
+use axonscript::{analyze, parse, Diagnostics, Source};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[cfg(feature = "llvm")]
+const BACKENDS: &[&str] = &["jit", "interp"];
+#[cfg(not(feature = "llvm"))]
+const BACKENDS: &[&str] = &["interp"];
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/modules").join(name)
+}
+
+// `axon run` in the project, its stdout
+fn run(name: &str, backend: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_AxonScript"))
+        .args(["run", "--ci", &format!("--backend={}", backend)])
+        .current_dir(fixture(name))
+        .output()
+        .expect("failed to start axon run");
+    assert!(output.status.success(), "{} under {}:\n{}", name, backend, String::from_utf8_lossy(&output.stdout));
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+// every error up to the semantic pass as `src/util/calc.ax:6:12 ERR-SEM-322`
+fn errors(name: &str) -> Vec<String> {
+    let dir = fixture(name);
+    let (source, _) = Source::project(&dir).expect("the fixture's project.asml is broken");
+    let diagnostics = match parse(&source).and_then(analyze) {
+        Ok(_) => Diagnostics::new(),
+        Err(diagnostics) => diagnostics,
+    };
+    diagnostics
+        .errors()
+        .map(|d| {
+            let error = &d.error;
+            let file = error.file.as_deref().unwrap_or("?");
+            let file = Path::new(file).strip_prefix(&dir).unwrap_or(Path::new(file));
+            let before = &error.src.as_deref().unwrap_or("")[..error.start];
+            let line = before.matches('\n').count() + 1;
+            let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+            format!("{}:{}:{} {}", file.display(), line, column, error.code.as_deref().unwrap_or("?"))
+        })
+        .collect()
+}
+
+// two modules with the same names side by side, a local of the same name as a module or a global shadows it
+#[test]
+fn imported_names_are_namespaced_by_module() {
+    for backend in BACKENDS {
+        let out = run("namespaces", backend);
+        assert!(out.starts_with("5 500\n10 100\n8 40\n3\n"), "under {}:\n{}", backend, out);
+    }
+}
+
+#[test]
+fn an_import_cycle_is_reported_where_it_closes() {
+    assert_eq!(errors("cycle"), vec!["src/graph/b.ax:1:1 ERR-MOD-002"]);
+}
+
+#[test]
+fn unresolved_imports_are_reported_in_the_importing_file() {
+    assert_eq!(errors("missing"), vec!["src/util/calc.ax:1:1 ERR-MOD-001", "src/init.ax:5:9 ERR-MOD-005"]);
+}
+
+#[test]
+fn semantic_errors_point_into_the_imported_file() {
+    assert_eq!(errors("type_error"), vec!["src/util/calc.ax:6:12 ERR-SEM-322"]);
+}